@@ -5,11 +5,21 @@
 
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
-use legaia_assets::converter::tmd_to_gltf;
-use psxutils::cdrom::CdRom;
-use psxutils::formats::{Tim, Tmd, Vag};
+use legaia_assets::converter::{
+    tmd_to_glb, tmd_to_glb_merged, tmd_to_gltf, tmd_to_gltf_merged, tmd_to_obj, vag_to_audio,
+    vag_to_wav, AudioFormat,
+};
+use legaia_assets::extraction::{archive_entries, write_archive_entry};
+use legaia_assets::{ExtractionManifest, OutputLayout};
+use psxutils::cdrom::{CdRom, DirectoryEntry};
+use psxutils::formats::{Tim, Tmd, Vab, Vag};
+use psxutils::{detect_format, AssetType, KnownFormat, SequentialScanner};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 use tracing::{info, warn};
 
 #[derive(Parser)]
@@ -32,6 +42,23 @@ enum Commands {
         /// Path to PSX disc image (.bin file)
         #[arg(short, long)]
         disc: PathBuf,
+
+        /// Only list paths matching a glob (e.g., "/**/*.TIM"), searching
+        /// the whole tree instead of the root directory
+        #[arg(short, long)]
+        pattern: Option<String>,
+    },
+
+    /// Print a report on a disc: volume, files by extension, XA audio and
+    /// the asset types packed in PROT.DAT
+    Info {
+        /// Path to PSX disc image (.bin file)
+        #[arg(short, long)]
+        disc: PathBuf,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Extract a specific file from the disc
@@ -58,13 +85,43 @@ enum Commands {
         output: PathBuf,
     },
 
-    /// Convert VAG audio to WAV
+    /// Convert every TIM texture in a directory to PNG
+    ConvertTimDir {
+        /// Directory containing .tim files
+        input_dir: PathBuf,
+
+        /// Output directory for PNG files (mirrors the input layout)
+        output_dir: PathBuf,
+
+        /// Also convert TIMs in subdirectories
+        #[arg(short, long)]
+        recursive: bool,
+
+        /// Number of worker threads (defaults to one per CPU)
+        #[arg(short, long)]
+        jobs: Option<usize>,
+    },
+
+    /// Convert VAG audio to WAV, FLAC or Ogg Vorbis
     ConvertVag {
         /// Input VAG file
         input: PathBuf,
 
-        /// Output WAV file
+        /// Output audio file
         output: PathBuf,
+
+        /// Output format (wav, flac, ogg); only WAV keeps loop points
+        #[arg(short, long, default_value = "wav")]
+        format: AudioFormat,
+    },
+
+    /// Split a VAB sound bank into standalone VAG samples
+    ExtractVab {
+        /// Input VAB file
+        input: PathBuf,
+
+        /// Output directory for the .vag files
+        output_dir: PathBuf,
     },
 
     /// Show TMD model info
@@ -73,13 +130,22 @@ enum Commands {
         input: PathBuf,
     },
 
-    /// Convert TMD model to glTF
+    /// Convert TMD model to glTF (or Wavefront OBJ)
     ConvertTmd {
         /// Input TMD file
         input: PathBuf,
 
-        /// Output glTF file (.gltf)
+        /// Output glTF file (.gltf, or .glb with --glb); a .obj output
+        /// writes OBJ plus a .mtl instead
         output: PathBuf,
+
+        /// Write a single binary .glb file instead of .gltf + .bin
+        #[arg(long)]
+        glb: bool,
+
+        /// Merge all objects into a single mesh (for static props)
+        #[arg(long)]
+        merge: bool,
     },
 
     /// Extract all assets from disc
@@ -95,6 +161,38 @@ enum Commands {
         /// Asset type to extract (textures, audio, models, all)
         #[arg(short, long, default_value = "all")]
         r#type: String,
+
+        /// Skip files already extracted by a previous run
+        #[arg(long)]
+        incremental: bool,
+
+        /// Output layout (mirror, by-type, flat, flat-lba)
+        #[arg(long, default_value = "mirror")]
+        layout: OutputLayout,
+    },
+
+    /// Extract entries of a DAT archive, numbered as in `info`
+    ExtractArchive {
+        /// Path to PSX disc image (.bin file); without it the archive is read
+        /// from a local file
+        #[arg(short, long)]
+        disc: Option<PathBuf>,
+
+        /// Archive path on disc (e.g. /PROT.DAT), or a local .dat file
+        archive_path: String,
+
+        /// Entry to extract
+        #[arg(short, long, required_unless_present = "all")]
+        index: Option<usize>,
+
+        /// Extract every entry as asset_NNNN into the output directory
+        #[arg(long, conflicts_with = "index")]
+        all: bool,
+
+        /// Output file, or output directory with --all (extension follows
+        /// the converted format)
+        #[arg(short, long)]
+        output: PathBuf,
     },
 }
 
@@ -112,29 +210,70 @@ fn main() -> Result<()> {
     tracing::subscriber::set_global_default(subscriber)?;
 
     match cli.command {
-        Commands::List { disc } => list_files(&disc)?,
+        Commands::List { disc, pattern } => list_files(&disc, pattern.as_deref())?,
+        Commands::Info { disc, json } => disc_info(&disc, json)?,
         Commands::Extract { disc, file, output } => extract_file(&disc, &file, &output)?,
         Commands::ConvertTim { input, output } => convert_tim(&input, &output)?,
-        Commands::ConvertVag { input, output } => convert_vag(&input, &output)?,
+        Commands::ConvertTimDir {
+            input_dir,
+            output_dir,
+            recursive,
+            jobs,
+        } => convert_tim_dir(&input_dir, &output_dir, recursive, jobs)?,
+        Commands::ConvertVag {
+            input,
+            output,
+            format,
+        } => convert_vag(&input, &output, format)?,
+        Commands::ExtractVab { input, output_dir } => extract_vab(&input, &output_dir)?,
         Commands::InfoTmd { input } => info_tmd(&input)?,
-        Commands::ConvertTmd { input, output } => convert_tmd(&input, &output)?,
+        Commands::ConvertTmd {
+            input,
+            output,
+            glb,
+            merge,
+        } => convert_tmd(&input, &output, glb, merge)?,
         Commands::ExtractAll {
             disc,
             output,
             r#type,
-        } => extract_all(&disc, &output, &r#type)?,
+            incremental,
+            layout,
+        } => extract_all(&disc, &output, &r#type, incremental, layout)?,
+        Commands::ExtractArchive {
+            disc,
+            archive_path,
+            index,
+            all,
+            output,
+        } => extract_archive(disc.as_ref(), &archive_path, index, all, &output)?,
     }
 
     Ok(())
 }
 
-fn list_files(disc_path: &PathBuf) -> Result<()> {
+fn list_files(disc_path: &PathBuf, pattern: Option<&str>) -> Result<()> {
     info!("Opening disc: {}", disc_path.display());
     let cdrom = CdRom::open(disc_path)
         .with_context(|| format!("Failed to open disc: {}", disc_path.display()))?;
 
-    info!("Reading root directory...");
-    let entries = cdrom.read_dir("/")?;
+    let entries = match pattern {
+        Some(pattern) => {
+            info!("Searching for: {}", pattern);
+            cdrom
+                .glob(pattern)?
+                .into_iter()
+                .map(|(path, entry)| DirectoryEntry {
+                    name: path,
+                    ..entry
+                })
+                .collect()
+        }
+        None => {
+            info!("Reading root directory...");
+            cdrom.read_dir("/")?
+        }
+    };
 
     println!("\nFiles on disc:");
     println!("{:<40} {:>12} {:>10}", "Name", "Size (bytes)", "LBA");
@@ -152,17 +291,236 @@ fn list_files(disc_path: &PathBuf) -> Result<()> {
     Ok(())
 }
 
+/// Disc report printed by the `info` command
+#[derive(Debug, Serialize)]
+struct DiscReport {
+    volume: VolumeReport,
+    total_files: usize,
+    total_bytes: u64,
+    /// Files by uppercase extension ("" for none)
+    extensions: BTreeMap<String, SizeStats>,
+    xa_streams: Vec<XaStreamReport>,
+    /// Scan of PROT.DAT, if the disc has one
+    prot_dat: Option<ProtReport>,
+}
+
+/// Identification strings from the Primary Volume Descriptor
+#[derive(Debug, Serialize)]
+struct VolumeReport {
+    system_id: String,
+    volume_id: String,
+    publisher_id: String,
+    application_id: String,
+}
+
+/// Number of items and their total size
+#[derive(Debug, Default, Serialize)]
+struct SizeStats {
+    count: usize,
+    bytes: u64,
+}
+
+impl SizeStats {
+    fn add(&mut self, bytes: u64) {
+        self.count += 1;
+        self.bytes += bytes;
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct XaStreamReport {
+    file_number: u8,
+    channel: u8,
+    start_sector: u32,
+    sector_count: u32,
+    sample_rate: u32,
+    bits_per_sample: u8,
+    stereo: bool,
+    emphasis: bool,
+    duration_secs: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct ProtReport {
+    path: String,
+    size: usize,
+    /// Discovered assets by type
+    assets: BTreeMap<&'static str, SizeStats>,
+    /// Bytes not covered by any discovered asset
+    unclaimed_bytes: usize,
+}
+
+fn disc_info(disc_path: &Path, json: bool) -> Result<()> {
+    info!("Opening disc: {}", disc_path.display());
+    let cdrom = CdRom::open(disc_path)
+        .with_context(|| format!("Failed to open disc: {}", disc_path.display()))?;
+
+    let volume = cdrom.volume_info()?;
+
+    let mut total_files = 0;
+    let mut total_bytes = 0;
+    let mut extensions: BTreeMap<String, SizeStats> = BTreeMap::new();
+    let mut prot_path = None;
+    for item in cdrom.walk() {
+        let (path, entry) = item?;
+        total_files += 1;
+        total_bytes += entry.size as u64;
+
+        let extension = Path::new(&entry.name)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_uppercase())
+            .unwrap_or_default();
+        extensions
+            .entry(extension)
+            .or_default()
+            .add(entry.size as u64);
+
+        if entry.name.eq_ignore_ascii_case("PROT.DAT") {
+            prot_path = Some(path);
+        }
+    }
+
+    info!("Scanning for XA audio streams...");
+    let xa_streams = cdrom
+        .scan_xa_streams()
+        .iter()
+        .map(|stream| XaStreamReport {
+            file_number: stream.file_number,
+            channel: stream.channel,
+            start_sector: stream.start_sector,
+            sector_count: stream.sector_count,
+            sample_rate: stream.coding_info.sample_rate(),
+            bits_per_sample: stream.coding_info.bits_per_sample(),
+            stereo: stream.coding_info.is_stereo(),
+            emphasis: stream.coding_info.has_emphasis(),
+            duration_secs: stream.duration_seconds(),
+        })
+        .collect();
+
+    let prot_dat = match prot_path {
+        Some(path) => {
+            info!("Scanning {}...", path);
+            let data = cdrom.read_file(&path)?;
+
+            let mut assets: BTreeMap<&'static str, SizeStats> = BTreeMap::new();
+            let mut claimed = 0;
+            for asset in SequentialScanner::new().scan(&data, |_| {}) {
+                assets
+                    .entry(asset_type_name(&asset.asset_type))
+                    .or_default()
+                    .add(asset.size as u64);
+                claimed += asset.size;
+            }
+
+            Some(ProtReport {
+                path,
+                size: data.len(),
+                assets,
+                unclaimed_bytes: data.len().saturating_sub(claimed),
+            })
+        }
+        None => None,
+    };
+
+    let report = DiscReport {
+        volume: VolumeReport {
+            system_id: volume.system_id,
+            volume_id: volume.volume_id,
+            publisher_id: volume.publisher_id,
+            application_id: volume.application_id,
+        },
+        total_files,
+        total_bytes,
+        extensions,
+        xa_streams,
+        prot_dat,
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_disc_report(&report);
+    }
+
+    Ok(())
+}
+
+/// Short name of a scanned asset type, used as a report key
+fn asset_type_name(asset_type: &AssetType) -> &'static str {
+    match asset_type {
+        AssetType::Tim { .. } => "tim",
+        AssetType::Tmd { .. } => "tmd",
+        AssetType::Vag => "vag",
+        AssetType::CustomModel => "custom_model",
+    }
+}
+
+fn print_disc_report(report: &DiscReport) {
+    println!("\nVolume:");
+    println!("  System: {}", report.volume.system_id);
+    println!("  Label: {}", report.volume.volume_id);
+    println!("  Publisher: {}", report.volume.publisher_id);
+    println!("  Application: {}", report.volume.application_id);
+
+    println!(
+        "\nFiles: {} ({} bytes)",
+        report.total_files, report.total_bytes
+    );
+    println!(
+        "  {:<12} {:>8} {:>14}",
+        "Extension", "Count", "Size (bytes)"
+    );
+    for (extension, stats) in &report.extensions {
+        let extension = if extension.is_empty() {
+            "(none)"
+        } else {
+            extension
+        };
+        println!("  {:<12} {:>8} {:>14}", extension, stats.count, stats.bytes);
+    }
+
+    println!("\nXA audio streams: {}", report.xa_streams.len());
+    for stream in &report.xa_streams {
+        println!(
+            "  File {} channel {}: LBA {}, {} sectors, {} Hz {}-bit {}{}, {:.1}s",
+            stream.file_number,
+            stream.channel,
+            stream.start_sector,
+            stream.sector_count,
+            stream.sample_rate,
+            stream.bits_per_sample,
+            if stream.stereo { "stereo" } else { "mono" },
+            if stream.emphasis { " (emphasis)" } else { "" },
+            stream.duration_secs
+        );
+    }
+
+    match &report.prot_dat {
+        Some(prot) => {
+            println!("\n{}: {} bytes", prot.path, prot.size);
+            println!(
+                "  {:<12} {:>8} {:>14}",
+                "Asset type", "Count", "Size (bytes)"
+            );
+            for (name, stats) in &prot.assets {
+                println!("  {:<12} {:>8} {:>14}", name, stats.count, stats.bytes);
+            }
+            println!("  Unclaimed: {} bytes", prot.unclaimed_bytes);
+        }
+        None => println!("\nNo PROT.DAT on disc"),
+    }
+}
+
 fn extract_file(disc_path: &PathBuf, file_path: &str, output_path: &PathBuf) -> Result<()> {
     info!("Opening disc: {}", disc_path.display());
     let cdrom = CdRom::open(disc_path)?;
 
-    info!("Reading file: {}", file_path);
-    let data = cdrom.read_file(file_path)?;
-
-    info!("Writing to: {}", output_path.display());
-    fs::write(output_path, &data)?;
+    info!("Extracting {} to: {}", file_path, output_path.display());
+    let mut out = BufWriter::new(fs::File::create(output_path)?);
+    let written = cdrom.read_file_into(file_path, &mut out)?;
+    out.flush()?;
 
-    info!("Extracted {} bytes", data.len());
+    info!("Extracted {} bytes", written);
     Ok(())
 }
 
@@ -189,7 +547,79 @@ fn convert_tim(input: &PathBuf, output: &PathBuf) -> Result<()> {
     Ok(())
 }
 
-fn convert_vag(input: &PathBuf, output: &PathBuf) -> Result<()> {
+fn convert_tim_dir(
+    input_dir: &Path,
+    output_dir: &Path,
+    recursive: bool,
+    jobs: Option<usize>,
+) -> Result<()> {
+    let mut files = Vec::new();
+    find_tim_files(input_dir, recursive, &mut files)?;
+    files.sort();
+    info!("Found {} TIM files in {}", files.len(), input_dir.display());
+
+    // Zero threads lets rayon pick one per CPU
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs.unwrap_or(0))
+        .build()
+        .context("Failed to create thread pool")?;
+
+    let converted = pool.install(|| {
+        files
+            .par_iter()
+            .filter(|input| {
+                let relative = input.strip_prefix(input_dir).unwrap_or(input.as_path());
+                let output = output_dir.join(relative).with_extension("png");
+
+                let data = match fs::read(input) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        warn!("Failed to read {}: {}", input.display(), e);
+                        return false;
+                    }
+                };
+                if let Some(parent) = output.parent() {
+                    if let Err(e) = fs::create_dir_all(parent) {
+                        warn!("Failed to create {}: {}", parent.display(), e);
+                        return false;
+                    }
+                }
+
+                convert_tim_data(&data, &output)
+            })
+            .count()
+    });
+
+    println!(
+        "
+TIM conversion complete:"
+    );
+    println!("  Converted: {}", converted);
+    println!("  Failed: {}", files.len() - converted);
+
+    Ok(())
+}
+
+/// Collect `.tim` files (any case) in a directory
+fn find_tim_files(dir: &Path, recursive: bool, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if recursive {
+                find_tim_files(&path, recursive, files)?;
+            }
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("tim"))
+        {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+fn convert_vag(input: &PathBuf, output: &Path, format: AudioFormat) -> Result<()> {
     info!("Reading VAG: {}", input.display());
     let data = fs::read(input)?;
 
@@ -201,26 +631,49 @@ fn convert_vag(input: &PathBuf, output: &PathBuf) -> Result<()> {
         vag.sample_rate,
         vag.duration_secs()
     );
-    let pcm_samples = vag.decode_to_pcm();
-
-    info!("Writing WAV: {}", output.display());
-    let spec = hound::WavSpec {
-        channels: 1,
-        sample_rate: vag.sample_rate,
-        bits_per_sample: 16,
-        sample_format: hound::SampleFormat::Int,
-    };
 
-    let mut writer = hound::WavWriter::create(output, spec)?;
-    for sample in pcm_samples {
-        writer.write_sample(sample)?;
-    }
-    writer.finalize()?;
+    info!(
+        "Writing {}: {}",
+        format.extension().to_uppercase(),
+        output.display()
+    );
+    vag_to_audio(&vag, output, format)?;
 
     info!("Conversion complete!");
     Ok(())
 }
 
+fn extract_vab(input: &Path, output_dir: &Path) -> Result<()> {
+    info!("Reading VAB: {}", input.display());
+    let data = fs::read(input)?;
+
+    info!("Parsing VAB...");
+    let vab = Vab::parse(&data)?;
+    info!(
+        "{} programs, {} tones, {} samples",
+        vab.programs.len(),
+        vab.tones.len(),
+        vab.vag_samples.len()
+    );
+
+    fs::create_dir_all(output_dir)?;
+
+    let mut written = 0;
+    for vag in vab.split_vags() {
+        if vag.data.is_empty() {
+            continue;
+        }
+
+        let output = output_dir.join(format!("{}.vag", vag.name));
+        fs::write(&output, vag.to_bytes())
+            .with_context(|| format!("Failed to write {}", output.display()))?;
+        written += 1;
+    }
+
+    info!("Wrote {} samples to {}", written, output_dir.display());
+    Ok(())
+}
+
 fn info_tmd(input: &PathBuf) -> Result<()> {
     info!("Reading TMD: {}", input.display());
     let data = fs::read(input)?;
@@ -228,38 +681,95 @@ fn info_tmd(input: &PathBuf) -> Result<()> {
     info!("Parsing TMD...");
     let tmd = Tmd::parse(&data)?;
 
+    let stats = tmd.stats();
+
     println!("\nTMD Model Information:");
     println!("  Flags: {:#010x}", tmd.flags);
     println!("  Objects: {}", tmd.object_count());
+    println!("  Total vertices: {}", stats.total_vertices);
+    println!("  Total normals: {}", stats.total_normals);
 
-    for (i, obj) in tmd.objects.iter().enumerate() {
+    for (i, (obj, counts)) in tmd.objects.iter().zip(&stats.objects).enumerate() {
         println!("\n  Object {}:", i);
-        println!("    Vertices: {}", obj.vertices.len());
-        println!("    Normals: {}", obj.normals.len());
-        println!("    Primitives: {}", obj.primitives.len());
+        println!("    Vertices: {}", counts.vertices);
+        println!("    Normals: {}", counts.normals);
+        println!(
+            "    Primitives: {} ({} triangles, {} quads, {} lines)",
+            obj.primitives.len(),
+            counts.triangles,
+            counts.quads,
+            counts.lines
+        );
+        println!("    Textured: {}", counts.textured);
+        println!("    Gouraud: {}", counts.gouraud);
         println!("    Scale: {}", obj.scale);
     }
 
+    if stats.is_valid() {
+        println!("\n  Vertex indices: OK");
+    } else {
+        println!(
+            "\n  Vertex indices: INVALID ({} dangling)",
+            stats.dangling.len()
+        );
+        for dangling in &stats.dangling {
+            println!(
+                "    Object {} primitive {}: vertex {} out of range",
+                dangling.object, dangling.primitive, dangling.index
+            );
+        }
+    }
+
     Ok(())
 }
 
-fn convert_tmd(input: &PathBuf, output: &PathBuf) -> Result<()> {
+fn convert_tmd(input: &PathBuf, output: &PathBuf, glb: bool, merge: bool) -> Result<()> {
     info!("Reading TMD: {}", input.display());
     let data = fs::read(input)?;
 
     info!("Parsing TMD...");
     let tmd = Tmd::parse(&data)?;
 
-    info!("Converting to glTF ({} objects)...", tmd.object_count());
-    tmd_to_gltf(&tmd, output)?;
+    if output
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("obj"))
+    {
+        info!("Converting to OBJ ({} objects)...", tmd.object_count());
+        tmd_to_obj(&tmd, output)?;
+        info!("Saved OBJ to: {}", output.display());
+        info!("Materials: {}", output.with_extension("mtl").display());
+        info!("Conversion complete!");
+        return Ok(());
+    }
 
-    info!("Saved glTF to: {}", output.display());
-    info!("Binary buffer: {}", output.with_extension("bin").display());
+    info!("Converting to glTF ({} objects)...", tmd.object_count());
+    if glb {
+        if merge {
+            tmd_to_glb_merged(&tmd, output)?;
+        } else {
+            tmd_to_glb(&tmd, output)?;
+        }
+        info!("Saved GLB to: {}", output.display());
+    } else {
+        if merge {
+            tmd_to_gltf_merged(&tmd, output)?;
+        } else {
+            tmd_to_gltf(&tmd, output)?;
+        }
+        info!("Saved glTF to: {}", output.display());
+        info!("Binary buffer: {}", output.with_extension("bin").display());
+    }
     info!("Conversion complete!");
     Ok(())
 }
 
-fn extract_all(disc_path: &PathBuf, output_dir: &PathBuf, asset_type: &str) -> Result<()> {
+fn extract_all(
+    disc_path: &PathBuf,
+    output_dir: &PathBuf,
+    asset_type: &str,
+    incremental: bool,
+    layout: OutputLayout,
+) -> Result<()> {
     info!("Opening disc: {}", disc_path.display());
     let cdrom = CdRom::open(disc_path)?;
 
@@ -268,8 +778,15 @@ fn extract_all(disc_path: &PathBuf, output_dir: &PathBuf, asset_type: &str) -> R
     info!("Reading root directory...");
     let entries = cdrom.read_dir("/")?;
 
+    let mut manifest = if incremental {
+        ExtractionManifest::load(output_dir, &layout.to_string())
+    } else {
+        ExtractionManifest::new(layout.to_string())
+    };
+
     let mut extracted_count = 0;
     let mut converted_count = 0;
+    let mut skipped_count = 0;
 
     for entry in &entries {
         if entry.is_dir {
@@ -291,28 +808,49 @@ fn extract_all(disc_path: &PathBuf, output_dir: &PathBuf, asset_type: &str) -> R
             continue;
         }
 
+        if incremental && manifest.is_current(output_dir, &entry.name, entry.lba, entry.size) {
+            skipped_count += 1;
+            continue;
+        }
+
         info!("Extracting: {}", entry.name);
 
         match cdrom.read_file(&entry.name) {
             Ok(data) => {
-                let output_path = output_dir.join(&entry.name);
+                let output_path = layout.output_path(output_dir, &entry.name, entry.lba);
+                if let Some(parent) = output_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
 
                 // Try to convert if it's a known format
-                let converted = if entry.name.ends_with(".TIM") {
-                    convert_tim_data(&data, &output_path.with_extension("png"))
-                } else if entry.name.ends_with(".VAG") {
-                    convert_vag_data(&data, &output_path.with_extension("wav"))
-                } else if entry.name.ends_with(".TMD") {
-                    convert_tmd_data(&data, &output_path.with_extension("gltf"))
-                } else {
-                    false
+                let format = detect_format(&data);
+                let target = match format {
+                    Some(KnownFormat::Tim) => output_path.with_extension("png"),
+                    Some(KnownFormat::Vag) => output_path.with_extension("wav"),
+                    Some(KnownFormat::Tmd) => output_path.with_extension("gltf"),
+                    _ => output_path.clone(),
+                };
+
+                let converted = match format {
+                    Some(KnownFormat::Tim) => convert_tim_data(&data, &target),
+                    Some(KnownFormat::Vag) => convert_vag_data(&data, &target),
+                    Some(KnownFormat::Tmd) => convert_tmd_data(&data, &target),
+                    _ => false,
                 };
 
                 if converted {
                     converted_count += 1;
+                    manifest.record(output_dir, entry.name.clone(), entry.lba, &data, &target);
                 } else {
                     // Just extract raw data
                     fs::write(&output_path, &data)?;
+                    manifest.record(
+                        output_dir,
+                        entry.name.clone(),
+                        entry.lba,
+                        &data,
+                        &output_path,
+                    );
                 }
 
                 extracted_count += 1;
@@ -323,14 +861,68 @@ fn extract_all(disc_path: &PathBuf, output_dir: &PathBuf, asset_type: &str) -> R
         }
     }
 
+    manifest.save(output_dir)?;
+
     info!(
-        "Extraction complete! {} files extracted, {} converted",
-        extracted_count, converted_count
+        "Extraction complete! {} files extracted, {} converted, {} skipped",
+        extracted_count, converted_count, skipped_count
     );
 
     Ok(())
 }
 
+fn extract_archive(
+    disc_path: Option<&PathBuf>,
+    archive_path: &str,
+    index: Option<usize>,
+    all: bool,
+    output: &Path,
+) -> Result<()> {
+    let data = match disc_path {
+        Some(disc_path) => {
+            info!("Opening disc: {}", disc_path.display());
+            let cdrom = CdRom::open(disc_path)
+                .with_context(|| format!("Failed to open disc: {}", disc_path.display()))?;
+            cdrom
+                .read_file(archive_path)
+                .with_context(|| format!("Failed to read {} from disc", archive_path))?
+        }
+        None => {
+            fs::read(archive_path).with_context(|| format!("Failed to read {}", archive_path))?
+        }
+    };
+
+    info!("Scanning {}...", archive_path);
+    let entries = archive_entries(&data);
+    info!("Found {} entries", entries.len());
+
+    if !all {
+        let index = index.context("Pass --index or --all")?;
+        let Some(entry) = entries.get(index) else {
+            anyhow::bail!("Archive has {} entries, no entry {}", entries.len(), index);
+        };
+        let path = write_archive_entry(&data, entry, output)?;
+        info!("Extracted entry {} to {}", index, path.display());
+        return Ok(());
+    }
+
+    fs::create_dir_all(output)?;
+    let mut extracted = 0;
+    for (index, entry) in entries.iter().enumerate() {
+        let target = output.join(format!("asset_{:04}", index));
+        match write_archive_entry(&data, entry, &target) {
+            Ok(path) => {
+                info!("  {} -> {}", index, path.display());
+                extracted += 1;
+            }
+            Err(e) => warn!("Failed to extract entry {}: {}", index, e),
+        }
+    }
+
+    info!("Extracted {} of {} entries", extracted, entries.len());
+    Ok(())
+}
+
 fn convert_tim_data(data: &[u8], output_path: &PathBuf) -> bool {
     match Tim::parse(data) {
         Ok(tim) => match tim.to_rgba8() {
@@ -364,34 +956,12 @@ fn convert_tim_data(data: &[u8], output_path: &PathBuf) -> bool {
 fn convert_vag_data(data: &[u8], output_path: &PathBuf) -> bool {
     match Vag::parse(data) {
         Ok(vag) => {
-            let pcm_samples = vag.decode_to_pcm();
-
-            let spec = hound::WavSpec {
-                channels: 1,
-                sample_rate: vag.sample_rate,
-                bits_per_sample: 16,
-                sample_format: hound::SampleFormat::Int,
-            };
-
-            match hound::WavWriter::create(output_path, spec) {
-                Ok(mut writer) => {
-                    for sample in pcm_samples {
-                        if let Err(e) = writer.write_sample(sample) {
-                            warn!("Failed to write WAV sample: {}", e);
-                            return false;
-                        }
-                    }
-                    if let Err(e) = writer.finalize() {
-                        warn!("Failed to finalize WAV: {}", e);
-                        return false;
-                    }
-                    info!("  -> Converted to WAV: {}", output_path.display());
-                    true
-                }
-                Err(e) => {
-                    warn!("Failed to create WAV writer: {}", e);
-                    false
-                }
+            if let Err(e) = vag_to_wav(&vag, output_path) {
+                warn!("Failed to convert VAG to WAV: {}", e);
+                false
+            } else {
+                info!("  -> Converted to WAV: {}", output_path.display());
+                true
             }
         }
         Err(e) => {
@@ -1,253 +1,528 @@
-//! TMD to glTF converter
+//! Asset converters (TMD to glTF, XA and VAG to audio files)
 
-use anyhow::Result;
+use crate::formats::TmdAnimation;
+use anyhow::{bail, Result};
 use gltf_json as json;
-use gltf_json::validation::USize64;
-use psxutils::formats::tmd::{Tmd, TmdPrimitive};
+use gltf_json::validation::{Checked, USize64};
+use psxutils::cdrom::CdRom;
+use psxutils::formats::tmd::{TextureInfo, Tmd, TmdNormal, TmdObject, TmdPrimitive};
+use psxutils::formats::{Vag, XaAudioStream};
+use psxutils::VramAtlas;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
 use std::path::Path;
 
-/// Convert a TMD model to glTF 2.0 format
-pub fn tmd_to_gltf(tmd: &Tmd, output_path: &Path) -> Result<()> {
-    let mut root = json::Root::default();
-    let mut buffer_data = Vec::new();
-    let mut buffer_views = Vec::new();
-    let mut accessors = Vec::new();
-    let mut meshes = Vec::new();
+/// Decode an XA audio stream from disc and write it as a WAV file
+///
+/// Only sectors matching the stream's file number and channel are decoded,
+/// so interleaved music/voice channels in the same XA file are ripped
+/// separately. Pre-emphasized streams are de-emphasized while decoding.
+pub fn xa_stream_to_wav(disc: &CdRom, stream: &XaAudioStream, output_path: &Path) -> Result<()> {
+    let pcm_samples = stream.decode_pcm(disc)?;
 
-    for (_obj_idx, object) in tmd.objects.iter().enumerate() {
-        // Skip empty objects
-        if object.vertices.is_empty() {
-            continue;
+    let spec = hound::WavSpec {
+        channels: if stream.coding_info.is_stereo() { 2 } else { 1 },
+        sample_rate: stream.coding_info.sample_rate(),
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut writer = hound::WavWriter::create(output_path, spec)?;
+    for sample in pcm_samples {
+        writer.write_sample(sample)?;
+    }
+    writer.finalize()?;
+
+    Ok(())
+}
+
+/// Output format for decoded audio
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AudioFormat {
+    /// 16-bit PCM WAV, with loop points in a `smpl` chunk
+    #[default]
+    Wav,
+    /// Lossless FLAC (needs the `flac` feature)
+    Flac,
+    /// Ogg Vorbis, lossy (needs the `ogg` feature)
+    Ogg,
+}
+
+impl AudioFormat {
+    /// File extension for this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "wav",
+            AudioFormat::Flac => "flac",
+            AudioFormat::Ogg => "ogg",
         }
+    }
+}
+
+impl std::str::FromStr for AudioFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "wav" => Ok(AudioFormat::Wav),
+            "flac" => Ok(AudioFormat::Flac),
+            "ogg" => Ok(AudioFormat::Ogg),
+            _ => Err(format!(
+                "Unknown audio format '{}' (expected wav, flac or ogg)",
+                s
+            )),
+        }
+    }
+}
+
+/// Decode a VAG sample and write it as a 16-bit mono WAV file
+///
+/// Loop points are kept in a `smpl` chunk so samplers and engines can loop
+/// the sound like the SPU would.
+pub fn vag_to_wav(vag: &Vag, output_path: &Path) -> Result<()> {
+    vag_to_audio(vag, output_path, AudioFormat::Wav)
+}
+
+/// Decode a VAG sample and write it in the given audio format
+///
+/// Only WAV output carries the loop points; FLAC and Ogg get the PCM alone.
+/// FLAC and Ogg fail unless the crate is built with the `flac`/`ogg` feature.
+pub fn vag_to_audio(vag: &Vag, output_path: &Path, format: AudioFormat) -> Result<()> {
+    let pcm_samples = vag.decode_to_pcm();
 
-        // Calculate scale factor
-        let scale = if object.scale == 0 {
-            1.0
+    match format {
+        AudioFormat::Wav => write_wav_with_loop(
+            output_path,
+            &pcm_samples,
+            vag.sample_rate,
+            vag.loop_start,
+            vag.loop_end,
+        )?,
+        #[cfg(feature = "flac")]
+        AudioFormat::Flac => write_flac(&pcm_samples, vag.sample_rate, output_path)?,
+        #[cfg(not(feature = "flac"))]
+        AudioFormat::Flac => bail!("FLAC output needs legaia-assets built with the `flac` feature"),
+        #[cfg(feature = "ogg")]
+        AudioFormat::Ogg => write_ogg(&pcm_samples, vag.sample_rate, output_path)?,
+        #[cfg(not(feature = "ogg"))]
+        AudioFormat::Ogg => bail!("Ogg output needs legaia-assets built with the `ogg` feature"),
+    }
+
+    Ok(())
+}
+
+/// Write 16-bit mono PCM as a WAV file, keeping loop points if present
+///
+/// With both `loop_start` and `loop_end` (sample frames, end exclusive) a
+/// `smpl` chunk describing a forward loop is appended after the data;
+/// otherwise the file is a plain WAV.
+pub fn write_wav_with_loop(
+    output_path: &Path,
+    samples: &[i16],
+    sample_rate: u32,
+    loop_start: Option<usize>,
+    loop_end: Option<usize>,
+) -> Result<()> {
+    fs::write(
+        output_path,
+        wav_bytes(samples, sample_rate, loop_start.zip(loop_end)),
+    )?;
+    Ok(())
+}
+
+/// Build a 16-bit mono WAV file, with a `smpl` chunk if there is a loop
+///
+/// `loop_points` are `(start, end)` sample positions with `end` exclusive,
+/// as found in VAG block flags.
+fn wav_bytes(samples: &[i16], sample_rate: u32, loop_points: Option<(usize, usize)>) -> Vec<u8> {
+    let data_size = samples.len() as u32 * 2;
+
+    let mut smpl = Vec::new();
+    if let Some((start, end)) = loop_points.filter(|(start, end)| end > start) {
+        // Sample period in nanoseconds, MIDI unity note 60 (middle C)
+        for value in [0, 0, 1_000_000_000 / sample_rate.max(1), 60, 0, 0, 0, 1, 0] {
+            smpl.extend_from_slice(&value.to_le_bytes());
+        }
+        // Cue point ID, forward loop, start, inclusive end, fraction, loop forever
+        for value in [0, 0, start as u32, end as u32 - 1, 0, 0] {
+            smpl.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    let mut wav = Vec::with_capacity(44 + data_size as usize + 8 + smpl.len());
+    let riff_size = 36
+        + data_size
+        + if smpl.is_empty() {
+            0
         } else {
-            object.scale as f32
+            8 + smpl.len() as u32
         };
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&riff_size.to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
 
-        // Convert vertices to f32 positions
-        let mut positions: Vec<f32> = Vec::new();
-        let mut pos_min = [f32::MAX, f32::MAX, f32::MAX];
-        let mut pos_max = [f32::MIN, f32::MIN, f32::MIN];
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // Mono
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * 2).to_le_bytes()); // Byte rate
+    wav.extend_from_slice(&2u16.to_le_bytes()); // Block align
+    wav.extend_from_slice(&16u16.to_le_bytes()); // Bits per sample
 
-        for vertex in &object.vertices {
-            let x = vertex.x as f32 / scale;
-            let y = vertex.y as f32 / scale;
-            let z = vertex.z as f32 / scale;
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
 
-            positions.push(x);
-            positions.push(y);
-            positions.push(z);
+    if !smpl.is_empty() {
+        wav.extend_from_slice(b"smpl");
+        wav.extend_from_slice(&(smpl.len() as u32).to_le_bytes());
+        wav.extend_from_slice(&smpl);
+    }
 
-            pos_min[0] = pos_min[0].min(x);
-            pos_min[1] = pos_min[1].min(y);
-            pos_min[2] = pos_min[2].min(z);
+    wav
+}
 
-            pos_max[0] = pos_max[0].max(x);
-            pos_max[1] = pos_max[1].max(y);
-            pos_max[2] = pos_max[2].max(z);
-        }
+/// Encode 16-bit mono PCM as FLAC
+#[cfg(feature = "flac")]
+fn write_flac(samples: &[i16], sample_rate: u32, output_path: &Path) -> Result<()> {
+    use flacenc::component::BitRepr;
+    use flacenc::error::Verify;
+
+    let config = flacenc::config::Encoder::default()
+        .into_verified()
+        .map_err(|(_, e)| anyhow::anyhow!("Invalid FLAC encoder config: {:?}", e))?;
+    let samples: Vec<i32> = samples.iter().map(|&s| s as i32).collect();
+    let source = flacenc::source::MemSource::from_samples(&samples, 1, 16, sample_rate as usize);
+
+    let stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+        .map_err(|e| anyhow::anyhow!("FLAC encoding failed: {:?}", e))?;
+    let mut sink = flacenc::bitsink::ByteSink::new();
+    stream
+        .write(&mut sink)
+        .map_err(|e| anyhow::anyhow!("FLAC encoding failed: {:?}", e))?;
+
+    fs::write(output_path, sink.as_slice())?;
+    Ok(())
+}
+
+/// Encode 16-bit mono PCM as Ogg Vorbis
+#[cfg(feature = "ogg")]
+fn write_ogg(samples: &[i16], sample_rate: u32, output_path: &Path) -> Result<()> {
+    use std::num::{NonZeroU32, NonZeroU8};
+
+    let sample_rate = NonZeroU32::new(sample_rate)
+        .ok_or_else(|| anyhow::anyhow!("Invalid sample rate: {}", sample_rate))?;
+    let file = std::io::BufWriter::new(fs::File::create(output_path)?);
+    let mut encoder =
+        vorbis_rs::VorbisEncoderBuilder::new(sample_rate, NonZeroU8::MIN, file)?.build()?;
+
+    let samples: Vec<f32> = samples.iter().map(|&s| s as f32 / 32768.0).collect();
+    encoder.encode_audio_block([&samples])?;
+    encoder.finish()?;
+
+    Ok(())
+}
+
+/// Where textured TMD primitives get their base color texture from
+pub enum TmdTextureSource<'a> {
+    /// Bake each texture page/CLUT combination from reconstructed VRAM into a
+    /// PNG written next to the glTF file
+    Vram(&'a VramAtlas),
+    /// Reference an existing image for every material (URI relative to the glTF file)
+    Image(&'a str),
+}
 
-        // Convert normals to f32
-        let mut normals: Vec<f32> = Vec::new();
+/// Convert a TMD model to glTF 2.0 format
+pub fn tmd_to_gltf(tmd: &Tmd, output_path: &Path) -> Result<()> {
+    tmd_to_gltf_with_texture(tmd, output_path, None)
+}
+
+/// Convert a TMD model to glTF 2.0 format, emitting UVs and materials
+///
+/// Objects with textured primitives get a TEXCOORD_0 attribute (UVs divided
+/// by 255) and one material per distinct texture page and CLUT, since the same
+/// page can be drawn with different palettes. Flat and gouraud colors of
+/// untextured primitives become a COLOR_0 attribute. TMD UVs and colors are
+/// per polygon corner, so those objects are de-indexed: every corner becomes
+/// its own vertex. Objects with neither are exported indexed.
+///
+/// If `texture` is given, materials reference it as their base color texture.
+pub fn tmd_to_gltf_with_texture(
+    tmd: &Tmd,
+    output_path: &Path,
+    texture: Option<&TmdTextureSource>,
+) -> Result<()> {
+    let (root, buffer_data) = build_gltf(tmd, output_path, texture, None, false)?;
+    write_gltf(root, &buffer_data, output_path)
+}
+
+/// Convert an animated TMD model to glTF 2.0 format
+///
+/// Each TMD object is its own node, so `animation` becomes one glTF
+/// animation with a rotation and a translation channel per animated object,
+/// sampled linearly at the animation's frame rate.
+pub fn tmd_to_gltf_animated(
+    tmd: &Tmd,
+    animation: Option<&TmdAnimation>,
+    output_path: &Path,
+) -> Result<()> {
+    let (root, buffer_data) = build_gltf(tmd, output_path, None, animation, false)?;
+    write_gltf(root, &buffer_data, output_path)
+}
+
+/// Convert a TMD model to glTF 2.0 format as a single mesh
+///
+/// All objects are merged into one mesh and node, which suits static props
+/// better than one node per object. Objects with UVs or colors are
+/// de-indexed as in [`tmd_to_gltf_with_texture`]; the merged mesh switches
+/// to u32 indices if it has too many vertices for u16.
+pub fn tmd_to_gltf_merged(tmd: &Tmd, output_path: &Path) -> Result<()> {
+    let (root, buffer_data) = build_gltf(tmd, output_path, None, None, true)?;
+    write_gltf(root, &buffer_data, output_path)
+}
+
+/// Write a glTF document with its buffer as a sidecar `.bin` file
+fn write_gltf(mut root: json::Root, buffer_data: &[u8], output_path: &Path) -> Result<()> {
+    let stem = output_path
+        .file_stem()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+    root.buffers[0].uri = Some(format!("{}.bin", stem));
+
+    // Write glTF JSON
+    let gltf_json = json::serialize::to_string_pretty(&root)?;
+    fs::write(output_path, gltf_json)?;
+
+    // Write binary buffer
+    let bin_path = output_path.with_extension("bin");
+    fs::write(bin_path, buffer_data)?;
+
+    Ok(())
+}
+
+/// GLB header magic ("glTF")
+const GLB_MAGIC: u32 = 0x4654_6C67;
+
+/// GLB container version
+const GLB_VERSION: u32 = 2;
+
+/// GLB chunk type of the JSON document ("JSON")
+const GLB_CHUNK_JSON: u32 = 0x4E4F_534A;
+
+/// GLB chunk type of the binary buffer ("BIN\0")
+const GLB_CHUNK_BIN: u32 = 0x004E_4942;
+
+/// Convert a TMD model to a single binary glTF (.glb) file
+///
+/// Same content as [`tmd_to_gltf`], but the buffer is embedded as the GLB
+/// BIN chunk instead of a sidecar `.bin` file.
+pub fn tmd_to_glb(tmd: &Tmd, output: &Path) -> Result<()> {
+    let (root, buffer_data) = build_gltf(tmd, output, None, None, false)?;
+    write_glb(&root, &buffer_data, output)
+}
+
+/// Convert a TMD model to a single binary glTF (.glb) file as a single mesh
+///
+/// Same content as [`tmd_to_gltf_merged`], embedded in a GLB container.
+pub fn tmd_to_glb_merged(tmd: &Tmd, output: &Path) -> Result<()> {
+    let (root, buffer_data) = build_gltf(tmd, output, None, None, true)?;
+    write_glb(&root, &buffer_data, output)
+}
+
+/// Write a glTF document and its buffer as a GLB file
+fn write_glb(root: &json::Root, buffer_data: &[u8], output: &Path) -> Result<()> {
+    let json_bytes = json::serialize::to_vec(root)?;
+    fs::write(output, glb_bytes(&json_bytes, buffer_data))?;
+
+    Ok(())
+}
+
+/// Convert a TMD model to Wavefront OBJ
+///
+/// Each TMD object becomes an `o` group, with its scale applied as in the
+/// glTF export. Quads are split into two triangles (0-1-2, 0-2-3) and lines
+/// are dropped. Textured primitives get `vt` coordinates (UVs divided by
+/// 255, V flipped for OBJ's bottom-left origin), and faces are grouped with
+/// `usemtl` per texture page and CLUT. The materials are listed in a `.mtl`
+/// file next to the OBJ. Vertex colors have no standard OBJ form and are
+/// not exported.
+pub fn tmd_to_obj(tmd: &Tmd, output: &Path) -> Result<()> {
+    use std::fmt::Write as _;
+
+    ensure_valid(tmd)?;
+
+    let mtl_path = output.with_extension("mtl");
+    let mtl_name = mtl_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("model.mtl");
+
+    let mut obj = String::new();
+    writeln!(obj, "mtllib {}", mtl_name)?;
+
+    let mut materials = BTreeSet::new();
+    let (mut vertex_base, mut normal_base, mut uv_count) = (0, 0, 0);
+
+    for (index, object) in tmd.objects.iter().enumerate() {
+        writeln!(obj, "o object_{}", index)?;
+
+        let positions = object_positions(object);
+        for [x, y, z] in &positions {
+            writeln!(obj, "v {} {} {}", x, y, z)?;
+        }
         for normal in &object.normals {
-            let nx = normal.nx as f32 / 4096.0;
-            let ny = normal.ny as f32 / 4096.0;
-            let nz = normal.nz as f32 / 4096.0;
-            let len = (nx * nx + ny * ny + nz * nz).sqrt();
-
-            if len > 0.0 {
-                normals.push(nx / len);
-                normals.push(ny / len);
-                normals.push(nz / len);
-            } else {
-                normals.push(0.0);
-                normals.push(1.0);
-                normals.push(0.0);
-            }
+            let [x, y, z] = normalize_normal(normal);
+            writeln!(obj, "vn {} {} {}", x, y, z)?;
         }
 
-        // Build index buffer from primitives
-        let mut indices: Vec<u16> = Vec::new();
+        // Faces per material, written after the object's vertex data
+        let mut groups: BTreeMap<Option<TextureKey>, Vec<String>> = BTreeMap::new();
+
+        for polygon in object_polygons(object) {
+            let normals = polygon
+                .normals
+                .filter(|n| n.iter().all(|&n| (n as usize) < object.normals.len()));
 
-        for primitive in &object.primitives {
-            match primitive {
-                TmdPrimitive::Triangle { vertices, .. } => {
-                    // Add triangle indices
-                    indices.push(vertices[0]);
-                    indices.push(vertices[1]);
-                    indices.push(vertices[2]);
+            let uv_base = uv_count;
+            if let Some(uvs) = polygon.uvs {
+                for &(u, v) in uvs {
+                    writeln!(obj, "vt {} {}", u as f32 / 255.0, 1.0 - v as f32 / 255.0)?;
                 }
-                TmdPrimitive::Quad { vertices, .. } => {
-                    // Split quad into two triangles (0-1-2, 0-2-3)
-                    indices.push(vertices[0]);
-                    indices.push(vertices[1]);
-                    indices.push(vertices[2]);
-
-                    indices.push(vertices[0]);
-                    indices.push(vertices[2]);
-                    indices.push(vertices[3]);
+                uv_count += uvs.len();
+            }
+
+            // OBJ indices are 1-based and global across objects
+            let corner = |c: usize| {
+                let v = vertex_base + polygon.vertices[c] as usize + 1;
+                let vt = polygon.uvs.map(|_| uv_base + c + 1);
+                let vn = normals.map(|n| normal_base + n[c] as usize + 1);
+                match (vt, vn) {
+                    (Some(vt), Some(vn)) => format!("{}/{}/{}", v, vt, vn),
+                    (None, Some(vn)) => format!("{}//{}", v, vn),
+                    (Some(vt), None) => format!("{}/{}", v, vt),
+                    (None, None) => v.to_string(),
                 }
+            };
+
+            let group = groups
+                .entry(polygon.texture_info.map(TextureKey::new))
+                .or_default();
+            for &[a, b, c] in polygon.triangles() {
+                group.push(format!("f {} {} {}", corner(a), corner(b), corner(c)));
             }
         }
 
-        // Skip objects with no primitives
-        if indices.is_empty() {
-            continue;
+        for (key, faces) in groups {
+            writeln!(obj, "usemtl {}", obj_material_name(key))?;
+            for face in faces {
+                writeln!(obj, "{}", face)?;
+            }
+            materials.insert(key);
         }
 
-        // --- Create position buffer and accessor ---
-        let position_bytes: Vec<u8> = positions.iter().flat_map(|f| f.to_le_bytes()).collect();
-        let position_offset = buffer_data.len();
-        buffer_data.extend_from_slice(&position_bytes);
+        vertex_base += positions.len();
+        normal_base += object.normals.len();
+    }
 
-        let position_view_idx = buffer_views.len();
-        buffer_views.push(json::buffer::View {
-            buffer: json::Index::new(0),
-            byte_length: USize64::from(position_bytes.len()),
-            byte_offset: Some(USize64::from(position_offset)),
-            byte_stride: None,
-            extensions: None,
-            extras: Default::default(),
-            name: None,
-            target: Some(json::validation::Checked::Valid(
-                json::buffer::Target::ArrayBuffer,
-            )),
-        });
+    let mut mtl = String::new();
+    for key in materials {
+        writeln!(mtl, "newmtl {}", obj_material_name(key))?;
+        writeln!(mtl, "Kd 1.0 1.0 1.0")?;
+        writeln!(mtl, "illum 1")?;
+        writeln!(mtl)?;
+    }
 
-        let position_accessor_idx = accessors.len();
-        accessors.push(json::Accessor {
-            buffer_view: Some(json::Index::new(position_view_idx as u32)),
-            byte_offset: Some(USize64(0)),
-            count: USize64::from(object.vertices.len()),
-            component_type: json::validation::Checked::Valid(json::accessor::GenericComponentType(
-                json::accessor::ComponentType::F32,
-            )),
-            extensions: None,
-            extras: Default::default(),
-            type_: json::validation::Checked::Valid(json::accessor::Type::Vec3),
-            min: Some(json::Value::from(vec![pos_min[0], pos_min[1], pos_min[2]])),
-            max: Some(json::Value::from(vec![pos_max[0], pos_max[1], pos_max[2]])),
-            name: None,
-            normalized: false,
-            sparse: None,
-        });
+    fs::write(output, obj)?;
+    fs::write(mtl_path, mtl)?;
+
+    Ok(())
+}
+
+/// OBJ material name for a texture key, named like the glTF materials
+fn obj_material_name(key: Option<TextureKey>) -> String {
+    key.map_or_else(|| "untextured".to_string(), |key| key.material_name())
+}
+
+/// Refuse models whose primitives reference vertices that don't exist
+///
+/// Such models come from misdetected or corrupt data; exporting them would
+/// index past the vertex list.
+fn ensure_valid(tmd: &Tmd) -> Result<()> {
+    let stats = tmd.stats();
+    if let Some(first) = stats.dangling.first() {
+        bail!(
+            "TMD has {} dangling vertex index(es), first {} in object {} primitive {}",
+            stats.dangling.len(),
+            first.index,
+            first.object,
+            first.primitive
+        );
+    }
+    Ok(())
+}
+
+/// Build the glTF document and binary buffer for a TMD model
+///
+/// The buffer URI is left unset; the caller either points it at a sidecar
+/// file or embeds the data in a GLB container. With `merge`, every object
+/// goes into one mesh; otherwise each object gets its own mesh and node.
+fn build_gltf(
+    tmd: &Tmd,
+    output_path: &Path,
+    texture: Option<&TmdTextureSource>,
+    animation: Option<&TmdAnimation>,
+    merge: bool,
+) -> Result<(json::Root, Vec<u8>)> {
+    ensure_valid(tmd)?;
+
+    let mut root = json::Root::default();
+    let mut buffer = GltfBuffer::default();
+    let mut materials = Materials::default();
+    let mut meshes = Vec::new();
+    // TMD object index of each mesh (and node)
+    let mut mesh_objects = Vec::new();
+
+    if merge {
+        let mut mesh = MeshData::default();
+        for object in &tmd.objects {
+            mesh.append_object(object);
+        }
 
-        // --- Create normal buffer and accessor (if normals exist) ---
-        let normal_accessor_idx = if !normals.is_empty() {
-            let normal_bytes: Vec<u8> = normals.iter().flat_map(|f| f.to_le_bytes()).collect();
-            let normal_offset = buffer_data.len();
-            buffer_data.extend_from_slice(&normal_bytes);
-
-            let normal_view_idx = buffer_views.len();
-            buffer_views.push(json::buffer::View {
-                buffer: json::Index::new(0),
-                byte_length: USize64::from(normal_bytes.len()),
-                byte_offset: Some(USize64::from(normal_offset)),
-                byte_stride: None,
+        let primitives = mesh.finish(&mut buffer, &mut materials);
+        if !primitives.is_empty() {
+            meshes.push(json::Mesh {
                 extensions: None,
                 extras: Default::default(),
                 name: None,
-                target: Some(json::validation::Checked::Valid(
-                    json::buffer::Target::ArrayBuffer,
-                )),
+                primitives,
+                weights: None,
             });
+        }
+    } else {
+        for (object_index, object) in tmd.objects.iter().enumerate() {
+            let mut mesh = MeshData::default();
+            mesh.append_object(object);
 
-            let idx = accessors.len();
-            accessors.push(json::Accessor {
-                buffer_view: Some(json::Index::new(normal_view_idx as u32)),
-                byte_offset: Some(USize64(0)),
-                count: USize64::from(object.normals.len()),
-                component_type: json::validation::Checked::Valid(
-                    json::accessor::GenericComponentType(json::accessor::ComponentType::F32),
-                ),
+            // Skip objects with no primitives
+            let primitives = mesh.finish(&mut buffer, &mut materials);
+            if primitives.is_empty() {
+                continue;
+            }
+
+            // Create mesh
+            meshes.push(json::Mesh {
                 extensions: None,
                 extras: Default::default(),
-                type_: json::validation::Checked::Valid(json::accessor::Type::Vec3),
-                min: None,
-                max: None,
                 name: None,
-                normalized: false,
-                sparse: None,
+                primitives,
+                weights: None,
             });
-
-            Some(idx)
-        } else {
-            None
-        };
-
-        // --- Create index buffer and accessor ---
-        let index_bytes: Vec<u8> = indices.iter().flat_map(|i| i.to_le_bytes()).collect();
-        let index_offset = buffer_data.len();
-        buffer_data.extend_from_slice(&index_bytes);
-
-        let index_view_idx = buffer_views.len();
-        buffer_views.push(json::buffer::View {
-            buffer: json::Index::new(0),
-            byte_length: USize64::from(index_bytes.len()),
-            byte_offset: Some(USize64::from(index_offset)),
-            byte_stride: None,
-            extensions: None,
-            extras: Default::default(),
-            name: None,
-            target: Some(json::validation::Checked::Valid(
-                json::buffer::Target::ElementArrayBuffer,
-            )),
-        });
-
-        let index_accessor_idx = accessors.len();
-        accessors.push(json::Accessor {
-            buffer_view: Some(json::Index::new(index_view_idx as u32)),
-            byte_offset: Some(USize64(0)),
-            count: USize64::from(indices.len()),
-            component_type: json::validation::Checked::Valid(json::accessor::GenericComponentType(
-                json::accessor::ComponentType::U16,
-            )),
-            extensions: None,
-            extras: Default::default(),
-            type_: json::validation::Checked::Valid(json::accessor::Type::Scalar),
-            min: None,
-            max: None,
-            name: None,
-            normalized: false,
-            sparse: None,
-        });
-
-        // --- Create mesh primitive with triangles ---
-        let mut attributes = std::collections::BTreeMap::new();
-        attributes.insert(
-            json::validation::Checked::Valid(json::mesh::Semantic::Positions),
-            json::Index::new(position_accessor_idx as u32),
-        );
-
-        if let Some(normal_idx) = normal_accessor_idx {
-            attributes.insert(
-                json::validation::Checked::Valid(json::mesh::Semantic::Normals),
-                json::Index::new(normal_idx as u32),
-            );
+            mesh_objects.push(object_index);
         }
-
-        let primitive = json::mesh::Primitive {
-            attributes,
-            extensions: None,
-            extras: Default::default(),
-            indices: Some(json::Index::new(index_accessor_idx as u32)),
-            material: None,
-            mode: json::validation::Checked::Valid(json::mesh::Mode::Triangles),
-            targets: None,
-        };
-
-        // Create mesh
-        meshes.push(json::Mesh {
-            extensions: None,
-            extras: Default::default(),
-            name: None,
-            primitives: vec![primitive],
-            weights: None,
-        });
     }
 
     // If no meshes were created, return an error
@@ -287,31 +562,1028 @@ pub fn tmd_to_gltf(tmd: &Tmd, output_path: &Path) -> Result<()> {
             .collect(),
     };
 
+    if let Some(animation) = animation {
+        root.animations = vec![build_animation(animation, &mesh_objects, &mut buffer)?];
+    }
+
+    let stem = output_path
+        .file_stem()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+
     // Build root
-    root.accessors = accessors;
+    root.accessors = buffer.accessors;
     root.buffers = vec![json::Buffer {
-        byte_length: USize64::from(buffer_data.len()),
+        byte_length: USize64::from(buffer.data.len()),
         extensions: None,
         extras: Default::default(),
         name: None,
-        uri: Some(format!(
-            "{}.bin",
-            output_path.file_stem().unwrap().to_string_lossy()
-        )),
+        uri: None,
     }];
-    root.buffer_views = buffer_views;
+    root.buffer_views = buffer.views;
     root.meshes = meshes;
     root.nodes = nodes;
     root.scenes = vec![scene];
     root.scene = Some(json::Index::new(0));
 
-    // Write glTF JSON
-    let gltf_json = json::serialize::to_string_pretty(&root)?;
-    fs::write(output_path, gltf_json)?;
+    if let Some(texture) = texture {
+        attach_textures(&mut root, &mut materials.list, texture, output_path, &stem)?;
+    }
+    root.materials = materials.list.into_iter().map(|(_, m)| m).collect();
 
-    // Write binary buffer
-    let bin_path = output_path.with_extension("bin");
-    fs::write(bin_path, buffer_data)?;
+    Ok((root, buffer.data))
+}
+
+/// Build a glTF animation targeting the nodes of animated objects
+///
+/// All channels share one keyframe time accessor. Objects without a mesh
+/// node (skipped as empty) are not animated.
+fn build_animation(
+    animation: &TmdAnimation,
+    mesh_objects: &[usize],
+    buffer: &mut GltfBuffer,
+) -> Result<json::Animation> {
+    if animation.frames.is_empty() || animation.frame_rate <= 0.0 {
+        return Err(anyhow::anyhow!(
+            "Animation needs at least one frame and a positive frame rate"
+        ));
+    }
+
+    let times: Vec<f32> = (0..animation.frames.len())
+        .map(|frame| frame as f32 / animation.frame_rate)
+        .collect();
+    let input = buffer.push_floats(
+        &times,
+        json::accessor::Type::Scalar,
+        Some(json::Value::from(vec![0.0f32])),
+        Some(json::Value::from(vec![animation.duration_secs()])),
+    );
+
+    let mut samplers = Vec::new();
+    let mut channels = Vec::new();
+    for object_index in 0..animation.animated_objects() {
+        let Some(node) = mesh_objects.iter().position(|&i| i == object_index) else {
+            continue;
+        };
+
+        let rotations: Vec<f32> = animation
+            .frames
+            .iter()
+            .flat_map(|frame| frame[object_index].rotation)
+            .collect();
+        let translations: Vec<f32> = animation
+            .frames
+            .iter()
+            .flat_map(|frame| frame[object_index].translation)
+            .collect();
+
+        for (values, type_, path) in [
+            (
+                rotations,
+                json::accessor::Type::Vec4,
+                json::animation::Property::Rotation,
+            ),
+            (
+                translations,
+                json::accessor::Type::Vec3,
+                json::animation::Property::Translation,
+            ),
+        ] {
+            let output = buffer.push_floats(&values, type_, None, None);
+            channels.push(json::animation::Channel {
+                sampler: json::Index::new(samplers.len() as u32),
+                target: json::animation::Target {
+                    extensions: None,
+                    extras: Default::default(),
+                    node: json::Index::new(node as u32),
+                    path: Checked::Valid(path),
+                },
+                extensions: None,
+                extras: Default::default(),
+            });
+            samplers.push(json::animation::Sampler {
+                extensions: None,
+                extras: Default::default(),
+                input,
+                interpolation: Checked::Valid(json::animation::Interpolation::Linear),
+                output,
+            });
+        }
+    }
+
+    if channels.is_empty() {
+        return Err(anyhow::anyhow!("Animation does not target any mesh object"));
+    }
+
+    Ok(json::Animation {
+        extensions: None,
+        extras: Default::default(),
+        channels,
+        name: animation.name.clone(),
+        samplers,
+    })
+}
+
+/// Assemble a GLB container from a JSON document and binary buffer
+///
+/// Chunks are padded to 4-byte alignment: JSON with spaces, BIN with zeros.
+fn glb_bytes(json_bytes: &[u8], bin: &[u8]) -> Vec<u8> {
+    let json_len = json_bytes.len().next_multiple_of(4);
+    let bin_len = bin.len().next_multiple_of(4);
+    let total_len = 12 + 8 + json_len + 8 + bin_len;
+
+    let mut glb = Vec::with_capacity(total_len);
+    glb.extend_from_slice(&GLB_MAGIC.to_le_bytes());
+    glb.extend_from_slice(&GLB_VERSION.to_le_bytes());
+    glb.extend_from_slice(&(total_len as u32).to_le_bytes());
+
+    glb.extend_from_slice(&(json_len as u32).to_le_bytes());
+    glb.extend_from_slice(&GLB_CHUNK_JSON.to_le_bytes());
+    glb.extend_from_slice(json_bytes);
+    glb.resize(12 + 8 + json_len, b' ');
+
+    glb.extend_from_slice(&(bin_len as u32).to_le_bytes());
+    glb.extend_from_slice(&GLB_CHUNK_BIN.to_le_bytes());
+    glb.extend_from_slice(bin);
+    glb.resize(total_len, 0);
+
+    glb
+}
+
+/// Binary buffer with its views and accessors under construction
+#[derive(Default)]
+struct GltfBuffer {
+    data: Vec<u8>,
+    views: Vec<json::buffer::View>,
+    accessors: Vec<json::Accessor>,
+}
+
+impl GltfBuffer {
+    /// Append `bytes` as a new buffer view and return an accessor over it
+    #[allow(clippy::too_many_arguments)]
+    fn push(
+        &mut self,
+        bytes: &[u8],
+        target: json::buffer::Target,
+        count: usize,
+        component_type: json::accessor::ComponentType,
+        type_: json::accessor::Type,
+        min: Option<json::Value>,
+        max: Option<json::Value>,
+    ) -> json::Index<json::Accessor> {
+        let offset = self.data.len();
+        self.data.extend_from_slice(bytes);
+
+        let view_idx = self.views.len();
+        self.views.push(json::buffer::View {
+            buffer: json::Index::new(0),
+            byte_length: USize64::from(bytes.len()),
+            byte_offset: Some(USize64::from(offset)),
+            byte_stride: None,
+            extensions: None,
+            extras: Default::default(),
+            name: None,
+            target: Some(Checked::Valid(target)),
+        });
+
+        let accessor_idx = self.accessors.len();
+        self.accessors.push(json::Accessor {
+            buffer_view: Some(json::Index::new(view_idx as u32)),
+            byte_offset: Some(USize64(0)),
+            count: USize64::from(count),
+            component_type: Checked::Valid(json::accessor::GenericComponentType(component_type)),
+            extensions: None,
+            extras: Default::default(),
+            type_: Checked::Valid(type_),
+            min,
+            max,
+            name: None,
+            normalized: false,
+            sparse: None,
+        });
+
+        json::Index::new(accessor_idx as u32)
+    }
+
+    /// Append float data that is not a vertex attribute (e.g. animation
+    /// keyframes); such buffer views must not declare a target
+    fn push_floats(
+        &mut self,
+        values: &[f32],
+        type_: json::accessor::Type,
+        min: Option<json::Value>,
+        max: Option<json::Value>,
+    ) -> json::Index<json::Accessor> {
+        let bytes: Vec<u8> = values.iter().flat_map(|f| f.to_le_bytes()).collect();
+        let components = type_.multiplicity();
+
+        let index = self.push(
+            &bytes,
+            json::buffer::Target::ArrayBuffer,
+            values.len() / components,
+            json::accessor::ComponentType::F32,
+            type_,
+            min,
+            max,
+        );
+        if let Some(view) = self.views.last_mut() {
+            view.target = None;
+        }
+        index
+    }
+
+    /// Append a VEC3 float attribute, with min/max bounds if requested
+    fn push_vec3(&mut self, values: &[[f32; 3]], bounds: bool) -> json::Index<json::Accessor> {
+        let bytes: Vec<u8> = values
+            .iter()
+            .flatten()
+            .flat_map(|f| f.to_le_bytes())
+            .collect();
+
+        let (min, max) = if bounds {
+            let mut min = [f32::MAX; 3];
+            let mut max = [f32::MIN; 3];
+            for value in values {
+                for ((lo, hi), &x) in min.iter_mut().zip(max.iter_mut()).zip(value) {
+                    *lo = lo.min(x);
+                    *hi = hi.max(x);
+                }
+            }
+            (
+                Some(json::Value::from(min.to_vec())),
+                Some(json::Value::from(max.to_vec())),
+            )
+        } else {
+            (None, None)
+        };
+
+        self.push(
+            &bytes,
+            json::buffer::Target::ArrayBuffer,
+            values.len(),
+            json::accessor::ComponentType::F32,
+            json::accessor::Type::Vec3,
+            min,
+            max,
+        )
+    }
+}
+
+/// Materials keyed by texture page and CLUT, in creation order
+#[derive(Default)]
+struct Materials {
+    list: Vec<(TextureKey, json::Material)>,
+    by_key: HashMap<TextureKey, u32>,
+}
+
+/// Texture page plus CLUT position identifying one material
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct TextureKey {
+    tpage: u16,
+    clut_x: u16,
+    clut_y: u16,
+}
+
+impl TextureKey {
+    fn new(info: &TextureInfo) -> Self {
+        Self {
+            tpage: info.tpage,
+            clut_x: info.clut_x,
+            clut_y: info.clut_y,
+        }
+    }
+
+    /// Material name for this texture page and CLUT
+    fn material_name(&self) -> String {
+        format!(
+            "tpage_{:04x}_clut_{}_{}",
+            self.tpage, self.clut_x, self.clut_y
+        )
+    }
+
+    /// CLUT attribute (CBA) as the GPU encodes it
+    fn cba(&self) -> u16 {
+        (self.clut_y << 6) | ((self.clut_x / 16) & 0x3F)
+    }
+}
+
+impl Materials {
+    fn get_or_insert(&mut self, key: TextureKey) -> json::Index<json::Material> {
+        let index = *self.by_key.entry(key).or_insert_with(|| {
+            self.list.push((
+                key,
+                json::Material {
+                    name: Some(key.material_name()),
+                    double_sided: true,
+                    ..Default::default()
+                },
+            ));
+            (self.list.len() - 1) as u32
+        });
+        json::Index::new(index)
+    }
+}
+
+/// Normalize a fixed-point (4096 = 1.0) TMD normal
+fn normalize_normal(normal: &TmdNormal) -> [f32; 3] {
+    let nx = normal.nx as f32 / 4096.0;
+    let ny = normal.ny as f32 / 4096.0;
+    let nz = normal.nz as f32 / 4096.0;
+    let len = (nx * nx + ny * ny + nz * nz).sqrt();
+
+    if len > 0.0 {
+        [nx / len, ny / len, nz / len]
+    } else {
+        [0.0, 1.0, 0.0]
+    }
+}
+
+/// Vertex positions of an object with its scale applied
+fn object_positions(object: &TmdObject) -> Vec<[f32; 3]> {
+    let scale = if object.scale == 0 {
+        1.0
+    } else {
+        object.scale as f32
+    };
+
+    object
+        .vertices
+        .iter()
+        .map(|v| [v.x as f32 / scale, v.y as f32 / scale, v.z as f32 / scale])
+        .collect()
+}
+
+/// Per-corner data of a triangle or quad primitive
+struct Polygon<'a> {
+    vertices: &'a [u16],
+    normals: Option<&'a [u16]>,
+    uvs: Option<&'a [(u8, u8)]>,
+    colors: Option<&'a [(u8, u8, u8)]>,
+    texture_info: Option<&'a TextureInfo>,
+}
+
+impl<'a> Polygon<'a> {
+    /// Corner data of a primitive; lines have no area and give `None`
+    fn new(primitive: &'a TmdPrimitive) -> Option<Self> {
+        let polygon = match primitive {
+            TmdPrimitive::Triangle {
+                vertices,
+                normals,
+                uvs,
+                colors,
+                texture_info,
+            } => Self {
+                vertices,
+                normals: normals.as_ref().map(|n| &n[..]),
+                uvs: uvs.as_ref().map(|uv| &uv[..]),
+                colors: colors.as_ref().map(|c| &c[..]),
+                texture_info: texture_info.as_ref(),
+            },
+            TmdPrimitive::Quad {
+                vertices,
+                normals,
+                uvs,
+                colors,
+                texture_info,
+            } => Self {
+                vertices,
+                normals: normals.as_ref().map(|n| &n[..]),
+                uvs: uvs.as_ref().map(|uv| &uv[..]),
+                colors: colors.as_ref().map(|c| &c[..]),
+                texture_info: texture_info.as_ref(),
+            },
+            TmdPrimitive::Line { .. } => return None,
+        };
+        Some(polygon)
+    }
+
+    /// Corners of each triangle: 0-1-2, and 0-2-3 as well for quads
+    fn triangles(&self) -> &'static [[usize; 3]] {
+        if self.vertices.len() == 4 {
+            &[[0, 1, 2], [0, 2, 3]]
+        } else {
+            &[[0, 1, 2]]
+        }
+    }
+}
+
+/// Triangles and quads of an object, skipping primitives that reference
+/// vertices which don't exist
+fn object_polygons(object: &TmdObject) -> impl Iterator<Item = Polygon<'_>> {
+    object
+        .primitives
+        .iter()
+        .filter_map(Polygon::new)
+        .filter(|polygon| {
+            polygon
+                .vertices
+                .iter()
+                .all(|&v| (v as usize) < object.vertices.len())
+        })
+}
+
+/// Vertex and index data of one glTF mesh under construction
+///
+/// Objects are appended one after another, with their indices offset past
+/// the vertices already added, so a mesh can hold a single object or a
+/// whole merged model. Every vertex gets a normal, UV and color (defaults
+/// where the object has none); each attribute is only emitted if some
+/// appended object actually had it.
+#[derive(Default)]
+struct MeshData {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    colors: Vec<[f32; 3]>,
+    has_normals: bool,
+    has_uvs: bool,
+    has_colors: bool,
+    /// Triangle indices per material
+    groups: BTreeMap<Option<TextureKey>, Vec<u32>>,
+}
+
+/// Normal of vertices without one
+const DEFAULT_NORMAL: [f32; 3] = [0.0, 1.0, 0.0];
+
+/// Color of vertices without one; white leaves textures unmodulated
+const DEFAULT_COLOR: [f32; 3] = [1.0, 1.0, 1.0];
+
+impl MeshData {
+    /// Append an object's triangles
+    ///
+    /// UVs and colors are per polygon corner, so objects with either are
+    /// de-indexed (every corner becomes its own vertex); others are added
+    /// indexed.
+    fn append_object(&mut self, object: &TmdObject) {
+        let has_corner_data = object
+            .primitives
+            .iter()
+            .filter_map(Polygon::new)
+            .any(|polygon| polygon.uvs.is_some() || polygon.colors.is_some());
+
+        if has_corner_data {
+            self.append_expanded(object);
+        } else {
+            self.append_indexed(object);
+        }
+    }
+
+    /// Append an untextured object, sharing its vertices between triangles
+    fn append_indexed(&mut self, object: &TmdObject) {
+        let vertex_count = object.vertices.len();
+        let base = self.positions.len() as u32;
+        let mut indices: Vec<u32> = Vec::new();
+
+        for polygon in object_polygons(object) {
+            let corners = polygon.triangles().iter().flatten();
+            indices.extend(corners.map(|&corner| base + polygon.vertices[corner] as u32));
+        }
+
+        if indices.is_empty() {
+            return;
+        }
+
+        self.positions.extend(object_positions(object));
+        self.normals.extend((0..vertex_count).map(|i| {
+            object
+                .normals
+                .get(i)
+                .map(normalize_normal)
+                .unwrap_or(DEFAULT_NORMAL)
+        }));
+        self.uvs.resize(self.positions.len(), [0.0, 0.0]);
+        self.colors.resize(self.positions.len(), DEFAULT_COLOR);
+        self.has_normals |= !object.normals.is_empty();
+        self.groups.entry(None).or_default().extend(indices);
+    }
+
+    /// Append an object with UVs or colors, one vertex per polygon corner
+    fn append_expanded(&mut self, object: &TmdObject) {
+        let positions = object_positions(object);
+
+        for polygon in object_polygons(object) {
+            let key = polygon.texture_info.map(TextureKey::new);
+            let indices = self.groups.entry(key).or_default();
+
+            for &corner in polygon.triangles().iter().flatten() {
+                indices.push(self.positions.len() as u32);
+                self.positions
+                    .push(positions[polygon.vertices[corner] as usize]);
+
+                let normal = polygon
+                    .normals
+                    .and_then(|n| object.normals.get(n[corner] as usize))
+                    .map(normalize_normal)
+                    .unwrap_or(DEFAULT_NORMAL);
+                self.normals.push(normal);
+
+                let (u, v) = polygon.uvs.map(|uv| uv[corner]).unwrap_or((0, 0));
+                self.uvs.push([u as f32 / 255.0, v as f32 / 255.0]);
+
+                let color = polygon
+                    .colors
+                    .map(|c| c[corner])
+                    .map(|(r, g, b)| [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0])
+                    .unwrap_or(DEFAULT_COLOR);
+                self.colors.push(color);
+            }
+
+            self.has_uvs |= polygon.uvs.is_some();
+            self.has_colors |= polygon.colors.is_some();
+        }
+
+        self.has_normals |= !object.normals.is_empty();
+    }
+
+    /// Write the vertex data to the buffer and build one primitive per
+    /// material
+    fn finish(
+        self,
+        buffer: &mut GltfBuffer,
+        materials: &mut Materials,
+    ) -> Vec<json::mesh::Primitive> {
+        if self.groups.values().all(Vec::is_empty) {
+            return Vec::new();
+        }
+
+        let mut attributes = BTreeMap::new();
+        attributes.insert(
+            Checked::Valid(json::mesh::Semantic::Positions),
+            buffer.push_vec3(&self.positions, true),
+        );
+
+        if self.has_normals {
+            attributes.insert(
+                Checked::Valid(json::mesh::Semantic::Normals),
+                buffer.push_vec3(&self.normals, false),
+            );
+        }
+
+        if self.has_uvs {
+            let uv_bytes: Vec<u8> = self
+                .uvs
+                .iter()
+                .flatten()
+                .flat_map(|f| f.to_le_bytes())
+                .collect();
+            attributes.insert(
+                Checked::Valid(json::mesh::Semantic::TexCoords(0)),
+                buffer.push(
+                    &uv_bytes,
+                    json::buffer::Target::ArrayBuffer,
+                    self.uvs.len(),
+                    json::accessor::ComponentType::F32,
+                    json::accessor::Type::Vec2,
+                    None,
+                    None,
+                ),
+            );
+        }
+
+        if self.has_colors {
+            attributes.insert(
+                Checked::Valid(json::mesh::Semantic::Colors(0)),
+                buffer.push_vec3(&self.colors, false),
+            );
+        }
+
+        let vertex_count = self.positions.len();
+
+        self.groups
+            .into_iter()
+            .filter(|(_, indices)| !indices.is_empty())
+            .map(|(key, indices)| {
+                let (index_bytes, component_type) = encode_indices(&indices, vertex_count);
+                let index_accessor = buffer.push(
+                    &index_bytes,
+                    json::buffer::Target::ElementArrayBuffer,
+                    indices.len(),
+                    component_type,
+                    json::accessor::Type::Scalar,
+                    None,
+                    None,
+                );
+
+                json::mesh::Primitive {
+                    attributes: attributes.clone(),
+                    extensions: None,
+                    extras: Default::default(),
+                    indices: Some(index_accessor),
+                    material: key.map(|key| materials.get_or_insert(key)),
+                    mode: Checked::Valid(json::mesh::Mode::Triangles),
+                    targets: None,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Encode triangle indices for a mesh with `vertex_count` vertices
+///
+/// Uses u16 when every index fits and u32 otherwise. The largest value of
+/// each type is reserved for primitive restart, so u16 covers at most
+/// 65535 vertices (indices 0..=65534).
+fn encode_indices(
+    indices: &[u32],
+    vertex_count: usize,
+) -> (Vec<u8>, json::accessor::ComponentType) {
+    if vertex_count <= u16::MAX as usize {
+        (
+            indices
+                .iter()
+                .flat_map(|&i| (i as u16).to_le_bytes())
+                .collect(),
+            json::accessor::ComponentType::U16,
+        )
+    } else {
+        (
+            indices.iter().flat_map(|i| i.to_le_bytes()).collect(),
+            json::accessor::ComponentType::U32,
+        )
+    }
+}
+
+/// Add images/textures/samplers and wire them into every material
+fn attach_textures(
+    root: &mut json::Root,
+    materials: &mut [(TextureKey, json::Material)],
+    texture: &TmdTextureSource,
+    output_path: &Path,
+    stem: &str,
+) -> Result<()> {
+    if materials.is_empty() {
+        return Ok(());
+    }
+
+    // PSX textures are point-sampled
+    root.samplers = vec![json::texture::Sampler {
+        mag_filter: Some(Checked::Valid(json::texture::MagFilter::Nearest)),
+        min_filter: Some(Checked::Valid(json::texture::MinFilter::Nearest)),
+        name: None,
+        wrap_s: Checked::Valid(json::texture::WrappingMode::ClampToEdge),
+        wrap_t: Checked::Valid(json::texture::WrappingMode::ClampToEdge),
+        extensions: None,
+        extras: Default::default(),
+    }];
+
+    let mut shared = None;
+    for (key, material) in materials.iter_mut() {
+        let texture_index = match texture {
+            TmdTextureSource::Image(uri) => {
+                *shared.get_or_insert_with(|| add_texture(root, uri.to_string()))
+            }
+            TmdTextureSource::Vram(vram) => {
+                // Texture coordinates are bytes, so a page is at most 256x256 texels
+                let cba = key.cba();
+                let mut rgba = Vec::with_capacity(256 * 256 * 4);
+                for v in 0..=255u8 {
+                    for u in 0..=255u8 {
+                        rgba.extend_from_slice(&vram.resolve_texture(key.tpage, cba, u, v));
+                    }
+                }
+
+                let file_name = format!("{}_tpage_{:04x}_{:04x}.png", stem, key.tpage, cba);
+                image::save_buffer(
+                    output_path.with_file_name(&file_name),
+                    &rgba,
+                    256,
+                    256,
+                    image::ColorType::Rgba8,
+                )?;
+                add_texture(root, file_name)
+            }
+        };
+
+        material.pbr_metallic_roughness.base_color_texture = Some(json::texture::Info {
+            index: texture_index,
+            tex_coord: 0,
+            extensions: None,
+            extras: Default::default(),
+        });
+        // Black with STP clear is the PSX transparency key
+        material.alpha_mode = Checked::Valid(json::material::AlphaMode::Mask);
+        material.alpha_cutoff = Some(json::material::AlphaCutoff(0.5));
+    }
 
     Ok(())
 }
+
+/// Add a PNG image and a texture sampling it with the shared sampler
+fn add_texture(root: &mut json::Root, uri: String) -> json::Index<json::Texture> {
+    root.images.push(json::Image {
+        buffer_view: None,
+        mime_type: Some(json::image::MimeType("image/png".to_string())),
+        name: None,
+        uri: Some(uri),
+        extensions: None,
+        extras: Default::default(),
+    });
+    root.textures.push(json::Texture {
+        name: None,
+        sampler: Some(json::Index::new(0)),
+        source: json::Index::new((root.images.len() - 1) as u32),
+        extensions: None,
+        extras: Default::default(),
+    });
+    json::Index::new((root.textures.len() - 1) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use psxutils::formats::tmd::TmdVertex;
+
+    fn triangle_tmd() -> Tmd {
+        Tmd {
+            flags: 0,
+            objects: vec![TmdObject {
+                vertices: vec![
+                    TmdVertex { x: 0, y: 0, z: 0 },
+                    TmdVertex { x: 100, y: 0, z: 0 },
+                    TmdVertex { x: 0, y: 100, z: 0 },
+                ],
+                normals: Vec::new(),
+                primitives: vec![TmdPrimitive::Triangle {
+                    vertices: [0, 1, 2],
+                    normals: None,
+                    uvs: None,
+                    colors: None,
+                    texture_info: None,
+                }],
+                scale: 0,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_obj_face_count() {
+        let mut tmd = triangle_tmd();
+        let object = &mut tmd.objects[0];
+        object.vertices.push(TmdVertex {
+            x: 100,
+            y: 100,
+            z: 0,
+        });
+        object.primitives.extend([
+            TmdPrimitive::Triangle {
+                vertices: [1, 3, 2],
+                normals: None,
+                uvs: Some([(0, 0), (255, 0), (0, 255)]),
+                colors: None,
+                texture_info: Some(TextureInfo {
+                    clut_x: 0,
+                    clut_y: 480,
+                    tpage: 0x0008,
+                }),
+            },
+            TmdPrimitive::Quad {
+                vertices: [0, 1, 2, 3],
+                normals: None,
+                uvs: None,
+                colors: None,
+                texture_info: None,
+            },
+            TmdPrimitive::Line {
+                vertices: [0, 1],
+                colors: None,
+            },
+        ]);
+        tmd.objects.push(triangle_tmd().objects.remove(0));
+
+        let path = std::env::temp_dir().join(format!("obj_{}.obj", std::process::id()));
+        tmd_to_obj(&tmd, &path).unwrap();
+        let obj = fs::read_to_string(&path).unwrap();
+        let mtl = fs::read_to_string(path.with_extension("mtl")).unwrap();
+        let count = |prefix: &str| obj.lines().filter(|l| l.starts_with(prefix)).count();
+
+        // 3 triangles + 2 x 1 quad; the line is dropped
+        assert_eq!(count("f "), 3 + 2);
+        assert_eq!(count("o "), 2);
+        assert_eq!(count("v "), 4 + 3);
+        assert_eq!(count("vt "), 3);
+        assert!(obj.contains("vt 1 1\n"));
+
+        // The second object's faces index past the first object's vertices
+        assert!(obj.lines().any(|l| l == "f 5 6 7"));
+        assert!(obj.lines().any(|l| l == "f 2/1 4/2 3/3"));
+        assert!(obj.contains("usemtl tpage_0008_clut_0_480\n"));
+        assert!(mtl.contains("newmtl tpage_0008_clut_0_480\n"));
+        assert!(mtl.contains("newmtl untextured\n"));
+
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(path.with_extension("mtl")).unwrap();
+    }
+
+    #[test]
+    fn test_wav_smpl_chunk() {
+        let samples = vec![0i16; 56];
+
+        let wav = wav_bytes(&samples, 22050, None);
+        assert_eq!(wav.len(), 44 + 112);
+        assert_eq!(&wav[36..40], b"data");
+
+        let wav = wav_bytes(&samples, 22050, Some((28, 56)));
+        let riff_size = u32::from_le_bytes(wav[4..8].try_into().unwrap());
+        assert_eq!(riff_size as usize, wav.len() - 8);
+
+        let smpl = &wav[44 + 112..];
+        assert_eq!(&smpl[0..4], b"smpl");
+        assert_eq!(u32::from_le_bytes(smpl[4..8].try_into().unwrap()), 60);
+        let field = |i: usize| u32::from_le_bytes(smpl[8 + i * 4..12 + i * 4].try_into().unwrap());
+        assert_eq!(field(7), 1); // Loop count
+        assert_eq!(field(11), 28); // Loop start
+        assert_eq!(field(12), 55); // Inclusive loop end
+    }
+
+    #[test]
+    fn test_write_wav_with_loop() {
+        let samples = vec![0i16; 56];
+        let has_smpl = |wav: &[u8]| wav.windows(4).any(|chunk| chunk == b"smpl");
+
+        let path = std::env::temp_dir().join(format!("loop_{}.wav", std::process::id()));
+        write_wav_with_loop(&path, &samples, 22050, Some(28), Some(56)).unwrap();
+        let wav = fs::read(&path).unwrap();
+        assert!(has_smpl(&wav));
+        let loop_field = |i: usize| {
+            let offset = 44 + 112 + 8 + 36 + i * 4;
+            u32::from_le_bytes(wav[offset..offset + 4].try_into().unwrap())
+        };
+        assert_eq!(loop_field(2), 28);
+        assert_eq!(loop_field(3), 55);
+
+        // hound reads the data chunk back regardless of the trailing chunk
+        let reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.len(), 56);
+        drop(reader);
+
+        write_wav_with_loop(&path, &samples, 22050, Some(28), None).unwrap();
+        let wav = fs::read(&path).unwrap();
+        assert!(!has_smpl(&wav));
+        assert_eq!(wav.len(), 44 + 112);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_audio_format_from_str() {
+        assert_eq!("FLAC".parse::<AudioFormat>().unwrap(), AudioFormat::Flac);
+        assert_eq!("ogg".parse::<AudioFormat>().unwrap().extension(), "ogg");
+        assert!("mp3".parse::<AudioFormat>().is_err());
+        assert_eq!(AudioFormat::default(), AudioFormat::Wav);
+    }
+
+    #[test]
+    fn test_glb_header() {
+        let tmd = triangle_tmd();
+
+        let path = std::env::temp_dir().join("legaia_assets_test_glb_header.glb");
+        tmd_to_glb(&tmd, &path).unwrap();
+        let glb = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(&glb[0..4], b"glTF");
+        assert_eq!(u32::from_le_bytes(glb[4..8].try_into().unwrap()), 2);
+        assert_eq!(
+            u32::from_le_bytes(glb[8..12].try_into().unwrap()) as usize,
+            glb.len()
+        );
+        assert_eq!(&glb[16..20], b"JSON");
+    }
+
+    #[test]
+    fn test_animation_channels() {
+        use crate::formats::ObjectTransform;
+
+        let tmd = triangle_tmd();
+        let animation = TmdAnimation {
+            name: Some("walk".to_string()),
+            frame_rate: 30.0,
+            frames: vec![
+                vec![ObjectTransform::default()],
+                vec![ObjectTransform::from_psx([0, 1024, 0], [0, 10, 0])],
+                vec![ObjectTransform::default()],
+            ],
+        };
+
+        let path = std::env::temp_dir().join("legaia_assets_test_animation.gltf");
+        let (root, _) = build_gltf(&tmd, &path, None, Some(&animation), false).unwrap();
+
+        assert_eq!(root.animations.len(), 1);
+        let gltf_animation = &root.animations[0];
+        assert_eq!(gltf_animation.name.as_deref(), Some("walk"));
+        assert_eq!(gltf_animation.channels.len(), 2);
+        assert_eq!(gltf_animation.samplers.len(), 2);
+        assert_eq!(gltf_animation.channels[0].target.node.value(), 0);
+
+        let input = &root.accessors[gltf_animation.samplers[0].input.value()];
+        assert_eq!(input.count, USize64(3));
+        let output = &root.accessors[gltf_animation.samplers[0].output.value()];
+        assert_eq!(output.count, USize64(3));
+        assert!(root.buffer_views[input.buffer_view.unwrap().value()]
+            .target
+            .is_none());
+    }
+
+    #[test]
+    fn test_merged_index_offsets() {
+        let mut tmd = triangle_tmd();
+        let mut second = tmd.objects[0].clone();
+        second.vertices.push(TmdVertex { x: 0, y: 0, z: 100 });
+        second.primitives = vec![TmdPrimitive::Quad {
+            vertices: [0, 1, 2, 3],
+            normals: None,
+            uvs: None,
+            colors: None,
+            texture_info: None,
+        }];
+        tmd.objects.push(second);
+
+        let path = std::env::temp_dir().join("legaia_assets_test_merged.gltf");
+        let (root, data) = build_gltf(&tmd, &path, None, None, true).unwrap();
+
+        assert_eq!(root.meshes.len(), 1);
+        assert_eq!(root.nodes.len(), 1);
+        let primitive = &root.meshes[0].primitives[0];
+        let positions = &root.accessors
+            [primitive.attributes[&Checked::Valid(json::mesh::Semantic::Positions)].value()];
+        assert_eq!(positions.count, USize64(7));
+
+        let indices = &root.accessors[primitive.indices.unwrap().value()];
+        let view = &root.buffer_views[indices.buffer_view.unwrap().value()];
+        let offset = view.byte_offset.unwrap().0 as usize;
+        let indices: Vec<u16> = data[offset..offset + view.byte_length.0 as usize]
+            .chunks_exact(2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .collect();
+
+        // The second object's vertices start after the first object's three
+        assert_eq!(indices, [0, 1, 2, 3, 4, 5, 3, 5, 6]);
+    }
+
+    #[test]
+    fn test_large_merge_uses_u32_indices() {
+        let mut mesh = MeshData::default();
+        let mut object = triangle_tmd().objects.remove(0);
+        object.vertices = vec![TmdVertex { x: 0, y: 0, z: 0 }; 40000];
+        mesh.append_object(&object);
+        mesh.append_object(&object);
+
+        let mut buffer = GltfBuffer::default();
+        let primitives = mesh.finish(&mut buffer, &mut Materials::default());
+        let indices = &buffer.accessors[primitives[0].indices.unwrap().value()];
+        assert!(matches!(
+            indices.component_type,
+            Checked::Valid(json::accessor::GenericComponentType(
+                json::accessor::ComponentType::U32
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_index_width_boundary() {
+        let (bytes, component_type) = encode_indices(&[0, 65534], 65535);
+        assert_eq!(component_type, json::accessor::ComponentType::U16);
+        assert_eq!(bytes, [0, 0, 0xfe, 0xff]);
+
+        let (bytes, component_type) = encode_indices(&[0, 65535], 65536);
+        assert_eq!(component_type, json::accessor::ComponentType::U32);
+        assert_eq!(bytes.len(), 8);
+    }
+
+    #[test]
+    fn test_large_object_uses_u32_indices() {
+        // De-indexed quads: 6 corners each, 12000 quads = 72000 vertices
+        let mut object = triangle_tmd().objects.remove(0);
+        object.vertices.push(TmdVertex { x: 0, y: 0, z: 100 });
+        object.primitives = vec![
+            TmdPrimitive::Quad {
+                vertices: [0, 1, 2, 3],
+                normals: None,
+                uvs: None,
+                colors: Some([(255, 0, 0); 4]),
+                texture_info: None,
+            };
+            12000
+        ];
+        let tmd = Tmd {
+            flags: 0,
+            objects: vec![object],
+        };
+
+        let path = std::env::temp_dir().join("legaia_assets_test_large.gltf");
+        let (root, _) = build_gltf(&tmd, &path, None, None, false).unwrap();
+
+        let primitive = &root.meshes[0].primitives[0];
+        let indices = &root.accessors[primitive.indices.unwrap().value()];
+        assert!(matches!(
+            indices.component_type,
+            Checked::Valid(json::accessor::GenericComponentType(
+                json::accessor::ComponentType::U32
+            ))
+        ));
+        assert_eq!(indices.count, USize64(72000));
+        let view = &root.buffer_views[indices.buffer_view.unwrap().value()];
+        assert_eq!(view.byte_length, USize64(72000 * 4));
+    }
+}
@@ -2,16 +2,33 @@
 //!
 //! Provides high-level API for extracting and converting assets from PSX disc.
 
-use crate::converter::tmd_to_gltf;
-use anyhow::{Context, Result};
+use crate::converter::{tmd_to_gltf, tmd_to_gltf_with_texture, vag_to_wav, TmdTextureSource};
+use crate::manifest::{
+    sha256_hex, AssetEntry, AssetManifest, AssetType, ExtractionManifest, SourceInfo,
+};
+use anyhow::{bail, Context, Result};
 use psxutils::cdrom::CdRom;
 use psxutils::formats::{Tim, Tmd, Vag};
+use psxutils::{
+    detect_format, AssetScanner, DiscoveredAsset, KnownFormat, SequentialScanner, VramAtlas,
+};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// File name of the asset manifest written to the output directory
+pub const ASSET_MANIFEST_FILE: &str = "manifest.json";
+
+/// Save the extraction manifest after this many newly extracted files
+const MANIFEST_SAVE_INTERVAL: usize = 64;
 
 /// Progress callback for extraction
+///
+/// Called concurrently from rayon worker threads while files are extracted,
+/// so reports may arrive out of order.
 pub type ProgressCallback = Arc<dyn Fn(ExtractionProgress) + Send + Sync>;
 
 /// Extraction progress information
@@ -25,6 +42,12 @@ pub struct ExtractionProgress {
     pub processed_files: usize,
     /// Files successfully converted
     pub converted_files: usize,
+    /// Files skipped because their output is up to date
+    pub skipped_files: usize,
+    /// Total size of the files to process, in bytes
+    pub total_bytes: u64,
+    /// Size of the files finished so far, in bytes
+    pub processed_bytes: u64,
     /// Current step description
     pub step: String,
 }
@@ -38,6 +61,128 @@ impl ExtractionProgress {
             self.processed_files as f32 / self.total_files as f32
         }
     }
+
+    /// Calculate progress as 0.0 to 1.0, weighted by file size
+    ///
+    /// Unlike [`fraction`](Self::fraction), one large file (like PROT.DAT)
+    /// counts for as much as its size rather than as a single file.
+    pub fn byte_fraction(&self) -> f32 {
+        if self.total_bytes == 0 {
+            0.0
+        } else {
+            (self.processed_bytes as f64 / self.total_bytes as f64) as f32
+        }
+    }
+}
+
+/// How extracted files are arranged in the output directory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputLayout {
+    /// Same directories as on disc
+    #[default]
+    MirrorDiscTree,
+    /// One folder per asset type (`textures/`, `audio/`, `models/`,
+    /// `other/`), chosen by the disc file's extension
+    ByType,
+    /// Every file directly in the output directory, optionally prefixed with
+    /// its LBA so names stay unique and sort in disc order
+    Flat {
+        /// Prefix names with the zero-padded LBA (`000123_FILE.DAT`)
+        prefix_lba: bool,
+    },
+}
+
+impl OutputLayout {
+    /// Where a disc file goes under `output_dir`
+    ///
+    /// Collisions between files from different directories aren't resolved
+    /// here; [`AssetExtractionService`] gives later files a numeric suffix.
+    pub fn output_path(&self, output_dir: &Path, disc_path: &str, lba: u32) -> PathBuf {
+        let relative = disc_path.trim_start_matches('/');
+        let name = relative.rsplit('/').next().unwrap_or(relative);
+
+        match self {
+            OutputLayout::MirrorDiscTree => output_dir.join(relative),
+            OutputLayout::ByType => {
+                let folder = match asset_type_of(disc_path) {
+                    AssetType::Texture => "textures",
+                    AssetType::Audio => "audio",
+                    AssetType::Model => "models",
+                    _ => "other",
+                };
+                output_dir.join(folder).join(name)
+            }
+            OutputLayout::Flat { prefix_lba: true } => {
+                output_dir.join(format!("{:06}_{}", lba, name))
+            }
+            OutputLayout::Flat { prefix_lba: false } => output_dir.join(name),
+        }
+    }
+}
+
+impl std::str::FromStr for OutputLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "mirror" => Ok(OutputLayout::MirrorDiscTree),
+            "by-type" => Ok(OutputLayout::ByType),
+            "flat" => Ok(OutputLayout::Flat { prefix_lba: false }),
+            "flat-lba" => Ok(OutputLayout::Flat { prefix_lba: true }),
+            _ => Err(format!(
+                "Unknown output layout '{}' (expected mirror, by-type, flat or flat-lba)",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for OutputLayout {
+    /// Name accepted by [`OutputLayout::from_str`](std::str::FromStr)
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            OutputLayout::MirrorDiscTree => "mirror",
+            OutputLayout::ByType => "by-type",
+            OutputLayout::Flat { prefix_lba: false } => "flat",
+            OutputLayout::Flat { prefix_lba: true } => "flat-lba",
+        })
+    }
+}
+
+/// Asset type of a disc file, going by its extension
+fn asset_type_of(disc_path: &str) -> AssetType {
+    let extension = Path::new(disc_path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_uppercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "TIM" => AssetType::Texture,
+        "VAG" | "VAB" | "XA" => AssetType::Audio,
+        "TMD" => AssetType::Model,
+        _ => AssetType::Other,
+    }
+}
+
+/// Make `path` unique among `used` by appending `_1`, `_2`, ... to its stem
+fn unique_path(path: PathBuf, used: &mut HashSet<PathBuf>) -> PathBuf {
+    if used.insert(path.clone()) {
+        return path;
+    }
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+
+    (1..)
+        .map(|n| path.with_file_name(format!("{}_{}{}", stem, n, extension)))
+        .find(|candidate| used.insert(candidate.clone()))
+        .unwrap()
 }
 
 /// Asset extraction service
@@ -45,6 +190,17 @@ pub struct AssetExtractionService {
     disc_path: PathBuf,
     output_dir: PathBuf,
     progress_callback: Option<ProgressCallback>,
+    incremental: bool,
+    textured_models: bool,
+    layout: OutputLayout,
+}
+
+/// File on disc and where it is extracted to
+struct DiscFile {
+    disc_path: String,
+    output_path: PathBuf,
+    lba: u32,
+    size: u32,
 }
 
 impl AssetExtractionService {
@@ -54,6 +210,9 @@ impl AssetExtractionService {
             disc_path,
             output_dir,
             progress_callback: None,
+            incremental: false,
+            textured_models: false,
+            layout: OutputLayout::default(),
         }
     }
 
@@ -63,6 +222,37 @@ impl AssetExtractionService {
         self
     }
 
+    /// Skip files already extracted by a previous run
+    ///
+    /// A file is skipped when the extraction manifest in the output directory
+    /// was written with the same layout and lists it with the same LBA and
+    /// size, and its output still exists. The manifest is written on every
+    /// run, incremental or not.
+    pub fn with_incremental(mut self, incremental: bool) -> Self {
+        self.incremental = incremental;
+        self
+    }
+
+    /// Export textured glTF models from archives holding TMDs and TIMs
+    ///
+    /// Each TMD embedded in an archive is grouped with the TIMs around it
+    /// (see [`group_models`]), the TIMs are uploaded to a [`VramAtlas`], and
+    /// the model is written as `<archive>_<NNN>.gltf` with its texture pages
+    /// baked to PNGs alongside. The raw archive is still written.
+    pub fn with_textured_models(mut self, textured_models: bool) -> Self {
+        self.textured_models = textured_models;
+        self
+    }
+
+    /// Arrange output files by `layout` instead of mirroring the disc
+    ///
+    /// Names that collide once directories are flattened get a numeric
+    /// suffix (`FILE_1.DAT`), assigned in disc path order.
+    pub fn with_layout(mut self, layout: OutputLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
     /// Extract all assets from disc
     pub fn extract_all(&self) -> Result<ExtractionStats> {
         // Open disc
@@ -71,6 +261,9 @@ impl AssetExtractionService {
             total_files: 0,
             processed_files: 0,
             converted_files: 0,
+            skipped_files: 0,
+            total_bytes: 0,
+            processed_bytes: 0,
             step: "Opening disc...".to_string(),
         });
 
@@ -91,23 +284,64 @@ impl AssetExtractionService {
             total_files: 0,
             processed_files: 0,
             converted_files: 0,
+            skipped_files: 0,
+            total_bytes: 0,
+            processed_bytes: 0,
             step: "Scanning directories...".to_string(),
         });
 
-        let all_files = self.collect_files_recursive(&cdrom, "/", &self.output_dir)?;
+        let all_files = self.collect_files(&cdrom, &self.output_dir)?;
         let total_files = all_files.len();
+        let total_bytes: u64 = all_files.iter().map(|file| file.size as u64).sum();
         let processed = AtomicUsize::new(0);
+        let processed_bytes = AtomicU64::new(0);
         let converted = AtomicUsize::new(0);
+        let skipped = AtomicUsize::new(0);
 
-        // Extract each file
-        for (disc_path, output_path) in &all_files {
+        let layout = self.layout.to_string();
+        let previous = if self.incremental {
+            ExtractionManifest::load(&self.output_dir, &layout)
+        } else {
+            ExtractionManifest::new(layout)
+        };
+        let manifest = Mutex::new(previous.clone());
+
+        // Skipped files keep their entries from the previous asset manifest
+        let asset_manifest_path = self.output_dir.join(ASSET_MANIFEST_FILE);
+        let previous_assets = if self.incremental {
+            AssetManifest::from_json(&asset_manifest_path).ok()
+        } else {
+            None
+        };
+        let assets = Mutex::new(previous_assets.unwrap_or_else(|| {
+            AssetManifest::new(SourceInfo {
+                game: "Legend of Legaia".to_string(),
+                region: "NTSC-U".to_string(), // TODO: Detect from disc
+                serial: "SCUS-94254".to_string(), // TODO: Detect from disc
+                path: self.disc_path.clone(),
+            })
+        }));
+
+        // Extract and convert files in parallel; conversions are independent
+        // and the mmap-backed disc is shared read-only between threads
+        all_files.par_iter().for_each(|file| {
+            let disc_path = &file.disc_path;
             let current = processed.fetch_add(1, Ordering::SeqCst);
 
+            if previous.is_current(&self.output_dir, disc_path, file.lba, file.size) {
+                skipped.fetch_add(1, Ordering::SeqCst);
+                processed_bytes.fetch_add(file.size as u64, Ordering::SeqCst);
+                return;
+            }
+
             self.report_progress(ExtractionProgress {
                 current_file: disc_path.clone(),
                 total_files,
                 processed_files: current,
                 converted_files: converted.load(Ordering::SeqCst),
+                skipped_files: skipped.load(Ordering::SeqCst),
+                total_bytes,
+                processed_bytes: processed_bytes.load(Ordering::SeqCst),
                 step: format!("Extracting {}", disc_path),
             });
 
@@ -115,85 +349,159 @@ impl AssetExtractionService {
             match cdrom.read_file(disc_path) {
                 Ok(data) => {
                     // Create parent directory if needed
-                    if let Some(parent) = output_path.parent() {
+                    if let Some(parent) = file.output_path.parent() {
                         let _ = fs::create_dir_all(parent);
                     }
 
-                    // Try to convert based on extension
-                    let was_converted = if disc_path.ends_with(".TIM") {
-                        self.convert_tim(&data, &output_path.with_extension("png"))
-                    } else if disc_path.ends_with(".VAG") {
-                        self.convert_vag(&data, &output_path.with_extension("wav"))
-                    } else if disc_path.ends_with(".TMD") {
-                        self.convert_tmd(&data, &output_path.with_extension("gltf"))
-                    } else {
-                        // Unknown format, just save raw data
-                        if let Err(e) = fs::write(output_path, &data) {
-                            tracing::warn!("Failed to write {}: {}", disc_path, e);
-                            false
-                        } else {
-                            true
+                    // Try to convert based on the file's magic number
+                    let (target, was_converted) = match detect_format(&data) {
+                        Some(KnownFormat::Tim) => {
+                            let target = file.output_path.with_extension("png");
+                            let ok = self.convert_tim(&data, &target);
+                            (target, ok)
+                        }
+                        Some(KnownFormat::Vag) => {
+                            let target = file.output_path.with_extension("wav");
+                            let ok = self.convert_vag(&data, &target);
+                            (target, ok)
+                        }
+                        Some(KnownFormat::Tmd) => {
+                            let target = file.output_path.with_extension("gltf");
+                            let ok = self.convert_tmd(&data, &target);
+                            (target, ok)
+                        }
+                        _ => {
+                            if self.textured_models {
+                                self.convert_textured_models(&data, &file.output_path);
+                            }
+
+                            // Unknown format, just save raw data
+                            let ok = match fs::write(&file.output_path, &data) {
+                                Ok(()) => true,
+                                Err(e) => {
+                                    tracing::warn!("Failed to write {}: {}", disc_path, e);
+                                    false
+                                }
+                            };
+                            (file.output_path.clone(), ok)
                         }
                     };
 
                     if was_converted {
                         converted.fetch_add(1, Ordering::SeqCst);
+
+                        match self.asset_entry(file, &target) {
+                            Ok(entry) => assets.lock().unwrap().add_asset(disc_path.clone(), entry),
+                            Err(e) => tracing::warn!("Failed to hash {}: {}", target.display(), e),
+                        }
+
+                        let mut manifest = manifest.lock().unwrap();
+                        manifest.record(
+                            &self.output_dir,
+                            disc_path.clone(),
+                            file.lba,
+                            &data,
+                            &target,
+                        );
+
+                        // Save now and then so an interrupted run can resume
+                        if manifest.files.len() % MANIFEST_SAVE_INTERVAL == 0
+                            && let Err(e) = manifest.save(&self.output_dir)
+                        {
+                            tracing::warn!("Failed to save extraction manifest: {}", e);
+                        }
                     }
                 }
                 Err(e) => {
                     tracing::warn!("Failed to read {}: {}", disc_path, e);
                 }
             }
-        }
+
+            processed_bytes.fetch_add(file.size as u64, Ordering::SeqCst);
+        });
+
+        manifest
+            .into_inner()
+            .unwrap()
+            .save(&self.output_dir)
+            .context("Failed to save extraction manifest")?;
+        assets
+            .into_inner()
+            .unwrap()
+            .to_json(&asset_manifest_path)
+            .context("Failed to save asset manifest")?;
 
         let final_processed = processed.load(Ordering::SeqCst);
         let final_converted = converted.load(Ordering::SeqCst);
+        let final_skipped = skipped.load(Ordering::SeqCst);
 
         self.report_progress(ExtractionProgress {
             current_file: String::new(),
             total_files,
             processed_files: final_processed,
             converted_files: final_converted,
+            skipped_files: final_skipped,
+            total_bytes,
+            processed_bytes: processed_bytes.load(Ordering::SeqCst),
             step: "Complete!".to_string(),
         });
 
         Ok(ExtractionStats {
             total_files,
-            extracted_files: final_processed,
+            extracted_files: final_processed - final_skipped,
             converted_files: final_converted,
+            skipped_files: final_skipped,
         })
     }
 
-    /// Recursively collect all files from disc
-    fn collect_files_recursive(
-        &self,
-        cdrom: &CdRom,
-        dir_path: &str,
-        output_base: &Path,
-    ) -> Result<Vec<(String, PathBuf)>> {
-        let mut files = Vec::new();
-        let entries = cdrom.read_dir(dir_path)?;
-
-        for entry in entries {
-            let full_path = if dir_path == "/" {
-                format!("/{}", entry.name)
-            } else {
-                format!("{}/{}", dir_path, entry.name)
-            };
-
-            let output_path = output_base.join(&entry.name);
-
-            if entry.is_dir {
-                // Recursively scan subdirectory
-                let subdir_files = self.collect_files_recursive(cdrom, &full_path, &output_path)?;
-                files.extend(subdir_files);
-            } else {
-                // Add file to list
-                files.push((full_path, output_path));
-            }
-        }
+    /// Describe a converted file for the asset manifest, hashing it as written
+    fn asset_entry(&self, file: &DiscFile, target: &Path) -> std::io::Result<AssetEntry> {
+        let data = fs::read(target)?;
+
+        let source_format = Path::new(&file.disc_path)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_ascii_uppercase())
+            .unwrap_or_default();
+        let target_format = target
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let asset_type = asset_type_of(&file.disc_path);
+
+        Ok(AssetEntry {
+            asset_type,
+            source_address: file.lba,
+            source_format,
+            file_path: target
+                .strip_prefix(&self.output_dir)
+                .unwrap_or(target)
+                .to_path_buf(),
+            target_format,
+            lba: Some(file.lba),
+            offset: Some(0),
+            sha256: Some(sha256_hex(&data)),
+            metadata: HashMap::new(),
+        })
+    }
 
-        Ok(files)
+    /// Collect all files from disc, placed under `output_base` by the layout
+    fn collect_files(&self, cdrom: &CdRom, output_base: &Path) -> Result<Vec<DiscFile>> {
+        let mut files = cdrom.walk().collect::<psxutils::Result<Vec<_>>>()?;
+        files.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut used = HashSet::new();
+        Ok(files
+            .into_iter()
+            .map(|(disc_path, entry)| DiscFile {
+                output_path: unique_path(
+                    self.layout.output_path(output_base, &disc_path, entry.lba),
+                    &mut used,
+                ),
+                disc_path,
+                lba: entry.lba,
+                size: entry.size,
+            })
+            .collect())
     }
 
     /// Convert TIM texture to PNG
@@ -230,37 +538,16 @@ impl AssetExtractionService {
     /// Convert VAG audio to WAV
     fn convert_vag(&self, data: &[u8], output_path: &Path) -> bool {
         match Vag::parse(data) {
-            Ok(vag) => {
-                let pcm_samples = vag.decode_to_pcm();
-
-                let spec = hound::WavSpec {
-                    channels: 1,
-                    sample_rate: vag.sample_rate,
-                    bits_per_sample: 16,
-                    sample_format: hound::SampleFormat::Int,
-                };
-
-                match hound::WavWriter::create(output_path, spec) {
-                    Ok(mut writer) => {
-                        for sample in pcm_samples {
-                            if let Err(e) = writer.write_sample(sample) {
-                                tracing::warn!("Failed to write WAV sample: {}", e);
-                                return false;
-                            }
-                        }
-                        if let Err(e) = writer.finalize() {
-                            tracing::warn!("Failed to finalize WAV: {}", e);
-                            return false;
-                        }
-                        tracing::debug!("Converted VAG → WAV: {}", output_path.display());
-                        true
-                    }
-                    Err(e) => {
-                        tracing::warn!("Failed to create WAV writer: {}", e);
-                        false
-                    }
+            Ok(vag) => match vag_to_wav(&vag, output_path) {
+                Ok(()) => {
+                    tracing::debug!("Converted VAG → WAV: {}", output_path.display());
+                    true
                 }
-            }
+                Err(e) => {
+                    tracing::warn!("Failed to write WAV: {}", e);
+                    false
+                }
+            },
             Err(e) => {
                 tracing::warn!("Failed to parse VAG: {}", e);
                 false
@@ -287,6 +574,51 @@ impl AssetExtractionService {
         }
     }
 
+    /// Convert the TMDs embedded in an archive to glTF, textured from the
+    /// TIMs grouped with them
+    ///
+    /// Returns the number of models written.
+    fn convert_textured_models(&self, data: &[u8], archive_path: &Path) -> usize {
+        let assets = AssetScanner::new(data).scan();
+        let stem = archive_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let mut written = 0;
+        for (index, group) in group_models(&assets).iter().enumerate() {
+            let mut vram = VramAtlas::new();
+            for texture in &group.textures {
+                match Tim::parse(&data[texture.offset..texture.offset + texture.size]) {
+                    Ok(tim) => vram.insert(&tim),
+                    Err(e) => tracing::warn!("Failed to parse TIM at {:#x}: {}", texture.offset, e),
+                }
+            }
+
+            let model = &data[group.model.offset..group.model.offset + group.model.size];
+            let target = archive_path.with_file_name(format!("{}_{:03}.gltf", stem, index));
+            let result = Tmd::parse(model)
+                .map_err(anyhow::Error::from)
+                .and_then(|tmd| {
+                    tmd_to_gltf_with_texture(&tmd, &target, Some(&TmdTextureSource::Vram(&vram)))
+                });
+
+            match result {
+                Ok(()) => {
+                    tracing::debug!("Converted TMD → textured glTF: {}", target.display());
+                    written += 1;
+                }
+                Err(e) => tracing::warn!(
+                    "Failed to convert TMD at {:#x} to glTF: {}",
+                    group.model.offset,
+                    e
+                ),
+            }
+        }
+
+        written
+    }
+
     /// Report progress via callback
     fn report_progress(&self, progress: ExtractionProgress) {
         if let Some(callback) = &self.progress_callback {
@@ -295,6 +627,107 @@ impl AssetExtractionService {
     }
 }
 
+/// A TMD model in an archive and the TIMs that texture it
+#[derive(Debug, Clone)]
+pub struct ModelGroup {
+    /// The TMD
+    pub model: DiscoveredAsset,
+    /// TIMs uploaded to VRAM before converting it
+    pub textures: Vec<DiscoveredAsset>,
+}
+
+/// Group the TMDs of a scanned archive with their textures
+///
+/// Archives don't record which TIMs belong to which model, so this goes by
+/// extraction order: each TMD takes the TIMs that follow it up to the next
+/// TMD, and any TIMs before the first TMD go to that first model. `assets`
+/// must be sorted by offset, as [`AssetScanner::scan`] returns them.
+pub fn group_models(assets: &[DiscoveredAsset]) -> Vec<ModelGroup> {
+    let mut groups: Vec<ModelGroup> = Vec::new();
+    let mut leading = Vec::new();
+
+    for asset in assets {
+        match asset.asset_type {
+            psxutils::AssetType::Tmd { .. } => groups.push(ModelGroup {
+                model: asset.clone(),
+                textures: std::mem::take(&mut leading),
+            }),
+            psxutils::AssetType::Tim { .. } => match groups.last_mut() {
+                Some(group) => group.textures.push(asset.clone()),
+                None => leading.push(asset.clone()),
+            },
+            _ => {}
+        }
+    }
+
+    groups
+}
+
+/// Assets in a DAT archive, numbered in discovery order
+///
+/// Uses [`SequentialScanner`], so indices match the PROT.DAT entry numbers
+/// reported by `legaia-extract info`.
+pub fn archive_entries(data: &[u8]) -> Vec<DiscoveredAsset> {
+    SequentialScanner::new().scan(data, |_| {})
+}
+
+/// Write entry `index` of a DAT archive to `output`, converted by its type
+///
+/// TIMs become PNGs, VAGs become WAVs, TMDs become glTF and LZSS blocks are
+/// decompressed to `.bin`; custom models are written raw as `.model`. The
+/// extension of `output` is replaced to match, and the path actually
+/// written is returned.
+pub fn extract_archive_entry(data: &[u8], index: usize, output: &Path) -> Result<PathBuf> {
+    let entries = archive_entries(data);
+    let Some(entry) = entries.get(index) else {
+        bail!("Archive has {} entries, no entry {}", entries.len(), index);
+    };
+    write_archive_entry(data, entry, output)
+}
+
+/// Convert and write one scanned archive entry (see [`extract_archive_entry`])
+pub fn write_archive_entry(data: &[u8], entry: &DiscoveredAsset, output: &Path) -> Result<PathBuf> {
+    let bytes = data
+        .get(entry.offset..entry.offset + entry.size)
+        .with_context(|| format!("Entry at {:#x} runs past the archive", entry.offset))?;
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let target = match entry.asset_type {
+        psxutils::AssetType::Tim { .. } => {
+            let target = output.with_extension("png");
+            let tim = Tim::parse(bytes)?;
+            image::save_buffer(
+                &target,
+                &tim.to_rgba8()?,
+                tim.width() as u32,
+                tim.height() as u32,
+                image::ColorType::Rgba8,
+            )?;
+            target
+        }
+        psxutils::AssetType::Vag => {
+            let target = output.with_extension("wav");
+            vag_to_wav(&Vag::parse(bytes)?, &target)?;
+            target
+        }
+        psxutils::AssetType::Tmd { .. } => {
+            let target = output.with_extension("gltf");
+            tmd_to_gltf(&Tmd::parse(bytes)?, &target)?;
+            target
+        }
+        psxutils::AssetType::CustomModel => {
+            let target = output.with_extension("model");
+            fs::write(&target, bytes)?;
+            target
+        }
+    };
+
+    Ok(target)
+}
+
 /// Statistics about extraction
 #[derive(Debug, Clone)]
 pub struct ExtractionStats {
@@ -304,4 +737,211 @@ pub struct ExtractionStats {
     pub extracted_files: usize,
     /// Files successfully converted to modern formats
     pub converted_files: usize,
+    /// Files skipped because a previous run already extracted them
+    pub skipped_files: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::iso_builder::IsoBuilder;
+    use psxutils::formats::tim::PixelMode;
+
+    /// One-object TMD with a single unlit textured quad using texture page 0
+    /// and the CLUT at (0, 480)
+    fn textured_quad_tmd() -> Vec<u8> {
+        let mut data = Vec::new();
+
+        // Header, then the object table: vertices at 40, no normals,
+        // primitives at 72
+        for value in [0x41u32, 0, 1, 40, 4, 72, 0, 72, 1, 0] {
+            data.extend(value.to_le_bytes());
+        }
+
+        for (x, y) in [(0i16, 0i16), (64, 0), (0, 64), (64, 64)] {
+            for value in [x, y, 0, 0] {
+                data.extend(value.to_le_bytes());
+            }
+        }
+
+        // Flat textured quad: 8-word packet, light calculation off
+        data.extend([8, 7, 0x01, 0x2C]);
+
+        // Normal index, then vertex indices
+        for index in [0u16, 0, 1, 2, 3] {
+            data.extend(index.to_le_bytes());
+        }
+
+        // UVs interleaved with the CLUT position and texture page
+        data.extend([0, 0]);
+        data.extend(0u16.to_le_bytes());
+        data.extend(480u16.to_le_bytes());
+        data.extend([15, 0]);
+        data.extend(0u16.to_le_bytes());
+        data.extend([0, 15, 0, 0, 15, 15]);
+        data.extend([0, 0]);
+
+        data
+    }
+
+    fn quad_texture() -> Vec<u8> {
+        let clut: Vec<u16> = (0..16).map(|i| 0x7C00 | i).collect();
+        let indices: Vec<u8> = (0..256).map(|i| (i % 16) as u8).collect();
+
+        let mut tim = Tim::from_indexed(16, 16, PixelMode::Clut4Bit, &clut, &indices).unwrap();
+        tim.clut.as_mut().unwrap().vram_pos = (0, 480);
+        tim.to_bytes()
+    }
+
+    #[test]
+    fn test_group_models_by_order() {
+        let mut data = quad_texture();
+        data.extend(textured_quad_tmd());
+        data.extend(quad_texture());
+        data.extend(textured_quad_tmd());
+
+        let groups = group_models(&AssetScanner::new(&data).scan());
+        assert_eq!(groups.len(), 2);
+        // The leading TIM and the one after the first model both go to it
+        assert_eq!(groups[0].textures.len(), 2);
+        assert!(groups[1].textures.is_empty());
+    }
+
+    #[test]
+    fn test_textured_model_references_png() {
+        let dir = std::env::temp_dir().join(format!("legaia_textured_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut data = textured_quad_tmd();
+        data.extend(quad_texture());
+
+        let service = AssetExtractionService::new(PathBuf::new(), dir.clone());
+        let archive = dir.join("MODEL.DAT");
+        assert_eq!(service.convert_textured_models(&data, &archive), 1);
+
+        let gltf: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.join("MODEL_000.gltf")).unwrap()).unwrap();
+        let uri = gltf["images"][0]["uri"].as_str().unwrap();
+        assert!(uri.ends_with(".png"));
+        assert!(dir.join(uri).exists());
+
+        let primitive = &gltf["meshes"][0]["primitives"][0];
+        assert!(primitive["attributes"]["TEXCOORD_0"].is_u64());
+        assert!(primitive["material"].is_u64());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_output_layout_paths() {
+        let out = Path::new("/out");
+        let path = |layout: OutputLayout| layout.output_path(out, "/CHR/PLAYER.TIM", 1234);
+
+        assert_eq!(
+            path(OutputLayout::MirrorDiscTree),
+            Path::new("/out/CHR/PLAYER.TIM")
+        );
+        assert_eq!(
+            path(OutputLayout::ByType),
+            Path::new("/out/textures/PLAYER.TIM")
+        );
+        assert_eq!(
+            OutputLayout::ByType.output_path(out, "/SND/BGM.VAB", 0),
+            Path::new("/out/audio/BGM.VAB")
+        );
+        assert_eq!(
+            OutputLayout::ByType.output_path(out, "/PROT.DAT", 0),
+            Path::new("/out/other/PROT.DAT")
+        );
+        assert_eq!(
+            path(OutputLayout::Flat { prefix_lba: false }),
+            Path::new("/out/PLAYER.TIM")
+        );
+        assert_eq!(
+            path(OutputLayout::Flat { prefix_lba: true }),
+            Path::new("/out/001234_PLAYER.TIM")
+        );
+    }
+
+    #[test]
+    fn test_flat_layout_collisions() {
+        let layout = OutputLayout::Flat { prefix_lba: false };
+        let out = Path::new("/out");
+        let mut used = HashSet::new();
+
+        let paths: Vec<_> = ["/A/FACE.TIM", "/B/FACE.TIM", "/C/FACE.TIM", "/C/FACE.TMD"]
+            .iter()
+            .map(|disc_path| unique_path(layout.output_path(out, disc_path, 0), &mut used))
+            .collect();
+
+        assert_eq!(
+            paths,
+            [
+                Path::new("/out/FACE.TIM"),
+                Path::new("/out/FACE_1.TIM"),
+                Path::new("/out/FACE_2.TIM"),
+                Path::new("/out/FACE.TMD"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_archive_entry() {
+        let dir = std::env::temp_dir().join(format!("legaia_archive_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        // Two TIMs separated by padding, as they sit in a DAT archive
+        let mut data = vec![0u8; 16];
+        data.extend(quad_texture());
+        let second = data.len() + 32;
+        data.resize(second, 0);
+        data.extend(quad_texture());
+
+        let entries = archive_entries(&data);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].offset, second);
+
+        let path = extract_archive_entry(&data, 1, &dir.join("entry.dat")).unwrap();
+        assert_eq!(path, dir.join("entry.png"));
+        let png = image::open(&path).unwrap();
+        assert_eq!((png.width(), png.height()), (16, 16));
+
+        assert!(extract_archive_entry(&data, 2, &dir.join("missing")).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_progress_counts_bytes() {
+        let dir = std::env::temp_dir().join(format!("legaia_progress_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let disc = dir.join("disc.bin");
+
+        IsoBuilder::new("TEST")
+            .add_file("/LARGE.BIN", vec![0x5A; 3000])
+            .add_file("/SMALL.BIN", b"abc".to_vec())
+            .add_file("/X.BIN", b"z".to_vec())
+            .build(&disc)
+            .unwrap();
+
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reports);
+        let stats = AssetExtractionService::new(disc, dir.join("out"))
+            .with_progress_callback(Arc::new(move |progress: ExtractionProgress| {
+                sink.lock().unwrap().push(progress)
+            }))
+            .extract_all()
+            .unwrap();
+        assert_eq!(stats.total_files, 3);
+
+        let reports = reports.lock().unwrap();
+        let last = reports.last().unwrap();
+        assert_eq!(last.step, "Complete!");
+        assert_eq!(last.total_bytes, 3004);
+        assert_eq!(last.processed_bytes, 3004);
+        assert_eq!(last.byte_fraction(), 1.0);
+        assert!(reports.iter().all(|report| report.processed_bytes <= 3004));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
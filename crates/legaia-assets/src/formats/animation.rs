@@ -0,0 +1,96 @@
+//! TMD object animation
+//!
+//! Character models are TMDs whose objects are posed by separate keyframe
+//! data: one rotation and translation per object per frame. This is the
+//! format-neutral form of that data; the Legaia-specific tables are parsed
+//! into it and exported with `converter::tmd_to_gltf_animated`.
+
+/// Full circle in PSX fixed-point angle units
+const PSX_FULL_CIRCLE: f32 = 4096.0;
+
+/// Pose of one TMD object in one frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObjectTransform {
+    /// Rotation quaternion (x, y, z, w)
+    pub rotation: [f32; 4],
+    /// Translation in model units
+    pub translation: [f32; 3],
+}
+
+impl Default for ObjectTransform {
+    fn default() -> Self {
+        Self {
+            rotation: [0.0, 0.0, 0.0, 1.0],
+            translation: [0.0; 3],
+        }
+    }
+}
+
+impl ObjectTransform {
+    /// Build a transform from PSX Euler angles (4096 = 360 degrees) and an
+    /// integer translation
+    ///
+    /// Rotations apply about X, then Y, then Z, as `RotMatrix` does.
+    pub fn from_psx(rotation: [i16; 3], translation: [i16; 3]) -> Self {
+        let half = |angle: i16| angle as f32 / PSX_FULL_CIRCLE * std::f32::consts::PI;
+        let (sx, cx) = half(rotation[0]).sin_cos();
+        let (sy, cy) = half(rotation[1]).sin_cos();
+        let (sz, cz) = half(rotation[2]).sin_cos();
+
+        // q = qz * qy * qx
+        let rotation = [
+            sx * cy * cz - cx * sy * sz,
+            cx * sy * cz + sx * cy * sz,
+            cx * cy * sz - sx * sy * cz,
+            cx * cy * cz + sx * sy * sz,
+        ];
+
+        Self {
+            rotation,
+            translation: translation.map(|t| t as f32),
+        }
+    }
+}
+
+/// Keyframed animation of a TMD model
+#[derive(Debug, Clone, PartialEq)]
+pub struct TmdAnimation {
+    /// Animation name
+    pub name: Option<String>,
+    /// Playback rate in frames per second
+    pub frame_rate: f32,
+    /// `frames[frame][object]`: pose of each object in each frame
+    ///
+    /// Objects past the end of a frame keep their rest pose.
+    pub frames: Vec<Vec<ObjectTransform>>,
+}
+
+impl TmdAnimation {
+    /// Number of objects with a pose in every frame
+    pub fn animated_objects(&self) -> usize {
+        self.frames.iter().map(Vec::len).min().unwrap_or(0)
+    }
+
+    /// Duration in seconds
+    pub fn duration_secs(&self) -> f32 {
+        self.frames.len().saturating_sub(1) as f32 / self.frame_rate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_psx_rotation() {
+        let identity = ObjectTransform::from_psx([0; 3], [1, 2, 3]);
+        assert_eq!(identity.rotation, [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(identity.translation, [1.0, 2.0, 3.0]);
+
+        // 1024 = 90 degrees about Y
+        let turned = ObjectTransform::from_psx([0, 1024, 0], [0; 3]);
+        let expected = std::f32::consts::FRAC_1_SQRT_2;
+        assert!((turned.rotation[1] - expected).abs() < 1e-6);
+        assert!((turned.rotation[3] - expected).abs() < 1e-6);
+    }
+}
@@ -1,7 +1,11 @@
 //! Game-specific asset format utilities
 //!
 //! This module re-exports PlayStation format parsers from `psxutils`
-//! and may add Legaia-specific format wrappers in the future.
+//! and adds Legaia-specific format wrappers.
+
+pub mod animation;
+
+pub use animation::{ObjectTransform, TmdAnimation};
 
 // Re-export PSX format parsers for convenience
 pub use psxutils::{Tim, Vab, Vag};
@@ -0,0 +1,523 @@
+//! Disc image builder for repacking extracted files
+//!
+//! The reverse of extraction: lays a set of files out as an ISO 9660
+//! filesystem and writes it as a raw BIN of 2352-byte Mode 2 Form 1 sectors,
+//! with sync, header, sub-header, EDC and ECC filled in so emulators accept
+//! it. There is no CD-XA interleaving or audio; the result is a single data
+//! track that [`CdRom::open`](psxutils::cdrom::CdRom::open) can read back.
+//!
+//! ## Layout
+//!
+//! ```text
+//! Sectors 0-15   System area (zeroed)
+//! Sector 16      Primary volume descriptor
+//! Sector 17      Volume descriptor set terminator
+//! Sector 18+     Type L path table, then type M path table
+//!                Directories in path table order
+//!                Files in path order
+//! ```
+
+use anyhow::{bail, Context, Result};
+use psxutils::cdrom::ecc::encode_form1_sector;
+use psxutils::cdrom::DATA_SIZE;
+use psxutils::formats::xa::SubMode;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// First sector of the volume descriptor set
+const PVD_SECTOR: u32 = 16;
+
+/// First sector after the volume descriptors
+const PATH_TABLE_SECTOR: u32 = 18;
+
+/// Longest identifier that still fits a directory record's u8 length
+const MAX_NAME_LEN: usize = 255 - 34;
+
+/// Directory in the image being built
+struct DirNode {
+    /// Path relative to the root, without leading slash ("" for the root)
+    path: String,
+    /// Index of the parent in path table order (the root is its own parent)
+    parent: usize,
+    lba: u32,
+    size: u32,
+}
+
+/// Builds a single-track Mode 2 Form 1 disc image from files
+///
+/// # Examples
+///
+/// ```no_run
+/// use legaia_assets::IsoBuilder;
+///
+/// let mut builder = IsoBuilder::new("LEGAIA");
+/// builder
+///     .add_file("/SYSTEM.CNF", b"BOOT = cdrom:\\SCUS_942.54;1\r\n".to_vec())
+///     .add_file("/DATA/PROT.DAT", std::fs::read("PROT.DAT")?);
+/// builder.build("legaia_mod.bin".as_ref())?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct IsoBuilder {
+    volume_id: String,
+    /// File contents keyed by normalized path ("DATA/PROT.DAT")
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl IsoBuilder {
+    /// Create an empty image with the given volume identifier
+    pub fn new(volume_id: &str) -> Self {
+        Self {
+            volume_id: volume_id.to_ascii_uppercase(),
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Add a file at `path` on the disc, creating parent directories as needed
+    ///
+    /// Paths are case-insensitive like the discs themselves and are stored
+    /// uppercase. Adding the same path again replaces the contents.
+    pub fn add_file(&mut self, path: &str, data: Vec<u8>) -> &mut Self {
+        let path = path
+            .split('/')
+            .filter(|component| !component.is_empty())
+            .collect::<Vec<_>>()
+            .join("/")
+            .to_ascii_uppercase();
+        self.files.insert(path, data);
+        self
+    }
+
+    /// Add every file below `root` on disk, keeping the directory layout
+    pub fn add_dir_all(&mut self, root: &Path) -> Result<&mut Self> {
+        self.add_dir_recursive(root, "")?;
+        Ok(self)
+    }
+
+    fn add_dir_recursive(&mut self, dir: &Path, prefix: &str) -> Result<()> {
+        for entry in
+            fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))?
+        {
+            let path = entry?.path();
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let disc_path = format!("{}/{}", prefix, name);
+
+            if path.is_dir() {
+                self.add_dir_recursive(&path, &disc_path)?;
+            } else {
+                let data = fs::read(&path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                self.add_file(&disc_path, data);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write the image to `output` as a raw BIN
+    pub fn build(&self, output: &Path) -> Result<()> {
+        let (user_data, sub_modes) = self.layout()?;
+
+        let file = fs::File::create(output)
+            .with_context(|| format!("Failed to create {}", output.display()))?;
+        let mut writer = BufWriter::new(file);
+        for (lba, (sector, sub_mode)) in user_data.chunks(DATA_SIZE).zip(sub_modes).enumerate() {
+            writer.write_all(&encode_form1_sector(lba as u32, sub_mode, sector))?;
+        }
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Lay out the filesystem as 2048-byte user data sectors
+    ///
+    /// Returns the user data of every sector plus each sector's sub-mode;
+    /// the last sector of every descriptor set, directory and file is
+    /// flagged end-of-record/end-of-file.
+    fn layout(&self) -> Result<(Vec<u8>, Vec<SubMode>)> {
+        let mut dirs = self.directories();
+
+        // Path tables, each padded to whole sectors
+        let path_table_size = dirs
+            .iter()
+            .map(|dir| path_table_record_len(dir_name(&dir.path)))
+            .sum::<usize>();
+        let path_table_sectors = path_table_size.div_ceil(DATA_SIZE).max(1) as u32;
+        let l_table_lba = PATH_TABLE_SECTOR;
+        let m_table_lba = l_table_lba + path_table_sectors;
+        let mut next_lba = m_table_lba + path_table_sectors;
+
+        // Directory extents
+        let contents: Vec<Vec<(String, bool)>> = (0..dirs.len())
+            .map(|index| self.dir_contents(&dirs, index))
+            .collect();
+        for (dir, entries) in dirs.iter_mut().zip(&contents) {
+            let size = directory_size(entries)?;
+            dir.lba = next_lba;
+            dir.size = size as u32;
+            next_lba += (size / DATA_SIZE) as u32;
+        }
+
+        // File extents
+        let mut file_lbas = BTreeMap::new();
+        for (path, data) in &self.files {
+            file_lbas.insert(path.as_str(), next_lba);
+            next_lba += data.len().div_ceil(DATA_SIZE) as u32;
+        }
+
+        let total_sectors = next_lba;
+        let mut user_data = vec![0u8; total_sectors as usize * DATA_SIZE];
+        let mut sub_modes = vec![SubMode::DATA; total_sectors as usize];
+        let end_of_extent = SubMode::END_OF_FILE | SubMode::END_OF_RECORD | SubMode::DATA;
+        let sector = |lba: u32| lba as usize * DATA_SIZE;
+
+        // Volume descriptors
+        let root_record = dir_record(&[0], dirs[0].lba, dirs[0].size, true);
+        let pvd = self.primary_volume_descriptor(
+            total_sectors,
+            path_table_size as u32,
+            l_table_lba,
+            m_table_lba,
+            &root_record,
+        );
+        user_data[sector(PVD_SECTOR)..sector(PVD_SECTOR + 1)].copy_from_slice(&pvd);
+        let terminator = &mut user_data[sector(PVD_SECTOR + 1)..sector(PVD_SECTOR + 2)];
+        terminator[0] = 255;
+        terminator[1..6].copy_from_slice(b"CD001");
+        terminator[6] = 1;
+        sub_modes[PVD_SECTOR as usize + 1] = end_of_extent;
+
+        // Path tables (type L little-endian, type M big-endian)
+        let mut l_table = Vec::with_capacity(path_table_size);
+        let mut m_table = Vec::with_capacity(path_table_size);
+        for dir in &dirs {
+            let name = dir_name(&dir.path);
+            let name = if name.is_empty() {
+                &[0u8][..]
+            } else {
+                name.as_bytes()
+            };
+            let parent = dir.parent as u16 + 1;
+
+            for (table, big_endian) in [(&mut l_table, false), (&mut m_table, true)] {
+                table.push(name.len() as u8);
+                table.push(0);
+                if big_endian {
+                    table.extend_from_slice(&dir.lba.to_be_bytes());
+                    table.extend_from_slice(&parent.to_be_bytes());
+                } else {
+                    table.extend_from_slice(&dir.lba.to_le_bytes());
+                    table.extend_from_slice(&parent.to_le_bytes());
+                }
+                table.extend_from_slice(name);
+                if name.len() % 2 == 1 {
+                    table.push(0);
+                }
+            }
+        }
+        let l_start = sector(l_table_lba);
+        user_data[l_start..l_start + l_table.len()].copy_from_slice(&l_table);
+        let m_start = sector(m_table_lba);
+        user_data[m_start..m_start + m_table.len()].copy_from_slice(&m_table);
+        sub_modes[(m_table_lba - 1) as usize] = end_of_extent;
+        sub_modes[(m_table_lba + path_table_sectors - 1) as usize] = end_of_extent;
+
+        // Directory records
+        for (index, entries) in contents.iter().enumerate() {
+            let dir = &dirs[index];
+            let parent = &dirs[dir.parent];
+            let start = sector(dir.lba);
+            let mut offset = 0;
+
+            let mut records = vec![
+                dir_record(&[0], dir.lba, dir.size, true),
+                dir_record(&[1], parent.lba, parent.size, true),
+            ];
+            for (name, is_dir) in entries {
+                let path = join_path(&dir.path, name);
+                let record = if *is_dir {
+                    let child = dirs.iter().find(|d| d.path == path).unwrap();
+                    dir_record(name.as_bytes(), child.lba, child.size, true)
+                } else {
+                    let size = self.files[&path].len() as u32;
+                    let identifier = format!("{};1", name);
+                    dir_record(identifier.as_bytes(), file_lbas[path.as_str()], size, false)
+                };
+                records.push(record);
+            }
+
+            for record in records {
+                // Records never straddle a sector boundary
+                if offset % DATA_SIZE + record.len() > DATA_SIZE {
+                    offset = offset.next_multiple_of(DATA_SIZE);
+                }
+                user_data[start + offset..start + offset + record.len()].copy_from_slice(&record);
+                offset += record.len();
+            }
+
+            sub_modes[(dir.lba + dir.size / DATA_SIZE as u32 - 1) as usize] = end_of_extent;
+        }
+
+        // File data
+        for (path, data) in &self.files {
+            let lba = file_lbas[path.as_str()];
+            let start = sector(lba);
+            user_data[start..start + data.len()].copy_from_slice(data);
+
+            let sectors = data.len().div_ceil(DATA_SIZE) as u32;
+            if sectors > 0 {
+                sub_modes[(lba + sectors - 1) as usize] = end_of_extent;
+            }
+        }
+
+        Ok((user_data, sub_modes))
+    }
+
+    /// Every directory implied by the file paths, in path table order
+    ///
+    /// Breadth-first with siblings sorted by name, so parents always come
+    /// before their children as ISO 9660 requires.
+    fn directories(&self) -> Vec<DirNode> {
+        let mut paths: Vec<String> = self
+            .files
+            .keys()
+            .flat_map(|path| {
+                let components: Vec<&str> = path.split('/').collect();
+                (1..components.len())
+                    .map(|depth| components[..depth].join("/"))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        paths.sort();
+        paths.dedup();
+
+        let mut dirs = vec![DirNode {
+            path: String::new(),
+            parent: 0,
+            lba: 0,
+            size: 0,
+        }];
+        let mut index = 0;
+        while index < dirs.len() {
+            let parent_path = dirs[index].path.clone();
+            let mut children: Vec<&String> = paths
+                .iter()
+                .filter(|path| parent_of(path) == parent_path)
+                .collect();
+            children.sort_by_key(|path| dir_name(path));
+
+            for path in children {
+                dirs.push(DirNode {
+                    path: path.clone(),
+                    parent: index,
+                    lba: 0,
+                    size: 0,
+                });
+            }
+            index += 1;
+        }
+
+        dirs
+    }
+
+    /// Subdirectories and files directly inside a directory, sorted by name
+    fn dir_contents(&self, dirs: &[DirNode], index: usize) -> Vec<(String, bool)> {
+        let dir_path = &dirs[index].path;
+
+        let mut entries: Vec<(String, bool)> = dirs
+            .iter()
+            .skip(1)
+            .filter(|dir| dir.parent == index)
+            .map(|dir| (dir_name(&dir.path).to_string(), true))
+            .chain(
+                self.files
+                    .keys()
+                    .filter(|path| parent_of(path) == *dir_path)
+                    .map(|path| (dir_name(path).to_string(), false)),
+            )
+            .collect();
+        entries.sort();
+
+        entries
+    }
+
+    /// Build the primary volume descriptor
+    fn primary_volume_descriptor(
+        &self,
+        total_sectors: u32,
+        path_table_size: u32,
+        l_table_lba: u32,
+        m_table_lba: u32,
+        root_record: &[u8],
+    ) -> Vec<u8> {
+        let mut pvd = vec![0u8; DATA_SIZE];
+        pvd[0] = 1;
+        pvd[1..6].copy_from_slice(b"CD001");
+        pvd[6] = 1;
+
+        // System, volume and other identifiers are space padded
+        pvd[8..72].fill(b' ');
+        pvd[190..813].fill(b' ');
+        pvd[8..19].copy_from_slice(b"PLAYSTATION");
+        let volume_id = &self.volume_id.as_bytes()[..self.volume_id.len().min(32)];
+        pvd[40..40 + volume_id.len()].copy_from_slice(volume_id);
+
+        put_both_u32(&mut pvd[80..88], total_sectors);
+        put_both_u16(&mut pvd[120..124], 1); // Volume set size
+        put_both_u16(&mut pvd[124..128], 1); // Volume sequence number
+        put_both_u16(&mut pvd[128..132], DATA_SIZE as u16);
+        put_both_u32(&mut pvd[132..140], path_table_size);
+        pvd[140..144].copy_from_slice(&l_table_lba.to_le_bytes());
+        pvd[148..152].copy_from_slice(&m_table_lba.to_be_bytes());
+        pvd[156..156 + root_record.len()].copy_from_slice(root_record);
+
+        // Creation, modification, expiration and effective dates: unspecified
+        for date in pvd[813..881].chunks_mut(17) {
+            date[..16].fill(b'0');
+        }
+        pvd[881] = 1; // File structure version
+
+        // PlayStation discs carry the CD-XA signature in the application use area
+        pvd[1024..1032].copy_from_slice(b"CD-XA001");
+
+        pvd
+    }
+}
+
+/// Last component of a path
+fn dir_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// Path of the directory containing `path` ("" for the root)
+fn parent_of(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(parent, _)| parent)
+}
+
+fn join_path(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+/// Size of a path table record (identifier padded to an even length)
+fn path_table_record_len(name: &str) -> usize {
+    let name_len = name.len().max(1);
+    8 + name_len + name_len % 2
+}
+
+/// Size of a directory record (the whole record is padded to an even length)
+fn dir_record_len(name_len: usize) -> usize {
+    33 + name_len + (name_len + 1) % 2
+}
+
+/// Size of a directory extent in bytes, rounded up to whole sectors
+fn directory_size(entries: &[(String, bool)]) -> Result<usize> {
+    // '.' and '..' records
+    let mut offset = 2 * dir_record_len(1);
+
+    for (name, is_dir) in entries {
+        let name_len = name.len() + if *is_dir { 0 } else { 2 };
+        if name_len > MAX_NAME_LEN {
+            bail!("Name too long for a directory record: {}", name);
+        }
+
+        let len = dir_record_len(name_len);
+        if offset % DATA_SIZE + len > DATA_SIZE {
+            offset = offset.next_multiple_of(DATA_SIZE);
+        }
+        offset += len;
+    }
+
+    Ok(offset.next_multiple_of(DATA_SIZE))
+}
+
+/// Build an ISO 9660 directory record
+fn dir_record(identifier: &[u8], lba: u32, size: u32, is_dir: bool) -> Vec<u8> {
+    let len = dir_record_len(identifier.len());
+    let mut record = vec![0u8; len];
+    record[0] = len as u8;
+    put_both_u32(&mut record[2..10], lba);
+    put_both_u32(&mut record[10..18], size);
+    record[25] = if is_dir { 0x02 } else { 0x00 };
+    put_both_u16(&mut record[28..32], 1); // Volume sequence number
+    record[32] = identifier.len() as u8;
+    record[33..33 + identifier.len()].copy_from_slice(identifier);
+    record
+}
+
+/// Write a both-byte-order u32 (LSB half, then MSB half)
+fn put_both_u32(field: &mut [u8], value: u32) {
+    field[..4].copy_from_slice(&value.to_le_bytes());
+    field[4..8].copy_from_slice(&value.to_be_bytes());
+}
+
+/// Write a both-byte-order u16 (LSB half, then MSB half)
+fn put_both_u16(field: &mut [u8], value: u16) {
+    field[..2].copy_from_slice(&value.to_le_bytes());
+    field[2..4].copy_from_slice(&value.to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use psxutils::cdrom::{CdRom, SectorIntegrity};
+
+    #[test]
+    fn test_round_trip() {
+        let prot: Vec<u8> = (0..3 * DATA_SIZE + 123).map(|i| (i % 253) as u8).collect();
+        // Enough entries to push the directory past one sector
+        let mut builder = IsoBuilder::new("legaia");
+        builder
+            .add_file("/SYSTEM.CNF", b"BOOT = cdrom:\\SCUS_942.54;1\r\n".to_vec())
+            .add_file("/data/PROT.DAT", prot.clone())
+            .add_file("/DATA/EMPTY.BIN", Vec::new())
+            .add_file("/MOV/DEMO/A.STR", vec![0x11; 10]);
+        for i in 0..60 {
+            builder.add_file(&format!("/SOUND/VOICE{:03}.VAG", i), vec![i as u8; 100]);
+        }
+
+        let path = std::env::temp_dir().join("legaia_iso_builder_round_trip.bin");
+        builder.build(&path).unwrap();
+
+        let disc = CdRom::open_verified(&path).unwrap();
+        for lba in 0..disc.sector_count() as u32 {
+            assert_eq!(disc.check_sector(lba).unwrap(), SectorIntegrity::Ok);
+        }
+
+        let root: Vec<String> = disc
+            .read_dir("/")
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect();
+        assert_eq!(root, ["DATA", "MOV", "SOUND", "SYSTEM.CNF"]);
+
+        assert_eq!(disc.read_file("/DATA/PROT.DAT").unwrap(), prot);
+        assert!(disc.read_file("/DATA/EMPTY.BIN").unwrap().is_empty());
+        assert_eq!(disc.read_file("/MOV/DEMO/A.STR").unwrap(), vec![0x11; 10]);
+        assert_eq!(
+            disc.read_file("/SYSTEM.CNF").unwrap(),
+            b"BOOT = cdrom:\\SCUS_942.54;1\r\n"
+        );
+
+        let voices = disc.read_dir("/SOUND").unwrap();
+        assert_eq!(voices.len(), 60);
+        assert_eq!(
+            disc.read_file("/SOUND/VOICE059.VAG").unwrap(),
+            vec![59; 100]
+        );
+
+        let table = disc.read_path_table().unwrap();
+        let names: Vec<&str> = table.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["", "DATA", "MOV", "SOUND", "DEMO"]);
+        assert_eq!(table[4].parent, 2);
+
+        drop(disc);
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -10,11 +10,13 @@ pub mod converter;
 pub mod extraction;
 pub mod extractor;
 pub mod formats;
+pub mod iso_builder;
 pub mod manifest;
 
-pub use extraction::{AssetExtractionService, ExtractionProgress, ExtractionStats};
+pub use extraction::{AssetExtractionService, ExtractionProgress, ExtractionStats, OutputLayout};
 pub use extractor::AssetExtractor;
-pub use manifest::{AssetEntry, AssetManifest};
+pub use iso_builder::IsoBuilder;
+pub use manifest::{AssetEntry, AssetManifest, ExtractionManifest, ManifestMismatch};
 
 use thiserror::Error;
 
@@ -1,7 +1,8 @@
 //! Asset manifest management
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
 /// Manifest describing all assets extracted from the game
@@ -51,11 +52,51 @@ pub struct AssetEntry {
     /// Converted format
     pub target_format: String,
 
+    /// Starting sector of the source file on disc
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lba: Option<u32>,
+
+    /// Byte offset of the asset within its source file (for embedded assets)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<u64>,
+
+    /// SHA-256 of the converted file (lowercase hex)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+
     /// Additional metadata
     #[serde(default)]
     pub metadata: HashMap<String, String>,
 }
 
+/// Problem found by [`AssetManifest::verify`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestMismatch {
+    /// Converted file no longer exists
+    Missing { id: String, path: PathBuf },
+    /// Converted file exists but could not be read
+    Unreadable {
+        id: String,
+        path: PathBuf,
+        error: String,
+    },
+    /// Converted file was modified or corrupted
+    HashMismatch {
+        id: String,
+        path: PathBuf,
+        expected: String,
+        actual: String,
+    },
+}
+
+/// SHA-256 digest of `data` as lowercase hex
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
 /// Types of assets
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -105,6 +146,53 @@ impl AssetManifest {
         toml::from_str(&contents).map_err(|e| crate::AssetError::ManifestError(e.to_string()))
     }
 
+    /// Re-hash converted files and report any that don't match the manifest
+    ///
+    /// Entries without a recorded hash are not checked. `assets_dir` is the
+    /// directory `file_path`s are relative to.
+    pub fn verify(&self, assets_dir: impl AsRef<Path>) -> Vec<ManifestMismatch> {
+        let mut mismatches = Vec::new();
+
+        // Sorted for stable reports
+        let mut ids: Vec<&String> = self.assets.keys().collect();
+        ids.sort();
+
+        for id in ids {
+            let entry = &self.assets[id];
+            let Some(expected) = &entry.sha256 else {
+                continue;
+            };
+
+            let path = assets_dir.as_ref().join(&entry.file_path);
+            match std::fs::read(&path) {
+                Ok(data) => {
+                    let actual = sha256_hex(&data);
+                    if !actual.eq_ignore_ascii_case(expected) {
+                        mismatches.push(ManifestMismatch::HashMismatch {
+                            id: id.clone(),
+                            path,
+                            expected: expected.clone(),
+                            actual,
+                        });
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    mismatches.push(ManifestMismatch::Missing {
+                        id: id.clone(),
+                        path,
+                    });
+                }
+                Err(e) => mismatches.push(ManifestMismatch::Unreadable {
+                    id: id.clone(),
+                    path,
+                    error: e.to_string(),
+                }),
+            }
+        }
+
+        mismatches
+    }
+
     /// Save manifest to TOML file
     pub fn to_toml(&self, path: impl AsRef<Path>) -> crate::Result<()> {
         let contents = toml::to_string_pretty(self)
@@ -113,3 +201,202 @@ impl AssetManifest {
         Ok(())
     }
 }
+
+/// File name of the extraction manifest inside the output directory
+pub const EXTRACTION_MANIFEST_FILE: &str = "extraction_manifest.json";
+
+/// Record of files already extracted to an output directory
+///
+/// Lets a later extraction skip files whose source on disc is unchanged and
+/// whose output still exists, so an interrupted run can be resumed. Outputs
+/// are stored relative to the output directory, and the manifest only
+/// applies to the layout it was written with.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExtractionManifest {
+    /// Output layout the files were arranged by (e.g. `mirror`)
+    #[serde(default)]
+    pub layout: String,
+    /// Extracted files indexed by disc path
+    pub files: BTreeMap<String, ExtractedFile>,
+}
+
+/// Source location and output of one extracted file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtractedFile {
+    /// Starting sector on disc
+    pub lba: u32,
+    /// Size on disc in bytes
+    pub size: u32,
+    /// File written for it (converted or raw), relative to the output
+    /// directory
+    pub output: PathBuf,
+    /// FNV-1a hash of the source data
+    pub hash: u64,
+}
+
+impl ExtractionManifest {
+    /// Create an empty manifest for files arranged by `layout`
+    pub fn new(layout: impl Into<String>) -> Self {
+        Self {
+            layout: layout.into(),
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Load the manifest from an output directory
+    ///
+    /// A missing or unreadable manifest, or one written for a different
+    /// layout, yields an empty one, so everything is extracted again.
+    pub fn load(output_dir: impl AsRef<Path>, layout: &str) -> Self {
+        let path = output_dir.as_ref().join(EXTRACTION_MANIFEST_FILE);
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<Self>(&contents).ok())
+            .filter(|manifest| manifest.layout == layout)
+            .unwrap_or_else(|| Self::new(layout))
+    }
+
+    /// Save the manifest to an output directory
+    pub fn save(&self, output_dir: impl AsRef<Path>) -> crate::Result<()> {
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| crate::AssetError::ManifestError(e.to_string()))?;
+        std::fs::write(output_dir.as_ref().join(EXTRACTION_MANIFEST_FILE), contents)?;
+        Ok(())
+    }
+
+    /// Whether a file was already extracted from the same source location
+    /// and its output is still present under `output_dir`
+    pub fn is_current(&self, output_dir: &Path, disc_path: &str, lba: u32, size: u32) -> bool {
+        self.files.get(disc_path).is_some_and(|file| {
+            file.lba == lba
+                && file.size == size
+                && file.output.is_relative()
+                && output_dir.join(&file.output).exists()
+        })
+    }
+
+    /// Record a successfully extracted file written to `output` under
+    /// `output_dir`
+    pub fn record(
+        &mut self,
+        output_dir: &Path,
+        disc_path: impl Into<String>,
+        lba: u32,
+        data: &[u8],
+        output: &Path,
+    ) {
+        let output = output.strip_prefix(output_dir).unwrap_or(output);
+        self.files.insert(
+            disc_path.into(),
+            ExtractedFile {
+                lba,
+                size: data.len() as u32,
+                output: output.to_path_buf(),
+                hash: fnv1a(data),
+            },
+        );
+    }
+}
+
+/// 64-bit FNV-1a hash (stable across builds, unlike `DefaultHasher`)
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xCBF2_9CE4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01B3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_manifest_verify() {
+        let dir = std::env::temp_dir().join(format!("legaia_verify_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.png"), b"texture").unwrap();
+        std::fs::write(dir.join("b.wav"), b"audio").unwrap();
+
+        let mut manifest = AssetManifest::new(SourceInfo {
+            game: "Legend of Legaia".to_string(),
+            region: "NTSC-U".to_string(),
+            serial: "SCUS-94254".to_string(),
+            path: PathBuf::from("legaia.bin"),
+        });
+        for (id, file, data) in [
+            ("/A.TIM", "a.png", &b"texture"[..]),
+            ("/B.VAG", "b.wav", &b"audio"[..]),
+            ("/C.TMD", "c.gltf", &b"model"[..]),
+        ] {
+            manifest.add_asset(
+                id,
+                AssetEntry {
+                    asset_type: AssetType::Other,
+                    source_address: 0,
+                    source_format: String::new(),
+                    file_path: PathBuf::from(file),
+                    target_format: String::new(),
+                    lba: Some(0),
+                    offset: None,
+                    sha256: Some(sha256_hex(data)),
+                    metadata: HashMap::new(),
+                },
+            );
+        }
+        // Only the never-written model is reported
+        assert_eq!(manifest.verify(&dir).len(), 1);
+
+        std::fs::write(dir.join("b.wav"), b"tampered").unwrap();
+        let mismatches = manifest.verify(&dir);
+        assert_eq!(mismatches.len(), 2);
+        assert!(matches!(
+            &mismatches[0],
+            ManifestMismatch::HashMismatch { id, .. } if id == "/B.VAG"
+        ));
+        assert!(matches!(
+            &mismatches[1],
+            ManifestMismatch::Missing { id, .. } if id == "/C.TMD"
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_extraction_manifest_round_trip() {
+        let dir = std::env::temp_dir().join(format!("legaia_manifest_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let output = dir.join("SYSTEM.CNF");
+        std::fs::write(&output, b"BOOT").unwrap();
+
+        let mut manifest = ExtractionManifest::new("mirror");
+        manifest.record(&dir, "/SYSTEM.CNF", 23, b"BOOT", &output);
+        manifest.save(&dir).unwrap();
+        assert_eq!(
+            manifest.files["/SYSTEM.CNF"].output,
+            Path::new("SYSTEM.CNF")
+        );
+
+        let loaded = ExtractionManifest::load(&dir, "mirror");
+        assert!(loaded.is_current(&dir, "/SYSTEM.CNF", 23, 4));
+        assert!(!loaded.is_current(&dir, "/SYSTEM.CNF", 24, 4));
+        assert!(!loaded.is_current(&dir, "/SYSTEM.CNF", 23, 5));
+        assert!(!loaded.is_current(&dir, "/OTHER.BIN", 23, 4));
+
+        // Another output root or layout doesn't have the file
+        assert!(!loaded.is_current(&dir.join("other"), "/SYSTEM.CNF", 23, 4));
+        assert!(ExtractionManifest::load(&dir, "flat").files.is_empty());
+
+        // A deleted output has to be extracted again
+        std::fs::remove_file(&output).unwrap();
+        assert!(!loaded.is_current(&dir, "/SYSTEM.CNF", 23, 4));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
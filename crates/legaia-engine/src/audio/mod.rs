@@ -6,8 +6,22 @@
 //! - 17 sound function handlers
 //! - Sound sequences with active flags
 //! - Reverb support via SPU
+//!
+//! Samples are decoded from VAG ADPCM to PCM and played through Bevy's
+//! audio as in-memory WAV sources. SEQ music is voiced through the same
+//! channels, see [`sequence`]. SPU reverb presets are approximated in
+//! [`reverb`].
+
+pub mod reverb;
+pub mod sequence;
 
+use crate::state::{GameState, StateTransitionEvent};
+use bevy::audio::{AudioSinkPlayback, Volume};
 use bevy::prelude::*;
+use psxutils::formats::Vag;
+use reverb::ReverbPreset;
+use sequence::SequencePlayback;
+use std::sync::Arc;
 
 /// Maximum number of sound channels
 pub const MAX_SOUND_CHANNELS: usize = 24;
@@ -15,6 +29,30 @@ pub const MAX_SOUND_CHANNELS: usize = 24;
 /// Size of each sound channel structure in bytes
 pub const CHANNEL_SIZE_BYTES: usize = 0x1b; // 27 bytes
 
+/// Channel status flag: a sound is playing on the channel
+pub const CHANNEL_ACTIVE: u8 = 0x01;
+
+/// Pan position of a centered sound
+pub const PAN_CENTER: u8 = 0x80;
+
+/// Left and right linear gains for a volume, pan and master volume
+///
+/// Volumes are 0..=255 (255 = unity gain). Pan runs from 0 (hard left)
+/// through [`PAN_CENTER`] to 255 (hard right) and follows an equal-power
+/// law, so a sound keeps the same loudness as it moves across the field.
+pub fn pan_gains(volume: u8, pan: u8, master: u8) -> (f32, f32) {
+    // Map pan onto 0..=1 with the center exactly halfway
+    let position = if pan <= PAN_CENTER {
+        pan as f32 / PAN_CENTER as f32 * 0.5
+    } else {
+        0.5 + (pan - PAN_CENTER) as f32 / (255 - PAN_CENTER) as f32 * 0.5
+    };
+    let angle = position * std::f32::consts::FRAC_PI_2;
+    let gain = volume as f32 / 255.0 * master as f32 / 255.0;
+
+    (gain * angle.cos(), gain * angle.sin())
+}
+
 /// Sound channel state
 #[derive(Debug, Clone, Copy)]
 pub struct SoundChannel {
@@ -47,6 +85,38 @@ impl Default for SoundChannel {
     }
 }
 
+impl SoundChannel {
+    /// Whether a sound is playing on this channel
+    pub fn is_active(&self) -> bool {
+        self.status & CHANNEL_ACTIVE != 0
+    }
+}
+
+/// Note to play a sample at, relative to the note it was recorded at
+///
+/// Taken from the VAB tone that owns the sample, so one recording can be
+/// played across the tone's whole key range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotePitch {
+    /// MIDI note to play
+    pub note: u8,
+    /// Note the sample plays at unmodified (VAB tone `center_note`)
+    pub center_note: u8,
+    /// Fine tuning in 1/128 semitones (VAB tone `center_tune`)
+    pub center_tune: u8,
+}
+
+/// Decoded sound waiting to be handed to Bevy's audio
+#[derive(Debug)]
+struct PendingSound {
+    channel: usize,
+    samples: Vec<i16>,
+    sample_rate: u32,
+    pitch: f32,
+    /// Linear (left, right) gains, master volume included
+    gains: (f32, f32),
+}
+
 /// Audio system state
 #[derive(Resource, Debug)]
 pub struct AudioSystem {
@@ -62,11 +132,31 @@ pub struct AudioSystem {
     /// Sound sequence status
     pub sequence_status: u8,
 
+    /// Master volume applied on top of every channel (255 = unity gain)
+    ///
+    /// Read when a sound starts, so changes affect the next sounds played.
+    pub master_volume: u8,
+
+    /// Sequence being played, if any
+    sequence: Option<SequencePlayback>,
+
     /// SPU reverb enabled
     pub reverb_enabled: bool,
 
+    /// Reverb preset used while reverb is enabled
+    reverb_preset: ReverbPreset,
+
     /// System initialized flag
     pub initialized: bool,
+
+    /// Sounds started since the last update
+    pending: Vec<PendingSound>,
+
+    /// Audio entity playing on each channel
+    players: [Option<Entity>; MAX_SOUND_CHANNELS],
+
+    /// Audio entities cut off by a new sound, despawned on the next update
+    stopped: Vec<Entity>,
 }
 
 impl Default for AudioSystem {
@@ -76,8 +166,14 @@ impl Default for AudioSystem {
             current_channel: 0,
             sequence_active: false,
             sequence_status: 0,
+            master_volume: 0xff,
+            sequence: None,
             reverb_enabled: false,
+            reverb_preset: ReverbPreset::default(),
             initialized: false,
+            pending: Vec::new(),
+            players: [None; MAX_SOUND_CHANNELS],
+            stopped: Vec::new(),
         }
     }
 }
@@ -108,6 +204,21 @@ impl AudioSystem {
         tracing::info!("SPU reverb disabled");
     }
 
+    /// Select a reverb preset
+    ///
+    /// Any preset but [`ReverbPreset::Off`] also enables reverb. Like the
+    /// SPU, the preset applies to sounds started from now on.
+    pub fn set_reverb(&mut self, preset: ReverbPreset) {
+        self.reverb_preset = preset;
+        self.reverb_enabled = preset != ReverbPreset::Off;
+        tracing::info!("SPU reverb preset: {:?}", preset);
+    }
+
+    /// Selected reverb preset, kept while reverb is disabled
+    pub fn reverb_preset(&self) -> ReverbPreset {
+        self.reverb_preset
+    }
+
     /// Get a channel by index
     pub fn get_channel(&self, index: usize) -> Option<&SoundChannel> {
         self.channels.get(index)
@@ -118,6 +229,127 @@ impl AudioSystem {
         self.channels.get_mut(index)
     }
 
+    /// Left and right linear gains of a channel, see [`pan_gains`]
+    ///
+    /// Combines the channel's volume and pan with the master volume.
+    pub fn channel_gains(&self, index: usize) -> Option<(f32, f32)> {
+        self.channels
+            .get(index)
+            .map(|channel| pan_gains(channel.volume, channel.pan, self.master_volume))
+    }
+
+    /// Find a channel for a new sound of the given priority
+    ///
+    /// Returns the first idle channel. When all channels are busy, the
+    /// lowest-priority one is evicted if its priority does not exceed
+    /// `priority`; otherwise `None` is returned and the sound is dropped.
+    pub fn allocate_channel(&mut self, priority: u8) -> Option<usize> {
+        if let Some(index) = self.channels.iter().position(|c| !c.is_active()) {
+            return Some(index);
+        }
+
+        let (index, lowest) = self
+            .channels
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, channel)| channel.priority)?;
+        if lowest.priority > priority {
+            return None;
+        }
+
+        tracing::debug!(
+            "Evicting audio channel {} (priority {:#04x})",
+            index,
+            lowest.priority
+        );
+        self.stop_channel(index);
+        Some(index)
+    }
+
+    /// Play a VAG sample on a channel
+    ///
+    /// Decodes the sample to PCM and marks the channel active; playback
+    /// starts on the next audio update. `pitch` scales the playback rate
+    /// (1.0 = original). When `note` is given the sample is first resampled
+    /// from its center note to that note. `volume` and `pan` are combined
+    /// with the master volume by [`channel_gains`](Self::channel_gains).
+    pub fn play_vag(
+        &mut self,
+        channel: usize,
+        vag: &Vag,
+        pitch: f32,
+        volume: u8,
+        pan: u8,
+        note: Option<NotePitch>,
+    ) {
+        if channel >= MAX_SOUND_CHANNELS {
+            tracing::warn!("Invalid audio channel: {}", channel);
+            return;
+        }
+
+        self.stop_channel(channel);
+
+        let state = &mut self.channels[channel];
+        state.status |= CHANNEL_ACTIVE;
+        state.volume = volume;
+        state.pan = pan;
+
+        let samples = match note {
+            Some(note) => vag.resample(note.center_note, note.note, note.center_tune),
+            None => vag.decode_to_pcm(),
+        };
+        let samples = if self.reverb_enabled {
+            reverb::apply_reverb(&samples, vag.sample_rate, self.reverb_preset)
+        } else {
+            samples
+        };
+
+        self.pending.retain(|sound| sound.channel != channel);
+        self.pending.push(PendingSound {
+            channel,
+            samples,
+            sample_rate: vag.sample_rate,
+            pitch,
+            gains: pan_gains(volume, pan, self.master_volume),
+        });
+    }
+
+    /// Number of channels with a sound playing or about to start
+    pub fn active_channel_count(&self) -> usize {
+        self.channels.iter().filter(|c| c.is_active()).count()
+    }
+
+    /// Stop the sound playing on a channel
+    ///
+    /// The channel is free immediately; its audio entity is despawned on
+    /// the next audio update.
+    pub fn stop_channel(&mut self, channel: usize) {
+        if let Some(entity) = self.players.get_mut(channel).and_then(Option::take) {
+            self.stopped.push(entity);
+        }
+        self.pending.retain(|sound| sound.channel != channel);
+        if let Some(state) = self.channels.get_mut(channel) {
+            state.status &= !CHANNEL_ACTIVE;
+        }
+    }
+
+    /// Free the channels whose sounds have finished playing
+    ///
+    /// `finished` is asked about each channel's audio entity. Sounds still
+    /// waiting to be spawned are left alone. Returns the number of channels
+    /// freed.
+    pub fn release_finished(&mut self, mut finished: impl FnMut(Entity) -> bool) -> usize {
+        let mut released = 0;
+        for (channel, player) in self.players.iter_mut().enumerate() {
+            if player.is_some_and(&mut finished) {
+                *player = None;
+                self.channels[channel].status &= !CHANNEL_ACTIVE;
+                released += 1;
+            }
+        }
+        released
+    }
+
     /// Cleanup sound sequence (variant 1)
     pub fn cleanup_sequence_1(&mut self) {
         self.sequence_active = false;
@@ -139,7 +371,47 @@ impl Plugin for AudioPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<AudioSystem>()
             .add_systems(Startup, setup_audio)
-            .add_systems(Update, update_audio);
+            .add_systems(
+                Update,
+                (
+                    stop_music_on_transition,
+                    release_finished_channels,
+                    update_audio,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Stop the playing sequence when a battle starts or ends
+///
+/// Field and battle each have their own track; whoever sets up the new
+/// state starts its music.
+fn stop_music_on_transition(
+    mut transitions: MessageReader<StateTransitionEvent>,
+    mut audio_system: ResMut<AudioSystem>,
+) {
+    for transition in transitions.read() {
+        if transition.enters(GameState::Battle) || transition.exits(GameState::Battle) {
+            audio_system.stop_sequence();
+        }
+    }
+}
+
+/// Free channels whose sounds finished playing
+///
+/// Sounds play with [`PlaybackSettings::DESPAWN`], so a finished sound is
+/// either already despawned or has an empty sink waiting to be.
+fn release_finished_channels(
+    mut audio_system: ResMut<AudioSystem>,
+    players: Query<Option<&AudioSink>, With<AudioPlayer>>,
+) {
+    let released = audio_system.release_finished(|entity| match players.get(entity) {
+        Ok(sink) => sink.is_some_and(|sink| sink.empty()),
+        Err(_) => true,
+    });
+    if released > 0 {
+        tracing::trace!("Released {} finished audio channels", released);
     }
 }
 
@@ -158,10 +430,219 @@ fn setup_audio(mut audio_system: ResMut<AudioSystem>) {
     );
 }
 
-fn update_audio() {
-    // TODO: Update audio playback
-    // This will handle:
-    // - Sound sequence updates
-    // - Channel state updates
-    // - Music streaming
+fn update_audio(
+    mut commands: Commands,
+    mut audio_system: ResMut<AudioSystem>,
+    mut sources: ResMut<Assets<AudioSource>>,
+    time: Res<Time>,
+) {
+    let audio_system = &mut *audio_system;
+
+    for entity in audio_system.stopped.drain(..) {
+        if let Ok(mut entity) = commands.get_entity(entity) {
+            entity.despawn();
+        }
+    }
+
+    // TODO: Music streaming
+    audio_system.advance_sequence(time.delta_secs_f64());
+
+    for sound in audio_system.pending.drain(..) {
+        // Bevy's non-spatial playback has no balance control, so the pan is
+        // baked into stereo samples and the overall gain set on playback
+        let (left, right) = sound.gains;
+        let volume = left.max(right);
+        let balance = if volume > 0.0 {
+            (left / volume, right / volume)
+        } else {
+            (1.0, 1.0)
+        };
+
+        let source = sources.add(AudioSource {
+            bytes: Arc::from(pcm_to_wav(
+                &pan_samples(&sound.samples, balance),
+                2,
+                sound.sample_rate,
+            )),
+        });
+        let entity = commands
+            .spawn((
+                AudioPlayer::new(source),
+                PlaybackSettings::DESPAWN
+                    .with_speed(sound.pitch)
+                    .with_volume(Volume::Linear(volume)),
+            ))
+            .id();
+        audio_system.players[sound.channel] = Some(entity);
+    }
+}
+
+/// Spread mono PCM to interleaved stereo with per-side gains
+fn pan_samples(samples: &[i16], (left, right): (f32, f32)) -> Vec<i16> {
+    samples
+        .iter()
+        .flat_map(|&sample| {
+            let sample = sample as f32;
+            [(sample * left) as i16, (sample * right) as i16]
+        })
+        .collect()
+}
+
+/// Wrap interleaved 16-bit PCM in a WAV container for Bevy's decoder
+fn pcm_to_wav(samples: &[i16], channels: u16, sample_rate: u32) -> Vec<u8> {
+    let data_size = (samples.len() * 2) as u32;
+    let block_align = channels * 2;
+    let mut wav = Vec::with_capacity(44 + data_size as usize);
+
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_size).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&channels.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes()); // Byte rate
+    wav.extend_from_slice(&block_align.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes()); // Bits per sample
+
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    wav
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_vag() -> Vag {
+        Vag {
+            name: "test".to_string(),
+            sample_rate: 22050,
+            data: vec![0; 32],
+            loop_start: None,
+            loop_end: None,
+        }
+    }
+
+    #[test]
+    fn test_play_vag_marks_channel_active() {
+        let mut audio = AudioSystem::new();
+        audio.play_vag(3, &test_vag(), 1.0, 0x80, 0x40, None);
+
+        let channel = audio.get_channel(3).unwrap();
+        assert!(channel.is_active());
+        assert_eq!(channel.status & CHANNEL_ACTIVE, CHANNEL_ACTIVE);
+        assert_eq!(channel.volume, 0x80);
+        assert_eq!(channel.pan, 0x40);
+        assert_eq!(audio.pending.len(), 1);
+        assert_eq!(audio.pending[0].samples.len(), 56);
+    }
+
+    #[test]
+    fn test_play_vag_at_note() {
+        let mut audio = AudioSystem::new();
+        let note = NotePitch {
+            note: 72,
+            center_note: 60,
+            center_tune: 0,
+        };
+        audio.play_vag(0, &test_vag(), 1.0, 0xff, 0, Some(note));
+
+        // One octave up plays back in half the samples
+        assert_eq!(audio.pending[0].samples.len(), 28);
+    }
+
+    #[test]
+    fn test_center_pan_is_equal_power() {
+        let (left, right) = pan_gains(0xff, PAN_CENTER, 0xff);
+        assert!((left - right).abs() < 1e-6);
+        assert!((left * left + right * right - 1.0).abs() < 1e-5);
+
+        let (left, right) = pan_gains(0xff, 0, 0xff);
+        assert!((left - 1.0).abs() < 1e-6 && right.abs() < 1e-6);
+        let (left, right) = pan_gains(0xff, 0xff, 0xff);
+        assert!(left.abs() < 1e-6 && (right - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_master_volume_scales_channels() {
+        let mut audio = AudioSystem::new();
+        audio.master_volume = 0x80;
+        audio.play_vag(2, &test_vag(), 1.0, 0xff, PAN_CENTER, None);
+
+        let (left, right) = audio.channel_gains(2).unwrap();
+        let expected = 128.0 / 255.0 * std::f32::consts::FRAC_1_SQRT_2;
+        assert!((left - expected).abs() < 1e-5);
+        assert!((right - expected).abs() < 1e-5);
+        assert_eq!(audio.pending[0].gains, (left, right));
+    }
+
+    #[test]
+    fn test_set_reverb() {
+        let mut audio = AudioSystem::new();
+        audio.set_reverb(ReverbPreset::Hall);
+        assert!(audio.reverb_enabled);
+        assert_eq!(audio.reverb_preset(), ReverbPreset::Hall);
+
+        // The reverb tail extends the sound
+        audio.play_vag(0, &test_vag(), 1.0, 0xff, PAN_CENTER, None);
+        assert!(audio.pending[0].samples.len() > 56);
+
+        audio.disable_reverb();
+        assert_eq!(audio.reverb_preset(), ReverbPreset::Hall);
+        audio.set_reverb(ReverbPreset::Off);
+        assert!(!audio.reverb_enabled);
+    }
+
+    #[test]
+    fn test_allocate_evicts_lowest_priority() {
+        let mut audio = AudioSystem::new();
+        for channel in 0..MAX_SOUND_CHANNELS {
+            audio.play_vag(channel, &test_vag(), 1.0, 0xff, 0, None);
+        }
+        audio.channels[5].priority = 0x10;
+
+        // Busy channels of higher priority are never evicted
+        assert_eq!(audio.allocate_channel(0x08), None);
+
+        assert_eq!(audio.allocate_channel(0x18), Some(5));
+        assert!(!audio.channels[5].is_active());
+    }
+
+    #[test]
+    fn test_finished_sink_releases_channel() {
+        let mut world = World::new();
+        let (playing, finished) = (world.spawn_empty().id(), world.spawn_empty().id());
+
+        let mut audio = AudioSystem::new();
+        for channel in 0..MAX_SOUND_CHANNELS {
+            audio.play_vag(channel, &test_vag(), 1.0, 0xff, 0, None);
+        }
+        assert_eq!(audio.active_channel_count(), MAX_SOUND_CHANNELS);
+
+        // Channels 1 and 4 were spawned; the rest are still pending
+        audio
+            .pending
+            .retain(|sound| sound.channel != 1 && sound.channel != 4);
+        audio.players[1] = Some(playing);
+        audio.players[4] = Some(finished);
+
+        assert_eq!(audio.release_finished(|entity| entity == finished), 1);
+        assert!(!audio.channels[4].is_active());
+        assert!(audio.channels[1].is_active());
+        assert_eq!(audio.active_channel_count(), MAX_SOUND_CHANNELS - 1);
+
+        // The freed channel is reused even by a sound too weak to evict
+        assert_eq!(audio.allocate_channel(0), Some(4));
+
+        audio.stop_channel(1);
+        assert_eq!(audio.active_channel_count(), MAX_SOUND_CHANNELS - 2);
+        assert_eq!(audio.stopped, vec![playing]);
+    }
 }
@@ -0,0 +1,227 @@
+//! SPU reverb presets
+//!
+//! The SPU reverb unit is programmed with one of a fixed set of parameter
+//! blocks (the libspu `SPU_REV_MODE_*` modes). Its exact work-area network
+//! isn't reproduced here: each preset is approximated by a Schroeder
+//! reverb (four parallel comb filters into two series allpass filters)
+//! tuned to a similar size and decay. Bevy has no effect bus, so the reverb
+//! is rendered into each sound's samples before playback, tail included.
+
+/// Longest tail rendered after a sound ends, in seconds
+const MAX_TAIL_SECS: f32 = 4.0;
+
+/// Allpass filter gain
+const ALLPASS_GAIN: f32 = 0.5;
+
+/// SPU reverb mode
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub enum ReverbPreset {
+    /// No reverb (`SPU_REV_MODE_OFF`)
+    Off,
+    /// Small room (`SPU_REV_MODE_ROOM`)
+    #[default]
+    Room,
+    /// Small studio (`SPU_REV_MODE_STUDIO_A`)
+    StudioA,
+    /// Medium studio (`SPU_REV_MODE_STUDIO_B`)
+    StudioB,
+    /// Large studio (`SPU_REV_MODE_STUDIO_C`)
+    StudioC,
+    /// Concert hall (`SPU_REV_MODE_HALL`)
+    Hall,
+    /// Long, diffuse space echo (`SPU_REV_MODE_SPACE`)
+    Space,
+    /// Repeating echo (`SPU_REV_MODE_ECHO`)
+    Echo,
+    /// Single delayed repeat (`SPU_REV_MODE_DELAY`)
+    Delay,
+    /// Short metallic pipe resonance (`SPU_REV_MODE_PIPE`)
+    Pipe,
+}
+
+/// Parameters of the reverb network for a preset
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ReverbParams {
+    /// Comb filter delays in milliseconds
+    comb_ms: [f32; 4],
+    /// Comb filter feedback
+    feedback: f32,
+    /// Allpass filter delays in milliseconds
+    allpass_ms: [f32; 2],
+    /// Level of the reverb mixed over the dry signal
+    wet: f32,
+}
+
+impl ReverbPreset {
+    /// Every preset, in libspu mode order
+    pub const ALL: [ReverbPreset; 10] = [
+        ReverbPreset::Off,
+        ReverbPreset::Room,
+        ReverbPreset::StudioA,
+        ReverbPreset::StudioB,
+        ReverbPreset::StudioC,
+        ReverbPreset::Hall,
+        ReverbPreset::Space,
+        ReverbPreset::Echo,
+        ReverbPreset::Delay,
+        ReverbPreset::Pipe,
+    ];
+
+    fn params(self) -> Option<ReverbParams> {
+        let params = |comb_ms, feedback, wet| ReverbParams {
+            comb_ms,
+            feedback,
+            allpass_ms: [5.0, 1.7],
+            wet,
+        };
+
+        Some(match self {
+            ReverbPreset::Off => return None,
+            ReverbPreset::Room => params([29.7, 37.1, 41.1, 43.7], 0.6, 0.25),
+            ReverbPreset::StudioA => params([23.0, 29.0, 31.0, 37.0], 0.55, 0.25),
+            ReverbPreset::StudioB => params([37.0, 43.0, 47.0, 53.0], 0.7, 0.3),
+            ReverbPreset::StudioC => params([53.0, 61.0, 67.0, 73.0], 0.78, 0.35),
+            ReverbPreset::Hall => params([71.0, 83.0, 97.0, 109.0], 0.85, 0.4),
+            ReverbPreset::Space => params([113.0, 131.0, 151.0, 173.0], 0.9, 0.45),
+            ReverbPreset::Echo => params([250.0, 251.0, 252.0, 253.0], 0.6, 0.35),
+            ReverbPreset::Delay => params([250.0, 251.0, 252.0, 253.0], 0.0, 0.5),
+            ReverbPreset::Pipe => params([11.0, 13.0, 17.0, 19.0], 0.85, 0.35),
+        })
+    }
+}
+
+/// Feedback comb filter
+struct Comb {
+    buffer: Vec<f32>,
+    pos: usize,
+    feedback: f32,
+}
+
+impl Comb {
+    fn process(&mut self, input: f32) -> f32 {
+        let output = self.buffer[self.pos];
+        self.buffer[self.pos] = input + output * self.feedback;
+        self.pos = (self.pos + 1) % self.buffer.len();
+        output
+    }
+}
+
+/// Schroeder allpass filter
+struct Allpass {
+    buffer: Vec<f32>,
+    pos: usize,
+}
+
+impl Allpass {
+    fn process(&mut self, input: f32) -> f32 {
+        let delayed = self.buffer[self.pos];
+        let output = delayed - input * ALLPASS_GAIN;
+        self.buffer[self.pos] = input + delayed * ALLPASS_GAIN;
+        self.pos = (self.pos + 1) % self.buffer.len();
+        output
+    }
+}
+
+/// Delay line length in samples, at least one
+fn delay_len(ms: f32, sample_rate: u32) -> usize {
+    ((ms / 1000.0 * sample_rate as f32) as usize).max(1)
+}
+
+/// Render `samples` through a reverb preset
+///
+/// Returns the dry signal with the reverb mixed over it, extended by the
+/// reverb tail (the time for the longest comb to decay by 60 dB, at most
+/// four seconds). [`ReverbPreset::Off`] returns the samples unchanged.
+pub fn apply_reverb(samples: &[i16], sample_rate: u32, preset: ReverbPreset) -> Vec<i16> {
+    let Some(params) = preset.params() else {
+        return samples.to_vec();
+    };
+
+    let mut combs: Vec<Comb> = params
+        .comb_ms
+        .iter()
+        .map(|&ms| Comb {
+            buffer: vec![0.0; delay_len(ms, sample_rate)],
+            pos: 0,
+            feedback: params.feedback,
+        })
+        .collect();
+    let mut allpasses: Vec<Allpass> = params
+        .allpass_ms
+        .iter()
+        .map(|&ms| Allpass {
+            buffer: vec![0.0; delay_len(ms, sample_rate)],
+            pos: 0,
+        })
+        .collect();
+
+    let longest = params.comb_ms.iter().copied().fold(0.0, f32::max) / 1000.0;
+    let tail_secs = if params.feedback > 0.0 {
+        longest * 0.001f32.ln() / params.feedback.ln()
+    } else {
+        longest
+    };
+    let tail = (tail_secs.min(MAX_TAIL_SECS) * sample_rate as f32) as usize;
+
+    let input = samples
+        .iter()
+        .map(|&sample| sample as f32)
+        .chain(std::iter::repeat_n(0.0, tail));
+
+    input
+        .map(|dry| {
+            let mut wet =
+                combs.iter_mut().map(|comb| comb.process(dry)).sum::<f32>() / combs.len() as f32;
+            for allpass in &mut allpasses {
+                wet = allpass.process(wet);
+            }
+
+            (dry + wet * params.wet).clamp(i16::MIN as f32, i16::MAX as f32) as i16
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn impulse() -> Vec<i16> {
+        let mut samples = vec![0; 64];
+        samples[0] = 20000;
+        samples
+    }
+
+    #[test]
+    fn test_off_is_dry() {
+        let samples = impulse();
+        assert_eq!(apply_reverb(&samples, 22050, ReverbPreset::Off), samples);
+    }
+
+    #[test]
+    fn test_reverb_adds_tail() {
+        let samples = impulse();
+        let output = apply_reverb(&samples, 22050, ReverbPreset::Hall);
+
+        assert!(output.len() > samples.len());
+        // The wet signal arrives after the shortest comb delay
+        assert_eq!(output[0], samples[0]);
+        assert!(output[samples.len()..].iter().any(|&s| s != 0));
+    }
+
+    #[test]
+    fn test_presets_differ() {
+        let samples = impulse();
+        let outputs: Vec<Vec<i16>> = ReverbPreset::ALL[1..]
+            .iter()
+            .map(|&preset| apply_reverb(&samples, 22050, preset))
+            .collect();
+
+        for (i, a) in outputs.iter().enumerate() {
+            for b in &outputs[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+}
@@ -0,0 +1,317 @@
+//! SEQ music playback
+//!
+//! Steps through a parsed [`Sequence`] in real time and voices its notes
+//! with the tones of a VAB sound bank, one sound channel per sounding note.
+//! Controllers and pitch bend are not applied yet.
+
+use super::{AudioSystem, NotePitch};
+use psxutils::formats::seq::{SeqEventKind, Sequence};
+use psxutils::formats::vab::Tone;
+use psxutils::formats::{Vab, Vag};
+
+/// Rate the SPU plays a VAB sample at for its center note
+const SPU_SAMPLE_RATE: u32 = 44100;
+
+/// Number of channels addressed by a sequence
+const SEQ_CHANNELS: usize = 16;
+
+/// A sequence playing against a sound bank
+#[derive(Debug)]
+pub struct SequencePlayback {
+    sequence: Sequence,
+    bank: Vab,
+    /// Bank samples by VAG index, wrapped once up front
+    samples: Vec<Vag>,
+    /// Index of the next event to play
+    next_event: usize,
+    /// Playback position in ticks
+    tick: f64,
+    /// Current tempo in microseconds per quarter note
+    tempo: u32,
+    /// Selected VAB program per sequence channel
+    programs: [u8; SEQ_CHANNELS],
+    /// Sounding notes as (sequence channel, note, sound channel)
+    voices: Vec<(u8, u8, usize)>,
+}
+
+impl SequencePlayback {
+    /// Prepare a sequence for playback from the start
+    pub fn new(sequence: Sequence, bank: Vab) -> Self {
+        let samples = bank
+            .vag_samples
+            .iter()
+            .enumerate()
+            .map(|(index, sample)| {
+                Vag::from_adpcm(
+                    &format!("vag_{:03}", index),
+                    SPU_SAMPLE_RATE,
+                    sample.data.clone(),
+                )
+            })
+            .collect();
+        let tempo = sequence.tempo;
+
+        Self {
+            sequence,
+            bank,
+            samples,
+            next_event: 0,
+            tick: 0.0,
+            tempo,
+            programs: [0; SEQ_CHANNELS],
+            voices: Vec::new(),
+        }
+    }
+
+    /// Whether every event has been played
+    pub fn is_finished(&self) -> bool {
+        self.next_event >= self.sequence.events.len()
+    }
+
+    /// Tone of `program` whose key range covers `note`
+    fn find_tone(&self, program: u8, note: u8) -> Option<&Tone> {
+        self.bank.tones.iter().find(|tone| {
+            tone.program_index == program as i16 && (tone.min_note..=tone.max_note).contains(&note)
+        })
+    }
+}
+
+impl AudioSystem {
+    /// Start playing a sequence with the instruments of `bank`
+    ///
+    /// Replaces any sequence already playing.
+    pub fn play_sequence(&mut self, sequence: Sequence, bank: Vab) {
+        self.stop_sequence();
+        self.sequence = Some(SequencePlayback::new(sequence, bank));
+        self.sequence_active = true;
+    }
+
+    /// Stop the current sequence and silence its notes
+    pub fn stop_sequence(&mut self) {
+        if let Some(playback) = self.sequence.take() {
+            for (_, _, channel) in playback.voices {
+                self.stop_channel(channel);
+            }
+        }
+        self.sequence_active = false;
+    }
+
+    /// Advance the playing sequence by `delta_secs`, firing every event due
+    pub fn advance_sequence(&mut self, delta_secs: f64) {
+        let Some(mut playback) = self.sequence.take() else {
+            return;
+        };
+
+        let mut remaining = delta_secs;
+        while let Some(event) = playback.sequence.events.get(playback.next_event).copied() {
+            let tick_secs = playback.sequence.tick_secs(playback.tempo);
+            let wait = (event.tick as f64 - playback.tick).max(0.0) * tick_secs;
+            if wait > remaining {
+                playback.tick += remaining / tick_secs;
+                break;
+            }
+
+            remaining -= wait;
+            playback.tick = event.tick as f64;
+            playback.next_event += 1;
+            self.handle_sequence_event(&mut playback, event.kind);
+        }
+
+        if playback.is_finished() {
+            tracing::debug!("Sequence finished");
+            self.sequence_active = false;
+        } else {
+            self.sequence = Some(playback);
+        }
+    }
+
+    fn handle_sequence_event(&mut self, playback: &mut SequencePlayback, kind: SeqEventKind) {
+        match kind {
+            SeqEventKind::NoteOn {
+                channel,
+                note,
+                velocity,
+            } => {
+                tracing::debug!(
+                    "Sequence note on: channel {} note {} velocity {}",
+                    channel,
+                    note,
+                    velocity
+                );
+                self.sequence_note_off(playback, channel, note);
+
+                let program = playback.programs[channel as usize % SEQ_CHANNELS];
+                let Some(tone) = playback.find_tone(program, note).cloned() else {
+                    tracing::debug!("No tone for program {} note {}", program, note);
+                    return;
+                };
+                let Some(vag) = playback.samples.get(tone.vag_index as usize) else {
+                    tracing::warn!("Tone references missing VAG {}", tone.vag_index);
+                    return;
+                };
+                let program_volume = playback
+                    .bank
+                    .get_program(program as usize)
+                    .map_or(127, |program| program.volume);
+                let Some(sound_channel) = self.allocate_channel(tone.priority) else {
+                    return;
+                };
+
+                let volume =
+                    velocity as u32 * tone.volume as u32 * program_volume as u32 * 2 / (127 * 127);
+                let pitch = NotePitch {
+                    note,
+                    center_note: tone.center_note,
+                    center_tune: tone.center_tune,
+                };
+                self.play_vag(
+                    sound_channel,
+                    vag,
+                    1.0,
+                    volume.min(255) as u8,
+                    // VAB pans run 0..=127 with 64 centered
+                    tone.pan.saturating_mul(2),
+                    Some(pitch),
+                );
+
+                // The channel may have been taken from another note
+                playback
+                    .voices
+                    .retain(|&(_, _, used)| used != sound_channel);
+                playback.voices.push((channel, note, sound_channel));
+            }
+            SeqEventKind::NoteOff { channel, note } => {
+                tracing::debug!("Sequence note off: channel {} note {}", channel, note);
+                self.sequence_note_off(playback, channel, note);
+            }
+            SeqEventKind::ProgramChange { channel, program } => {
+                playback.programs[channel as usize % SEQ_CHANNELS] = program;
+            }
+            SeqEventKind::Tempo(tempo) => playback.tempo = tempo,
+            SeqEventKind::Controller { .. } | SeqEventKind::PitchBend { .. } => {
+                tracing::trace!("Unhandled sequence event: {:?}", kind);
+            }
+            SeqEventKind::EndOfTrack => playback.next_event = playback.sequence.events.len(),
+        }
+    }
+
+    /// Release a sounding note, if any
+    fn sequence_note_off(&mut self, playback: &mut SequencePlayback, channel: u8, note: u8) {
+        if let Some(index) = playback
+            .voices
+            .iter()
+            .position(|&(c, n, _)| c == channel && n == note)
+        {
+            let (_, _, sound_channel) = playback.voices.remove(index);
+            self.stop_channel(sound_channel);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use psxutils::formats::seq::SeqEvent;
+    use psxutils::formats::vab::{Program, VagSample};
+
+    fn test_bank() -> Vab {
+        Vab {
+            vab_id: 0,
+            master_volume: 127,
+            master_pan: 64,
+            programs: vec![Program {
+                num_tones: 1,
+                volume: 127,
+                priority: 0,
+                mode: 0,
+                pan: 64,
+                pitch_bend: 0,
+            }],
+            tones: vec![Tone {
+                priority: 0x18,
+                mode: 0,
+                volume: 127,
+                pan: 64,
+                center_note: 60,
+                center_tune: 0,
+                min_note: 0,
+                max_note: 127,
+                vibrato_width: 0,
+                vibrato_time: 0,
+                portamento_width: 0,
+                portamento_time: 0,
+                pitch_bend_min: 0,
+                pitch_bend_max: 0,
+                adsr1: 0,
+                adsr2: 0,
+                program_index: 0,
+                vag_index: 0,
+            }],
+            vag_samples: vec![VagSample { data: vec![0; 32] }],
+        }
+    }
+
+    fn test_sequence() -> Sequence {
+        let event = |tick, kind| SeqEvent { tick, kind };
+        Sequence {
+            resolution: 480,
+            tempo: 500_000, // 0.5 s per quarter note
+            numerator: 4,
+            denominator: 2,
+            events: vec![
+                event(
+                    0,
+                    SeqEventKind::NoteOn {
+                        channel: 0,
+                        note: 72,
+                        velocity: 127,
+                    },
+                ),
+                event(
+                    480,
+                    SeqEventKind::NoteOff {
+                        channel: 0,
+                        note: 72,
+                    },
+                ),
+                event(960, SeqEventKind::EndOfTrack),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_sequence_plays_notes_in_time() {
+        let mut audio = AudioSystem::new();
+        audio.play_sequence(test_sequence(), test_bank());
+        assert!(audio.sequence_active);
+
+        audio.advance_sequence(0.0);
+        assert!(audio.channels[0].is_active());
+        // Played an octave above the center note
+        assert_eq!(audio.pending[0].samples.len(), 28);
+        assert_eq!(audio.channels[0].volume, 254);
+
+        audio.advance_sequence(0.4);
+        assert!(audio.channels[0].is_active());
+
+        audio.advance_sequence(0.2);
+        assert!(!audio.channels[0].is_active());
+        assert!(audio.sequence_active);
+
+        audio.advance_sequence(0.5);
+        assert!(!audio.sequence_active);
+    }
+
+    #[test]
+    fn test_stop_sequence_silences_notes() {
+        let mut audio = AudioSystem::new();
+        audio.play_sequence(test_sequence(), test_bank());
+        audio.advance_sequence(0.1);
+
+        audio.stop_sequence();
+        assert!(!audio.sequence_active);
+        assert!(!audio.channels[0].is_active());
+        audio.advance_sequence(1.0);
+        assert!(!audio.channels[0].is_active());
+    }
+}
@@ -1,22 +1,119 @@
 //! Battle system
 //!
 //! Implements the turn-based battle system including:
-//! - Art system (combo input)
+//! - Art system (combo input, buffered by `input::InputBuffer`)
 //! - Damage calculation
 //! - Enemy AI
 //! - Battle animations
+//!
+//! Damage is announced with [`DamageDealt`]; hits rumble the gamepad
+//! through [`Haptics`], harder on criticals.
 
+use crate::input::{Haptics, InputBuffer};
+use crate::state::{GameState, StateScopedSystems, StateTransitionEvent};
 use bevy::prelude::*;
+use std::time::Duration;
+
+/// How hard a hit landed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitSeverity {
+    Miss,
+    Normal,
+    Critical,
+}
+
+impl HitSeverity {
+    /// Rumble strength and duration for a hit of this severity
+    pub fn rumble(self) -> Option<(f32, Duration)> {
+        match self {
+            HitSeverity::Miss => None,
+            HitSeverity::Normal => Some((0.4, Duration::from_millis(100))),
+            HitSeverity::Critical => Some((1.0, Duration::from_millis(250))),
+        }
+    }
+}
+
+/// Sent when an attack resolves against a target
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DamageDealt {
+    pub target: Entity,
+    pub amount: u32,
+    pub severity: HitSeverity,
+}
 
 pub struct BattlePlugin;
 
 impl Plugin for BattlePlugin {
     fn build(&self, app: &mut App) {
-        // TODO: Add state-based systems when state management is configured
-        app.add_systems(Update, update_battle);
+        app.add_message::<DamageDealt>()
+            .add_state_systems(GameState::Battle, update_battle)
+            .add_systems(Update, (clear_inputs_on_enter, rumble_on_damage));
+    }
+}
+
+/// Pulse the gamepad for every hit that landed
+pub fn rumble_on_damage(mut damage: MessageReader<DamageDealt>, mut haptics: ResMut<Haptics>) {
+    for hit in damage.read() {
+        if let Some((strength, duration)) = hit.severity.rumble() {
+            haptics.pulse(strength, duration);
+        }
+    }
+}
+
+/// Drop directions buffered on the field so they can't complete an Art
+fn clear_inputs_on_enter(
+    mut transitions: MessageReader<StateTransitionEvent>,
+    mut buffer: ResMut<InputBuffer>,
+) {
+    if transitions
+        .read()
+        .any(|transition| transition.enters(GameState::Battle))
+    {
+        buffer.clear();
     }
 }
 
 fn update_battle() {
     // TODO: Update battle logic
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_critical_hit_rumbles_harder() {
+        let mut app = App::new();
+        app.add_message::<DamageDealt>()
+            .init_resource::<Haptics>()
+            .add_systems(Update, rumble_on_damage);
+
+        for severity in [
+            HitSeverity::Normal,
+            HitSeverity::Miss,
+            HitSeverity::Critical,
+        ] {
+            app.world_mut().write_message(DamageDealt {
+                target: Entity::PLACEHOLDER,
+                amount: 10,
+                severity,
+            });
+        }
+        app.update();
+
+        let pulses = app.world().resource::<Haptics>().pending();
+        assert_eq!(pulses.len(), 2);
+        assert!(pulses[1].strength > pulses[0].strength);
+        assert!(pulses[1].duration > pulses[0].duration);
+
+        // Disabled vibration drops pulses
+        app.world_mut().resource_mut::<Haptics>().set_enabled(false);
+        app.world_mut().write_message(DamageDealt {
+            target: Entity::PLACEHOLDER,
+            amount: 10,
+            severity: HitSeverity::Critical,
+        });
+        app.update();
+        assert!(app.world().resource::<Haptics>().pending().is_empty());
+    }
+}
@@ -2,7 +2,9 @@
 //!
 //! Global state management for the Legend of Legaia game engine using Bevy ECS.
 
+use crate::input::PsxButton;
 use bevy::prelude::*;
+use std::time::Duration;
 
 /// Display settings for screen effects
 ///
@@ -30,6 +32,90 @@ impl Default for DisplaySettings {
     }
 }
 
+/// Field camera parameters
+///
+/// Mirrors the original camera globals (`g_camera_x/y/z_offset`,
+/// `g_camera_distance`, `g_camera_zoom_level`). Values are in PSX units;
+/// offsets use the PSX convention of negative Y pointing up.
+#[derive(Resource, Debug, Clone)]
+pub struct CameraState {
+    /// Offset from the target toward the camera (X)
+    pub x_offset: i16,
+    /// Offset from the target toward the camera (Y, negative = up)
+    pub y_offset: i16,
+    /// Offset from the target toward the camera (Z)
+    pub z_offset: i16,
+    /// Base distance from the target
+    pub distance: u16,
+    /// Zoom as 4.12 fixed point (0x1000 = 1.0); higher zooms in
+    pub zoom_level: u16,
+    /// Entity the camera follows (None = world origin)
+    pub target: Option<Entity>,
+}
+
+impl Default for CameraState {
+    fn default() -> Self {
+        Self {
+            x_offset: 0,
+            y_offset: -0x100,
+            z_offset: 0x200,
+            distance: 0x400,
+            zoom_level: 0x1000,
+            target: None,
+        }
+    }
+}
+
+impl CameraState {
+    /// Follow an entity
+    pub fn set_target(&mut self, entity: Entity) {
+        self.target = Some(entity);
+    }
+
+    /// Stop following and look at the world origin
+    pub fn clear_target(&mut self) {
+        self.target = None;
+    }
+
+    /// Distance from the target after applying the zoom level
+    pub fn effective_distance(&self) -> f32 {
+        self.distance as f32 * 4096.0 / self.zoom_level.max(1) as f32
+    }
+}
+
+/// GPU drawing configuration
+///
+/// Configures color processing for primitives drawn without a texture.
+#[derive(Resource, Debug, Clone)]
+pub struct GpuConfig {
+    /// Color mask for RGB channels (0xffffff = no masking)
+    pub color_mask: u32,
+    /// Default primitive color as 0xAABBGGRR
+    pub primitive_color: u32,
+}
+
+impl Default for GpuConfig {
+    fn default() -> Self {
+        Self {
+            color_mask: 0x00ff_ffff,
+            primitive_color: 0x2c80_8080,
+        }
+    }
+}
+
+impl GpuConfig {
+    /// Base color for untextured meshes
+    ///
+    /// Unpacks [`primitive_color`](Self::primitive_color) with
+    /// [`color_mask`](Self::color_mask) applied to its RGB channels.
+    pub fn default_material_color(&self) -> Color {
+        let rgb = self.primitive_color & self.color_mask;
+        let [r, g, b, _] = rgb.to_le_bytes();
+        let a = (self.primitive_color >> 24) as u8;
+        Color::srgba_u8(r, g, b, a)
+    }
+}
+
 /// Game debug configuration
 ///
 /// Development and testing options.
@@ -59,12 +145,154 @@ impl Default for DebugConfig {
     }
 }
 
+/// Controller state
+///
+/// Mirrors the 16-bit pad bitfield the original game polls each frame:
+/// active-low, so 0xffff means no buttons pressed and a cleared bit means
+/// the button is held. Updated by the input system in `PreUpdate`.
+#[derive(Resource, Debug, Clone)]
+pub struct InputState {
+    /// Current pad bitfield (active-low)
+    pub controller_state: u16,
+    /// Pad bitfield from the previous frame (active-low)
+    pub previous_state: u16,
+}
+
+impl Default for InputState {
+    fn default() -> Self {
+        Self {
+            controller_state: 0xffff,
+            previous_state: 0xffff,
+        }
+    }
+}
+
+impl InputState {
+    /// Advance one frame with the given set of held buttons
+    ///
+    /// `pressed` is active-high (a set bit means held); it is stored
+    /// inverted to match the original bitfield.
+    pub fn update(&mut self, pressed: u16) {
+        self.previous_state = self.controller_state;
+        self.controller_state = !pressed;
+    }
+
+    /// Whether a button is currently held
+    pub fn is_pressed(&self, button: PsxButton) -> bool {
+        self.controller_state & button.mask() == 0
+    }
+
+    /// Whether a button was pressed this frame
+    pub fn just_pressed(&self, button: PsxButton) -> bool {
+        self.is_pressed(button) && self.previous_state & button.mask() != 0
+    }
+
+    /// Whether a button was released this frame
+    pub fn just_released(&self, button: PsxButton) -> bool {
+        !self.is_pressed(button) && self.previous_state & button.mask() == 0
+    }
+}
+
+/// Television standard, which sets the VSync rate
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VideoMode {
+    /// 60 Hz (North America, Japan)
+    #[default]
+    Ntsc,
+    /// 50 Hz (Europe)
+    Pal,
+}
+
+impl VideoMode {
+    /// VSync rate in Hz
+    pub fn refresh_hz(self) -> u32 {
+        match self {
+            VideoMode::Ntsc => 60,
+            VideoMode::Pal => 50,
+        }
+    }
+}
+
+/// Game logic timing
+///
+/// The original logic is locked to VSync, advancing once every
+/// `vsync_frames_target` vertical blanks. Frame-locked systems run in
+/// `FixedUpdate`, whose timestep follows this resource.
+#[derive(Resource, Debug, Clone)]
+pub struct TimingState {
+    /// Video standard the game runs at
+    pub video_mode: VideoMode,
+    /// VSyncs per logic frame (1 = every VSync)
+    pub vsync_frames_target: u32,
+}
+
+impl Default for TimingState {
+    fn default() -> Self {
+        Self {
+            video_mode: VideoMode::Ntsc,
+            vsync_frames_target: 1,
+        }
+    }
+}
+
+impl TimingState {
+    /// Logic frames per second
+    pub fn tick_hz(&self) -> u32 {
+        (self.video_mode.refresh_hz() / self.vsync_frames_target.max(1)).max(1)
+    }
+
+    /// Length of one logic frame
+    ///
+    /// Truncated to whole nanoseconds, so a simulated second always holds
+    /// [`tick_hz`](Self::tick_hz) frames.
+    pub fn tick_duration(&self) -> Duration {
+        Duration::from_nanos(1_000_000_000 / self.tick_hz() as u64)
+    }
+}
+
+/// Keep the fixed timestep in step with [`TimingState`]
+fn sync_fixed_timestep(timing: Res<TimingState>, mut fixed: ResMut<Time<Fixed>>) {
+    if timing.is_changed() {
+        fixed.set_timestep(timing.tick_duration());
+        tracing::debug!("Logic frame rate: {} Hz", timing.tick_hz());
+    }
+}
+
 /// Plugin to register all core state resources
 pub struct CoreStatePlugin;
 
 impl Plugin for CoreStatePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<DisplaySettings>()
-            .init_resource::<DebugConfig>();
+            .init_resource::<CameraState>()
+            .init_resource::<GpuConfig>()
+            .init_resource::<DebugConfig>()
+            .init_resource::<InputState>()
+            .init_resource::<TimingState>()
+            .add_systems(PreUpdate, sync_fixed_timestep);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_material_color() {
+        let gpu = GpuConfig::default();
+        assert_eq!(
+            gpu.default_material_color().to_srgba().to_u8_array(),
+            [0x80, 0x80, 0x80, 0x2c]
+        );
+
+        // Only the RGB channels are masked
+        let masked = GpuConfig {
+            color_mask: 0x0000_00ff,
+            ..gpu
+        };
+        assert_eq!(
+            masked.default_material_color().to_srgba().to_u8_array(),
+            [0x80, 0, 0, 0x2c]
+        );
     }
 }
@@ -0,0 +1,197 @@
+//! Field collision
+//!
+//! Each map has a grid of walkable and blocked cells on the XZ plane.
+//! Movement is resolved one axis at a time, so running diagonally into a
+//! wall keeps the component along the wall and the player slides instead of
+//! stopping dead.
+
+use bevy::prelude::*;
+
+/// Gap kept between an entity and the blocked cell it stopped against
+const SKIN: f32 = 0.001;
+
+/// Walkable/blocked grid of the current map
+#[derive(Resource, Debug, Clone)]
+pub struct CollisionMap {
+    /// World position (X, Z) of the corner of cell (0, 0)
+    origin: Vec2,
+    /// Width and depth of one cell in world units
+    cell_size: f32,
+    /// Cells along X
+    width: usize,
+    /// Cells along Z
+    depth: usize,
+    /// Blocked flag per cell, row-major by Z
+    blocked: Vec<bool>,
+}
+
+impl Default for CollisionMap {
+    fn default() -> Self {
+        Self::new(Vec2::ZERO, 1.0, 0, 0)
+    }
+}
+
+impl CollisionMap {
+    /// Create a fully walkable grid
+    pub fn new(origin: Vec2, cell_size: f32, width: usize, depth: usize) -> Self {
+        Self {
+            origin,
+            cell_size: cell_size.max(f32::EPSILON),
+            width,
+            depth,
+            blocked: vec![false; width * depth],
+        }
+    }
+
+    /// Mark a cell as blocked or walkable (out-of-range cells are ignored)
+    pub fn set_blocked(&mut self, cell_x: usize, cell_z: usize, blocked: bool) {
+        if cell_x < self.width && cell_z < self.depth {
+            self.blocked[cell_z * self.width + cell_x] = blocked;
+        }
+    }
+
+    /// Cell containing a world position, if inside the grid
+    pub fn cell_at(&self, x: f32, z: f32) -> Option<(usize, usize)> {
+        let cell_x = ((x - self.origin.x) / self.cell_size).floor();
+        let cell_z = ((z - self.origin.y) / self.cell_size).floor();
+
+        if cell_x < 0.0 || cell_z < 0.0 {
+            return None;
+        }
+        let (cell_x, cell_z) = (cell_x as usize, cell_z as usize);
+        (cell_x < self.width && cell_z < self.depth).then_some((cell_x, cell_z))
+    }
+
+    /// Whether a world position can be walked on
+    ///
+    /// Positions outside the grid are never walkable.
+    pub fn is_walkable(&self, x: f32, z: f32) -> bool {
+        self.cell_at(x, z)
+            .is_some_and(|(cell_x, cell_z)| !self.blocked[cell_z * self.width + cell_x])
+    }
+
+    /// Move from `from` by `delta`, stopping at blocked cells and sliding
+    /// along them
+    ///
+    /// The delta is applied in steps of at most half a cell so fast movement
+    /// can't skip over a wall. Y is applied without collision.
+    pub fn resolve_move(&self, from: Vec3, delta: Vec3) -> Vec3 {
+        let horizontal = Vec2::new(delta.x, delta.z);
+        let steps = (horizontal.length() / (self.cell_size * 0.5))
+            .ceil()
+            .max(1.0) as usize;
+        let step = horizontal / steps as f32;
+
+        let mut position = Vec2::new(from.x, from.z);
+        for _ in 0..steps {
+            position.x = self.move_axis(position.x, step.x, self.origin.x, |x| {
+                self.is_walkable(x, position.y)
+            });
+            position.y = self.move_axis(position.y, step.y, self.origin.y, |z| {
+                self.is_walkable(position.x, z)
+            });
+        }
+
+        Vec3::new(position.x, from.y + delta.y, position.y)
+    }
+
+    /// Move along one axis, clamping to the edge of the current cell if the
+    /// target is blocked
+    fn move_axis(&self, value: f32, step: f32, origin: f32, walkable: impl Fn(f32) -> bool) -> f32 {
+        if step == 0.0 || walkable(value + step) {
+            return value + step;
+        }
+
+        let cell = ((value - origin) / self.cell_size).floor();
+        let edge = if step > 0.0 {
+            origin + (cell + 1.0) * self.cell_size - SKIN
+        } else {
+            origin + cell * self.cell_size + SKIN
+        };
+
+        // Never push the entity backwards if it is already inside the skin
+        if step > 0.0 {
+            edge.max(value)
+        } else {
+            edge.min(value)
+        }
+    }
+}
+
+/// Movement an entity wants to make this frame
+///
+/// Applied by [`apply_move_intents`] against the [`CollisionMap`] and then
+/// reset to zero.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq)]
+pub struct MoveIntent {
+    pub delta: Vec3,
+}
+
+/// Move entities by their intent, clamped against the collision map
+pub fn apply_move_intents(
+    map: Res<CollisionMap>,
+    mut movers: Query<(&mut Transform, &mut MoveIntent)>,
+) {
+    for (mut transform, mut intent) in &mut movers {
+        if intent.delta != Vec3::ZERO {
+            transform.translation = map.resolve_move(transform.translation, intent.delta);
+            intent.delta = Vec3::ZERO;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 4x4 map with a wall along cell column x = 2
+    fn walled_map() -> CollisionMap {
+        let mut map = CollisionMap::new(Vec2::ZERO, 1.0, 4, 4);
+        for z in 0..4 {
+            map.set_blocked(2, z, true);
+        }
+        map
+    }
+
+    #[test]
+    fn test_is_walkable() {
+        let map = walled_map();
+        assert!(map.is_walkable(0.5, 0.5));
+        assert!(!map.is_walkable(2.5, 0.5));
+        assert!(!map.is_walkable(-0.5, 0.5));
+        assert!(!map.is_walkable(0.5, 4.5));
+    }
+
+    #[test]
+    fn test_move_into_wall_is_clamped() {
+        let mut app = App::new();
+        app.insert_resource(walled_map())
+            .add_systems(Update, apply_move_intents);
+
+        let player = app
+            .world_mut()
+            .spawn((
+                Transform::from_xyz(1.5, 0.0, 1.5),
+                MoveIntent {
+                    delta: Vec3::new(2.0, 0.0, 0.0),
+                },
+            ))
+            .id();
+        app.update();
+
+        let entity = app.world().entity(player);
+        let position = entity.get::<Transform>().unwrap().translation;
+        assert!(position.x < 2.0 && position.x > 1.99);
+        assert_eq!(position.z, 1.5);
+        assert_eq!(entity.get::<MoveIntent>().unwrap().delta, Vec3::ZERO);
+    }
+
+    #[test]
+    fn test_slide_along_wall() {
+        let map = walled_map();
+        let position = map.resolve_move(Vec3::new(1.5, 0.0, 1.5), Vec3::new(1.0, 0.0, 1.0));
+
+        assert!(position.x < 2.0);
+        assert!((position.z - 2.5).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,263 @@
+//! Random encounters
+//!
+//! Every [`EncounterTable::step_length`] world units the player walks counts
+//! as one step, and each step rolls against the current zone's encounter
+//! rate. A hit picks an enemy group by weight, sends [`StartBattle`] and
+//! requests a faded transition to [`GameState::Battle`]. After a battle the
+//! player gets [`EncounterTable::safe_distance`] of free walking before rolls
+//! resume.
+//!
+//! Rolls come from the shared [`GameRng`], so a fixed seed gives a
+//! reproducible encounter sequence.
+
+use super::FieldPlayer;
+use crate::rng::GameRng;
+use crate::state::{GameState, StateManager, TransitionEffect};
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// Encounter rate that triggers on every step
+pub const RATE_ALWAYS: u32 = 256;
+
+/// Request to start a battle against an enemy group
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StartBattle {
+    pub group: u16,
+}
+
+/// Enemy group that can appear in a zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncounterGroup {
+    pub group: u16,
+    /// Relative chance of this group being picked
+    pub weight: u32,
+}
+
+/// Encounter settings for one zone of a map
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EncounterZone {
+    /// Chance of an encounter per step, out of 256
+    pub rate: u32,
+    pub groups: Vec<EncounterGroup>,
+}
+
+impl EncounterZone {
+    /// Pick a group by weight from a roll in `0..=PsxRand::MAX`
+    fn pick_group(&self, roll: u32) -> Option<u16> {
+        let total: u32 = self.groups.iter().map(|g| g.weight).sum();
+        if total == 0 {
+            return None;
+        }
+
+        let mut pick = roll % total;
+        for group in &self.groups {
+            if pick < group.weight {
+                return Some(group.group);
+            }
+            pick -= group.weight;
+        }
+        None
+    }
+}
+
+/// Encounter zones of the current map
+#[derive(Resource, Debug, Clone)]
+pub struct EncounterTable {
+    /// Zones by id
+    pub zones: HashMap<u32, EncounterZone>,
+    /// Zone the player is in (`None` for towns and other safe areas)
+    pub current_zone: Option<u32>,
+    /// World distance counted as one step
+    pub step_length: f32,
+    /// Distance walked after a battle before rolls resume
+    pub safe_distance: f32,
+}
+
+impl Default for EncounterTable {
+    fn default() -> Self {
+        Self {
+            zones: HashMap::new(),
+            current_zone: None,
+            step_length: 1.0,
+            safe_distance: 16.0,
+        }
+    }
+}
+
+impl EncounterTable {
+    /// Zone the player is in, if it has encounters
+    pub fn zone(&self) -> Option<&EncounterZone> {
+        self.current_zone.and_then(|id| self.zones.get(&id))
+    }
+}
+
+/// Progress toward the next encounter
+#[derive(Resource, Debug, Clone, Default)]
+pub struct EncounterState {
+    /// Distance walked toward the next step
+    pub distance: f32,
+    /// Steps taken since the last encounter
+    pub steps: u32,
+    /// Distance left before rolls resume
+    pub safe_distance: f32,
+    /// Encounters disabled (e.g. by a repel item)
+    pub disabled: bool,
+    /// Player position last frame
+    pub last_position: Option<Vec3>,
+}
+
+impl EncounterState {
+    /// Count `distance` walked, returning the group of any encounter it
+    /// triggers
+    ///
+    /// Safe distance is used up before any steps count. Nothing is rolled
+    /// while encounters are disabled, outside an encounter zone, or when the
+    /// table's step length isn't positive.
+    pub fn walk(
+        &mut self,
+        distance: f32,
+        table: &EncounterTable,
+        rng: &mut GameRng,
+    ) -> Option<u16> {
+        let mut distance = distance;
+        if self.safe_distance > 0.0 {
+            let used = distance.min(self.safe_distance);
+            self.safe_distance -= used;
+            distance -= used;
+        }
+
+        // A zero, negative or NaN step length would never finish a step
+        let zone = table
+            .zone()
+            .filter(|_| !self.disabled && table.step_length > 0.0)?;
+        self.distance += distance;
+
+        while self.distance >= table.step_length {
+            self.distance -= table.step_length;
+            self.steps += 1;
+
+            if rng.rand() % RATE_ALWAYS < zone.rate {
+                let group = zone.pick_group(rng.rand())?;
+                self.distance = 0.0;
+                self.steps = 0;
+                self.safe_distance = table.safe_distance;
+                return Some(group);
+            }
+        }
+
+        None
+    }
+}
+
+/// Accumulate the player's movement and start a battle on an encounter
+pub fn update_encounters(
+    table: Res<EncounterTable>,
+    mut state: ResMut<EncounterState>,
+    mut rng: ResMut<GameRng>,
+    player: Query<&Transform, With<FieldPlayer>>,
+    mut battles: MessageWriter<StartBattle>,
+    mut state_mgr: ResMut<StateManager>,
+) {
+    let Ok(transform) = player.single() else {
+        return;
+    };
+
+    let position = transform.translation;
+    let Some(last) = state.last_position.replace(position) else {
+        return;
+    };
+
+    let walked = Vec2::new(position.x - last.x, position.z - last.z).length();
+    if let Some(group) = state.walk(walked, &table, &mut rng) {
+        tracing::info!("Random encounter: group {}", group);
+        battles.write(StartBattle { group });
+        state_mgr.request_transition_with(GameState::Battle, TransitionEffect::Fade);
+        state.last_position = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(rate: u32) -> EncounterTable {
+        let zone = EncounterZone {
+            rate,
+            groups: vec![
+                EncounterGroup {
+                    group: 3,
+                    weight: 1,
+                },
+                EncounterGroup {
+                    group: 4,
+                    weight: 3,
+                },
+            ],
+        };
+        EncounterTable {
+            zones: HashMap::from([(1, zone)]),
+            current_zone: Some(1),
+            ..default()
+        }
+    }
+
+    /// Walk one step at a time until an encounter, returning the steps taken
+    fn steps_to_encounter(
+        state: &mut EncounterState,
+        table: &EncounterTable,
+        rng: &mut GameRng,
+    ) -> (u32, u16) {
+        for step in 1..=1000 {
+            if let Some(group) = state.walk(table.step_length, table, rng) {
+                return (step, group);
+            }
+        }
+        panic!("no encounter");
+    }
+
+    #[test]
+    fn test_fixed_seed_encounter() {
+        let table = table(32);
+        let mut state = EncounterState::default();
+        let mut rng = GameRng::new(1);
+
+        // The 14th step's roll (12060 % 256 = 28) is the first below 32, and
+        // the group roll (32225 % 4 = 1) lands in the weight-3 group
+        assert_eq!(steps_to_encounter(&mut state, &table, &mut rng), (14, 4));
+        assert_eq!(state.steps, 0);
+        assert_eq!(state.safe_distance, table.safe_distance);
+    }
+
+    #[test]
+    fn test_safe_distance_and_repel() {
+        let table = table(RATE_ALWAYS);
+        let mut state = EncounterState::default();
+        let mut rng = GameRng::new(1);
+        state.safe_distance = 2.5;
+
+        assert_eq!(state.walk(2.0, &table, &mut rng), None);
+        assert_eq!(state.walk(0.5, &table, &mut rng), None);
+        assert!(state.walk(1.0, &table, &mut rng).is_some());
+
+        state.safe_distance = 0.0;
+        state.disabled = true;
+        assert_eq!(state.walk(10.0, &table, &mut rng), None);
+        assert_eq!(state.steps, 0);
+    }
+
+    #[test]
+    fn test_non_positive_step_length_never_rolls() {
+        let mut rng = GameRng::new(1);
+
+        for step_length in [0.0, -1.0, f32::NAN] {
+            let table = EncounterTable {
+                step_length,
+                ..table(RATE_ALWAYS)
+            };
+            let mut state = EncounterState::default();
+
+            assert_eq!(state.walk(10.0, &table, &mut rng), None);
+            assert_eq!(state.steps, 0);
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! NPC interactions
+//!
+//! Pressing [`PsxButton::CONFIRM`] while facing an [`Interactable`] within
+//! its range sends [`Interact`] for the nearest one. While a [`DialogueBox`]
+//! is open, confirm belongs to the text and nothing is sent. The message
+//! carries the script callback name and the NPC's flags, which is what
+//! `legaia_scripting`'s `ScriptEngine::call_entity_callback` takes (the
+//! flags go in `EntityScriptContext::flags`). The engine doesn't link the
+//! Lua runtime itself, since `legaia_scripting` is built against a
+//! different Bevy release, so whatever hosts the script engine reads these
+//! messages and makes the call.
+
+use super::FieldPlayer;
+use crate::core_state::InputState;
+use crate::graphics::DialogueBox;
+use crate::input::PsxButton;
+use bevy::prelude::*;
+
+/// Cosine of the widest angle between the player's facing and an NPC that
+/// still counts as facing it (60 degrees either side)
+const FACING_COS: f32 = 0.5;
+
+/// Entity the player can talk to or examine
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Interactable {
+    /// Script function called on interaction
+    pub callback: String,
+    /// Greatest distance on the XZ plane the player can interact from
+    pub range: f32,
+    /// Flags passed to the script (e.g. dialogue progress)
+    pub flags: u32,
+}
+
+impl Interactable {
+    pub fn new(callback: impl Into<String>, range: f32) -> Self {
+        Self {
+            callback: callback.into(),
+            range,
+            flags: 0,
+        }
+    }
+}
+
+/// The player interacted with an [`Interactable`]
+#[derive(Message, Debug, Clone, PartialEq, Eq)]
+pub struct Interact {
+    pub entity: Entity,
+    pub callback: String,
+    pub flags: u32,
+}
+
+/// Whether `player` is within `range` of `target` and facing it
+pub fn can_interact(player: &Transform, target: Vec3, range: f32) -> bool {
+    let offset = Vec2::new(
+        target.x - player.translation.x,
+        target.z - player.translation.z,
+    );
+    let distance = offset.length();
+    if distance > range {
+        return false;
+    }
+    if distance <= f32::EPSILON {
+        return true;
+    }
+
+    let forward = player.forward();
+    let facing = Vec2::new(forward.x, forward.z).normalize_or_zero();
+    facing.dot(offset / distance) >= FACING_COS
+}
+
+/// Send [`Interact`] for the nearest faced interactable when confirm is
+/// pressed
+///
+/// Runs before [`advance_dialogue`](crate::graphics::dialogue::advance_dialogue)
+/// so the press that closes the last page still sees the box open and
+/// doesn't start the conversation again.
+pub fn trigger_interactions(
+    input: Res<InputState>,
+    dialogue: Res<DialogueBox>,
+    player: Query<&Transform, With<FieldPlayer>>,
+    interactables: Query<(Entity, &Transform, &Interactable)>,
+    mut interactions: MessageWriter<Interact>,
+) {
+    if dialogue.is_open() || !input.just_pressed(PsxButton::CONFIRM) {
+        return;
+    }
+    let Ok(player) = player.single() else {
+        return;
+    };
+
+    let nearest = interactables
+        .iter()
+        .filter(|(_, transform, interactable)| {
+            can_interact(player, transform.translation, interactable.range)
+        })
+        .min_by(|(_, a, _), (_, b, _)| {
+            let a = a.translation.distance_squared(player.translation);
+            let b = b.translation.distance_squared(player.translation);
+            a.total_cmp(&b)
+        });
+
+    if let Some((entity, _, interactable)) = nearest {
+        interactions.write(Interact {
+            entity,
+            callback: interactable.callback.clone(),
+            flags: interactable.flags,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct Calls(Vec<String>);
+
+    fn record_calls(mut interactions: MessageReader<Interact>, mut calls: ResMut<Calls>) {
+        calls.0.extend(
+            interactions
+                .read()
+                .map(|interaction| interaction.callback.clone()),
+        );
+    }
+
+    /// Stand-in for the script host: every interaction opens a one-page box
+    fn open_dialogue(mut interactions: MessageReader<Interact>, mut dialogue: ResMut<DialogueBox>) {
+        for _ in interactions.read() {
+            dialogue.open(["Welcome to Rim Elm."]);
+        }
+    }
+
+    #[test]
+    fn test_facing_and_range() {
+        // Transform::forward is -Z
+        let player = Transform::default();
+        assert!(can_interact(&player, Vec3::new(0.0, 0.0, -1.0), 1.5));
+        assert!(!can_interact(&player, Vec3::new(0.0, 0.0, -2.0), 1.5));
+        assert!(!can_interact(&player, Vec3::new(0.0, 0.0, 1.0), 1.5));
+        assert!(!can_interact(&player, Vec3::new(1.0, 0.0, 0.0), 1.5));
+    }
+
+    #[test]
+    fn test_confirm_calls_callback_once() {
+        let mut app = App::new();
+        app.init_resource::<InputState>()
+            .init_resource::<DialogueBox>()
+            .init_resource::<Calls>()
+            .add_message::<Interact>()
+            .add_systems(Update, (trigger_interactions, record_calls).chain());
+
+        app.world_mut().spawn((Transform::default(), FieldPlayer));
+        app.world_mut().spawn((
+            Transform::from_xyz(0.0, 0.0, -1.0),
+            Interactable::new("talk_elder", 1.5),
+        ));
+        app.world_mut().spawn((
+            Transform::from_xyz(0.0, 0.0, 1.0),
+            Interactable::new("behind_player", 1.5),
+        ));
+
+        // Confirm held for two frames is a single press
+        for _ in 0..2 {
+            app.world_mut()
+                .resource_mut::<InputState>()
+                .update(PsxButton::CONFIRM.mask());
+            app.update();
+        }
+
+        assert_eq!(app.world().resource::<Calls>().0, ["talk_elder"]);
+    }
+    #[test]
+    fn test_closing_dialogue_does_not_reopen() {
+        let mut app = App::new();
+        app.init_resource::<InputState>()
+            .init_resource::<DialogueBox>()
+            .init_resource::<Calls>()
+            .add_message::<Interact>()
+            .add_systems(
+                Update,
+                (
+                    trigger_interactions,
+                    crate::graphics::dialogue::advance_dialogue,
+                    record_calls,
+                    open_dialogue,
+                )
+                    .chain(),
+            );
+
+        app.world_mut().spawn((Transform::default(), FieldPlayer));
+        app.world_mut().spawn((
+            Transform::from_xyz(0.0, 0.0, -1.0),
+            Interactable::new("talk_elder", 1.5),
+        ));
+
+        // Talk, reveal the page, then close it: three separate presses
+        for _ in 0..3 {
+            for mask in [PsxButton::CONFIRM.mask(), 0] {
+                app.world_mut().resource_mut::<InputState>().update(mask);
+                app.update();
+            }
+        }
+
+        assert!(!app.world().resource::<DialogueBox>().is_open());
+        assert_eq!(app.world().resource::<Calls>().0, ["talk_elder"]);
+    }
+}
@@ -5,18 +5,67 @@
 //! - Collision detection
 //! - NPC interactions
 //! - Random encounters
+//!
+//! Movement goes through a [`MoveIntent`] on the entity, which is resolved
+//! against the current map's [`CollisionMap`]. The distance the
+//! [`FieldPlayer`] walks feeds the random encounter rolls, and pressing
+//! confirm near an [`Interactable`] sends [`Interact`] for its script.
+
+pub mod collision;
+pub mod encounter;
+pub mod interact;
 
+pub use collision::{CollisionMap, MoveIntent};
+pub use encounter::{EncounterState, EncounterTable, StartBattle};
+pub use interact::{Interact, Interactable};
+
+use crate::graphics::dialogue::advance_dialogue;
+use crate::state::{GameState, StateScopedSystems, StateTransitionEvent};
 use bevy::prelude::*;
 
 pub struct FieldPlugin;
 
 impl Plugin for FieldPlugin {
     fn build(&self, app: &mut App) {
-        // TODO: Add state-based systems when state management is configured
-        app.add_systems(Update, update_field);
+        app.init_resource::<CollisionMap>()
+            .init_resource::<EncounterTable>()
+            .init_resource::<EncounterState>()
+            .add_message::<StartBattle>()
+            .add_message::<Interact>()
+            .add_state_systems(
+                GameState::Field,
+                (
+                    update_field,
+                    collision::apply_move_intents,
+                    encounter::update_encounters,
+                    interact::trigger_interactions.before(advance_dialogue),
+                )
+                    .chain(),
+            )
+            .add_systems(Update, reset_field_on_enter);
     }
 }
 
+/// Forget the player's last position when entering the field
+///
+/// The player may have been moved while away (e.g. after a battle), which
+/// must not count as walked distance for encounters.
+fn reset_field_on_enter(
+    mut transitions: MessageReader<StateTransitionEvent>,
+    mut encounters: ResMut<EncounterState>,
+) {
+    if transitions
+        .read()
+        .any(|transition| transition.enters(GameState::Field))
+    {
+        encounters.last_position = None;
+    }
+}
+
+/// Marker for the entity the player controls on the field
+#[derive(Component, Debug, Default)]
+pub struct FieldPlayer;
+
 fn update_field() {
     // TODO: Update field logic
 }
@@ -0,0 +1,63 @@
+//! Field camera controller
+//!
+//! Positions the `Camera3d` each frame from [`CameraState`]: the camera sits
+//! along the offset direction from its target, at the zoomed distance, and
+//! looks back at the target.
+
+use crate::core_state::CameraState;
+use bevy::prelude::*;
+
+/// Camera transform for a given focus point
+pub fn camera_transform(state: &CameraState, focus: Vec3) -> Transform {
+    // PSX Y points down
+    let offset = Vec3::new(
+        state.x_offset as f32,
+        -(state.y_offset as f32),
+        state.z_offset as f32,
+    );
+    let direction = offset.try_normalize().unwrap_or(Vec3::Z);
+    let eye = focus + direction * state.effective_distance();
+
+    Transform::from_translation(eye).looking_at(focus, Vec3::Y)
+}
+
+/// Move cameras to follow the [`CameraState`] target
+pub fn update_camera(
+    camera_state: Res<CameraState>,
+    targets: Query<&GlobalTransform>,
+    mut cameras: Query<&mut Transform, With<Camera3d>>,
+) {
+    let focus = camera_state
+        .target
+        .and_then(|entity| targets.get(entity).ok())
+        .map_or(Vec3::ZERO, GlobalTransform::translation);
+
+    let transform = camera_transform(&camera_state, focus);
+    for mut camera in &mut cameras {
+        *camera = transform;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zoom_moves_camera_closer() {
+        let focus = Vec3::new(100.0, 0.0, 50.0);
+        let mut state = CameraState::default();
+        let far = camera_transform(&state, focus).translation.distance(focus);
+
+        state.zoom_level = 0x2000;
+        let near = camera_transform(&state, focus).translation.distance(focus);
+
+        assert!(near < far);
+        assert!((far - 1024.0).abs() < 1e-3);
+        assert!((near - 512.0).abs() < 1e-3);
+
+        // Camera above the target (negative PSX Y) looks down at it
+        let transform = camera_transform(&state, focus);
+        assert!(transform.translation.y > focus.y);
+        assert!(transform.forward().y < 0.0);
+    }
+}
@@ -0,0 +1,392 @@
+//! Dialogue box
+//!
+//! NPC conversations are shown a page at a time in a bordered panel at the
+//! bottom of the screen. Characters are revealed typewriter-style at
+//! [`DialogueBox::chars_per_frame`] per logic frame; confirm either
+//! finishes the current page or moves to the next one, closing the box
+//! after the last.
+//!
+//! Page text may contain control codes:
+//! - `{pause}` holds the reveal for [`PAUSE_FRAMES`]
+//! - `{color:r,g,b}` switches the color of the following text
+//! - `{name}` is replaced with [`DialogueBox::player_name`]
+//!
+//! Unknown codes are shown as written.
+
+use crate::core_state::InputState;
+use crate::input::PsxButton;
+use bevy::prelude::*;
+
+/// Frames a `{pause}` holds the reveal (half a second at 60 Hz)
+pub const PAUSE_FRAMES: u32 = 30;
+
+/// Default reveal speed in characters per frame
+pub const DEFAULT_CHARS_PER_FRAME: f32 = 0.5;
+
+/// Default text color
+pub const DEFAULT_DIALOGUE_COLOR: Color = Color::WHITE;
+
+/// Parsed element of a dialogue page
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DialogueToken {
+    Char(char),
+    Pause,
+    Color(Color),
+}
+
+/// Parse page text, expanding `{name}` to `player_name`
+pub fn parse_dialogue(text: &str, player_name: &str) -> Vec<DialogueToken> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('{') {
+        tokens.extend(rest[..start].chars().map(DialogueToken::Char));
+        rest = &rest[start..];
+
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        let code = &rest[1..end];
+
+        match parse_code(code) {
+            Some(ControlCode::Pause) => tokens.push(DialogueToken::Pause),
+            Some(ControlCode::Color(color)) => tokens.push(DialogueToken::Color(color)),
+            Some(ControlCode::Name) => tokens.extend(player_name.chars().map(DialogueToken::Char)),
+            None => tokens.extend(rest[..=end].chars().map(DialogueToken::Char)),
+        }
+        rest = &rest[end + 1..];
+    }
+
+    tokens.extend(rest.chars().map(DialogueToken::Char));
+    tokens
+}
+
+enum ControlCode {
+    Pause,
+    Color(Color),
+    Name,
+}
+
+fn parse_code(code: &str) -> Option<ControlCode> {
+    match code {
+        "pause" => Some(ControlCode::Pause),
+        "name" => Some(ControlCode::Name),
+        _ => {
+            let rgb = code.strip_prefix("color:")?;
+            let mut channels = rgb.split(',').map(|c| c.trim().parse::<u8>());
+            let (Some(Ok(r)), Some(Ok(g)), Some(Ok(b)), None) = (
+                channels.next(),
+                channels.next(),
+                channels.next(),
+                channels.next(),
+            ) else {
+                return None;
+            };
+            Some(ControlCode::Color(Color::srgb_u8(r, g, b)))
+        }
+    }
+}
+
+/// Active conversation and its reveal progress
+#[derive(Resource, Debug, Clone)]
+pub struct DialogueBox {
+    /// Parsed pages (empty when closed)
+    pages: Vec<Vec<DialogueToken>>,
+    /// Page being shown
+    page: usize,
+    /// Tokens of the page revealed so far
+    revealed: usize,
+    /// Fractional characters carried to the next frame
+    progress: f32,
+    /// Frames left in a `{pause}`
+    pause_frames: u32,
+    /// Reveal speed
+    pub chars_per_frame: f32,
+    /// Text substituted for `{name}`
+    pub player_name: String,
+}
+
+impl Default for DialogueBox {
+    fn default() -> Self {
+        Self {
+            pages: Vec::new(),
+            page: 0,
+            revealed: 0,
+            progress: 0.0,
+            pause_frames: 0,
+            chars_per_frame: DEFAULT_CHARS_PER_FRAME,
+            player_name: "Vahn".to_string(),
+        }
+    }
+}
+
+impl DialogueBox {
+    /// Start a conversation, replacing any open one
+    pub fn open<S: AsRef<str>>(&mut self, pages: impl IntoIterator<Item = S>) {
+        self.pages = pages
+            .into_iter()
+            .map(|page| parse_dialogue(page.as_ref(), &self.player_name))
+            .collect();
+        self.page = 0;
+        self.reset_page();
+    }
+
+    /// Close the box
+    pub fn close(&mut self) {
+        self.pages.clear();
+        self.reset_page();
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.page < self.pages.len()
+    }
+
+    /// Whether the current page is fully revealed
+    pub fn page_complete(&self) -> bool {
+        self.current_page()
+            .is_none_or(|page| self.revealed >= page.len())
+    }
+
+    /// Advance the reveal by one frame
+    pub fn tick(&mut self) {
+        let Some(page) = self.pages.get(self.page) else {
+            return;
+        };
+
+        if self.pause_frames > 0 {
+            self.pause_frames -= 1;
+            return;
+        }
+
+        self.progress += self.chars_per_frame;
+        while let Some(token) = page.get(self.revealed) {
+            match token {
+                DialogueToken::Color(_) => self.revealed += 1,
+                DialogueToken::Pause => {
+                    self.revealed += 1;
+                    self.pause_frames = PAUSE_FRAMES;
+                    self.progress = 0.0;
+                    break;
+                }
+                DialogueToken::Char(_) => {
+                    if self.progress < 1.0 {
+                        break;
+                    }
+                    self.progress -= 1.0;
+                    self.revealed += 1;
+                }
+            }
+        }
+    }
+
+    /// Handle confirm: finish the page, or go to the next and close after
+    /// the last
+    pub fn confirm(&mut self) {
+        if !self.page_complete() {
+            self.revealed = self.current_page().map_or(0, Vec::len);
+            self.pause_frames = 0;
+            return;
+        }
+
+        self.page += 1;
+        self.reset_page();
+        if !self.is_open() {
+            self.close();
+        }
+    }
+
+    /// Revealed text of the current page as runs of one color
+    pub fn visible_runs(&self) -> Vec<(Color, String)> {
+        let mut runs: Vec<(Color, String)> = Vec::new();
+        let mut color = DEFAULT_DIALOGUE_COLOR;
+
+        let tokens = self
+            .current_page()
+            .map_or(&[][..], |page| &page[..self.revealed]);
+        for token in tokens {
+            match *token {
+                DialogueToken::Color(new_color) => color = new_color,
+                DialogueToken::Pause => {}
+                DialogueToken::Char(c) => match runs.last_mut() {
+                    Some((run_color, text)) if *run_color == color => text.push(c),
+                    _ => runs.push((color, c.to_string())),
+                },
+            }
+        }
+
+        runs
+    }
+
+    fn current_page(&self) -> Option<&Vec<DialogueToken>> {
+        self.pages.get(self.page)
+    }
+
+    fn reset_page(&mut self) {
+        self.revealed = 0;
+        self.progress = 0.0;
+        self.pause_frames = 0;
+    }
+}
+
+/// Marker for the dialogue panel
+#[derive(Component)]
+pub struct DialoguePanel;
+
+/// Marker for the text inside the dialogue panel
+#[derive(Component)]
+pub struct DialogueText;
+
+/// Spawn the (hidden) dialogue panel
+pub fn spawn_dialogue_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(5.0),
+                right: Val::Percent(5.0),
+                bottom: Val::Percent(4.0),
+                height: Val::Percent(25.0),
+                border: UiRect::all(Val::Px(2.0)),
+                padding: UiRect::all(Val::Px(12.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.25, 0.85)),
+            BorderColor::all(Color::WHITE),
+            Visibility::Hidden,
+            DialoguePanel,
+        ))
+        .with_child((
+            Text::default(),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            DialogueText,
+        ));
+}
+
+/// Advance the reveal once per logic frame
+pub fn tick_dialogue(mut dialogue: ResMut<DialogueBox>) {
+    if dialogue.is_open() && !dialogue.page_complete() {
+        dialogue.tick();
+    }
+}
+
+/// Finish or advance the page on confirm
+pub fn advance_dialogue(input: Res<InputState>, mut dialogue: ResMut<DialogueBox>) {
+    if dialogue.is_open() && input.just_pressed(PsxButton::CONFIRM) {
+        dialogue.confirm();
+    }
+}
+
+/// Show the panel while a conversation is open and rebuild its text spans
+pub fn render_dialogue(
+    mut commands: Commands,
+    dialogue: Res<DialogueBox>,
+    mut panels: Query<&mut Visibility, With<DialoguePanel>>,
+    texts: Query<Entity, With<DialogueText>>,
+) {
+    if !dialogue.is_changed() {
+        return;
+    }
+
+    for mut visibility in &mut panels {
+        *visibility = if dialogue.is_open() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    let runs = dialogue.visible_runs();
+    for text in &texts {
+        commands
+            .entity(text)
+            .despawn_related::<Children>()
+            .with_children(|parent| {
+                for (color, run) in &runs {
+                    parent.spawn((TextSpan::new(run.clone()), TextColor(*color)));
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames_to_reveal(dialogue: &mut DialogueBox) -> u32 {
+        let mut frames = 0;
+        while !dialogue.page_complete() {
+            dialogue.tick();
+            frames += 1;
+            assert!(frames < 1000);
+        }
+        frames
+    }
+
+    #[test]
+    fn test_reveal_speed() {
+        let mut dialogue = DialogueBox::default();
+        dialogue.open(["0123456789"]);
+        assert_eq!(frames_to_reveal(&mut dialogue), 20);
+
+        dialogue.chars_per_frame = 2.0;
+        dialogue.open(["0123456789"]);
+        dialogue.tick();
+        assert_eq!(
+            dialogue.visible_runs(),
+            [(DEFAULT_DIALOGUE_COLOR, "01".to_string())]
+        );
+        assert_eq!(frames_to_reveal(&mut dialogue), 4);
+    }
+
+    #[test]
+    fn test_control_codes() {
+        let tokens = parse_dialogue("Hi {name}{pause}{color:255,0,0}!{bogus}", "Noa");
+        let text: String = tokens
+            .iter()
+            .filter_map(|token| match token {
+                DialogueToken::Char(c) => Some(*c),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, "Hi Noa!{bogus}");
+        assert!(tokens.contains(&DialogueToken::Pause));
+        assert!(tokens.contains(&DialogueToken::Color(Color::srgb_u8(255, 0, 0))));
+
+        // The pause adds its frames to the reveal
+        let mut dialogue = DialogueBox {
+            chars_per_frame: 1.0,
+            ..default()
+        };
+        dialogue.open(["ab{pause}cd"]);
+        assert_eq!(frames_to_reveal(&mut dialogue), 4 + PAUSE_FRAMES);
+    }
+
+    #[test]
+    fn test_confirm_pages() {
+        let mut dialogue = DialogueBox::default();
+        dialogue.open(["First {color:255,255,0}page", "Second"]);
+
+        // Confirm mid-page reveals the rest of it
+        dialogue.tick();
+        dialogue.confirm();
+        assert!(dialogue.page_complete());
+        assert_eq!(
+            dialogue.visible_runs(),
+            [
+                (DEFAULT_DIALOGUE_COLOR, "First ".to_string()),
+                (Color::srgb_u8(255, 255, 0), "page".to_string()),
+            ]
+        );
+
+        dialogue.confirm();
+        assert!(dialogue.is_open());
+        assert!(dialogue.visible_runs().is_empty());
+
+        dialogue.confirm();
+        dialogue.confirm();
+        assert!(!dialogue.is_open());
+    }
+}
@@ -0,0 +1,172 @@
+//! Screen fade transitions
+//!
+//! Fades step [`DisplaySettings::brightness`] toward a target at
+//! [`DisplaySettings::fade_speed`] and draw a fullscreen black overlay whose
+//! opacity is the missing brightness. The original stores brightness as
+//! 0..=0xf0 and draws the overlay with `0xf0 - brightness`; here brightness
+//! is normalized to 0.0..=1.0, so overlay alpha is `1.0 - brightness`.
+//!
+//! State transitions fade by sending [`StartFade`] (e.g. fade out before
+//! leaving the field, fade in once the battle is set up) and waiting for
+//! [`FadeComplete`].
+
+use crate::core_state::DisplaySettings;
+use bevy::prelude::*;
+
+/// Fade direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FadeDirection {
+    /// Fade from black toward full brightness
+    In,
+    /// Fade toward black
+    Out,
+}
+
+impl FadeDirection {
+    /// Default target brightness for this direction
+    pub fn target_brightness(self) -> f32 {
+        match self {
+            FadeDirection::In => 1.0,
+            FadeDirection::Out => 0.0,
+        }
+    }
+}
+
+/// Request a fade to `target_brightness`
+#[derive(Message, Debug, Clone, Copy)]
+pub struct StartFade {
+    pub direction: FadeDirection,
+    pub target_brightness: f32,
+}
+
+impl StartFade {
+    /// Fade in to full brightness
+    pub fn fade_in() -> Self {
+        Self {
+            direction: FadeDirection::In,
+            target_brightness: FadeDirection::In.target_brightness(),
+        }
+    }
+
+    /// Fade out to black
+    pub fn fade_out() -> Self {
+        Self {
+            direction: FadeDirection::Out,
+            target_brightness: FadeDirection::Out.target_brightness(),
+        }
+    }
+}
+
+/// Sent when a fade reaches its target brightness
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FadeComplete {
+    pub direction: FadeDirection,
+}
+
+/// Fade in progress, if any
+#[derive(Resource, Debug, Default)]
+pub struct FadeState {
+    /// Direction of the active fade
+    pub active: Option<FadeDirection>,
+}
+
+/// Marker for the fullscreen fade overlay
+#[derive(Component)]
+pub struct FadeOverlay;
+
+/// Step brightness toward the target by `fade_speed * delta_secs`
+///
+/// Returns true once the target brightness is reached.
+pub fn step_fade(settings: &mut DisplaySettings, delta_secs: f32) -> bool {
+    let step = settings.fade_speed * delta_secs;
+    let remaining = settings.target_brightness - settings.brightness;
+
+    if remaining.abs() <= step {
+        settings.brightness = settings.target_brightness;
+        true
+    } else {
+        settings.brightness += step.copysign(remaining);
+        false
+    }
+}
+
+/// Spawn the fade overlay above all other UI
+pub fn spawn_fade_overlay(mut commands: Commands, settings: Res<DisplaySettings>) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        BackgroundColor(Color::BLACK.with_alpha(1.0 - settings.brightness)),
+        GlobalZIndex(i32::MAX),
+        FadeOverlay,
+    ));
+}
+
+/// Start requested fades and advance the active one
+pub fn update_fade(
+    time: Res<Time>,
+    mut requests: MessageReader<StartFade>,
+    mut completed: MessageWriter<FadeComplete>,
+    mut settings: ResMut<DisplaySettings>,
+    mut fade: ResMut<FadeState>,
+) {
+    // A newer request replaces any fade in progress
+    if let Some(request) = requests.read().last() {
+        settings.target_brightness = request.target_brightness.clamp(0.0, 1.0);
+        fade.active = Some(request.direction);
+    }
+
+    let Some(direction) = fade.active else {
+        return;
+    };
+
+    if step_fade(&mut settings, time.delta_secs()) {
+        fade.active = None;
+        completed.write(FadeComplete { direction });
+    }
+}
+
+/// Keep the overlay opacity in sync with brightness
+pub fn update_fade_overlay(
+    settings: Res<DisplaySettings>,
+    mut overlays: Query<&mut BackgroundColor, With<FadeOverlay>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for mut color in &mut overlays {
+        color.0 = Color::BLACK.with_alpha(1.0 - settings.brightness);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fade_reaches_target() {
+        let mut settings = DisplaySettings {
+            target_brightness: 0.0,
+            ..default()
+        };
+
+        // 2.0 units per second at 64 FPS: a full fade takes 32 frames
+        let mut frames = 0;
+        while !step_fade(&mut settings, 1.0 / 64.0) {
+            frames += 1;
+            assert!(settings.brightness > 0.0);
+        }
+        frames += 1;
+
+        assert_eq!(frames, 32);
+        assert_eq!(settings.brightness, 0.0);
+
+        settings.target_brightness = 0.5;
+        assert!(!step_fade(&mut settings, 0.1));
+        assert!((settings.brightness - 0.2).abs() < 1e-6);
+    }
+}
@@ -4,21 +4,62 @@
 //! - Model rendering
 //! - Texture management
 //! - Animation playback
-//! - Camera control
+//! - Camera control (following [`CameraState`](crate::CameraState))
 //! - Debug text rendering
+//! - Screen fades
+//! - Dialogue boxes
+//! - CLUT palette cycling
 
+pub mod camera;
 pub mod debug;
+pub mod dialogue;
+pub mod fade;
+pub mod palette;
 
+use crate::state::StateTransitionEvent;
 use bevy::prelude::*;
 pub use debug::DebugRenderer;
+pub use dialogue::DialogueBox;
+pub use fade::{FadeComplete, FadeDirection, FadeState, StartFade};
+pub use palette::PaletteAnimation;
 
 pub struct GraphicsPlugin;
 
 impl Plugin for GraphicsPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<DebugRenderer>()
-            .add_systems(Startup, setup_graphics)
+            .init_resource::<FadeState>()
+            .init_resource::<DialogueBox>()
+            .add_message::<StartFade>()
+            .add_message::<FadeComplete>()
+            .add_systems(
+                Startup,
+                (
+                    setup_graphics,
+                    fade::spawn_fade_overlay,
+                    dialogue::spawn_dialogue_panel,
+                ),
+            )
             .add_systems(Update, update_graphics)
+            .add_systems(
+                PostUpdate,
+                camera::update_camera.before(bevy::transform::TransformSystems::Propagate),
+            )
+            .add_systems(
+                Update,
+                (fade::update_fade, fade::update_fade_overlay).chain(),
+            )
+            .add_systems(FixedUpdate, dialogue::tick_dialogue)
+            .add_systems(
+                Update,
+                (
+                    close_dialogue_on_transition,
+                    dialogue::advance_dialogue,
+                    dialogue::render_dialogue,
+                )
+                    .chain(),
+            )
+            .add_systems(Update, palette::animate_palettes)
             .add_systems(Update, debug::render_debug_text)
             .add_systems(Update, debug::handle_debug_input);
     }
@@ -30,6 +71,16 @@ fn setup_graphics(debug_renderer: Res<DebugRenderer>) {
     // TODO: Setup rendering pipeline
 }
 
+/// Close any open dialogue when the game state changes
+fn close_dialogue_on_transition(
+    mut transitions: MessageReader<StateTransitionEvent>,
+    mut dialogue: ResMut<DialogueBox>,
+) {
+    if transitions.read().count() > 0 && dialogue.is_open() {
+        dialogue.close();
+    }
+}
+
 fn update_graphics() {
     // TODO: Update graphics
 }
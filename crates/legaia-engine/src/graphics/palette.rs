@@ -0,0 +1,217 @@
+//! CLUT palette cycling
+//!
+//! Water, fire and lava animate by rotating runs of CLUT entries while the
+//! pixel indices stay put. A [`PaletteAnimation`] keeps the TIM and its
+//! loaded CLUT; [`animate_palettes`] advances each animation and rewrites the
+//! texture of the [`StandardMaterial`] on the same entity, which Bevy then
+//! re-uploads.
+//!
+//! Ranges index the whole CLUT, so on multi-row CLUTs they can cycle entries
+//! of any row; [`PaletteAnimation::palette_row`] picks the row that is drawn.
+
+use anyhow::{bail, Context, Result};
+use bevy::asset::RenderAssetUsages;
+use bevy::image::ImageSampler;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use psxutils::formats::tim::ClutData;
+use psxutils::Tim;
+use std::ops::Range;
+
+/// Cycles CLUT entry ranges of an indexed TIM over time
+#[derive(Component, Debug, Clone)]
+pub struct PaletteAnimation {
+    /// CLUT as loaded; rotations are always applied to a copy of it
+    pub base: ClutData,
+    /// CLUT entry ranges to cycle, each rotated independently
+    pub ranges: Vec<Range<usize>>,
+    /// Rotation steps per second
+    pub speed: f32,
+    /// CLUT row used when rendering the texture
+    pub palette_row: usize,
+    /// Texture being recolored (its CLUT is the rotated copy)
+    tim: Tim,
+    /// Time since the animation started, in seconds
+    elapsed: f32,
+    /// Rotation steps applied so far
+    steps: usize,
+}
+
+impl PaletteAnimation {
+    /// Default rotation speed: one step every four frames at 60 FPS
+    pub const DEFAULT_SPEED: f32 = 15.0;
+
+    /// Animate `ranges` of an indexed TIM's CLUT
+    ///
+    /// Fails if the TIM has no CLUT or a range is empty or runs past the end
+    /// of the CLUT.
+    pub fn from_tim(tim: &Tim, ranges: Vec<Range<usize>>) -> Result<Self> {
+        let base = tim
+            .clut
+            .clone()
+            .with_context(|| format!("{:?} TIM has no CLUT to animate", tim.pixel_mode))?;
+
+        for range in &ranges {
+            if range.is_empty() || range.end > base.data.len() {
+                bail!(
+                    "Palette range {:?} outside CLUT ({} entries)",
+                    range,
+                    base.data.len()
+                );
+            }
+        }
+
+        Ok(Self {
+            base,
+            ranges,
+            speed: Self::DEFAULT_SPEED,
+            palette_row: 0,
+            tim: tim.clone(),
+            elapsed: 0.0,
+            steps: 0,
+        })
+    }
+
+    /// Set the rotation speed in steps per second
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Rotation steps applied so far
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Current CLUT with every range rotated by [`Self::steps`]
+    ///
+    /// Each step moves colors one entry toward the start of their range, so
+    /// entry `start + i` shows the base color at `start + (i + steps) % len`.
+    pub fn clut(&self) -> ClutData {
+        let mut clut = self.base.clone();
+        for range in &self.ranges {
+            let len = range.len();
+            clut.data[range.clone()].rotate_left(self.steps % len);
+        }
+        clut
+    }
+
+    /// Advance the animation by `delta_secs`
+    ///
+    /// Returns true if the rotation changed and the texture needs updating.
+    pub fn advance(&mut self, delta_secs: f32) -> bool {
+        self.elapsed += delta_secs;
+        let steps = (self.elapsed * self.speed) as usize;
+        if steps == self.steps {
+            return false;
+        }
+
+        self.steps = steps;
+        self.tim.clut = Some(self.clut());
+        true
+    }
+
+    /// Render the texture with the current palette as RGBA8
+    pub fn rgba8(&self) -> Result<Vec<u8>> {
+        Ok(self.tim.to_rgba8_with_palette(self.palette_row)?)
+    }
+
+    /// Build a Bevy image of the texture with the current palette
+    ///
+    /// Use this as the material's `base_color_texture`. Sampling is nearest
+    /// neighbor so palette edges stay crisp.
+    pub fn image(&self) -> Result<Image> {
+        let mut image = Image::new(
+            Extent3d {
+                width: self.tim.width() as u32,
+                height: self.tim.height() as u32,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            self.rgba8()?,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        image.sampler = ImageSampler::nearest();
+        Ok(image)
+    }
+}
+
+/// Advance palette animations and rewrite their material textures
+pub fn animate_palettes(
+    time: Res<Time>,
+    mut animations: Query<(&mut PaletteAnimation, &MeshMaterial3d<StandardMaterial>)>,
+    materials: Res<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for (mut animation, material) in &mut animations {
+        if !animation.advance(time.delta_secs()) {
+            continue;
+        }
+
+        let Some(texture) = materials
+            .get(&material.0)
+            .and_then(|material| material.base_color_texture.as_ref())
+        else {
+            continue;
+        };
+
+        match animation.rgba8() {
+            Ok(rgba) => {
+                if let Some(image) = images.get_mut(texture) {
+                    image.data = Some(rgba);
+                }
+            }
+            Err(e) => tracing::warn!("Palette animation failed: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use psxutils::formats::tim::PixelMode;
+
+    #[test]
+    fn test_palette_rotation() {
+        // 4x1 4-bit texture over a 16-entry CLUT
+        let clut: Vec<u16> = (0..16).collect();
+        let tim = Tim::from_indexed(4, 1, PixelMode::Clut4Bit, &clut, &[4, 5, 6, 7]).unwrap();
+        let mut animation = PaletteAnimation::from_tim(&tim, vec![4..8, 10..13])
+            .unwrap()
+            .with_speed(16.0);
+
+        // 16 steps per second at 64 FPS: one step every four frames
+        let mut updates = 0;
+        for _ in 0..40 {
+            if animation.advance(1.0 / 64.0) {
+                updates += 1;
+            }
+        }
+        assert_eq!(animation.steps(), 10);
+        assert_eq!(updates, 10);
+
+        // 10 steps is 2 around the 4-entry range and 1 around the 3-entry one
+        let clut = animation.clut();
+        assert_eq!(&clut.data[4..8], &[6, 7, 4, 5]);
+        assert_eq!(&clut.data[10..13], &[11, 12, 10]);
+        assert_eq!(&clut.data[..4], &[0, 1, 2, 3]);
+        assert_eq!(&clut.data[13..], &[13, 14, 15]);
+    }
+
+    #[test]
+    fn test_invalid_ranges() {
+        let clut: Vec<u16> = (0..16).collect();
+        let tim = Tim::from_indexed(4, 1, PixelMode::Clut4Bit, &clut, &[0; 4]).unwrap();
+
+        let past_end = 12..17;
+        let empty = 3..3;
+        assert!(PaletteAnimation::from_tim(&tim, vec![past_end]).is_err());
+        assert!(PaletteAnimation::from_tim(&tim, vec![empty]).is_err());
+
+        let mut direct = tim.clone();
+        direct.clut = None;
+        let whole = 0..4;
+        assert!(PaletteAnimation::from_tim(&direct, vec![whole]).is_err());
+    }
+}
@@ -0,0 +1,89 @@
+//! Gamepad rumble
+//!
+//! Game systems queue pulses on the [`Haptics`] resource;
+//! [`apply_haptics`] turns them into rumble requests for every connected
+//! gamepad. With no gamepad connected, or with vibration turned off in the
+//! options, pulses are simply dropped.
+
+use bevy::input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest};
+use bevy::prelude::*;
+use std::time::Duration;
+
+/// One rumble pulse
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pulse {
+    /// Motor strength (0.0..=1.0)
+    pub strength: f32,
+    /// How long the motors run
+    pub duration: Duration,
+}
+
+/// Queue of rumble pulses for the connected gamepads
+#[derive(Resource, Debug, Clone)]
+pub struct Haptics {
+    /// Vibration option; pulses are ignored while disabled
+    pub enabled: bool,
+    /// Pulses queued since the last update
+    pending: Vec<Pulse>,
+}
+
+impl Default for Haptics {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl Haptics {
+    /// Rumble every connected gamepad at `strength` (clamped to 0.0..=1.0)
+    pub fn pulse(&mut self, strength: f32, duration: Duration) {
+        if !self.enabled {
+            return;
+        }
+
+        self.pending.push(Pulse {
+            strength: strength.clamp(0.0, 1.0),
+            duration,
+        });
+    }
+
+    /// Turn vibration on or off, dropping queued pulses when turned off
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.pending.clear();
+        }
+    }
+
+    /// Pulses waiting to be sent, oldest first
+    pub fn pending(&self) -> &[Pulse] {
+        &self.pending
+    }
+}
+
+/// Send queued pulses to every connected gamepad
+pub fn apply_haptics(
+    mut haptics: ResMut<Haptics>,
+    gamepads: Query<Entity, With<Gamepad>>,
+    mut rumble: MessageWriter<GamepadRumbleRequest>,
+) {
+    if haptics.pending.is_empty() {
+        return;
+    }
+
+    for pulse in haptics.pending.drain(..) {
+        let intensity = GamepadRumbleIntensity {
+            strong_motor: pulse.strength,
+            weak_motor: pulse.strength,
+        };
+        for gamepad in &gamepads {
+            rumble.write(GamepadRumbleRequest::Add {
+                gamepad,
+                intensity,
+                duration: pulse.duration,
+            });
+        }
+    }
+}
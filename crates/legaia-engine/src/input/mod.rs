@@ -5,23 +5,383 @@
 //! - Keyboard input
 //! - Input buffering (for Art system)
 //! - Menu navigation
+//! - Gamepad rumble
+//!
+//! Keyboard keys and gamepad buttons are mapped to PSX pad buttons through
+//! the [`InputMap`] resource, and the result is written to
+//! [`InputState`](crate::InputState) once per frame. Directional presses
+//! are also recorded in the [`InputBuffer`] for Art combo input, whose
+//! window advances with the fixed logic frame rate. Rumble pulses are
+//! queued on [`Haptics`].
+
+pub mod haptics;
+
+pub use haptics::Haptics;
 
+use crate::core_state::InputState;
 use bevy::prelude::*;
+use std::collections::VecDeque;
+
+/// Directional inputs kept by the [`InputBuffer`]
+pub const INPUT_BUFFER_CAPACITY: usize = 16;
+
+/// Frames a buffered input stays valid (2 seconds at 60 Hz)
+pub const INPUT_BUFFER_WINDOW: u32 = 120;
+
+/// PSX digital pad button
+///
+/// Discriminants are bit positions in the pad bitfield.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PsxButton {
+    Select = 0,
+    Start = 3,
+    Up = 4,
+    Right = 5,
+    Down = 6,
+    Left = 7,
+    L2 = 8,
+    R2 = 9,
+    L1 = 10,
+    R1 = 11,
+    Triangle = 12,
+    Circle = 13,
+    Cross = 14,
+    Square = 15,
+}
+
+impl PsxButton {
+    /// Button that confirms menu choices and talks/examines on the field
+    pub const CONFIRM: PsxButton = PsxButton::Cross;
+
+    /// Button that cancels menu choices
+    pub const CANCEL: PsxButton = PsxButton::Triangle;
+
+    /// All pad buttons
+    pub const ALL: [PsxButton; 14] = [
+        PsxButton::Select,
+        PsxButton::Start,
+        PsxButton::Up,
+        PsxButton::Right,
+        PsxButton::Down,
+        PsxButton::Left,
+        PsxButton::L2,
+        PsxButton::R2,
+        PsxButton::L1,
+        PsxButton::R1,
+        PsxButton::Triangle,
+        PsxButton::Circle,
+        PsxButton::Cross,
+        PsxButton::Square,
+    ];
+
+    /// Bit of this button in the pad bitfield
+    pub fn mask(self) -> u16 {
+        1 << self as u16
+    }
+}
+
+/// Direction of an Art command input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArtDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl ArtDirection {
+    /// All directions
+    pub const ALL: [ArtDirection; 4] = [
+        ArtDirection::Up,
+        ArtDirection::Down,
+        ArtDirection::Left,
+        ArtDirection::Right,
+    ];
+
+    /// D-pad button entering this direction
+    pub fn button(self) -> PsxButton {
+        match self {
+            ArtDirection::Up => PsxButton::Up,
+            ArtDirection::Down => PsxButton::Down,
+            ArtDirection::Left => PsxButton::Left,
+            ArtDirection::Right => PsxButton::Right,
+        }
+    }
+}
+
+/// Recent directional inputs with the frame each was entered on
+///
+/// Holds at most [`INPUT_BUFFER_CAPACITY`] inputs; inputs older than the
+/// buffer's window are evicted as frames advance.
+#[derive(Resource, Debug, Clone)]
+pub struct InputBuffer {
+    /// Buffered inputs, oldest first
+    entries: VecDeque<(ArtDirection, u32)>,
+    /// Current frame
+    frame: u32,
+    /// Frames an input is kept for
+    window_frames: u32,
+}
+
+impl Default for InputBuffer {
+    fn default() -> Self {
+        Self::new(INPUT_BUFFER_WINDOW)
+    }
+}
+
+impl InputBuffer {
+    /// Create an empty buffer keeping inputs for `window_frames` frames
+    pub fn new(window_frames: u32) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(INPUT_BUFFER_CAPACITY),
+            frame: 0,
+            window_frames,
+        }
+    }
+
+    /// Advance one frame, evicting inputs older than the window
+    pub fn tick(&mut self) {
+        self.frame = self.frame.wrapping_add(1);
+        while let Some(&(_, frame)) = self.entries.front() {
+            if self.frame.wrapping_sub(frame) <= self.window_frames {
+                break;
+            }
+            self.entries.pop_front();
+        }
+    }
+
+    /// Record a directional input on the current frame
+    pub fn record(&mut self, direction: ArtDirection) {
+        if self.entries.len() == INPUT_BUFFER_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((direction, self.frame));
+    }
+
+    /// Whether the most recent inputs are `pattern`, all entered within
+    /// the last `window_frames` frames
+    pub fn matches(&self, pattern: &[ArtDirection], window_frames: u32) -> bool {
+        if pattern.is_empty() || pattern.len() > self.entries.len() {
+            return false;
+        }
+
+        let recent = self.entries.range(self.entries.len() - pattern.len()..);
+        let (_, first_frame) = self.entries[self.entries.len() - pattern.len()];
+
+        self.frame.wrapping_sub(first_frame) <= window_frames
+            && recent
+                .zip(pattern)
+                .all(|((direction, _), expected)| direction == expected)
+    }
+
+    /// Buffered inputs, oldest first
+    pub fn entries(&self) -> impl Iterator<Item = &(ArtDirection, u32)> {
+        self.entries.iter()
+    }
+
+    /// Drop all buffered inputs (e.g. once an Art has been executed)
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Mapping from keyboard keys and gamepad buttons to PSX buttons
+///
+/// Several inputs may map to the same PSX button.
+#[derive(Resource, Debug, Clone)]
+pub struct InputMap {
+    /// Keyboard bindings
+    pub keyboard: Vec<(KeyCode, PsxButton)>,
+    /// Gamepad bindings (applied to every connected gamepad)
+    pub gamepad: Vec<(GamepadButton, PsxButton)>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self {
+            keyboard: vec![
+                (KeyCode::ArrowUp, PsxButton::Up),
+                (KeyCode::ArrowDown, PsxButton::Down),
+                (KeyCode::ArrowLeft, PsxButton::Left),
+                (KeyCode::ArrowRight, PsxButton::Right),
+                (KeyCode::KeyX, PsxButton::Cross),
+                (KeyCode::KeyC, PsxButton::Circle),
+                (KeyCode::KeyD, PsxButton::Triangle),
+                (KeyCode::KeyS, PsxButton::Square),
+                (KeyCode::KeyQ, PsxButton::L1),
+                (KeyCode::KeyE, PsxButton::R1),
+                (KeyCode::Digit1, PsxButton::L2),
+                (KeyCode::Digit3, PsxButton::R2),
+                (KeyCode::Enter, PsxButton::Start),
+                (KeyCode::Backspace, PsxButton::Select),
+            ],
+            gamepad: vec![
+                (GamepadButton::DPadUp, PsxButton::Up),
+                (GamepadButton::DPadDown, PsxButton::Down),
+                (GamepadButton::DPadLeft, PsxButton::Left),
+                (GamepadButton::DPadRight, PsxButton::Right),
+                (GamepadButton::South, PsxButton::Cross),
+                (GamepadButton::East, PsxButton::Circle),
+                (GamepadButton::North, PsxButton::Triangle),
+                (GamepadButton::West, PsxButton::Square),
+                (GamepadButton::LeftTrigger, PsxButton::L1),
+                (GamepadButton::RightTrigger, PsxButton::R1),
+                (GamepadButton::LeftTrigger2, PsxButton::L2),
+                (GamepadButton::RightTrigger2, PsxButton::R2),
+                (GamepadButton::Start, PsxButton::Start),
+                (GamepadButton::Select, PsxButton::Select),
+            ],
+        }
+    }
+}
+
+impl InputMap {
+    /// Bind a key to a PSX button, replacing any previous binding of the key
+    pub fn bind_key(&mut self, key: KeyCode, button: PsxButton) {
+        self.keyboard.retain(|(bound, _)| *bound != key);
+        self.keyboard.push((key, button));
+    }
+
+    /// Bind a gamepad button to a PSX button, replacing any previous binding
+    pub fn bind_gamepad(&mut self, gamepad_button: GamepadButton, button: PsxButton) {
+        self.gamepad.retain(|(bound, _)| *bound != gamepad_button);
+        self.gamepad.push((gamepad_button, button));
+    }
+
+    /// Remove all keyboard and gamepad bindings of a PSX button
+    pub fn unbind(&mut self, button: PsxButton) {
+        self.keyboard.retain(|(_, bound)| *bound != button);
+        self.gamepad.retain(|(_, bound)| *bound != button);
+    }
+
+    /// Active-high mask of PSX buttons held on the keyboard and gamepads
+    pub fn pressed_mask<'a>(
+        &self,
+        keyboard: &ButtonInput<KeyCode>,
+        gamepads: impl IntoIterator<Item = &'a Gamepad>,
+    ) -> u16 {
+        let mut mask = self
+            .keyboard
+            .iter()
+            .filter(|(key, _)| keyboard.pressed(*key))
+            .fold(0, |mask, (_, button)| mask | button.mask());
+
+        for gamepad in gamepads {
+            mask = self
+                .gamepad
+                .iter()
+                .filter(|(gamepad_button, _)| gamepad.pressed(*gamepad_button))
+                .fold(mask, |mask, (_, button)| mask | button.mask());
+        }
+
+        mask
+    }
+}
 
 pub struct InputPlugin;
 
 impl Plugin for InputPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_input)
-            .add_systems(PreUpdate, handle_input);
+        app.init_resource::<InputMap>()
+            .init_resource::<InputBuffer>()
+            .init_resource::<Haptics>()
+            .add_systems(Startup, setup_input)
+            .add_systems(PreUpdate, handle_input)
+            .add_systems(FixedUpdate, tick_input_buffer)
+            .add_systems(PostUpdate, haptics::apply_haptics);
+    }
+}
+
+fn setup_input(input_map: Res<InputMap>) {
+    tracing::info!(
+        "Input system initialized ({} key bindings, {} gamepad bindings)",
+        input_map.keyboard.len(),
+        input_map.gamepad.len()
+    );
+}
+
+fn handle_input(
+    input_map: Res<InputMap>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut input_state: ResMut<InputState>,
+    mut input_buffer: ResMut<InputBuffer>,
+) {
+    let pressed = input_map.pressed_mask(&keyboard, gamepads);
+    input_state.update(pressed);
+
+    for direction in ArtDirection::ALL {
+        if input_state.just_pressed(direction.button()) {
+            input_buffer.record(direction);
+        }
     }
 }
 
-fn setup_input() {
-    tracing::info!("Input system initialized");
-    // TODO: Setup input mappings
+/// Advance the combo window by one logic frame
+fn tick_input_buffer(mut input_buffer: ResMut<InputBuffer>) {
+    input_buffer.tick();
 }
 
-fn handle_input() {
-    // TODO: Process input
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_state_edges() {
+        let mut state = InputState::default();
+        assert!(!state.is_pressed(PsxButton::Cross));
+
+        state.update(PsxButton::Cross.mask());
+        assert_eq!(state.controller_state, !PsxButton::Cross.mask());
+        assert!(state.is_pressed(PsxButton::Cross));
+        assert!(state.just_pressed(PsxButton::Cross));
+
+        state.update(PsxButton::Cross.mask());
+        assert!(state.is_pressed(PsxButton::Cross));
+        assert!(!state.just_pressed(PsxButton::Cross));
+
+        state.update(0);
+        assert_eq!(state.controller_state, 0xffff);
+        assert!(state.just_released(PsxButton::Cross));
+    }
+
+    #[test]
+    fn test_keyboard_mapping() {
+        let mut input_map = InputMap::default();
+        input_map.bind_key(KeyCode::KeyX, PsxButton::Circle);
+
+        let mut keyboard = ButtonInput::<KeyCode>::default();
+        keyboard.press(KeyCode::KeyX);
+        keyboard.press(KeyCode::ArrowUp);
+
+        let mask = input_map.pressed_mask(&keyboard, std::iter::empty());
+        assert_eq!(mask, PsxButton::Circle.mask() | PsxButton::Up.mask());
+    }
+
+    #[test]
+    fn test_input_buffer_window() {
+        use ArtDirection::*;
+
+        let mut buffer = InputBuffer::new(30);
+        for direction in [Left, Right, Down] {
+            buffer.record(direction);
+            for _ in 0..5 {
+                buffer.tick();
+            }
+        }
+
+        assert!(buffer.matches(&[Left, Right, Down], 30));
+        assert!(buffer.matches(&[Right, Down], 30));
+        assert!(!buffer.matches(&[Right, Left, Down], 30));
+
+        // The sequence started 15 frames ago
+        assert!(!buffer.matches(&[Left, Right, Down], 10));
+
+        // Expired inputs are evicted
+        for _ in 0..30 {
+            buffer.tick();
+        }
+        assert!(!buffer.matches(&[Left, Right, Down], 30));
+        assert_eq!(buffer.entries().count(), 0);
+    }
 }
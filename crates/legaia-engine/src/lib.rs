@@ -16,11 +16,13 @@ pub mod field;
 pub mod graphics;
 pub mod input;
 pub mod menu;
+pub mod rng;
 pub mod setup;
 pub mod state;
 
 pub use core_state::*;
-pub use state::{GameState, StateManager};
+pub use rng::GameRng;
+pub use state::{GameState, StateManager, StateTransitionEvent, TransitionEffect};
 
 use bevy::prelude::*;
 
@@ -34,11 +36,13 @@ impl Plugin for LegaiaEnginePlugin {
             .add_plugins(setup::SetupPlugin)
             // Core state resources
             .add_plugins(CoreStatePlugin)
+            .init_resource::<GameRng>()
             // State management
             .init_state::<GameState>()
             .init_resource::<StateManager>()
-            // Add state management systems
-            .add_systems(Update, state::update_frame_counter)
+            .add_message::<StateTransitionEvent>()
+            // Add state management systems (the frame counter is VSync-locked)
+            .add_systems(FixedUpdate, state::update_frame_counter)
             .add_systems(Update, state::handle_state_transitions)
             // Add core systems
             .add_systems(Startup, setup_engine)
@@ -59,10 +63,8 @@ impl Plugin for LegaiaEnginePlugin {
 
 fn setup_engine(mut commands: Commands) {
     // Camera setup (Bevy 0.18+ uses required components instead of bundles)
-    commands.spawn((
-        Camera3d::default(),
-        Transform::from_xyz(0.0, 5.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y),
-    ));
+    // Positioned each frame from CameraState by graphics::camera
+    commands.spawn((Camera3d::default(), Transform::default()));
 
     // Lighting (Bevy 0.18+ uses required components)
     commands.spawn((
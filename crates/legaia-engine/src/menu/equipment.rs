@@ -0,0 +1,199 @@
+//! Character equipment
+//!
+//! Each character wears a weapon, armor and an accessory. The stat bonuses
+//! of everything worn are summed whenever the equipment changes, so the
+//! status screen and battle read one precomputed [`StatModifiers`].
+
+use super::inventory::ItemId;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::ops::{Add, AddAssign};
+
+/// Equipment slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EquipSlot {
+    Weapon,
+    Armor,
+    Accessory,
+}
+
+impl EquipSlot {
+    /// Every slot, in menu order
+    pub const ALL: [EquipSlot; 3] = [EquipSlot::Weapon, EquipSlot::Armor, EquipSlot::Accessory];
+}
+
+/// Signed stat changes granted by equipment
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatModifiers {
+    pub max_hp: i32,
+    pub max_mp: i32,
+    pub attack: i32,
+    pub defense: i32,
+    pub speed: i32,
+}
+
+impl StatModifiers {
+    /// Apply a modifier to a base stat, never going below zero
+    pub fn apply(base: u32, modifier: i32) -> u32 {
+        base.saturating_add_signed(modifier)
+    }
+}
+
+impl Add for StatModifiers {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            max_hp: self.max_hp + other.max_hp,
+            max_mp: self.max_mp + other.max_mp,
+            attack: self.attack + other.attack,
+            defense: self.defense + other.defense,
+            speed: self.speed + other.speed,
+        }
+    }
+}
+
+impl AddAssign for StatModifiers {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+/// Piece of equipment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EquipItem {
+    /// Item this piece comes from in the inventory
+    pub item: ItemId,
+    /// Stat changes while worn
+    pub modifiers: StatModifiers,
+}
+
+/// Equipment worn by a character
+///
+/// Only the worn items are serialized; the modifiers are recomputed on load.
+#[derive(Component, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "WornItems")]
+pub struct Equipment {
+    weapon: Option<EquipItem>,
+    armor: Option<EquipItem>,
+    accessory: Option<EquipItem>,
+    /// Sum of the modifiers of everything worn
+    #[serde(skip_serializing)]
+    modifiers: StatModifiers,
+}
+
+/// Serialized form of [`Equipment`]
+#[derive(Deserialize)]
+struct WornItems {
+    weapon: Option<EquipItem>,
+    armor: Option<EquipItem>,
+    accessory: Option<EquipItem>,
+}
+
+impl From<WornItems> for Equipment {
+    fn from(worn: WornItems) -> Self {
+        let mut equipment = Equipment {
+            weapon: worn.weapon,
+            armor: worn.armor,
+            accessory: worn.accessory,
+            modifiers: StatModifiers::default(),
+        };
+        equipment.recompute();
+        equipment
+    }
+}
+
+impl Equipment {
+    /// Item worn in a slot
+    pub fn get(&self, slot: EquipSlot) -> Option<&EquipItem> {
+        match slot {
+            EquipSlot::Weapon => self.weapon.as_ref(),
+            EquipSlot::Armor => self.armor.as_ref(),
+            EquipSlot::Accessory => self.accessory.as_ref(),
+        }
+    }
+
+    /// Put an item in a slot, returning the item it replaces
+    pub fn equip(&mut self, slot: EquipSlot, item: EquipItem) -> Option<EquipItem> {
+        let previous = self.slot_mut(slot).replace(item);
+        self.recompute();
+        previous
+    }
+
+    /// Empty a slot, returning its item
+    pub fn unequip(&mut self, slot: EquipSlot) -> Option<EquipItem> {
+        let previous = self.slot_mut(slot).take();
+        self.recompute();
+        previous
+    }
+
+    /// Combined stat changes of everything worn
+    pub fn modifiers(&self) -> StatModifiers {
+        self.modifiers
+    }
+
+    fn slot_mut(&mut self, slot: EquipSlot) -> &mut Option<EquipItem> {
+        match slot {
+            EquipSlot::Weapon => &mut self.weapon,
+            EquipSlot::Armor => &mut self.armor,
+            EquipSlot::Accessory => &mut self.accessory,
+        }
+    }
+
+    fn recompute(&mut self) {
+        self.modifiers = EquipSlot::ALL
+            .iter()
+            .filter_map(|&slot| self.get(slot))
+            .fold(StatModifiers::default(), |sum, item| sum + item.modifiers);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sword(id: u16, attack: i32) -> EquipItem {
+        EquipItem {
+            item: ItemId(id),
+            modifiers: StatModifiers {
+                attack,
+                ..default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_equip_swap_returns_old_item() {
+        let mut equipment = Equipment::default();
+        assert_eq!(equipment.equip(EquipSlot::Weapon, sword(10, 12)), None);
+        assert_eq!(equipment.modifiers().attack, 12);
+
+        let old = equipment.equip(EquipSlot::Weapon, sword(11, 20));
+        assert_eq!(old, Some(sword(10, 12)));
+        assert_eq!(equipment.get(EquipSlot::Weapon), Some(&sword(11, 20)));
+        assert_eq!(equipment.modifiers().attack, 20);
+    }
+
+    #[test]
+    fn test_modifiers_sum_slots() {
+        let mut equipment = Equipment::default();
+        let armor = EquipItem {
+            item: ItemId(30),
+            modifiers: StatModifiers {
+                defense: 15,
+                speed: -2,
+                ..default()
+            },
+        };
+        equipment.equip(EquipSlot::Weapon, sword(10, 12));
+        equipment.equip(EquipSlot::Armor, armor);
+
+        let modifiers = equipment.modifiers();
+        assert_eq!((modifiers.attack, modifiers.defense), (12, 15));
+        assert_eq!(StatModifiers::apply(1, modifiers.speed), 0);
+
+        assert_eq!(equipment.unequip(EquipSlot::Armor), Some(armor));
+        assert_eq!(equipment.modifiers().defense, 0);
+        assert_eq!(equipment.unequip(EquipSlot::Accessory), None);
+    }
+}
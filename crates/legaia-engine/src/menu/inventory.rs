@@ -0,0 +1,178 @@
+//! Party inventory
+//!
+//! Items are kept in stacks of up to [`MAX_STACK`]; adding past a full
+//! stack starts a new one, as long as the inventory has a free slot.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Most items one inventory slot holds
+pub const MAX_STACK: u32 = 99;
+
+/// Number of inventory slots
+pub const INVENTORY_SLOTS: usize = 64;
+
+/// Item identifier (index into the game's item table)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ItemId(pub u16);
+
+/// Inventory slot holding some quantity of one item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemStack {
+    pub item: ItemId,
+    pub quantity: u32,
+}
+
+/// Items carried by the party
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Inventory {
+    /// Occupied slots in display order
+    stacks: Vec<ItemStack>,
+    /// Most items per slot
+    max_stack: u32,
+    /// Most slots
+    capacity: usize,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self::new(MAX_STACK, INVENTORY_SLOTS)
+    }
+}
+
+impl Inventory {
+    /// Create an empty inventory with the given stack size and slot count
+    pub fn new(max_stack: u32, capacity: usize) -> Self {
+        Self {
+            stacks: Vec::new(),
+            max_stack: max_stack.max(1),
+            capacity,
+        }
+    }
+
+    /// Add items, topping up existing stacks before starting new ones
+    ///
+    /// Returns how many items did not fit.
+    pub fn add(&mut self, item: ItemId, quantity: u32) -> u32 {
+        let mut remaining = quantity;
+
+        for stack in self.stacks.iter_mut().filter(|stack| stack.item == item) {
+            let added = remaining.min(self.max_stack - stack.quantity);
+            stack.quantity += added;
+            remaining -= added;
+        }
+
+        while remaining > 0 && self.stacks.len() < self.capacity {
+            let added = remaining.min(self.max_stack);
+            self.stacks.push(ItemStack {
+                item,
+                quantity: added,
+            });
+            remaining -= added;
+        }
+
+        remaining
+    }
+
+    /// Remove items, emptying the last stacks first
+    ///
+    /// Removes nothing and returns `false` if fewer than `quantity` are held.
+    pub fn remove(&mut self, item: ItemId, quantity: u32) -> bool {
+        if self.count(item) < quantity {
+            return false;
+        }
+
+        let mut remaining = quantity;
+        for stack in self
+            .stacks
+            .iter_mut()
+            .rev()
+            .filter(|stack| stack.item == item)
+        {
+            let removed = remaining.min(stack.quantity);
+            stack.quantity -= removed;
+            remaining -= removed;
+        }
+        self.stacks.retain(|stack| stack.quantity > 0);
+
+        true
+    }
+
+    /// Total quantity of an item across all stacks
+    pub fn count(&self, item: ItemId) -> u32 {
+        self.stacks
+            .iter()
+            .filter(|stack| stack.item == item)
+            .map(|stack| stack.quantity)
+            .sum()
+    }
+
+    /// Occupied slots in display order
+    pub fn stacks(&self) -> &[ItemStack] {
+        &self.stacks
+    }
+
+    /// Whether every slot is occupied
+    pub fn is_full(&self) -> bool {
+        self.stacks.len() >= self.capacity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POTION: ItemId = ItemId(1);
+    const ETHER: ItemId = ItemId(2);
+
+    #[test]
+    fn test_stack_overflow_starts_new_slot() {
+        let mut inventory = Inventory::default();
+        assert_eq!(inventory.add(POTION, 90), 0);
+        assert_eq!(inventory.add(POTION, 20), 0);
+
+        assert_eq!(
+            inventory.stacks(),
+            [
+                ItemStack {
+                    item: POTION,
+                    quantity: 99
+                },
+                ItemStack {
+                    item: POTION,
+                    quantity: 11
+                },
+            ]
+        );
+        assert_eq!(inventory.count(POTION), 110);
+    }
+
+    #[test]
+    fn test_full_inventory_returns_leftover() {
+        let mut inventory = Inventory::new(10, 2);
+        assert_eq!(inventory.add(POTION, 15), 0);
+        // Both slots hold potions, so no room for a new item
+        assert_eq!(inventory.add(ETHER, 3), 3);
+        assert!(inventory.is_full());
+        assert_eq!(inventory.add(POTION, 8), 3);
+        assert_eq!(inventory.count(POTION), 20);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut inventory = Inventory::default();
+        inventory.add(POTION, 110);
+        inventory.add(ETHER, 1);
+
+        assert!(!inventory.remove(POTION, 111));
+        assert_eq!(inventory.count(POTION), 110);
+
+        assert!(inventory.remove(POTION, 20));
+        assert_eq!(inventory.count(POTION), 90);
+        assert_eq!(inventory.stacks().len(), 2);
+
+        assert!(inventory.remove(ETHER, 1));
+        assert_eq!(inventory.stacks().len(), 1);
+    }
+}
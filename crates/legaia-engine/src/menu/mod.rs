@@ -6,15 +6,28 @@
 //! - Equipment
 //! - Save/load
 //! - Options
+//!
+//! The party's items are held in the [`Inventory`] resource and each
+//! character's gear in an [`Equipment`] component. Both are written to a
+//! save slot as part of a [`SaveGame`].
+
+pub mod equipment;
+pub mod inventory;
+pub mod save;
+
+pub use equipment::{EquipItem, EquipSlot, Equipment, StatModifiers};
+pub use inventory::{Inventory, ItemId, ItemStack};
+pub use save::{PartyMember, SaveGame};
 
+use crate::state::{GameState, StateScopedSystems};
 use bevy::prelude::*;
 
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
-        // TODO: Add state-based systems when state management is configured
-        app.add_systems(Update, update_menu);
+        app.init_resource::<Inventory>()
+            .add_state_systems(GameState::Menu, update_menu);
     }
 }
 
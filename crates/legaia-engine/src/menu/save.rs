@@ -0,0 +1,218 @@
+//! Save games
+//!
+//! A save file is a fixed header (the [`SAVE_MAGIC`] bytes followed by the
+//! format version as a little-endian `u32`) and a TOML body. The version is
+//! checked before the body is parsed, so older saves can be migrated and
+//! newer ones rejected with a clear error.
+
+use super::{Equipment, Inventory};
+use crate::setup::SetupConfig;
+use crate::state::GameState;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Bytes every save file starts with
+pub const SAVE_MAGIC: [u8; 4] = *b"LGSV";
+
+/// Current save format version
+pub const SAVE_VERSION: u32 = 1;
+
+/// Number of save slots
+pub const SAVE_SLOTS: usize = 15;
+
+/// Length of the magic and version header
+const HEADER_LEN: usize = 8;
+
+/// Saved state of one party member
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartyMember {
+    pub name: String,
+    pub level: u32,
+    pub experience: u32,
+    pub hp: u32,
+    pub max_hp: u32,
+    pub mp: u32,
+    pub max_mp: u32,
+    pub attack: u32,
+    pub defense: u32,
+    pub speed: u32,
+    pub equipment: Equipment,
+}
+
+/// Everything written to a save slot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveGame {
+    /// State to resume in
+    pub state: GameState,
+    /// Map the party is on
+    pub map_id: u32,
+    /// Total time played
+    pub playtime: Duration,
+    /// Party members in formation order
+    pub party: Vec<PartyMember>,
+    /// Items carried by the party
+    pub inventory: Inventory,
+    /// [`GameRng`](crate::rng::GameRng) state when saved; reseed with it on
+    /// load so the game makes the same random rolls as before saving
+    #[serde(default)]
+    pub rng_state: u32,
+}
+
+impl SaveGame {
+    /// Directory holding the save slots (platform-specific)
+    pub fn saves_dir() -> PathBuf {
+        SetupConfig::config_dir().join("saves")
+    }
+
+    /// Path of a save slot
+    pub fn slot_path(slot: usize) -> PathBuf {
+        Self::saves_dir().join(format!("slot{:02}.sav", slot))
+    }
+
+    /// Write this save to a slot
+    pub fn save(&self, slot: usize) -> Result<()> {
+        check_slot(slot)?;
+        self.save_to(&Self::slot_path(slot))
+    }
+
+    /// Read the save in a slot
+    pub fn load(slot: usize) -> Result<SaveGame> {
+        check_slot(slot)?;
+        Self::load_from(&Self::slot_path(slot))
+    }
+
+    /// Write this save to a file
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, self.to_bytes()?)
+            .with_context(|| format!("Failed to write save {}", path.display()))?;
+
+        tracing::info!("Saved game to {}", path.display());
+        Ok(())
+    }
+
+    /// Read a save from a file
+    pub fn load_from(path: &Path) -> Result<SaveGame> {
+        let data = std::fs::read(path)
+            .with_context(|| format!("Failed to read save {}", path.display()))?;
+        Self::from_bytes(&data).with_context(|| format!("Invalid save {}", path.display()))
+    }
+
+    /// Encode as header and TOML body
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let body = toml::to_string(self)?;
+
+        let mut data = Vec::with_capacity(HEADER_LEN + body.len());
+        data.extend_from_slice(&SAVE_MAGIC);
+        data.extend_from_slice(&SAVE_VERSION.to_le_bytes());
+        data.extend_from_slice(body.as_bytes());
+        Ok(data)
+    }
+
+    /// Decode from header and TOML body
+    pub fn from_bytes(data: &[u8]) -> Result<SaveGame> {
+        if data.len() < HEADER_LEN || data[..4] != SAVE_MAGIC {
+            bail!("Not a save file");
+        }
+
+        let version = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+        if version != SAVE_VERSION {
+            bail!("Unsupported save version {}", version);
+        }
+
+        let body = std::str::from_utf8(&data[HEADER_LEN..])?;
+        Ok(toml::from_str(body)?)
+    }
+}
+
+fn check_slot(slot: usize) -> Result<()> {
+    if slot >= SAVE_SLOTS {
+        bail!("Save slot {} out of range (0-{})", slot, SAVE_SLOTS - 1);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::menu::{EquipItem, EquipSlot, ItemId, StatModifiers};
+    use crate::rng::GameRng;
+    use bevy::prelude::default;
+
+    fn populated() -> SaveGame {
+        let mut equipment = Equipment::default();
+        equipment.equip(
+            EquipSlot::Weapon,
+            EquipItem {
+                item: ItemId(40),
+                modifiers: StatModifiers {
+                    attack: 12,
+                    ..default()
+                },
+            },
+        );
+
+        let mut inventory = Inventory::default();
+        inventory.add(ItemId(1), 120);
+        inventory.add(ItemId(2), 3);
+
+        SaveGame {
+            state: GameState::Field,
+            map_id: 7,
+            playtime: Duration::from_secs(3 * 3600 + 25),
+            party: vec![PartyMember {
+                name: "Vahn".to_string(),
+                level: 12,
+                experience: 8400,
+                hp: 310,
+                max_hp: 420,
+                mp: 18,
+                max_mp: 40,
+                attack: 55,
+                defense: 38,
+                speed: 30,
+                equipment,
+            }],
+            inventory,
+            rng_state: 0x5eed,
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let save = populated();
+        let data = save.to_bytes().unwrap();
+        assert_eq!(data[..4], SAVE_MAGIC);
+
+        let loaded = SaveGame::from_bytes(&data).unwrap();
+        assert_eq!(loaded, save);
+        assert_eq!(loaded.party[0].equipment.modifiers().attack, 12);
+        assert_eq!(loaded.inventory.count(ItemId(1)), 120);
+
+        let mut rng = GameRng::default();
+        rng.reseed(loaded.rng_state);
+        assert_eq!(rng, GameRng::new(0x5eed));
+    }
+
+    #[test]
+    fn test_rejects_bad_header() {
+        let mut data = populated().to_bytes().unwrap();
+        assert!(SaveGame::from_bytes(&data[..4]).is_err());
+
+        data[4] = 2;
+        assert!(SaveGame::from_bytes(&data).is_err());
+
+        data[0] = b'X';
+        assert!(SaveGame::from_bytes(&data).is_err());
+    }
+
+    #[test]
+    fn test_slot_range() {
+        assert!(SaveGame::load(SAVE_SLOTS).is_err());
+        assert!(SaveGame::slot_path(3).ends_with("saves/slot03.sav"));
+    }
+}
@@ -0,0 +1,109 @@
+//! Deterministic random numbers
+//!
+//! Every random roll in the game (encounters, battle) draws from the one
+//! [`GameRng`] resource. It runs the same LCG as the PSX libc `rand()`, so a
+//! seed fully determines what follows: reseeding with the same value, or
+//! loading a save (which stores [`GameRng::state`]), replays the same rolls.
+
+use bevy::prelude::*;
+use std::ops::Range;
+
+/// PSX libc `rand()` linear congruential generator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PsxRand {
+    state: u32,
+}
+
+impl PsxRand {
+    /// Largest value returned by [`PsxRand::rand`]
+    pub const MAX: u32 = 0x7fff;
+
+    pub fn new(seed: u32) -> Self {
+        Self { state: seed }
+    }
+
+    /// Next value in `0..=MAX`
+    pub fn rand(&mut self) -> u32 {
+        self.state = self.state.wrapping_mul(0x41c6_4e6d).wrapping_add(12345);
+        (self.state >> 16) & Self::MAX
+    }
+}
+
+/// Seeded random number generator shared by field and battle
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct GameRng {
+    rng: PsxRand,
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl GameRng {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            rng: PsxRand::new(seed),
+        }
+    }
+
+    /// Restart the sequence from `seed`
+    pub fn reseed(&mut self, seed: u32) {
+        self.rng = PsxRand::new(seed);
+    }
+
+    /// Generator state; reseeding with it continues the current sequence
+    pub fn state(&self) -> u32 {
+        self.rng.state
+    }
+
+    /// Next value in `0..=PsxRand::MAX`
+    pub fn rand(&mut self) -> u32 {
+        self.rng.rand()
+    }
+
+    /// Next value in `range`, or `range.start` if it is empty
+    ///
+    /// Like the original game this reduces with `%`, so spans that don't
+    /// divide `PsxRand::MAX + 1` are very slightly biased.
+    pub fn range(&mut self, range: Range<u32>) -> u32 {
+        let roll = self.rand();
+        match range.end.checked_sub(range.start) {
+            Some(span) if span > 0 => range.start + roll % span,
+            _ => range.start,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_psx_rand_sequence() {
+        let mut rng = PsxRand::new(1);
+        assert_eq!(rng.rand(), 16838);
+        assert_eq!(rng.rand(), 5758);
+        assert_eq!(rng.rand(), 10113);
+    }
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = GameRng::new(0x1234);
+        let mut b = GameRng::new(0x1234);
+        let rolls: Vec<u32> = (0..64).map(|_| a.rand()).collect();
+        assert_eq!(rolls, (0..64).map(|_| b.rand()).collect::<Vec<_>>());
+
+        // Resuming from a saved state continues the same sequence
+        let mut c = GameRng::default();
+        c.reseed(a.state());
+        assert_eq!(
+            (0..8).map(|_| a.range(10..20)).collect::<Vec<_>>(),
+            (0..8).map(|_| c.range(10..20)).collect::<Vec<_>>()
+        );
+
+        assert_eq!(a.range(5..5), 5);
+        assert!(GameRng::new(1).rand() != GameRng::new(2).rand());
+    }
+}
@@ -1,7 +1,8 @@
 //! First-run setup system for asset extraction
 //!
 //! This module handles the one-time extraction of assets from the PSX disc
-//! when the user first launches the game.
+//! when the user first launches the game. The disc is identified from the
+//! boot executable named in its `SYSTEM.CNF` before anything is extracted.
 
 use bevy::prelude::*;
 use legaia_assets::{AssetExtractionService, ExtractionProgress as ExtProgress, ExtractionStats};
@@ -34,8 +35,10 @@ pub struct SetupProgress {
     pub extraction_progress: Arc<Mutex<Option<ExtProgress>>>,
     /// Extraction stats (when complete)
     pub stats: Option<ExtractionStats>,
-    /// Error message if extraction failed
+    /// Error message if validation or extraction failed
     pub error: Option<String>,
+    /// Disc identified by validation
+    pub disc: Option<DiscId>,
 }
 
 impl Default for SetupProgress {
@@ -45,10 +48,89 @@ impl Default for SetupProgress {
             extraction_progress: Arc::new(Mutex::new(None)),
             stats: None,
             error: None,
+            disc: None,
         }
     }
 }
 
+/// Release region of a disc
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscRegion {
+    /// North America
+    NtscU,
+    /// Japan
+    NtscJ,
+    /// Europe
+    Pal,
+}
+
+/// Serials of the Legend of Legaia releases
+pub const KNOWN_SERIALS: [(&str, DiscRegion); 5] = [
+    ("SCUS-94254", DiscRegion::NtscU),
+    ("SCPS-10059", DiscRegion::NtscJ),
+    ("SCES-01752", DiscRegion::Pal), // English
+    ("SCES-01944", DiscRegion::Pal), // French
+    ("SCES-01946", DiscRegion::Pal), // German
+];
+
+/// Identity of a Legend of Legaia disc
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscId {
+    /// Product serial (e.g. "SCUS-94254")
+    pub serial: String,
+    /// Release region
+    pub region: DiscRegion,
+}
+
+impl DiscId {
+    /// Identify a disc from the contents of its `SYSTEM.CNF`
+    ///
+    /// The serial comes from the boot executable name, so
+    /// `BOOT = cdrom:\SCUS_942.54;1` gives "SCUS-94254". Fails with a
+    /// message for the user if the file has no `BOOT` line or the serial
+    /// isn't a Legend of Legaia release.
+    pub fn from_system_cnf(system_cnf: &str) -> Result<Self, String> {
+        let serial = parse_boot_serial(system_cnf)
+            .ok_or_else(|| "SYSTEM.CNF has no BOOT line".to_string())?;
+
+        KNOWN_SERIALS
+            .iter()
+            .find(|(known, _)| *known == serial)
+            .map(|&(_, region)| DiscId {
+                serial: serial.clone(),
+                region,
+            })
+            .ok_or_else(|| format!("{} is not a Legend of Legaia disc", serial))
+    }
+}
+
+/// Serial of the boot executable named by a `SYSTEM.CNF` `BOOT` line
+fn parse_boot_serial(system_cnf: &str) -> Option<String> {
+    let path = system_cnf.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case("BOOT")
+            .then(|| value.trim())
+    })?;
+
+    // "cdrom:\SCUS_942.54;1" -> "SCUS_942.54"
+    let name = path.rsplit(['\\', '/', ':']).next()?;
+    let name = name.split(';').next()?;
+    let serial: String = name
+        .chars()
+        .filter(|&c| c != '.')
+        .map(|c| {
+            if c == '_' {
+                '-'
+            } else {
+                c.to_ascii_uppercase()
+            }
+        })
+        .collect();
+
+    (!serial.is_empty()).then_some(serial)
+}
+
 /// Configuration file tracking setup completion
 #[derive(serde::Serialize, serde::Deserialize, Default)]
 pub struct SetupConfig {
@@ -58,6 +140,9 @@ pub struct SetupConfig {
     pub assets_path: PathBuf,
     /// Path to disc image (for re-extraction if needed)
     pub disc_path: Option<PathBuf>,
+    /// Region of the disc the assets were extracted from
+    #[serde(default)]
+    pub region: Option<DiscRegion>,
 }
 
 impl SetupConfig {
@@ -96,6 +181,11 @@ impl SetupConfig {
 
     /// Get config file path (platform-specific)
     fn config_path() -> PathBuf {
+        Self::config_dir().join("config.toml")
+    }
+
+    /// Get the game's config directory (platform-specific)
+    pub fn config_dir() -> PathBuf {
         // Use platform-specific config directory
         #[cfg(target_os = "windows")]
         let base = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
@@ -106,7 +196,7 @@ impl SetupConfig {
             PathBuf::from(home).join(".config")
         });
 
-        base.join("legaia").join("config.toml")
+        base.join("legaia")
     }
 
     /// Get assets directory path
@@ -170,7 +260,8 @@ fn prompt_disc_path(
     // TODO: Replace with actual UI
     // For now, check common locations or environment variable
 
-    if progress.disc_path.is_none() {
+    // A rejected disc isn't picked up again from the environment
+    if progress.disc_path.is_none() && progress.error.is_none() {
         // Check environment variable
         if let Ok(disc_path) = std::env::var("LEGAIA_DISC_PATH") {
             let path = PathBuf::from(disc_path);
@@ -187,24 +278,46 @@ fn prompt_disc_path(
 }
 
 /// Validate the disc image
-fn validate_disc(progress: Res<SetupProgress>, mut next_state: ResMut<NextState<SetupState>>) {
-    if let Some(disc_path) = &progress.disc_path {
-        // Try to open the disc
-        match psxutils::cdrom::CdRom::open(disc_path) {
-            Ok(_cdrom) => {
-                info!("Disc validated successfully");
-                // TODO: Verify it's the correct game (check SYSTEM.CNF or SCUS_942.54)
-                next_state.set(SetupState::Extracting);
-            }
-            Err(e) => {
-                error!("Failed to open disc: {}", e);
-                // TODO: Show error UI and go back to prompt
-                next_state.set(SetupState::PromptDiscPath);
-            }
-        }
-    } else {
+///
+/// Opens the image and checks `SYSTEM.CNF` names a Legend of Legaia boot
+/// executable. Rejected discs go back to the prompt with the reason in
+/// [`SetupProgress::error`].
+fn validate_disc(
+    mut progress: ResMut<SetupProgress>,
+    mut next_state: ResMut<NextState<SetupState>>,
+) {
+    let Some(disc_path) = progress.disc_path.clone() else {
         error!("No disc path set");
         next_state.set(SetupState::PromptDiscPath);
+        return;
+    };
+
+    let identified = psxutils::cdrom::CdRom::open(&disc_path)
+        .map_err(|e| format!("Failed to open disc: {}", e))
+        .and_then(|cdrom| {
+            cdrom
+                .read_file("SYSTEM.CNF")
+                .map_err(|e| format!("Failed to read SYSTEM.CNF: {}", e))
+        })
+        .and_then(|system_cnf| DiscId::from_system_cnf(&String::from_utf8_lossy(&system_cnf)));
+
+    match identified {
+        Ok(disc) => {
+            info!(
+                "Disc validated successfully: {} ({:?})",
+                disc.serial, disc.region
+            );
+            progress.disc = Some(disc);
+            progress.error = None;
+            next_state.set(SetupState::Extracting);
+        }
+        Err(message) => {
+            error!("{}: {}", disc_path.display(), message);
+            // TODO: Show error UI
+            progress.error = Some(message);
+            progress.disc_path = None;
+            next_state.set(SetupState::PromptDiscPath);
+        }
     }
 }
 
@@ -265,6 +378,7 @@ fn monitor_extraction(
                     total_files: p.total_files,
                     extracted_files: p.processed_files,
                     converted_files: p.converted_files,
+                    skipped_files: p.skipped_files,
                 })
             } else {
                 None
@@ -288,6 +402,7 @@ fn monitor_extraction(
             setup_complete: true,
             assets_path: SetupConfig::assets_dir(),
             disc_path: progress.disc_path.clone(),
+            region: progress.disc.as_ref().map(|disc| disc.region),
         };
 
         if let Err(e) = config.save() {
@@ -303,3 +418,32 @@ fn finish_setup() {
     info!("Setup complete! Starting game...");
     // TODO: Transition to main menu state
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identify_system_cnf() {
+        let system_cnf =
+            "BOOT = cdrom:\\SCUS_942.54;1\r\nTCB = 4\r\nEVENT = 10\r\nSTACK = 801FFFF0\r\n";
+        assert_eq!(parse_boot_serial(system_cnf).as_deref(), Some("SCUS-94254"));
+        assert_eq!(
+            DiscId::from_system_cnf(system_cnf).unwrap(),
+            DiscId {
+                serial: "SCUS-94254".to_string(),
+                region: DiscRegion::NtscU,
+            }
+        );
+
+        let pal = DiscId::from_system_cnf("BOOT=cdrom:SCES_017.52;1").unwrap();
+        assert_eq!(pal.region, DiscRegion::Pal);
+    }
+
+    #[test]
+    fn test_reject_other_discs() {
+        let err = DiscId::from_system_cnf("BOOT = cdrom:\\SLUS_007.41;1").unwrap_err();
+        assert!(err.contains("SLUS-00741"));
+        assert!(DiscId::from_system_cnf("TCB = 4").is_err());
+    }
+}
@@ -4,14 +4,30 @@
 //! - 6 function handlers per state (likely: init, update, draw, cleanup, + 2 unknown)
 //! - State transitions reset 4 counters
 //! - Negative state value triggers exit
+//!
+//! Per-state logic is scheduled with [`StateScopedSystems`], so e.g. field
+//! systems only tick while in [`GameState::Field`].
+//!
+//! Every change of [`StateManager::current_state`] is announced with a
+//! [`StateTransitionEvent`], which plugins read to react to specific
+//! transitions (stopping field music when a battle starts, etc.) without
+//! polling. [`StateManager::request_transition_with`] can fade the screen
+//! out before the switch and back in after it.
 
-use bevy::prelude::{ResMut, Resource};
+use crate::graphics::fade::{FadeComplete, FadeDirection, StartFade};
+use bevy::ecs::system::ScheduleSystem;
+use bevy::prelude::{
+    in_state, App, IntoScheduleConfigs, Message, MessageReader, MessageWriter, ResMut, Resource,
+    Update,
+};
 use bevy::state::state::{NextState, States};
 
 /// Main game states
 ///
 /// Each state has 6 function pointers in the original game.
-#[derive(States, Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+#[derive(
+    States, Debug, Clone, Copy, Default, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize,
+)]
 pub enum GameState {
     /// Loading screen - initial state
     #[default]
@@ -30,6 +46,66 @@ pub enum GameState {
     Exit,
 }
 
+/// Sent by [`handle_state_transitions`] whenever the game state changes
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateTransitionEvent {
+    pub from: GameState,
+    pub to: GameState,
+}
+
+impl StateTransitionEvent {
+    /// Whether this transition enters `state`
+    pub fn enters(&self, state: GameState) -> bool {
+        self.to == state
+    }
+
+    /// Whether this transition leaves `state`
+    pub fn exits(&self, state: GameState) -> bool {
+        self.from == state
+    }
+}
+
+/// Screen effect played around a requested transition
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TransitionEffect {
+    /// Switch immediately
+    #[default]
+    None,
+    /// Fade out, switch, then fade back in
+    ///
+    /// Needs the graphics plugin, which sends [`FadeComplete`].
+    Fade,
+}
+
+/// Transition waiting for [`handle_state_transitions`]
+#[derive(Debug, Clone, Copy)]
+struct PendingTransition {
+    to: GameState,
+    effect: TransitionEffect,
+    /// Fade out already requested
+    fading: bool,
+}
+
+/// Scheduling of systems that only run in one [`GameState`]
+pub trait StateScopedSystems {
+    /// Add systems to `Update` that run only while `state` is active
+    fn add_state_systems<M>(
+        &mut self,
+        state: GameState,
+        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+    ) -> &mut Self;
+}
+
+impl StateScopedSystems for App {
+    fn add_state_systems<M>(
+        &mut self,
+        state: GameState,
+        systems: impl IntoScheduleConfigs<ScheduleSystem, M>,
+    ) -> &mut Self {
+        self.add_systems(Update, systems.run_if(in_state(state)))
+    }
+}
+
 /// State machine manager
 #[derive(Resource, Debug)]
 pub struct StateManager {
@@ -46,6 +122,8 @@ pub struct StateManager {
     pub counter_4: u32,
     /// Frame counter
     pub frame_counter: u32,
+    /// Requested transition not yet applied
+    pending: Option<PendingTransition>,
 }
 
 impl Default for StateManager {
@@ -59,6 +137,7 @@ impl Default for StateManager {
             counter_3: 0,
             counter_4: 0,
             frame_counter: 0,
+            pending: None,
         }
     }
 }
@@ -92,6 +171,28 @@ impl StateManager {
         }
     }
 
+    /// Request a transition to `state`, applied by the next
+    /// [`handle_state_transitions`]
+    pub fn request_transition(&mut self, state: GameState) {
+        self.request_transition_with(state, TransitionEffect::None);
+    }
+
+    /// Request a transition to `state` with a screen effect
+    ///
+    /// Replaces any request still pending.
+    pub fn request_transition_with(&mut self, state: GameState, effect: TransitionEffect) {
+        self.pending = Some(PendingTransition {
+            to: state,
+            effect,
+            fading: false,
+        });
+    }
+
+    /// Whether a requested transition hasn't been applied yet
+    pub fn transition_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
     /// Update frame counter (called once per frame)
     pub fn tick_frame(&mut self) {
         self.frame_counter = self.frame_counter.wrapping_add(1);
@@ -99,16 +200,55 @@ impl StateManager {
 }
 
 /// System to update frame counter
+///
+/// Runs in `FixedUpdate`, once per logic frame (see [`TimingState`](crate::TimingState)).
 pub fn update_frame_counter(mut state_mgr: ResMut<StateManager>) {
     state_mgr.tick_frame();
 }
 
-/// System to detect and handle state transitions
+/// System to apply requested transitions and announce state changes
+///
+/// Faded transitions send [`StartFade`] and switch once the fade out
+/// completes, then fade back in.
 pub fn handle_state_transitions(
     mut state_mgr: ResMut<StateManager>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut transitions: MessageWriter<StateTransitionEvent>,
+    mut fades: MessageWriter<StartFade>,
+    mut fades_complete: MessageReader<FadeComplete>,
 ) {
+    let faded_out = fades_complete
+        .read()
+        .any(|fade| fade.direction == FadeDirection::Out);
+
+    if let Some(pending) = state_mgr.pending {
+        match pending.effect {
+            TransitionEffect::None => {
+                state_mgr.pending = None;
+                state_mgr.transition_to(pending.to);
+            }
+            TransitionEffect::Fade if !pending.fading => {
+                fades.write(StartFade::fade_out());
+                state_mgr.pending = Some(PendingTransition {
+                    fading: true,
+                    ..pending
+                });
+            }
+            TransitionEffect::Fade if faded_out => {
+                state_mgr.pending = None;
+                state_mgr.transition_to(pending.to);
+                fades.write(StartFade::fade_in());
+            }
+            TransitionEffect::Fade => {}
+        }
+    }
+
     if state_mgr.state_changed() {
+        let transition = StateTransitionEvent {
+            from: state_mgr.previous_state,
+            to: state_mgr.current_state,
+        };
+
         // Update previous state tracker
         state_mgr.previous_state = state_mgr.current_state;
 
@@ -117,8 +257,161 @@ pub fn handle_state_transitions(
 
         tracing::info!(
             "State transition: {:?} -> {:?}",
-            state_mgr.previous_state,
-            state_mgr.current_state
+            transition.from,
+            transition.to
         );
+        transitions.write(transition);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::prelude::{Messages, State};
+    use bevy::state::app::{AppExtStates, StatesPlugin};
+
+    #[derive(Resource, Default)]
+    struct FieldTicks(u32);
+
+    fn tick_field(mut ticks: ResMut<FieldTicks>) {
+        ticks.0 += 1;
+    }
+
+    /// Frame counter ticks over one simulated second at the given timing
+    fn ticks_per_second(timing: crate::TimingState) -> u32 {
+        use bevy::prelude::FixedUpdate;
+        use bevy::time::{TimePlugin, TimeUpdateStrategy};
+        use std::time::Duration;
+
+        let mut app = App::new();
+        app.add_plugins((TimePlugin, crate::CoreStatePlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                100,
+            )))
+            .insert_resource(timing)
+            .init_resource::<StateManager>()
+            .add_systems(FixedUpdate, update_frame_counter);
+
+        // The first update only starts the clock
+        app.update();
+        let start = app.world().resource::<StateManager>().frame_counter;
+        for _ in 0..10 {
+            app.update();
+        }
+        app.world().resource::<StateManager>().frame_counter - start
+    }
+
+    #[test]
+    fn test_frame_counter_follows_video_mode() {
+        use crate::{TimingState, VideoMode};
+
+        assert_eq!(ticks_per_second(TimingState::default()), 60);
+        assert_eq!(
+            ticks_per_second(TimingState {
+                video_mode: VideoMode::Pal,
+                vsync_frames_target: 1,
+            }),
+            50
+        );
+        assert_eq!(
+            ticks_per_second(TimingState {
+                video_mode: VideoMode::Ntsc,
+                vsync_frames_target: 2,
+            }),
+            30
+        );
+    }
+
+    /// App running only the transition system, recording its events
+    fn transition_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin)
+            .init_state::<GameState>()
+            .init_resource::<StateManager>()
+            .add_message::<StateTransitionEvent>()
+            .add_message::<StartFade>()
+            .add_message::<FadeComplete>()
+            .add_systems(Update, handle_state_transitions);
+        app
+    }
+
+    fn drain_transitions(app: &mut App) -> Vec<StateTransitionEvent> {
+        app.world_mut()
+            .resource_mut::<Messages<StateTransitionEvent>>()
+            .drain()
+            .collect()
+    }
+
+    #[test]
+    fn test_requested_transition_emits_one_event() {
+        let mut app = transition_app();
+        app.world_mut()
+            .resource_mut::<StateManager>()
+            .request_transition(GameState::Field);
+
+        app.update();
+        app.update();
+
+        assert_eq!(
+            drain_transitions(&mut app),
+            vec![StateTransitionEvent {
+                from: GameState::Loading,
+                to: GameState::Field,
+            }]
+        );
+        assert_eq!(
+            *app.world().resource::<State<GameState>>().get(),
+            GameState::Field
+        );
+    }
+
+    #[test]
+    fn test_faded_transition_waits_for_fade_out() {
+        let mut app = transition_app();
+        app.world_mut()
+            .resource_mut::<StateManager>()
+            .request_transition_with(GameState::Battle, TransitionEffect::Fade);
+
+        app.update();
+        app.update();
+        assert!(drain_transitions(&mut app).is_empty());
+        assert!(app.world().resource::<StateManager>().transition_pending());
+
+        app.world_mut().write_message(FadeComplete {
+            direction: FadeDirection::Out,
+        });
+        app.update();
+
+        assert_eq!(
+            drain_transitions(&mut app),
+            vec![StateTransitionEvent {
+                from: GameState::Loading,
+                to: GameState::Battle,
+            }]
+        );
+        assert!(!app.world().resource::<StateManager>().transition_pending());
+    }
+
+    #[test]
+    fn test_field_systems_pause_in_battle() {
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin)
+            .init_state::<GameState>()
+            .init_resource::<FieldTicks>()
+            .add_state_systems(GameState::Field, tick_field);
+
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Field);
+        app.update();
+        app.update();
+        assert_eq!(app.world().resource::<FieldTicks>().0, 2);
+
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Battle);
+        app.update();
+        app.update();
+        assert_eq!(app.world().resource::<FieldTicks>().0, 2);
     }
 }
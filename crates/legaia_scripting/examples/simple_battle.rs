@@ -35,6 +35,7 @@ fn setup_battle(mut commands: Commands, mut script_engine: ResMut<ScriptEngine>)
             defense: 25,
             speed: 30,
             level: 8,
+            experience: 0,
         },
         ColorInterpolation {
             current: Vec3::new(1.0, 1.0, 1.0),
@@ -67,6 +68,7 @@ fn setup_battle(mut commands: Commands, mut script_engine: ResMut<ScriptEngine>)
             defense: 10,
             speed: 12,
             level: 3,
+            experience: 0,
         },
         ScriptCallback {
             script_path: "scripts/entities/enemy_slime.lua".to_string(),
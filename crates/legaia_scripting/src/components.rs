@@ -64,6 +64,9 @@ pub struct CombatStats {
     pub defense: u32,
     pub speed: u32,
     pub level: u32,
+    /// Total experience earned (see [`growth`](crate::growth))
+    #[serde(default)]
+    pub experience: u32,
 }
 
 /// Turn state for turn-based combat
@@ -110,9 +113,9 @@ pub enum BattleMode {
 }
 
 /// Battle state resource
+///
+/// Turn order lives in [`TurnQueue`](crate::turn::TurnQueue).
 #[derive(Resource, Debug, Clone)]
 pub struct BattleState {
     pub mode: BattleMode,
-    pub turn_order: Vec<Entity>,
-    pub current_turn_index: usize,
 }
@@ -1,26 +1,102 @@
 //! Damage calculation system
 //!
 //! Scriptable damage formulas for combat
+//!
+//! The built-in physical and Art formulas are available natively through
+//! [`physical_damage`] and [`art_damage`]; Lua scripts reach the same math
+//! through the `calculate_*` helpers.
 
 use crate::components::*;
 use mlua::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::{Arc, Mutex};
+
+/// Outcome of a damage calculation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DamageResult {
+    /// Damage before the defender's defense is applied
+    pub raw: u32,
+    /// Damage prevented by the defender's defense
+    pub mitigated: u32,
+    /// Damage dealt (never below 1)
+    pub damage: u32,
+}
+
+impl DamageResult {
+    /// Apply a defense reduction to raw damage, keeping the minimum of 1
+    fn new(raw: u32, reduction: u32) -> Self {
+        let damage = raw.saturating_sub(reduction).max(1);
+        Self {
+            raw,
+            mitigated: raw.saturating_sub(damage),
+            damage,
+        }
+    }
+
+    /// Whether defense reduced the damage at all
+    pub fn was_reduced(&self) -> bool {
+        self.mitigated > 0
+    }
+
+    /// Whether defense cut the hit down to the minimum damage
+    pub fn was_blocked(&self) -> bool {
+        self.damage == 1 && self.was_reduced()
+    }
+}
+
+/// Physical attack damage
+///
+/// `attack * level / 10`, reduced by half the defender's defense.
+pub fn physical_damage(attacker: &CombatStats, defender: &CombatStats) -> DamageResult {
+    let raw = attacker.attack as u64 * attacker.level as u64 / 10;
+    DamageResult::new(saturate(raw), defender.defense / 2)
+}
+
+/// Art (special move) damage
+///
+/// `attack * art_power * level / 100`, reduced by 70% of the defender's
+/// defense (Arts ignore 30% of defense).
+pub fn art_damage(attacker: &CombatStats, art_power: u32, defender: &CombatStats) -> DamageResult {
+    let raw = attacker.attack as u64 * art_power as u64 * attacker.level as u64 / 100;
+    let reduction = defender.defense as u64 * 7 / 10;
+    DamageResult::new(saturate(raw), saturate(reduction))
+}
+
+/// Clamp a widened intermediate back to `u32`
+fn saturate(value: u64) -> u32 {
+    value.min(u32::MAX as u64) as u32
+}
 
 /// Damage formula engine
 pub struct DamageEngine {
     lua: Lua,
+    /// Generator behind the Lua `apply_random_variance` helper
+    rng: Arc<Mutex<StdRng>>,
 }
 
 impl DamageEngine {
     pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    /// Create a damage engine whose variance rolls start from `seed`
+    pub fn with_seed(seed: u64) -> Self {
         let lua = Lua::new();
+        let rng = Arc::new(Mutex::new(StdRng::seed_from_u64(seed)));
 
         // Register damage calculation helpers
-        Self::register_functions(&lua).expect("Failed to register damage functions");
+        Self::register_functions(&lua, &rng).expect("Failed to register damage functions");
 
-        Self { lua }
+        Self { lua, rng }
     }
 
-    fn register_functions(lua: &Lua) -> LuaResult<()> {
+    /// Restart the variance sequence from `seed`
+    pub fn reseed(&self, seed: u64) {
+        *self.rng.lock().unwrap() = StdRng::seed_from_u64(seed);
+    }
+
+    fn register_functions(lua: &Lua, rng: &Arc<Mutex<StdRng>>) -> LuaResult<()> {
         let globals = lua.globals();
 
         globals.set(
@@ -44,9 +120,13 @@ impl DamageEngine {
             })?,
         )?;
 
+        let variance_rng = Arc::clone(rng);
         globals.set(
             "apply_random_variance",
-            lua.create_function(|_, damage: i64| Ok(Self::apply_random_variance(damage)))?,
+            lua.create_function(move |_, damage: i64| {
+                let mut rng = variance_rng.lock().unwrap();
+                Ok(Self::apply_random_variance(damage, &mut *rng))
+            })?,
         )?;
 
         Ok(())
@@ -82,10 +162,9 @@ impl DamageEngine {
         (damage - reduction).max(1)
     }
 
-    /// Apply random variance (typically ±5%)
-    pub fn apply_random_variance(damage: i64) -> i64 {
-        use rand::Rng;
-        let variance = rand::thread_rng().gen_range(-5..=5);
+    /// Apply random variance (typically ±5%), rolled from `rng`
+    pub fn apply_random_variance(damage: i64, rng: &mut impl Rng) -> i64 {
+        let variance = rng.gen_range(-5..=5);
         let variance_amount = (damage * variance) / 100;
         (damage + variance_amount).max(1)
     }
@@ -141,6 +220,66 @@ mod tests {
         assert_eq!(damage, 736); // (50 * 150 * 10 / 100) - (20 * 7 / 10) = 750 - 14 = 736
     }
 
+    fn stats(attack: u32, defense: u32, level: u32) -> CombatStats {
+        CombatStats {
+            hp: 100,
+            max_hp: 100,
+            mp: 0,
+            max_mp: 0,
+            attack,
+            defense,
+            speed: 0,
+            level,
+            experience: 0,
+        }
+    }
+
+    #[test]
+    fn test_native_damage_matches_script_helpers() {
+        let attacker = stats(50, 30, 10);
+        let defender = stats(40, 20, 8);
+
+        let physical = physical_damage(&attacker, &defender);
+        assert_eq!(
+            physical,
+            DamageResult {
+                raw: 50,
+                mitigated: 10,
+                damage: 40
+            }
+        );
+        assert!(physical.was_reduced());
+        assert!(!physical.was_blocked());
+
+        let art = art_damage(&attacker, 150, &defender);
+        assert_eq!(art.raw, 750);
+        assert_eq!(art.mitigated, 14);
+        assert_eq!(
+            art.damage as i64,
+            DamageEngine::calculate_art_damage(50, 150, 20, 10)
+        );
+    }
+
+    #[test]
+    fn test_defense_above_attack_clamps_to_one() {
+        let attacker = stats(10, 0, 1);
+        let defender = stats(0, 255, 1);
+
+        let physical = physical_damage(&attacker, &defender);
+        assert_eq!(physical.raw, 1);
+        assert_eq!(physical.damage, 1);
+        assert_eq!(physical.mitigated, 0);
+
+        let art = art_damage(&stats(20, 0, 5), 100, &defender);
+        assert_eq!(art.raw, 100);
+        assert_eq!(art.damage, 1);
+        assert_eq!(art.mitigated, 99);
+        assert!(art.was_blocked());
+
+        // Zero attack still deals the minimum
+        assert_eq!(physical_damage(&stats(0, 0, 1), &stats(0, 0, 1)).damage, 1);
+    }
+
     #[test]
     fn test_damage_formula_script() {
         let engine = DamageEngine::new();
@@ -154,6 +293,7 @@ mod tests {
             defense: 30,
             speed: 40,
             level: 10,
+            experience: 0,
         };
 
         let defender = CombatStats {
@@ -165,6 +305,7 @@ mod tests {
             defense: 20,
             speed: 35,
             level: 8,
+            experience: 0,
         };
 
         // Test custom formula using Lua's "return" syntax
@@ -173,4 +314,28 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), 40);
     }
+
+    #[test]
+    fn test_same_seed_same_variance() {
+        let attacker = stats(50, 30, 10);
+        let defender = stats(40, 20, 8);
+        let formula = "return apply_random_variance(1000)";
+        let rolls = |engine: &DamageEngine| -> Vec<i64> {
+            (0..16)
+                .map(|_| {
+                    engine
+                        .eval_damage_formula(formula, &attacker, &defender, 0)
+                        .unwrap()
+                })
+                .collect()
+        };
+
+        let engine = DamageEngine::with_seed(3);
+        let first = rolls(&engine);
+        assert_eq!(first, rolls(&DamageEngine::with_seed(3)));
+        assert!(first.iter().all(|damage| (950..=1050).contains(damage)));
+
+        engine.reseed(3);
+        assert_eq!(rolls(&engine), first);
+    }
 }
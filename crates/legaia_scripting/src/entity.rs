@@ -36,6 +36,7 @@ pub fn update_entity_callbacks(
             alive_enemies: 0, // TODO: count from query
             alive_allies: 0,  // TODO: count from query
             turn_number: 0,   // TODO: get from battle state
+            flags: 0,
         };
 
         // Call script callback
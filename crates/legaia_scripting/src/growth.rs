@@ -0,0 +1,282 @@
+//! Experience and level-up growth
+//!
+//! Each character levels along a [`GrowthCurve`]: the experience needed per
+//! level grows linearly, and every level adds fixed stat gains up to a cap.
+//! Curves live in the [`GrowthTable`] resource so they can be loaded from
+//! data and differ per character.
+
+use crate::components::CombatStats;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Stat raised by a level-up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Stat {
+    MaxHp,
+    MaxMp,
+    Attack,
+    Defense,
+    Speed,
+}
+
+/// Value per growable stat, used for per-level gains and for caps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatBlock {
+    pub max_hp: u32,
+    pub max_mp: u32,
+    pub attack: u32,
+    pub defense: u32,
+    pub speed: u32,
+}
+
+impl StatBlock {
+    fn get(&self, stat: Stat) -> u32 {
+        match stat {
+            Stat::MaxHp => self.max_hp,
+            Stat::MaxMp => self.max_mp,
+            Stat::Attack => self.attack,
+            Stat::Defense => self.defense,
+            Stat::Speed => self.speed,
+        }
+    }
+}
+
+/// How a character grows with experience
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GrowthCurve {
+    /// Experience to go from level 1 to level 2
+    pub base_xp: u32,
+    /// Extra experience needed for each level after that
+    pub xp_increment: u32,
+    /// Highest reachable level
+    pub max_level: u32,
+    /// Stats gained per level
+    pub gains: StatBlock,
+    /// Stats never exceed these values
+    pub caps: StatBlock,
+}
+
+impl Default for GrowthCurve {
+    fn default() -> Self {
+        Self {
+            base_xp: 100,
+            xp_increment: 50,
+            max_level: 99,
+            gains: StatBlock {
+                max_hp: 25,
+                max_mp: 4,
+                attack: 3,
+                defense: 2,
+                speed: 2,
+            },
+            caps: StatBlock {
+                max_hp: 9999,
+                max_mp: 999,
+                attack: 999,
+                defense: 999,
+                speed: 999,
+            },
+        }
+    }
+}
+
+impl GrowthCurve {
+    /// Experience needed to advance from `level` to the next level
+    pub fn xp_for_level(&self, level: u32) -> u32 {
+        let step = self.xp_increment as u64 * level.saturating_sub(1) as u64;
+        (self.base_xp as u64 + step).min(u32::MAX as u64) as u32
+    }
+
+    /// Total experience needed to reach `level` from level 1
+    pub fn total_xp_for_level(&self, level: u32) -> u64 {
+        (1..level)
+            .map(|level| self.xp_for_level(level) as u64)
+            .sum()
+    }
+}
+
+/// Growth curves by character
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GrowthTable {
+    /// Curves keyed by character name
+    pub curves: HashMap<String, GrowthCurve>,
+    /// Curve for characters without their own
+    pub default: GrowthCurve,
+}
+
+impl GrowthTable {
+    /// Growth curve of a character
+    pub fn curve(&self, character: &str) -> &GrowthCurve {
+        self.curves.get(character).unwrap_or(&self.default)
+    }
+}
+
+/// Stats raised by reaching a new level
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelUp {
+    /// Level reached
+    pub level: u32,
+    /// Increase of each stat that grew (capped stats are left out)
+    pub gains: Vec<(Stat, u32)>,
+}
+
+impl CombatStats {
+    /// Experience still needed for the next level, or `None` at max level
+    pub fn xp_to_next_level(&self, curve: &GrowthCurve) -> Option<u32> {
+        if self.level >= curve.max_level {
+            return None;
+        }
+
+        let needed = curve.total_xp_for_level(self.level + 1);
+        Some(needed.saturating_sub(self.experience as u64) as u32)
+    }
+
+    /// Grant experience, applying every level-up it earns
+    ///
+    /// A level-up adds the curve's gains, capped, and heals HP and MP by the
+    /// amount their maximums grew. Experience keeps accumulating at max
+    /// level but earns nothing more.
+    pub fn add_experience(&mut self, xp: u32, curve: &GrowthCurve) -> Vec<LevelUp> {
+        self.experience = self.experience.saturating_add(xp);
+
+        let mut level_ups = Vec::new();
+        while self.level < curve.max_level
+            && self.experience as u64 >= curve.total_xp_for_level(self.level + 1)
+        {
+            self.level += 1;
+            level_ups.push(LevelUp {
+                level: self.level,
+                gains: self.apply_gains(curve),
+            });
+        }
+
+        level_ups
+    }
+
+    /// Add one level's stat gains, returning the non-zero increases
+    fn apply_gains(&mut self, curve: &GrowthCurve) -> Vec<(Stat, u32)> {
+        let mut gains = Vec::new();
+
+        for stat in [
+            Stat::MaxHp,
+            Stat::MaxMp,
+            Stat::Attack,
+            Stat::Defense,
+            Stat::Speed,
+        ] {
+            let value = match stat {
+                Stat::MaxHp => &mut self.max_hp,
+                Stat::MaxMp => &mut self.max_mp,
+                Stat::Attack => &mut self.attack,
+                Stat::Defense => &mut self.defense,
+                Stat::Speed => &mut self.speed,
+            };
+
+            let grown = value
+                .saturating_add(curve.gains.get(stat))
+                .min(curve.caps.get(stat).max(*value));
+            let gain = grown - *value;
+            *value = grown;
+
+            if gain > 0 {
+                gains.push((stat, gain));
+            }
+        }
+
+        for (stat, gain) in &gains {
+            match stat {
+                Stat::MaxHp => self.hp = (self.hp + gain).min(self.max_hp),
+                Stat::MaxMp => self.mp = (self.mp + gain).min(self.max_mp),
+                _ => {}
+            }
+        }
+
+        gains
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats() -> CombatStats {
+        CombatStats {
+            hp: 100,
+            max_hp: 100,
+            mp: 10,
+            max_mp: 10,
+            attack: 20,
+            defense: 10,
+            speed: 10,
+            level: 1,
+            experience: 0,
+        }
+    }
+
+    #[test]
+    fn test_xp_to_next_level() {
+        let curve = GrowthCurve::default();
+        let mut stats = stats();
+        assert_eq!(stats.xp_to_next_level(&curve), Some(100));
+
+        assert!(stats.add_experience(60, &curve).is_empty());
+        assert_eq!(stats.xp_to_next_level(&curve), Some(40));
+
+        stats.level = curve.max_level;
+        assert_eq!(stats.xp_to_next_level(&curve), None);
+    }
+
+    #[test]
+    fn test_multiple_level_ups() {
+        let curve = GrowthCurve::default();
+        let mut stats = stats();
+
+        // Level 2 at 100, level 3 at 250, level 4 at 450
+        let level_ups = stats.add_experience(300, &curve);
+        assert_eq!(
+            level_ups.iter().map(|l| l.level).collect::<Vec<_>>(),
+            [2, 3]
+        );
+        assert_eq!(stats.level, 3);
+        assert_eq!(stats.max_hp, 150);
+        assert_eq!(stats.hp, 150);
+        assert_eq!(stats.attack, 26);
+        assert!(level_ups[0].gains.contains(&(Stat::Speed, 2)));
+        assert_eq!(stats.xp_to_next_level(&curve), Some(150));
+    }
+
+    #[test]
+    fn test_stat_caps() {
+        let mut curve = GrowthCurve::default();
+        curve.caps.attack = 24;
+        curve.caps.speed = 10;
+        let mut stats = stats();
+
+        let level_ups = stats.add_experience(450, &curve);
+        assert_eq!(stats.level, 4);
+        assert_eq!(stats.attack, 24);
+        assert_eq!(stats.speed, 10);
+
+        // Speed was already capped, attack hit the cap on the second level
+        assert!(level_ups
+            .iter()
+            .all(|l| l.gains.iter().all(|(stat, _)| *stat != Stat::Speed)));
+        assert!(level_ups[1].gains.contains(&(Stat::Attack, 1)));
+        assert!(level_ups[2]
+            .gains
+            .iter()
+            .all(|(stat, _)| *stat != Stat::Attack));
+    }
+
+    #[test]
+    fn test_growth_table_fallback() {
+        let mut table = GrowthTable::default();
+        let mut vahn = GrowthCurve::default();
+        vahn.base_xp = 80;
+        table.curves.insert("Vahn".to_string(), vahn);
+
+        assert_eq!(table.curve("Vahn").base_xp, 80);
+        assert_eq!(table.curve("Noa").base_xp, 100);
+    }
+}
@@ -6,10 +6,14 @@
 pub mod components;
 pub mod damage;
 pub mod entity;
+pub mod growth;
 pub mod script;
 pub mod systems;
+pub mod turn;
 
 pub use components::*;
 pub use entity::*;
+pub use growth::{GrowthCurve, GrowthTable, LevelUp, Stat};
 pub use script::*;
 pub use systems::*;
+pub use turn::*;
@@ -4,16 +4,21 @@
 
 use bevy::prelude::*;
 use mlua::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
 use crate::components::*;
+use crate::damage::DamageEngine;
 
 /// Script engine resource
 #[derive(Resource, Clone)]
 pub struct ScriptEngine {
     lua: Arc<Mutex<Lua>>,
     loaded_scripts: Arc<Mutex<HashMap<String, ()>>>,
+    /// Generator behind the Lua `random` and `random_range` functions
+    rng: Arc<Mutex<StdRng>>,
 }
 
 impl Default for ScriptEngine {
@@ -24,29 +29,103 @@ impl Default for ScriptEngine {
 
 impl ScriptEngine {
     pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    /// Create a script engine whose random rolls start from `seed`
+    ///
+    /// The same seed replays the same `random`/`random_range` results; the
+    /// game seeds this from its shared `GameRng`.
+    pub fn with_seed(seed: u64) -> Self {
         let lua = Lua::new();
+        let rng = Arc::new(Mutex::new(StdRng::seed_from_u64(seed)));
 
         // Register the entity API
-        Self::register_api(&lua).expect("Failed to register Lua API");
+        Self::register_api(&lua, &rng).expect("Failed to register Lua API");
 
         Self {
             lua: Arc::new(Mutex::new(lua)),
             loaded_scripts: Arc::new(Mutex::new(HashMap::new())),
+            rng,
         }
     }
 
+    /// Restart the script random sequence from `seed`
+    pub fn reseed(&self, seed: u64) {
+        *self.rng.lock().unwrap() = StdRng::seed_from_u64(seed);
+    }
+
     /// Load a script from file
     pub fn load_script(&mut self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let lua = self.lua.lock().unwrap();
         let code = std::fs::read_to_string(path)?;
-        lua.load(&code).set_name(path).exec()?;
+        self.load_script_source(path, &code)
+    }
+
+    /// Load a script from source, registered under `name`
+    pub fn load_script_source(
+        &mut self,
+        name: &str,
+        code: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let lua = self.lua.lock().unwrap();
+        lua.load(code).set_name(name).exec()?;
 
         let mut scripts = self.loaded_scripts.lock().unwrap();
-        scripts.insert(path.to_string(), ());
+        scripts.insert(name.to_string(), ());
 
         Ok(())
     }
 
+    /// Ask the loaded AI script what an enemy does this turn
+    ///
+    /// Calls the global Lua function `choose_action(context)`. The context
+    /// table holds `actor`, `allies` and `enemies` (arrays of combatant
+    /// tables with their stats, `slot` and `hp_fraction`) and
+    /// `turn_number`. The function returns a table such as
+    /// `{ action = "attack", target = 2 }`, see [`AiAction`].
+    pub fn choose_action(
+        &self,
+        context: &AiContext,
+    ) -> Result<AiAction, Box<dyn std::error::Error>> {
+        let lua = self.lua.lock().unwrap();
+
+        let combatants = |list: &[AiCombatant]| -> LuaResult<LuaTable> {
+            let table = lua.create_table()?;
+            for combatant in list {
+                table.push(Self::combatant_table(&lua, combatant)?)?;
+            }
+            Ok(table)
+        };
+
+        let table = lua.create_table()?;
+        table.set("actor", Self::combatant_table(&lua, &context.actor)?)?;
+        table.set("allies", combatants(&context.allies)?)?;
+        table.set("enemies", combatants(&context.enemies)?)?;
+        table.set("turn_number", context.turn_number)?;
+
+        let func: LuaFunction = lua.globals().get("choose_action")?;
+        let action: LuaTable = func.call(table)?;
+
+        Ok(AiAction::from_table(&action)?)
+    }
+
+    /// Lua table describing a combatant for AI scripts
+    fn combatant_table(lua: &Lua, combatant: &AiCombatant) -> LuaResult<LuaTable> {
+        let stats = &combatant.stats;
+        let table = lua.create_table()?;
+        table.set("slot", combatant.slot)?;
+        table.set("hp", stats.hp)?;
+        table.set("max_hp", stats.max_hp)?;
+        table.set("mp", stats.mp)?;
+        table.set("max_mp", stats.max_mp)?;
+        table.set("attack", stats.attack)?;
+        table.set("defense", stats.defense)?;
+        table.set("speed", stats.speed)?;
+        table.set("level", stats.level)?;
+        table.set("hp_fraction", stats.hp as f64 / stats.max_hp.max(1) as f64)?;
+        Ok(table)
+    }
+
     /// Call a script function with entity context
     pub fn call_entity_callback(
         &self,
@@ -87,6 +166,9 @@ impl ScriptEngine {
         entity.set("alive_allies", entity_data.alive_allies)?;
         entity.set("turn_number", entity_data.turn_number)?;
 
+        // Flags
+        entity.set("flags", entity_data.flags)?;
+
         // Call the function
         let func: LuaFunction = lua.globals().get(function)?;
         func.call::<()>(entity)?;
@@ -95,7 +177,7 @@ impl ScriptEngine {
     }
 
     /// Register all script API functions
-    fn register_api(lua: &Lua) -> LuaResult<()> {
+    fn register_api(lua: &Lua, rng: &Arc<Mutex<StdRng>>) -> LuaResult<()> {
         let globals = lua.globals();
 
         // Entity modification functions
@@ -118,6 +200,13 @@ impl ScriptEngine {
             })?,
         )?;
 
+        globals.set(
+            "get_stat",
+            lua.create_function(|_, (entity, stat): (LuaTable, String)| {
+                entity.get::<LuaValue>(stat)
+            })?,
+        )?;
+
         globals.set(
             "set_hp",
             lua.create_function(|_, (entity, hp): (LuaTable, u32)| {
@@ -158,19 +247,17 @@ impl ScriptEngine {
         )?;
 
         // Random functions for AI
+        let random_rng = Arc::clone(rng);
         globals.set(
             "random",
-            lua.create_function(|_, ()| {
-                use rand::Rng;
-                Ok(rand::thread_rng().gen::<f64>())
-            })?,
+            lua.create_function(move |_, ()| Ok(random_rng.lock().unwrap().gen::<f64>()))?,
         )?;
 
+        let range_rng = Arc::clone(rng);
         globals.set(
             "random_range",
-            lua.create_function(|_, (min, max): (i64, i64)| {
-                use rand::Rng;
-                Ok(rand::thread_rng().gen_range(min..=max))
+            lua.create_function(move |_, (min, max): (i64, i64)| {
+                Ok(range_rng.lock().unwrap().gen_range(min..=max))
             })?,
         )?;
 
@@ -178,18 +265,14 @@ impl ScriptEngine {
         globals.set(
             "calculate_physical_damage",
             lua.create_function(|_, (atk, def, level): (i64, i64, i64)| {
-                let base_damage = (atk * level) / 10;
-                let defense_reduction = def / 2;
-                Ok((base_damage - defense_reduction).max(1))
+                Ok(DamageEngine::calculate_physical_damage(atk, def, level))
             })?,
         )?;
 
         globals.set(
             "calculate_art_damage",
             lua.create_function(|_, (atk, power, def, level): (i64, i64, i64, i64)| {
-                let base_damage = (atk * power * level) / 100;
-                let defense_reduction = (def * 7) / 10; // Arts ignore 30% defense
-                Ok((base_damage - defense_reduction).max(1))
+                Ok(DamageEngine::calculate_art_damage(atk, power, def, level))
             })?,
         )?;
 
@@ -217,6 +300,73 @@ pub struct EntityScriptContext {
     pub alive_enemies: usize,
     pub alive_allies: usize,
     pub turn_number: u32,
+
+    /// Entity-specific flags (e.g. an NPC's dialogue progress)
+    pub flags: u32,
+}
+
+/// Battle state passed to an enemy AI script
+///
+/// From the game's point of view: `allies` are the player's party, the
+/// AI's targets, and `enemies` are the monsters on the actor's side.
+#[derive(Debug, Clone)]
+pub struct AiContext {
+    /// Combatant choosing its action
+    pub actor: AiCombatant,
+    /// Living party members
+    pub allies: Vec<AiCombatant>,
+    /// Living monsters, including the actor
+    pub enemies: Vec<AiCombatant>,
+    /// Current battle turn
+    pub turn_number: u32,
+}
+
+/// Combatant as seen by an AI script
+#[derive(Debug, Clone)]
+pub struct AiCombatant {
+    /// Battle slot, used as the target of an [`AiAction`]
+    pub slot: usize,
+    /// Combat stats
+    pub stats: ScriptStats,
+}
+
+/// Action chosen by an AI script
+///
+/// Returned from Lua as a table with an `action` name (`"attack"`, `"art"`,
+/// `"defend"` or `"item"`), a target `slot` in `target` and an `id` for
+/// Arts and items.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiAction {
+    /// Basic attack on a target
+    Attack { target: usize },
+    /// Use an Art (or enemy skill) on a target
+    Art { art: u32, target: usize },
+    /// Guard for the turn
+    Defend,
+    /// Use an item on a target
+    Item { item: u32, target: usize },
+}
+
+impl AiAction {
+    /// Read an action table returned by a script
+    fn from_table(table: &LuaTable) -> LuaResult<Self> {
+        let action: String = table.get("action")?;
+        match action.as_str() {
+            "attack" => Ok(AiAction::Attack {
+                target: table.get("target")?,
+            }),
+            "art" => Ok(AiAction::Art {
+                art: table.get("id")?,
+                target: table.get("target")?,
+            }),
+            "defend" => Ok(AiAction::Defend),
+            "item" => Ok(AiAction::Item {
+                item: table.get("id")?,
+                target: table.get("target")?,
+            }),
+            other => Err(LuaError::runtime(format!("Unknown AI action: {}", other))),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -245,3 +395,165 @@ impl From<&CombatStats> for ScriptStats {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn combatant(slot: usize, hp: u32) -> AiCombatant {
+        AiCombatant {
+            slot,
+            stats: ScriptStats {
+                hp,
+                max_hp: 100,
+                mp: 0,
+                max_mp: 0,
+                attack: 10,
+                defense: 5,
+                speed: 8,
+                level: 1,
+            },
+        }
+    }
+
+    #[test]
+    fn test_ai_attacks_lowest_hp_ally() {
+        let mut engine = ScriptEngine::new();
+        engine
+            .load_script_source(
+                "lowest_hp",
+                r#"
+                function choose_action(context)
+                    local target = context.allies[1]
+                    for _, ally in ipairs(context.allies) do
+                        if get_stat(ally, "hp_fraction") < target.hp_fraction then
+                            target = ally
+                        end
+                    end
+                    return { action = "attack", target = target.slot }
+                end
+                "#,
+            )
+            .unwrap();
+
+        let context = AiContext {
+            actor: combatant(3, 100),
+            allies: vec![combatant(0, 80), combatant(1, 15), combatant(2, 40)],
+            enemies: vec![combatant(3, 100)],
+            turn_number: 1,
+        };
+        assert_eq!(
+            engine.choose_action(&context).unwrap(),
+            AiAction::Attack { target: 1 }
+        );
+    }
+
+    #[test]
+    fn test_ai_action_kinds() {
+        let mut engine = ScriptEngine::new();
+        engine
+            .load_script_source(
+                "by_turn",
+                r#"
+                function choose_action(context)
+                    if context.turn_number == 1 then
+                        return { action = "art", id = 7, target = 0 }
+                    elseif context.turn_number == 2 then
+                        return { action = "defend" }
+                    elseif context.turn_number == 3 then
+                        return { action = "item", id = 2, target = context.actor.slot }
+                    end
+                    return { action = "flee" }
+                end
+                "#,
+            )
+            .unwrap();
+
+        let mut context = AiContext {
+            actor: combatant(3, 100),
+            allies: vec![combatant(0, 80)],
+            enemies: vec![combatant(3, 100)],
+            turn_number: 1,
+        };
+        let mut choose = |turn| {
+            context.turn_number = turn;
+            engine.choose_action(&context)
+        };
+
+        assert_eq!(choose(1).unwrap(), AiAction::Art { art: 7, target: 0 });
+        assert_eq!(choose(2).unwrap(), AiAction::Defend);
+        assert_eq!(choose(3).unwrap(), AiAction::Item { item: 2, target: 3 });
+        assert!(choose(4).is_err());
+    }
+
+    #[test]
+    fn test_entity_callback_receives_flags() {
+        let mut engine = ScriptEngine::new();
+        engine
+            .load_script_source(
+                "npc",
+                r#"
+                calls = 0
+                function talk(entity)
+                    calls = calls + 1
+                    seen_flags = entity.flags
+                    seen_hp = get_stat(entity, "hp")
+                end
+                "#,
+            )
+            .unwrap();
+
+        let context = EntityScriptContext {
+            stats: combatant(0, 40).stats,
+            current_color: [0; 3],
+            target_color: [0; 3],
+            timers: (0, 0, 0),
+            alive_enemies: 0,
+            alive_allies: 0,
+            turn_number: 0,
+            flags: 0b101,
+        };
+        engine.call_entity_callback("talk", context).unwrap();
+
+        let lua = engine.lua.lock().unwrap();
+        let globals = lua.globals();
+        assert_eq!(globals.get::<u32>("calls").unwrap(), 1);
+        assert_eq!(globals.get::<u32>("seen_flags").unwrap(), 0b101);
+        assert_eq!(globals.get::<u32>("seen_hp").unwrap(), 40);
+    }
+
+    #[test]
+    fn test_same_seed_same_script_rolls() {
+        let rolls = |engine: &mut ScriptEngine| -> (Vec<i64>, f64) {
+            engine
+                .load_script_source(
+                    "rolls",
+                    r#"
+                    rolls = {}
+                    for i = 1, 16 do
+                        rolls[i] = random_range(1, 100)
+                    end
+                    fraction = random()
+                    "#,
+                )
+                .unwrap();
+            let lua = engine.lua.lock().unwrap();
+            let globals = lua.globals();
+            (
+                globals.get("rolls").unwrap(),
+                globals.get("fraction").unwrap(),
+            )
+        };
+
+        let mut a = ScriptEngine::with_seed(42);
+        let mut b = ScriptEngine::with_seed(42);
+        let first = rolls(&mut a);
+        assert_eq!(first, rolls(&mut b));
+        assert!(first.0.iter().all(|roll| (1..=100).contains(roll)));
+
+        // Reseeding replays the sequence
+        a.reseed(42);
+        assert_eq!(rolls(&mut a), first);
+        assert_ne!(rolls(&mut ScriptEngine::with_seed(7)).0, first.0);
+    }
+}
@@ -5,6 +5,7 @@
 use crate::components::*;
 use crate::entity::*;
 use crate::script::*;
+use crate::turn::*;
 use bevy::prelude::*;
 
 /// Combat system plugin
@@ -16,10 +17,10 @@ impl Plugin for CombatPlugin {
             // Resources
             .insert_resource(BattleState {
                 mode: BattleMode::Normal,
-                turn_order: Vec::new(),
-                current_turn_index: 0,
             })
+            .init_resource::<TurnQueue>()
             .insert_resource(ScriptEngine::new())
+            .add_event::<TurnStartEvent>()
             // Systems - matches PSX execution order:
             // 1. Update entity callbacks (game logic)
             // 2. Update animations/interpolations
@@ -35,30 +36,3 @@ impl Plugin for CombatPlugin {
             );
     }
 }
-
-/// Turn-based combat system
-pub fn turn_system(
-    mut battle_state: ResMut<BattleState>,
-    query: Query<(Entity, &CombatStats, &TurnState)>,
-) {
-    // Skip if no entities
-    if query.is_empty() {
-        return;
-    }
-
-    // Build turn order if empty
-    if battle_state.turn_order.is_empty() {
-        let mut entities: Vec<_> = query
-            .iter()
-            .filter(|(_, stats, state)| **state != TurnState::Dead && stats.hp > 0)
-            .collect();
-
-        // Sort by speed (descending)
-        entities.sort_by(|a, b| b.1.speed.cmp(&a.1.speed));
-
-        battle_state.turn_order = entities.into_iter().map(|(e, _, _)| e).collect();
-        battle_state.current_turn_index = 0;
-    }
-
-    // TODO: Process current turn
-}
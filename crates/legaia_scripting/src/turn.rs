@@ -0,0 +1,245 @@
+//! Turn order (initiative) for turn-based combat
+//!
+//! Each round, living combatants act once in descending speed order. Ties
+//! are broken by entity id so the order is deterministic. Speed changes
+//! during a round re-sort the combatants that have not acted yet.
+
+use crate::components::*;
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+/// Fired when a combatant's turn begins
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TurnStartEvent {
+    pub entity: Entity,
+}
+
+/// Turn order for the current round
+#[derive(Resource, Debug, Clone, Default)]
+pub struct TurnQueue {
+    /// Combatants still to act this round, with the speed they are ordered by
+    remaining: VecDeque<(Entity, u32)>,
+    /// Combatant whose turn it is
+    current: Option<Entity>,
+    /// Rounds started so far
+    round: u32,
+    /// Turns started so far
+    turn: u64,
+    /// Last turn a `TurnStartEvent` was sent for
+    announced_turn: u64,
+}
+
+impl TurnQueue {
+    /// Start a new round with the given combatants and their speeds
+    ///
+    /// Any combatants left over from the previous round are discarded.
+    pub fn start_round(&mut self, combatants: impl IntoIterator<Item = (Entity, u32)>) {
+        self.remaining = combatants.into_iter().collect();
+        self.sort();
+        self.current = None;
+        self.round += 1;
+    }
+
+    /// Combatant whose turn it is
+    pub fn current(&self) -> Option<Entity> {
+        self.current
+    }
+
+    /// End the current turn and start the next one in this round
+    ///
+    /// Returns the combatant now acting, or `None` once the round is over.
+    pub fn advance(&mut self) -> Option<Entity> {
+        self.current = self.remaining.pop_front().map(|(entity, _)| entity);
+        if self.current.is_some() {
+            self.turn += 1;
+        }
+        self.current
+    }
+
+    /// Combatants still to act this round, in order
+    pub fn remaining(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.remaining.iter().map(|&(entity, _)| entity)
+    }
+
+    /// Number of rounds started
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    /// Whether every combatant has acted this round
+    pub fn is_round_over(&self) -> bool {
+        self.current.is_none() && self.remaining.is_empty()
+    }
+
+    /// Update a combatant's speed, re-sorting those yet to act
+    pub fn update_speed(&mut self, entity: Entity, speed: u32) {
+        let mut changed = false;
+        for (queued, queued_speed) in self.remaining.iter_mut() {
+            if *queued == entity && *queued_speed != speed {
+                *queued_speed = speed;
+                changed = true;
+            }
+        }
+        if changed {
+            self.sort();
+        }
+    }
+
+    /// Drop a combatant from the round (e.g. when it dies)
+    ///
+    /// If it was acting, its turn ends without starting the next one.
+    pub fn remove(&mut self, entity: Entity) {
+        self.remaining.retain(|&(queued, _)| queued != entity);
+        if self.current == Some(entity) {
+            self.current = None;
+        }
+    }
+
+    /// Sort by descending speed, then ascending entity id
+    fn sort(&mut self) {
+        self.remaining
+            .make_contiguous()
+            .sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    }
+}
+
+/// Whether a combatant can still take turns
+fn is_alive(stats: &CombatStats, state: &TurnState) -> bool {
+    stats.hp > 0 && *state != TurnState::Dead
+}
+
+/// Turn-based combat system
+///
+/// Keeps the [`TurnQueue`] in sync with combatant speed and health, starts a
+/// new round when the previous one is over, and sends a [`TurnStartEvent`]
+/// whenever a turn begins. Turns end when battle logic calls
+/// [`TurnQueue::advance`].
+pub fn turn_system(
+    mut queue: ResMut<TurnQueue>,
+    mut turn_events: EventWriter<TurnStartEvent>,
+    query: Query<(Entity, &CombatStats, &TurnState)>,
+) {
+    // Skip if no entities
+    if query.is_empty() {
+        return;
+    }
+
+    let queued: Vec<Entity> = queue
+        .current()
+        .into_iter()
+        .chain(queue.remaining())
+        .collect();
+    for entity in queued {
+        match query.get(entity) {
+            Ok((_, stats, state)) if is_alive(stats, state) => {
+                queue.update_speed(entity, stats.speed);
+            }
+            _ => queue.remove(entity),
+        }
+    }
+
+    if queue.current().is_none() {
+        if queue.remaining.is_empty() {
+            let alive = query
+                .iter()
+                .filter(|(_, stats, state)| is_alive(stats, state))
+                .map(|(entity, stats, _)| (entity, stats.speed));
+            queue.start_round(alive);
+        }
+        queue.advance();
+    }
+
+    if let Some(entity) = queue.current() {
+        if queue.announced_turn != queue.turn {
+            queue.announced_turn = queue.turn;
+            turn_events.send(TurnStartEvent { entity });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn combatant(speed: u32, hp: u32) -> (CombatStats, TurnState) {
+        (
+            CombatStats {
+                hp,
+                max_hp: 100,
+                mp: 0,
+                max_mp: 0,
+                attack: 10,
+                defense: 10,
+                speed,
+                level: 1,
+                experience: 0,
+            },
+            TurnState::Waiting,
+        )
+    }
+
+    #[test]
+    fn test_round_order_by_speed() {
+        let mut world = World::new();
+        let slow = world.spawn_empty().id();
+        let fast = world.spawn_empty().id();
+        let medium = world.spawn_empty().id();
+
+        let mut queue = TurnQueue::default();
+        queue.start_round([(slow, 10), (fast, 50), (medium, 30)]);
+
+        assert_eq!(queue.advance(), Some(fast));
+        assert_eq!(queue.current(), Some(fast));
+        assert_eq!(queue.advance(), Some(medium));
+        assert_eq!(queue.advance(), Some(slow));
+        assert_eq!(queue.advance(), None);
+        assert!(queue.is_round_over());
+    }
+
+    #[test]
+    fn test_speed_change_resorts_remaining() {
+        let mut world = World::new();
+        let a = world.spawn_empty().id();
+        let b = world.spawn_empty().id();
+        let c = world.spawn_empty().id();
+
+        let mut queue = TurnQueue::default();
+        queue.start_round([(a, 30), (b, 20), (c, 20)]);
+
+        // Tie broken by entity id
+        assert_eq!(queue.remaining().collect::<Vec<_>>(), vec![a, b, c]);
+
+        assert_eq!(queue.advance(), Some(a));
+        queue.update_speed(c, 40);
+        assert_eq!(queue.advance(), Some(c));
+        assert_eq!(queue.advance(), Some(b));
+    }
+
+    #[test]
+    fn test_turn_system_skips_dead() {
+        let mut app = App::new();
+        app.init_resource::<TurnQueue>()
+            .add_event::<TurnStartEvent>()
+            .add_systems(Update, turn_system);
+
+        let slow = app.world_mut().spawn(combatant(10, 100)).id();
+        let dead = app.world_mut().spawn(combatant(99, 0)).id();
+        let fast = app.world_mut().spawn(combatant(50, 100)).id();
+
+        let mut reader = app
+            .world()
+            .resource::<Events<TurnStartEvent>>()
+            .get_reader();
+        let mut started = Vec::new();
+        for _ in 0..3 {
+            app.update();
+            let events = app.world().resource::<Events<TurnStartEvent>>();
+            started.extend(reader.read(events).map(|event| event.entity));
+            app.world_mut().resource_mut::<TurnQueue>().advance();
+        }
+
+        assert!(!started.contains(&dead));
+        assert_eq!(started, vec![fast, slow, fast]);
+        assert_eq!(app.world().resource::<TurnQueue>().round(), 2);
+    }
+}
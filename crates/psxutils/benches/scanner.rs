@@ -0,0 +1,50 @@
+//! Asset scanner throughput
+//!
+//! Run with `cargo bench -p psxutils --bench scanner`.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use psxutils::AssetScanner;
+use std::hint::black_box;
+
+/// 16 MiB of noise with a small 16-bit TIM every 64 KiB
+fn container() -> Vec<u8> {
+    let mut tim = Vec::new();
+    tim.extend_from_slice(&0x10u32.to_le_bytes());
+    tim.extend_from_slice(&2u32.to_le_bytes());
+    tim.extend_from_slice(&(12u32 + 16 * 16 * 2).to_le_bytes());
+    for value in [0u16, 0, 16, 16] {
+        tim.extend_from_slice(&value.to_le_bytes());
+    }
+    tim.resize(20 + 16 * 16 * 2, 0x7f);
+
+    // Xorshift noise, so stray magic bytes show up at a realistic rate
+    let mut state = 0x2545_f491u32;
+    let mut data: Vec<u8> = (0..16 << 20)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect();
+
+    for offset in (0..data.len() - tim.len()).step_by(64 << 10) {
+        data[offset..offset + tim.len()].copy_from_slice(&tim);
+    }
+    data
+}
+
+fn bench_scan(c: &mut Criterion) {
+    let data = container();
+
+    let mut group = c.benchmark_group("scanner");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.sample_size(10);
+    group.bench_function("scan", |b| {
+        b.iter(|| AssetScanner::new(black_box(&data)).scan())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_scan);
+criterion_main!(benches);
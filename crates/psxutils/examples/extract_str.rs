@@ -0,0 +1,63 @@
+use psxutils::cdrom::CdRom;
+use psxutils::formats::StrMovie;
+use std::error::Error;
+use std::path::Path;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let disc_path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "/home/admin/Downloads/Legend of Legaia.bin".to_string());
+
+    let movie_path = std::env::args()
+        .nth(2)
+        .unwrap_or_else(|| "/MOV/INTRO.STR".to_string());
+
+    let output_dir = std::env::args()
+        .nth(3)
+        .unwrap_or_else(|| "/tmp/extracted_str".to_string());
+
+    println!("Opening disc: {}", disc_path);
+    let cdrom = CdRom::open(&disc_path)?;
+
+    std::fs::create_dir_all(&output_dir)?;
+
+    println!("Demuxing {}...", movie_path);
+    let movie = StrMovie::open(&cdrom, &movie_path)?;
+    println!("Found {} frames", movie.frame_count());
+
+    for (idx, frame) in movie.frames.iter().enumerate() {
+        let frame_path = Path::new(&output_dir).join(format!("frame_{:05}.png", idx));
+
+        match frame.decode() {
+            Ok(rgba) => {
+                image::save_buffer(
+                    &frame_path,
+                    &rgba,
+                    frame.width as u32,
+                    frame.height as u32,
+                    image::ColorType::Rgba8,
+                )?;
+                println!(
+                    "  [{}/{}] Frame {} ({}x{}) ✓",
+                    idx + 1,
+                    movie.frame_count(),
+                    frame.frame_number,
+                    frame.width,
+                    frame.height
+                );
+            }
+            Err(e) => println!(
+                "  [{}/{}] Frame {} ✗ Error: {}",
+                idx + 1,
+                movie.frame_count(),
+                frame.frame_number,
+                e
+            ),
+        }
+    }
+
+    println!("\n✓ Extraction complete!");
+    println!("  Output: {}", output_dir);
+
+    Ok(())
+}
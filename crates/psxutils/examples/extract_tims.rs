@@ -19,6 +19,10 @@ use {
     serde::Serialize,
 };
 
+/// Largest thumbnail side in pixels
+#[cfg(feature = "extraction")]
+const MAX_THUMB_SIZE: u32 = 256;
+
 /// Metadata for an extracted TIM texture
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "extraction", derive(Serialize))]
@@ -94,23 +98,19 @@ fn run_extraction() -> Result<()> {
     let disc = CdRom::open(disc_path)?;
 
     // Get PROT.DAT info
-    let entries = disc.read_dir("/")?;
-    let prot_entry = entries
-        .iter()
-        .find(|e| e.name.eq_ignore_ascii_case("PROT.DAT"))
-        .context("PROT.DAT not found")?;
+    let prot_entry = disc.stat("/PROT.DAT").context("PROT.DAT not found")?;
 
     println!("📂 PROT.DAT: {} MB", prot_entry.size / 1024 / 1024);
     println!("   Starting at LBA: {}\n", prot_entry.lba);
 
     // Scan for TIM textures in chunks
     println!("🔍 Scanning for TIM textures...");
-    let tim_assets = scan_prot_dat(&disc, prot_entry)?;
+    let tim_assets = scan_prot_dat(&disc, &prot_entry)?;
     println!("✅ Found {} TIM textures\n", tim_assets.len());
 
     // Extract all TIMs with progress bar
     println!("🎨 Extracting textures...");
-    let metadata = extract_all_tims(&disc, prot_entry, &tim_assets, &full_dir, &thumb_dir)?;
+    let metadata = extract_all_tims(&disc, &prot_entry, &tim_assets, &full_dir, &thumb_dir)?;
 
     // Calculate statistics
     let stats = ExtractionStats {
@@ -333,8 +333,10 @@ fn extract_single_tim(
     .context("Failed to save PNG")?;
 
     // Generate thumbnail (max 256x256)
-    let thumb_path = thumb_dir.join(&filename);
-    generate_thumbnail(&rgba_data, width, height, &thumb_path)?;
+    tim.thumbnail(MAX_THUMB_SIZE)
+        .context("Failed to create thumbnail")?
+        .save(thumb_dir.join(&filename))
+        .context("Failed to save thumbnail")?;
 
     Ok(TimMetadata {
         index,
@@ -349,35 +351,3 @@ fn extract_single_tim(
         error: None,
     })
 }
-
-#[cfg(feature = "extraction")]
-fn generate_thumbnail(rgba_data: &[u8], width: u16, height: u16, output_path: &Path) -> Result<()> {
-    const MAX_THUMB_SIZE: u32 = 256;
-
-    let (thumb_w, thumb_h) = if width > MAX_THUMB_SIZE as u16 || height > MAX_THUMB_SIZE as u16 {
-        let scale = (MAX_THUMB_SIZE as f32 / width.max(height) as f32).min(1.0);
-        (
-            (width as f32 * scale) as u32,
-            (height as f32 * scale) as u32,
-        )
-    } else {
-        (width as u32, height as u32)
-    };
-
-    // Use image crate for resizing
-    let img = image::RgbaImage::from_raw(width as u32, height as u32, rgba_data.to_vec())
-        .context("Failed to create image")?;
-
-    let thumb = image::imageops::resize(
-        &img,
-        thumb_w,
-        thumb_h,
-        image::imageops::FilterType::Lanczos3,
-    );
-
-    thumb
-        .save(output_path)
-        .context("Failed to save thumbnail")?;
-
-    Ok(())
-}
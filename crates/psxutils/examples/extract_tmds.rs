@@ -4,7 +4,7 @@
 //! TMD files are PSX 3D model format containing vertices, normals, and primitives.
 
 use anyhow::{Context, Result};
-use psxutils::CdRom;
+use psxutils::{CdRom, Tmd};
 use std::fs;
 use std::path::PathBuf;
 
@@ -21,10 +21,8 @@ fn main() -> Result<()> {
     let cdrom = CdRom::open(&disc_path).context("Failed to open disc image")?;
 
     println!("Finding PROT.DAT...");
-    let entries = cdrom.read_dir("/")?;
-    let prot_entry = entries
-        .iter()
-        .find(|f| f.name.eq_ignore_ascii_case("PROT.DAT"))
+    let prot_entry = cdrom
+        .stat("/PROT.DAT")
         .context("PROT.DAT not found on disc")?;
 
     println!(
@@ -32,7 +30,7 @@ fn main() -> Result<()> {
         prot_entry.size / 1024 / 1024,
         prot_entry.lba
     );
-    let tmds = scan_prot_dat_for_tmds(&cdrom, prot_entry)?;
+    let tmds = scan_prot_dat_for_tmds(&cdrom, &prot_entry)?;
 
     println!("Found {} TMD models", tmds.len());
 
@@ -147,48 +145,24 @@ fn scan_prot_dat_for_tmds(
 }
 
 /// Scan for TMD models in data
+///
+/// Candidates are checked with [`Tmd::validate`], which sizes a model from
+/// its object table without parsing it.
 fn scan_tmd_models(data: &[u8]) -> Result<Vec<TmdInfo>> {
-    const TMD_MAGIC: u32 = 0x00000041;
     let mut tmds = Vec::new();
     let mut offset = 0;
 
     while offset + 12 <= data.len() {
-        // Check for TMD magic number
-        if let Some(magic_bytes) = data.get(offset..offset + 4) {
-            let magic = u32::from_le_bytes([
-                magic_bytes[0],
-                magic_bytes[1],
-                magic_bytes[2],
-                magic_bytes[3],
-            ]);
-
-            if magic == TMD_MAGIC {
-                // Read object count from header
-                if let Some(count_bytes) = data.get(offset + 8..offset + 12) {
-                    let object_count = u32::from_le_bytes([
-                        count_bytes[0],
-                        count_bytes[1],
-                        count_bytes[2],
-                        count_bytes[3],
-                    ]);
-
-                    // Validate object count (sanity check)
-                    if object_count > 0 && object_count <= 100 {
-                        // Try to estimate TMD size
-                        if let Some(size) = estimate_tmd_size(data, offset, object_count) {
-                            tmds.push(TmdInfo {
-                                offset,
-                                size,
-                                object_count,
-                            });
-
-                            // Skip past this TMD
-                            offset += size;
-                            continue;
-                        }
-                    }
-                }
-            }
+        if let Ok((object_count, size)) = Tmd::validate(&data[offset..]) {
+            tmds.push(TmdInfo {
+                offset,
+                size,
+                object_count,
+            });
+
+            // Skip past this TMD
+            offset += size.next_multiple_of(4);
+            continue;
         }
 
         offset += 4; // Align to 4-byte boundaries for faster scanning
@@ -196,59 +170,3 @@ fn scan_tmd_models(data: &[u8]) -> Result<Vec<TmdInfo>> {
 
     Ok(tmds)
 }
-
-/// Estimate the size of a TMD file
-///
-/// TMD format doesn't have an explicit size field, so we calculate it
-/// from the object table entries.
-fn estimate_tmd_size(data: &[u8], offset: usize, object_count: u32) -> Option<usize> {
-    // TMD structure:
-    // - Header: 12 bytes
-    // - Object table: object_count * 28 bytes
-    // - Data (vertices, normals, primitives): variable
-
-    let header_size = 12;
-    let obj_table_size = object_count as usize * 28;
-    let obj_table_offset = offset + header_size;
-
-    if obj_table_offset + obj_table_size > data.len() {
-        return None; // Object table doesn't fit
-    }
-
-    // Find the maximum extent by reading object table entries
-    // Start with end of object table as minimum size
-    let mut max_extent = offset + header_size + obj_table_size;
-
-    for i in 0..object_count as usize {
-        let entry_offset = obj_table_offset + (i * 28);
-        let entry = &data[entry_offset..entry_offset + 28];
-
-        // Read offsets and counts from object entry
-        // Offsets are relative to TMD start, not absolute
-        let vert_offset = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]) as usize;
-        let vert_count = u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]) as usize;
-
-        let normal_offset = u32::from_le_bytes([entry[8], entry[9], entry[10], entry[11]]) as usize;
-        let normal_count =
-            u32::from_le_bytes([entry[12], entry[13], entry[14], entry[15]]) as usize;
-
-        let prim_offset = u32::from_le_bytes([entry[16], entry[17], entry[18], entry[19]]) as usize;
-        let prim_count = u32::from_le_bytes([entry[20], entry[21], entry[22], entry[23]]) as usize;
-
-        // Sanity check: offsets and counts should be reasonable
-        if vert_count > 10000 || normal_count > 10000 || prim_count > 10000 {
-            return None; // Probably not a valid TMD
-        }
-
-        // Calculate extents (add offset to convert to absolute)
-        let vert_end = offset + vert_offset + (vert_count * 8);
-        let normal_end = offset + normal_offset + (normal_count * 8);
-        // Primitives are variable size, estimate conservatively
-        let prim_end = offset + prim_offset + (prim_count * 32); // 32 bytes per prim is upper bound
-
-        max_extent = max_extent.max(vert_end).max(normal_end).max(prim_end);
-    }
-
-    // Return size relative to TMD start
-    Some(max_extent - offset)
-}
@@ -152,6 +152,10 @@ fn extract_stream(
         stream.coding_info.bits_per_sample(),
         stream.coding_info.is_stereo(),
         1.0, // Volume = 1.0 (normal)
+    )
+    .with_emphasis(
+        stream.coding_info.has_emphasis(),
+        stream.coding_info.sample_rate(),
     );
 
     // Decode all sectors
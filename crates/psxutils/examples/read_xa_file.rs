@@ -10,11 +10,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     // Find XA file location
     println!("\nFinding XA/XA1.XA...");
-    let xa_entries = cdrom.read_dir("/XA")?;
-    let xa1 = xa_entries
-        .iter()
-        .find(|e| e.name == "XA1.XA")
-        .expect("XA1.XA not found");
+    let xa1 = cdrom.stat("/XA/XA1.XA").expect("XA1.XA not found");
 
     println!("XA1.XA: {} bytes at LBA {}", xa1.size, xa1.lba);
 
@@ -1,4 +1,4 @@
-/// Test asset scanner on PROT.DAT using chunked scanning
+/// Test asset scanner on PROT.DAT using streaming scanning
 use anyhow::Result;
 use psxutils::{AssetScanner, AssetType, CdRom};
 
@@ -10,11 +10,7 @@ fn main() -> Result<()> {
 
     // Get file info without reading entire file
     println!("Finding PROT.DAT...");
-    let entries = disc.read_dir("/")?;
-    let prot_entry = entries
-        .iter()
-        .find(|e| e.name.eq_ignore_ascii_case("PROT.DAT"))
-        .expect("PROT.DAT not found");
+    let prot_entry = disc.stat("/PROT.DAT").expect("PROT.DAT not found");
 
     println!(
         "PROT.DAT size: {} bytes ({} MB)",
@@ -23,45 +19,11 @@ fn main() -> Result<()> {
     );
     println!("  Starting at LBA: {}", prot_entry.lba);
 
-    // Scan in 5MB chunks to avoid OOM
-    const CHUNK_SIZE: usize = 5 * 1024 * 1024; // 5 MB chunks
-    let file_size = prot_entry.size as usize;
-    let file_lba = prot_entry.lba;
-    let mut all_assets = Vec::new();
-    let mut file_offset = 0;
-
-    println!("\nScanning for embedded assets in chunks...");
-    while file_offset < file_size {
-        let chunk_size = CHUNK_SIZE.min(file_size - file_offset);
-
-        // Calculate which sectors to read
-        let sectors_to_skip = file_offset / 2048;
-        let chunk_lba = file_lba + sectors_to_skip as u32;
-
-        print!(
-            "  Scanning offset {}-{} MB... ",
-            file_offset / 1024 / 1024,
-            (file_offset + chunk_size) / 1024 / 1024
-        );
-
-        let chunk_data = disc.read_data(chunk_lba, chunk_size)?;
-
-        let scanner = AssetScanner::new(&chunk_data).with_min_size(64);
-        let chunk_assets = scanner.scan();
-        let asset_count = chunk_assets.len();
-
-        // Adjust offsets to be relative to file start
-        for mut asset in chunk_assets {
-            asset.offset += file_offset;
-            all_assets.push(asset);
-        }
-
-        println!("found {} new assets", asset_count);
-
-        file_offset += chunk_size;
-    }
-
-    let assets = all_assets;
+    // Stream sector by sector to avoid loading the whole file; unlike fixed
+    // chunks, this also finds assets straddling chunk boundaries
+    println!("\nScanning for embedded assets...");
+    let reader = disc.read_file_stream("/PROT.DAT")?;
+    let assets = AssetScanner::scan_streaming(reader, 64)?;
 
     // Count by type
     let mut tim_count = 0;
@@ -73,6 +35,8 @@ fn main() -> Result<()> {
             AssetType::Tim { .. } => tim_count += 1,
             AssetType::Tmd { .. } => tmd_count += 1,
             AssetType::Vag => vag_count += 1,
+            // Only produced by the sequential scanner
+            AssetType::CustomModel => {}
         }
     }
 
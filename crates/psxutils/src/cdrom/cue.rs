@@ -0,0 +1,263 @@
+//! CUE sheet parsing for multi-track disc images
+//!
+//! Rips of PlayStation discs are often stored as a `.cue` sheet describing
+//! one or more raw 2352-byte `.bin` files: a MODE2 data track followed by
+//! CD-DA audio tracks. Only the `FILE`, `TRACK` and `INDEX 01` commands are
+//! needed to locate each track; everything else is ignored.
+//!
+//! ```text
+//! FILE "Legaia.bin" BINARY
+//!   TRACK 01 MODE2/2352
+//!     INDEX 01 00:00:00
+//!   TRACK 02 AUDIO
+//!     INDEX 00 52:10:20
+//!     INDEX 01 52:12:20
+//! ```
+
+use super::SECTOR_SIZE;
+use crate::{PsxError, Result};
+use std::path::{Path, PathBuf};
+
+/// CD frames (sectors) per second, used by `mm:ss:ff` timestamps
+const FRAMES_PER_SECOND: u32 = 75;
+
+/// Track content type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackType {
+    /// Mode 1 data (2352-byte raw sectors)
+    Mode1,
+    /// Mode 2 / CD-XA data (2352-byte raw sectors)
+    Mode2,
+    /// CD-DA audio (16-bit stereo 44.1 kHz PCM)
+    Audio,
+}
+
+impl TrackType {
+    /// Whether the track holds data sectors rather than audio
+    pub fn is_data(&self) -> bool {
+        matches!(self, TrackType::Mode1 | TrackType::Mode2)
+    }
+}
+
+/// Track of a disc image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackInfo {
+    /// Track number (1-based)
+    pub number: u8,
+    /// Track content type
+    pub track_type: TrackType,
+    /// Absolute start sector (INDEX 01) on the disc
+    pub start_lba: u32,
+    /// Length in sectors, excluding the next track's pregap
+    pub length: u32,
+    /// Pregap (INDEX 00 to INDEX 01) in sectors, usually 150 (2 seconds)
+    /// for audio tracks; not part of `length`
+    pub pregap: u32,
+    /// Image file holding the track
+    pub(crate) file: PathBuf,
+    /// Byte offset of the track's first sector within `file`
+    pub(crate) file_offset: u64,
+}
+
+/// Track as written in the CUE sheet, before file sizes are known
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CueTrack {
+    /// File name as given on the preceding FILE line
+    pub file: String,
+    pub number: u8,
+    pub track_type: TrackType,
+    /// INDEX 00 (pregap start) position in sectors, relative to the start of `file`
+    pub index00: Option<u32>,
+    /// INDEX 01 position in sectors, relative to the start of `file`
+    pub index01: u32,
+}
+
+/// Parse the tracks of a CUE sheet
+pub(crate) fn parse_cue_sheet(text: &str) -> Result<Vec<CueTrack>> {
+    let mut tracks: Vec<CueTrack> = Vec::new();
+    let mut current_file: Option<String> = None;
+    let mut pending: Option<(u8, TrackType)> = None;
+    let mut index00 = None;
+
+    for line in text.lines() {
+        let line = line.trim();
+        let (command, args) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+
+        match command.to_ascii_uppercase().as_str() {
+            "FILE" => current_file = Some(parse_file_name(args.trim())?),
+            "TRACK" => {
+                let mut parts = args.split_whitespace();
+                let number = parts
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or_else(|| PsxError::ParseError(format!("Invalid TRACK line: {}", line)))?;
+                let track_type = match parts.next().map(str::to_ascii_uppercase).as_deref() {
+                    Some("MODE1/2352") => TrackType::Mode1,
+                    Some("MODE2/2352") => TrackType::Mode2,
+                    Some("AUDIO") => TrackType::Audio,
+                    Some(other) => {
+                        return Err(PsxError::InvalidFormat(format!(
+                            "Unsupported track mode: {} (only raw 2352-byte sectors are supported)",
+                            other
+                        )))
+                    }
+                    None => {
+                        return Err(PsxError::ParseError(format!(
+                            "Invalid TRACK line: {}",
+                            line
+                        )))
+                    }
+                };
+                pending = Some((number, track_type));
+                index00 = None;
+            }
+            "INDEX" => {
+                let mut parts = args.split_whitespace();
+                match parts.next() {
+                    Some("00") => {
+                        index00 = parts.next().map(parse_msf).transpose()?;
+                        continue;
+                    }
+                    Some("01") => {}
+                    _ => continue,
+                }
+                let (number, track_type) = pending.take().ok_or_else(|| {
+                    PsxError::ParseError(format!("INDEX outside of a TRACK: {}", line))
+                })?;
+                let file = current_file
+                    .clone()
+                    .ok_or_else(|| PsxError::ParseError(format!("TRACK {} has no FILE", number)))?;
+                let index01 =
+                    parts.next().map(parse_msf).transpose()?.ok_or_else(|| {
+                        PsxError::ParseError(format!("Invalid INDEX line: {}", line))
+                    })?;
+
+                tracks.push(CueTrack {
+                    file,
+                    number,
+                    track_type,
+                    index00: index00.take(),
+                    index01,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    if tracks.is_empty() {
+        return Err(PsxError::ParseError("CUE sheet has no tracks".to_string()));
+    }
+
+    Ok(tracks)
+}
+
+/// Extract the file name from the arguments of a FILE line
+///
+/// The name may be quoted (and contain spaces); the trailing file type
+/// (BINARY, WAVE, ...) is dropped.
+fn parse_file_name(args: &str) -> Result<String> {
+    if let Some(rest) = args.strip_prefix('"') {
+        if let Some(end) = rest.find('"') {
+            return Ok(rest[..end].to_string());
+        }
+    } else if let Some(name) = args.split_whitespace().next() {
+        return Ok(name.to_string());
+    }
+
+    Err(PsxError::ParseError(format!("Invalid FILE line: {}", args)))
+}
+
+/// Parse an `mm:ss:ff` timestamp into a sector count
+fn parse_msf(msf: &str) -> Result<u32> {
+    let parts: Vec<u32> = msf
+        .split(':')
+        .map(|part| part.parse::<u32>())
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|_| PsxError::ParseError(format!("Invalid timestamp: {}", msf)))?;
+
+    match parts[..] {
+        [minutes, seconds, frames] => Ok((minutes * 60 + seconds) * FRAMES_PER_SECOND + frames),
+        _ => Err(PsxError::ParseError(format!("Invalid timestamp: {}", msf))),
+    }
+}
+
+/// Lay the tracks of a CUE sheet out on the disc
+///
+/// Files referenced by the sheet are resolved relative to `base_dir`. Each
+/// file's sector count comes from its size on disk, which gives the length
+/// of the last track in a file and the absolute position of the next file.
+pub(crate) fn resolve_tracks(cue_tracks: &[CueTrack], base_dir: &Path) -> Result<Vec<TrackInfo>> {
+    let mut tracks = Vec::with_capacity(cue_tracks.len());
+    let mut file_start_lba = 0;
+    let mut file_sectors = 0;
+
+    for (i, track) in cue_tracks.iter().enumerate() {
+        let path = base_dir.join(&track.file);
+
+        // A new FILE starts right after the previous one
+        if i == 0 || cue_tracks[i - 1].file != track.file {
+            file_start_lba += file_sectors;
+            file_sectors = (std::fs::metadata(&path)?.len() / SECTOR_SIZE as u64) as u32;
+        }
+
+        // The track ends where the next track of the same file begins,
+        // including that track's pregap
+        let end = match cue_tracks.get(i + 1) {
+            Some(next) if next.file == track.file => next.index00.unwrap_or(next.index01),
+            _ => file_sectors,
+        };
+
+        tracks.push(TrackInfo {
+            number: track.number,
+            track_type: track.track_type,
+            start_lba: file_start_lba + track.index01,
+            length: end.saturating_sub(track.index01),
+            pregap: track
+                .index00
+                .map_or(0, |index00| track.index01.saturating_sub(index00)),
+            file: path,
+            file_offset: track.index01 as u64 * SECTOR_SIZE as u64,
+        });
+    }
+
+    Ok(tracks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cue_sheet() {
+        let cue = r#"
+FILE "Legend of Legaia (Track 1).bin" BINARY
+  TRACK 01 MODE2/2352
+    INDEX 01 00:00:00
+FILE "Legend of Legaia (Track 2).bin" BINARY
+  TRACK 02 AUDIO
+    INDEX 00 00:00:00
+    INDEX 01 00:02:00
+  TRACK 03 AUDIO
+    INDEX 01 01:00:10
+"#;
+
+        let tracks = parse_cue_sheet(cue).unwrap();
+        assert_eq!(tracks.len(), 3);
+        assert_eq!(tracks[0].file, "Legend of Legaia (Track 1).bin");
+        assert_eq!(tracks[0].track_type, TrackType::Mode2);
+        assert_eq!(tracks[0].index01, 0);
+        assert_eq!(tracks[1].file, "Legend of Legaia (Track 2).bin");
+        assert_eq!(tracks[1].track_type, TrackType::Audio);
+        assert_eq!(tracks[1].index00, Some(0));
+        assert_eq!(tracks[1].index01, 150);
+        assert_eq!(tracks[2].index00, None);
+        assert_eq!(tracks[2].number, 3);
+        assert_eq!(tracks[2].index01, 60 * 75 + 10);
+    }
+
+    #[test]
+    fn test_unsupported_track_mode() {
+        let cue = "FILE \"a.bin\" BINARY\nTRACK 01 MODE2/2336\nINDEX 01 00:00:00\n";
+        assert!(parse_cue_sheet(cue).is_err());
+    }
+}
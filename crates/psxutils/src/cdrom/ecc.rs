@@ -0,0 +1,197 @@
+//! CD-ROM Error Correction Code (ECC) and sector encoding
+//!
+//! Mode 1 and Mode 2 Form 1 sectors end with 276 bytes of Reed-Solomon
+//! product code over GF(2^8) (polynomial 0x11D), computed from offset 12
+//! onward:
+//!
+//! ```text
+//! P parity: 86 columns of 24 bytes over 12..2076, stored at 2076 (172 bytes)
+//! Q parity: 52 diagonals of 43 bytes over 12..2248, stored at 2248 (104 bytes)
+//! ```
+//!
+//! Q covers the P parity, so P must be computed first. For Mode 2 the
+//! 4-byte header is treated as zero, so sectors can be relocated without
+//! recomputing parity.
+
+use super::edc::compute_edc;
+use super::{DATA_SIZE, SECTOR_SIZE};
+use crate::formats::xa::{SubMode, XA_DATA_OFFSET, XA_SUBHEADER_OFFSET};
+
+/// CD sync pattern at the start of every raw data sector
+pub const SYNC_PATTERN: [u8; 12] = [
+    0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00,
+];
+
+/// Offset of the 4-byte header (minute, second, frame, mode)
+const HEADER_OFFSET: usize = 12;
+
+/// Offset of the EDC in a Mode 2 Form 1 sector
+const FORM1_EDC_OFFSET: usize = 2072;
+
+/// Offset of the P parity bytes
+const P_PARITY_OFFSET: usize = 2076;
+
+/// Offset of the Q parity bytes
+const Q_PARITY_OFFSET: usize = 2248;
+
+/// Sectors before LBA 0 (the 2-second lead-in counted by MSF addresses)
+const MSF_LBA_OFFSET: u32 = 150;
+
+/// GF(2^8) multiply-by-2 table
+const ECC_F_LUT: [u8; 256] = build_f_lut();
+
+/// Inverse of `x ^ 2x`, used to solve for the parity pair
+const ECC_B_LUT: [u8; 256] = build_b_lut();
+
+const fn build_f_lut() -> [u8; 256] {
+    let mut table = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        let j = (i << 1) ^ if i & 0x80 != 0 { 0x11D } else { 0 };
+        table[i] = j as u8;
+        i += 1;
+    }
+    table
+}
+
+const fn build_b_lut() -> [u8; 256] {
+    let mut table = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        let j = (i << 1) ^ if i & 0x80 != 0 { 0x11D } else { 0 };
+        table[i ^ j] = i as u8;
+        i += 1;
+    }
+    table
+}
+
+/// Compute one parity block (P or Q) of a raw sector in place
+///
+/// `major_count` code words of `minor_count` bytes are read from offset 12,
+/// stepping `minor_inc` bytes (wrapping) within a word; each yields two
+/// parity bytes, stored `major_count` apart at `dest`.
+fn compute_block(
+    raw: &mut [u8],
+    major_count: usize,
+    minor_count: usize,
+    major_mult: usize,
+    minor_inc: usize,
+    dest: usize,
+) {
+    let size = major_count * minor_count;
+
+    for major in 0..major_count {
+        let mut index = (major >> 1) * major_mult + (major & 1);
+        let mut ecc_a = 0u8;
+        let mut ecc_b = 0u8;
+
+        for _ in 0..minor_count {
+            let byte = raw[HEADER_OFFSET + index];
+            index += minor_inc;
+            if index >= size {
+                index -= size;
+            }
+            ecc_a ^= byte;
+            ecc_b ^= byte;
+            ecc_a = ECC_F_LUT[ecc_a as usize];
+        }
+
+        ecc_a = ECC_B_LUT[(ECC_F_LUT[ecc_a as usize] ^ ecc_b) as usize];
+        raw[dest + major] = ecc_a;
+        raw[dest + major + major_count] = ecc_a ^ ecc_b;
+    }
+}
+
+/// Fill in the P and Q parity of a raw Mode 2 Form 1 sector
+///
+/// The header is zeroed while computing, as Mode 2 requires, and restored
+/// afterwards. The EDC must already be in place since it is covered too.
+pub fn compute_ecc(raw: &mut [u8]) {
+    let header: [u8; 4] = raw[HEADER_OFFSET..HEADER_OFFSET + 4].try_into().unwrap();
+    raw[HEADER_OFFSET..HEADER_OFFSET + 4].fill(0);
+
+    compute_block(raw, 86, 24, 2, 86, P_PARITY_OFFSET);
+    compute_block(raw, 52, 43, 86, 88, Q_PARITY_OFFSET);
+
+    raw[HEADER_OFFSET..HEADER_OFFSET + 4].copy_from_slice(&header);
+}
+
+/// Convert a binary value (0-99) to BCD
+fn to_bcd(value: u32) -> u8 {
+    (((value / 10) << 4) | (value % 10)) as u8
+}
+
+/// Build a raw 2352-byte Mode 2 Form 1 sector
+///
+/// Writes the sync pattern, the BCD MSF address for `lba`, a duplicated
+/// sub-header (file and channel 0) with `sub_mode`, the payload (zero
+/// padded to 2048 bytes), and a valid EDC and ECC.
+pub fn encode_form1_sector(lba: u32, sub_mode: SubMode, data: &[u8]) -> Vec<u8> {
+    let mut raw = vec![0u8; SECTOR_SIZE];
+    raw[..12].copy_from_slice(&SYNC_PATTERN);
+
+    let frames = lba + MSF_LBA_OFFSET;
+    raw[HEADER_OFFSET] = to_bcd(frames / (75 * 60));
+    raw[HEADER_OFFSET + 1] = to_bcd(frames / 75 % 60);
+    raw[HEADER_OFFSET + 2] = to_bcd(frames % 75);
+    raw[HEADER_OFFSET + 3] = 2;
+
+    raw[XA_SUBHEADER_OFFSET + 2] = sub_mode.bits();
+    raw[XA_SUBHEADER_OFFSET + 6] = sub_mode.bits();
+
+    let len = data.len().min(DATA_SIZE);
+    raw[XA_DATA_OFFSET..XA_DATA_OFFSET + len].copy_from_slice(&data[..len]);
+
+    let edc = compute_edc(&raw[XA_SUBHEADER_OFFSET..FORM1_EDC_OFFSET]);
+    raw[FORM1_EDC_OFFSET..FORM1_EDC_OFFSET + 4].copy_from_slice(&edc.to_le_bytes());
+
+    compute_ecc(&mut raw);
+    raw
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cdrom::edc::{check_raw_sector, SectorIntegrity};
+
+    #[test]
+    fn test_lut_inverse() {
+        // ECC_B_LUT undoes x -> x ^ 2x
+        for (i, &forward) in ECC_F_LUT.iter().enumerate() {
+            let j = i as u8 ^ forward;
+            assert_eq!(ECC_B_LUT[j as usize], i as u8);
+        }
+    }
+
+    #[test]
+    fn test_encode_form1_sector() {
+        let raw = encode_form1_sector(16, SubMode::DATA, b"CD001");
+
+        assert_eq!(raw[..12], SYNC_PATTERN);
+        // LBA 16 + 150 = 166 frames = 00:02:16
+        assert_eq!(raw[12..16], [0x00, 0x02, 0x16, 0x02]);
+        assert_eq!(&raw[24..29], b"CD001");
+        assert_eq!(check_raw_sector(&raw), SectorIntegrity::Ok);
+    }
+
+    #[test]
+    fn test_ecc_ignores_header() {
+        // Mode 2 parity is computed with a zero header, so the address
+        // doesn't affect it
+        let a = encode_form1_sector(0, SubMode::DATA, &[0x5A; DATA_SIZE]);
+        let b = encode_form1_sector(1000, SubMode::DATA, &[0x5A; DATA_SIZE]);
+        assert_eq!(a[P_PARITY_OFFSET..], b[P_PARITY_OFFSET..]);
+
+        let c = encode_form1_sector(0, SubMode::DATA, &[0x5B; DATA_SIZE]);
+        assert_ne!(a[P_PARITY_OFFSET..], c[P_PARITY_OFFSET..]);
+    }
+
+    #[test]
+    fn test_zero_sector_parity() {
+        // All-zero protected area (sub-header, data and EDC) has zero parity
+        let mut raw = vec![0u8; SECTOR_SIZE];
+        raw[P_PARITY_OFFSET..].fill(0xFF);
+        compute_ecc(&mut raw);
+        assert!(raw[P_PARITY_OFFSET..].iter().all(|&b| b == 0));
+    }
+}
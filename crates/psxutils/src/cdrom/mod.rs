@@ -2,6 +2,8 @@
 //!
 //! This module provides functionality to read PlayStation disc images
 //! in ISO 9660 format with CD-XA extensions, plus CD-ROM streaming types.
+//! When a Joliet supplementary volume descriptor is present, its long
+//! Unicode names are used instead of the 8.3 primary names.
 //!
 //! ## Example
 //!
@@ -16,16 +18,28 @@
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 
+pub mod cue;
+pub mod ecc;
+pub mod edc;
 pub mod streaming;
 
+pub use cue::{TrackInfo, TrackType};
+pub use edc::SectorIntegrity;
 pub use streaming::{
     timeouts, CdromAsyncMode, CdromPosition, CdromState, CdromStreamParams, CdromSyncStatus,
 };
 
+use crate::formats::xa::{self, XaAudioStream, XaSubHeader, XA_DATA_OFFSET, XA_SUBHEADER_OFFSET};
 use crate::{PsxError, Result};
+use bitflags::bitflags;
 use memmap2::Mmap;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::path::Path;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+#[cfg(test)]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{PoisonError, RwLock};
 
 /// CD-ROM sector size (Mode 2 Form 1)
 pub const SECTOR_SIZE: usize = 2352;
@@ -33,21 +47,101 @@ pub const SECTOR_SIZE: usize = 2352;
 /// CD-ROM data size per sector (Mode 2)
 pub const DATA_SIZE: usize = 2048;
 
+/// CD-DA sample rate (16-bit stereo, 588 frames per sector)
+pub const CDDA_SAMPLE_RATE: u32 = 44100;
+
 /// Primary Volume Descriptor is at sector 16
 const PVD_SECTOR: u32 = 16;
 
 /// Volume descriptor type codes
 const VD_PRIMARY: u8 = 1;
+const VD_SUPPLEMENTARY: u8 = 2;
+const VD_TERMINATOR: u8 = 255;
+
+/// Joliet escape sequences (UCS-2 level 1, 2 and 3) at offset 88 of an SVD
+const JOLIET_ESCAPES: [&[u8; 3]; 3] = [b"%/@", b"%/C", b"%/E"];
 
 /// ISO 9660 directory record flags
 const FLAG_DIRECTORY: u8 = 0x02;
 
+/// Size of the CD-XA system use record following a file identifier
+const XA_RECORD_SIZE: usize = 14;
+
+/// Backing storage of a disc image
+///
+/// Sectors are served straight from these bytes, either a memory-mapped
+/// file ([`CdRom::open`]) or an owned buffer ([`CdRom::open_from_bytes`]).
+trait SectorSource: Send + Sync {
+    fn bytes(&self) -> &[u8];
+}
+
+impl SectorSource for Mmap {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+impl SectorSource for Vec<u8> {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+}
+
 /// PlayStation CD-ROM disc image
 pub struct CdRom {
-    _file: File,
-    mmap: Mmap,
+    /// Image file kept open while it is mapped (`None` for in-memory images)
+    _file: Option<File>,
+    source: Box<dyn SectorSource>,
+    /// Byte offset of the data track within the image
+    data_offset: usize,
+    tracks: Vec<TrackInfo>,
+    /// Check the EDC of every Mode 2 sector read
+    verify: bool,
     root_dir_lba: u32,
     root_dir_size: u32,
+    path_table_lba: u32,
+    path_table_size: u32,
+    joliet: bool,
+    /// Parsed directory records by LBA, see [`CdRom::clear_cache`]
+    dir_cache: RwLock<HashMap<u32, Vec<DirectoryEntry>>>,
+    /// Directories parsed from disc rather than served from the cache
+    #[cfg(test)]
+    directory_parses: AtomicUsize,
+}
+
+/// Entry of the ISO 9660 path table
+///
+/// The path table lists every directory on the disc in breadth-first order,
+/// so the whole directory tree can be rebuilt without reading any directory
+/// records. The first entry is always the root directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathTableEntry {
+    /// Directory name (empty for the root)
+    pub name: String,
+    /// Starting sector (LBA) of the directory's records
+    pub lba: u32,
+    /// Index of the parent directory in the path table (the root is its own parent)
+    pub parent: usize,
+}
+
+/// Identification strings of the Primary Volume Descriptor
+///
+/// Fields are d-characters padded with spaces on disc; the padding is
+/// trimmed. PlayStation discs use "PLAYSTATION" as the system identifier.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VolumeInfo {
+    /// System identifier
+    pub system_id: String,
+    /// Volume label
+    pub volume_id: String,
+    /// Volume set identifier
+    pub volume_set_id: String,
+    /// Publisher identifier
+    pub publisher_id: String,
+    /// Data preparer identifier
+    pub data_preparer_id: String,
+    /// Application identifier
+    pub application_id: String,
 }
 
 /// Directory entry in ISO 9660 filesystem
@@ -61,26 +155,344 @@ pub struct DirectoryEntry {
     pub lba: u32,
     /// Is this a directory?
     pub is_dir: bool,
+    /// Recording date and time
+    pub recorded: RecordingTime,
+    /// CD-XA extension record, if the disc was mastered with one
+    pub xa: Option<XaEntry>,
+}
+
+/// Recording date and time of a directory record
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecordingTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    /// Offset from GMT in 15-minute intervals
+    pub gmt_offset: i8,
+}
+
+impl RecordingTime {
+    /// Parse the 7-byte date at offset 18 of a directory record
+    fn parse(bytes: &[u8]) -> Self {
+        Self {
+            year: 1900 + bytes[0] as u16,
+            month: bytes[1],
+            day: bytes[2],
+            hour: bytes[3],
+            minute: bytes[4],
+            second: bytes[5],
+            gmt_offset: bytes[6] as i8,
+        }
+    }
+}
+
+bitflags! {
+    /// CD-XA file attributes
+    ///
+    /// The low bits are Unix-style permissions (there is no write bit on a
+    /// CD); the high bits describe how the file's sectors are recorded.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct XaAttributes: u16 {
+        const OWNER_READ = 0x0001;
+        const OWNER_EXECUTE = 0x0004;
+        const GROUP_READ = 0x0010;
+        const GROUP_EXECUTE = 0x0040;
+        const WORLD_READ = 0x0100;
+        const WORLD_EXECUTE = 0x0400;
+        /// Mode 2 Form 1 sectors
+        const FORM1 = 0x0800;
+        /// Mode 2 Form 2 sectors (XA audio, STR video)
+        const FORM2 = 0x1000;
+        /// Sectors interleaved with other files or channels
+        const INTERLEAVED = 0x2000;
+        /// CD-DA audio track
+        const CDDA = 0x4000;
+        const DIRECTORY = 0x8000;
+    }
+}
+
+/// CD-XA system use record of a directory entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XaEntry {
+    /// Owner group ID
+    pub group_id: u16,
+    /// Owner user ID
+    pub user_id: u16,
+    /// Permissions and recording attributes
+    pub attributes: XaAttributes,
+    /// File number matched against the XA sub-header of interleaved sectors
+    pub file_number: u8,
+}
+
+impl XaEntry {
+    /// Parse the system use area following a file identifier
+    ///
+    /// Returns `None` unless it starts with a 14-byte record carrying the
+    /// `XA` signature. Fields are big-endian.
+    fn parse(system_use: &[u8]) -> Option<Self> {
+        if system_use.len() < XA_RECORD_SIZE || &system_use[6..8] != b"XA" {
+            return None;
+        }
+
+        Some(Self {
+            group_id: u16::from_be_bytes([system_use[0], system_use[1]]),
+            user_id: u16::from_be_bytes([system_use[2], system_use[3]]),
+            attributes: XaAttributes::from_bits_retain(u16::from_be_bytes([
+                system_use[4],
+                system_use[5],
+            ])),
+            file_number: system_use[8],
+        })
+    }
+
+    /// Whether the file is recorded as interleaved Form 2 sectors
+    pub fn is_interleaved(&self) -> bool {
+        self.attributes.contains(XaAttributes::INTERLEAVED)
+    }
+}
+
+/// Sector-by-sector reader over a file on the disc
+///
+/// Iterating yields each sector's data payload (at most 2048 bytes, the last
+/// one trimmed to the file size) borrowed straight from the memory-mapped
+/// image, without copying. It also implements [`Read`] so it can feed
+/// stream-based consumers such as [`crate::AssetScanner::scan_streaming`].
+pub struct SectorReader<'a> {
+    cdrom: &'a CdRom,
+    lba: u32,
+    remaining: usize,
+    /// Unread part of the current sector, for the `Read` impl
+    current: &'a [u8],
+}
+
+impl<'a> Iterator for SectorReader<'a> {
+    type Item = Result<&'a [u8]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        match self.cdrom.read_sector(self.lba) {
+            Ok(sector) => {
+                let len = self.remaining.min(DATA_SIZE).min(sector.len());
+                self.lba += 1;
+                self.remaining -= len;
+                Some(Ok(&sector[..len]))
+            }
+            Err(e) => {
+                // Stop after the first bad sector
+                self.remaining = 0;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl Read for SectorReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.current.is_empty() {
+            match self.next() {
+                None => return Ok(0),
+                Some(Err(e)) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+                Some(Ok(sector)) => self.current = sector,
+            }
+        }
+
+        let n = buf.len().min(self.current.len());
+        buf[..n].copy_from_slice(&self.current[..n]);
+        self.current = &self.current[n..];
+        Ok(n)
+    }
+}
+
+/// Lazy depth-first traversal of every file on a disc
+///
+/// Returned by [`CdRom::walk`]. Each directory is read only when the walk
+/// reaches it, and a directory extent is never entered twice, so corrupt
+/// images whose records point back at an ancestor cannot loop forever.
+pub struct Walk<'a> {
+    cdrom: &'a CdRom,
+    /// Directories being listed, innermost last, with their unvisited entries
+    stack: Vec<(String, std::vec::IntoIter<DirectoryEntry>)>,
+    /// Directory extents already entered
+    visited: HashSet<u32>,
+    /// Error reading the root directory, reported on the first call
+    root_error: Option<PsxError>,
+}
+
+impl Iterator for Walk<'_> {
+    type Item = Result<(String, DirectoryEntry)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.root_error.take() {
+            return Some(Err(e));
+        }
+
+        loop {
+            let (dir_path, entries) = self.stack.last_mut()?;
+            let Some(entry) = entries.next() else {
+                self.stack.pop();
+                continue;
+            };
+
+            let path = format!("{}/{}", dir_path, entry.name);
+            if !entry.is_dir {
+                return Some(Ok((path, entry)));
+            }
+
+            if !self.visited.insert(entry.lba) {
+                tracing::warn!(
+                    "Skipping directory {} at LBA {}: already visited",
+                    path,
+                    entry.lba
+                );
+                continue;
+            }
+
+            match self.cdrom.parse_directory_entries(entry.lba, entry.size) {
+                Ok(children) => self.stack.push((path, children.into_iter())),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
 }
 
 impl CdRom {
     /// Open a PlayStation disc image
     ///
-    /// Supports BIN files (raw CD image format)
+    /// Supports single-track BIN files (raw CD image format). Use
+    /// [`CdRom::open_cue`] for images with audio tracks.
     pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::open_bin(path.as_ref(), false)
+    }
+
+    /// Map a single-track BIN and locate its filesystem
+    fn open_bin(path: &Path, verify: bool) -> Result<Self> {
         let file = File::open(path)?;
         let mmap = unsafe { Mmap::map(&file)? };
 
+        let track = TrackInfo {
+            number: 1,
+            track_type: TrackType::Mode2,
+            start_lba: 0,
+            length: (mmap.len() / SECTOR_SIZE) as u32,
+            file: path.to_path_buf(),
+            pregap: 0,
+            file_offset: 0,
+        };
+
+        Self::from_source(Some(file), Box::new(mmap), 0, vec![track], verify)
+    }
+
+    /// Open a single-track disc image held in memory
+    ///
+    /// Same as [`CdRom::open`] but without a file or mmap, for images
+    /// fetched over the network or on targets like WASM. Audio tracks
+    /// can't be read since the image has no backing file.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use psxutils::cdrom::CdRom;
+    /// let image = std::fs::read("game.bin")?;
+    /// let disc = CdRom::open_from_bytes(image)?;
+    /// let config = disc.read_file("/SYSTEM.CNF")?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn open_from_bytes(data: Vec<u8>) -> Result<Self> {
+        let track = TrackInfo {
+            number: 1,
+            track_type: TrackType::Mode2,
+            start_lba: 0,
+            length: (data.len() / SECTOR_SIZE) as u32,
+            file: PathBuf::new(),
+            pregap: 0,
+            file_offset: 0,
+        };
+
+        Self::from_source(None, Box::new(data), 0, vec![track], false)
+    }
+
+    /// Open a single-track BIN with EDC verification enabled
+    ///
+    /// Every Mode 2 sector read through [`CdRom::read_sector`] has its
+    /// EDC recomputed; a mismatch fails the read with the sector number.
+    /// This is slower than [`CdRom::open`], which trusts the image.
+    pub fn open_verified(path: impl AsRef<Path>) -> Result<Self> {
+        // Verification starts before the volume descriptors are read
+        Self::open_bin(path.as_ref(), true)
+    }
+
+    /// Open a disc image described by a CUE sheet
+    ///
+    /// The first data track is used for the filesystem; its offset within
+    /// its BIN file is applied to every sector read. Audio tracks are listed
+    /// by [`CdRom::tracks`] and can be read with [`CdRom::read_audio_track`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use psxutils::cdrom::CdRom;
+    /// let disc = CdRom::open_cue("Legend of Legaia.cue".as_ref())?;
+    /// for track in disc.tracks() {
+    ///     println!("Track {:02} {:?}: {} sectors", track.number, track.track_type, track.length);
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn open_cue(cue_path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(cue_path)?;
+        let base_dir = cue_path.parent().unwrap_or(Path::new(""));
+        let tracks = cue::resolve_tracks(&cue::parse_cue_sheet(&text)?, base_dir)?;
+
+        let data_track = tracks
+            .iter()
+            .find(|track| track.track_type.is_data())
+            .ok_or_else(|| PsxError::InvalidFormat("CUE sheet has no data track".to_string()))?;
+
+        let file = File::open(&data_track.file)?;
+        let mmap = unsafe { Mmap::map(&file)? };
+        let data_offset = data_track.file_offset as usize;
+
+        Self::from_source(Some(file), Box::new(mmap), data_offset, tracks, false)
+    }
+
+    /// Build a disc from an image and locate its filesystem
+    fn from_source(
+        file: Option<File>,
+        source: Box<dyn SectorSource>,
+        data_offset: usize,
+        tracks: Vec<TrackInfo>,
+        verify: bool,
+    ) -> Result<Self> {
         let mut cdrom = Self {
             _file: file,
-            mmap,
+            source,
+            data_offset,
+            tracks,
+            verify,
             root_dir_lba: 0,
             root_dir_size: 0,
+            path_table_lba: 0,
+            path_table_size: 0,
+            joliet: false,
+            dir_cache: RwLock::new(HashMap::new()),
+            #[cfg(test)]
+            directory_parses: AtomicUsize::new(0),
         };
 
         // Parse the Primary Volume Descriptor to find the root directory
         cdrom.parse_pvd()?;
 
+        // Prefer the Joliet tree (long Unicode names) when the disc has one
+        if let Some(svd) = cdrom.find_joliet_svd() {
+            cdrom.load_volume_descriptor(&svd);
+            cdrom.joliet = true;
+        }
+
         Ok(cdrom)
     }
 
@@ -103,66 +515,144 @@ impl CdRom {
             )));
         }
 
-        // Root directory record starts at offset 156 in the PVD
-        let root_record = &pvd[156..];
+        self.load_volume_descriptor(&pvd);
 
-        // Parse root directory LBA (LSB order at offset 2, 4 bytes)
-        self.root_dir_lba = u32::from_le_bytes([
-            root_record[2],
-            root_record[3],
-            root_record[4],
-            root_record[5],
-        ]);
+        Ok(())
+    }
 
-        // Parse root directory size (LSB order at offset 10, 4 bytes)
-        self.root_dir_size = u32::from_le_bytes([
-            root_record[10],
-            root_record[11],
-            root_record[12],
-            root_record[13],
-        ]);
+    /// Find a Joliet Supplementary Volume Descriptor after the PVD
+    ///
+    /// Walks the volume descriptor set until the terminator and returns the
+    /// first type 2 descriptor carrying a Joliet escape sequence.
+    fn find_joliet_svd(&self) -> Option<Vec<u8>> {
+        for lba in PVD_SECTOR + 1.. {
+            let vd = self.read_sector(lba).ok()?;
+            if vd.len() < DATA_SIZE || &vd[1..6] != b"CD001" || vd[0] == VD_TERMINATOR {
+                return None;
+            }
 
-        Ok(())
+            if vd[0] == VD_SUPPLEMENTARY && JOLIET_ESCAPES.iter().any(|esc| &vd[88..91] == *esc) {
+                return Some(vd.to_vec());
+            }
+        }
+
+        None
+    }
+
+    /// Take the path table and root directory from a primary or supplementary
+    /// volume descriptor (both share the same layout for these fields)
+    fn load_volume_descriptor(&mut self, vd: &[u8]) {
+        // Path table size (both-byte order at offset 132) and type L path table LBA (offset 140)
+        self.path_table_size = parse_pair(&vd[132..140]);
+        self.path_table_lba = u32::from_le_bytes([vd[140], vd[141], vd[142], vd[143]]);
+
+        // Root directory record starts at offset 156 in the volume descriptor
+        let root_record = &vd[156..];
+
+        // Root directory LBA and size (both-byte order at offsets 2 and 10)
+        self.root_dir_lba = parse_pair(&root_record[2..10]);
+        self.root_dir_size = parse_pair(&root_record[10..18]);
+    }
+
+    /// Read the identification strings of the Primary Volume Descriptor
+    pub fn volume_info(&self) -> Result<VolumeInfo> {
+        let pvd = self.read_sector(PVD_SECTOR)?;
+        let field = |range: std::ops::Range<usize>| {
+            String::from_utf8_lossy(&pvd[range])
+                .trim_end_matches([' ', '\0'])
+                .to_string()
+        };
+
+        Ok(VolumeInfo {
+            system_id: field(8..40),
+            volume_id: field(40..72),
+            volume_set_id: field(190..318),
+            publisher_id: field(318..446),
+            data_preparer_id: field(446..574),
+            application_id: field(574..702),
+        })
+    }
+
+    /// Whether names are read from a Joliet (UCS-2) directory tree
+    pub fn is_joliet(&self) -> bool {
+        self.joliet
+    }
+
+    /// Decode a file or directory identifier
+    ///
+    /// Joliet identifiers are UCS-2 big-endian; the single-byte '.' and '..'
+    /// identifiers (0x00 and 0x01) are left as-is.
+    fn decode_name(&self, bytes: &[u8]) -> String {
+        if self.joliet && bytes.len() > 1 {
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        } else {
+            String::from_utf8_lossy(bytes).to_string()
+        }
     }
 
     /// Read a raw sector at the given LBA (all 2352 bytes)
     ///
     /// Returns the complete raw sector including sync pattern, header, and data.
     pub fn read_raw_sector(&self, lba: u32) -> Result<&[u8]> {
-        let offset = lba as usize * SECTOR_SIZE;
+        let offset = self.data_offset + lba as usize * SECTOR_SIZE;
 
-        if offset + SECTOR_SIZE > self.mmap.len() {
+        let image = self.source.bytes();
+
+        if offset + SECTOR_SIZE > image.len() {
             return Err(PsxError::ParseError(format!(
                 "Sector {} out of bounds",
                 lba
             )));
         }
 
-        Ok(&self.mmap[offset..offset + SECTOR_SIZE])
+        Ok(&image[offset..offset + SECTOR_SIZE])
+    }
+
+    /// Check the EDC of the sector at the given LBA
+    ///
+    /// Reports the result instead of failing, so a whole image can be scanned
+    /// for bad sectors. Only Mode 2 sectors are verified; Form 2 sectors
+    /// without a stored EDC are reported as unchecked.
+    pub fn check_sector(&self, lba: u32) -> Result<SectorIntegrity> {
+        Ok(edc::check_raw_sector(self.read_raw_sector(lba)?))
     }
 
     /// Read a sector at the given LBA (Logical Block Address)
     ///
     /// Returns only the 2048-byte data payload (Mode 2 Form 1).
     pub fn read_sector(&self, lba: u32) -> Result<&[u8]> {
-        let offset = lba as usize * SECTOR_SIZE;
+        let offset = self.data_offset + lba as usize * SECTOR_SIZE;
+        let image = self.source.bytes();
 
-        if offset + SECTOR_SIZE > self.mmap.len() {
+        if offset + SECTOR_SIZE > image.len() {
             return Err(PsxError::ParseError(format!(
                 "Sector {} out of bounds",
                 lba
             )));
         }
 
+        if self.verify
+            && let SectorIntegrity::EdcMismatch { stored, computed } = self.check_sector(lba)?
+        {
+            return Err(PsxError::ParseError(format!(
+                "EDC mismatch in sector {} (stored {:08X}, computed {:08X})",
+                lba, stored, computed
+            )));
+        }
+
         // For Mode 2 Form 1, data starts at offset 24 in the sector
         let data_offset = offset + 24;
         let data_end = data_offset + DATA_SIZE;
 
-        if data_end > self.mmap.len() {
+        if data_end > image.len() {
             // Fallback: return what we can
-            Ok(&self.mmap[offset..offset + SECTOR_SIZE.min(self.mmap.len() - offset)])
+            Ok(&image[offset..offset + SECTOR_SIZE.min(image.len() - offset)])
         } else {
-            Ok(&self.mmap[data_offset..data_end])
+            Ok(&image[data_offset..data_end])
         }
     }
 
@@ -199,37 +689,290 @@ impl CdRom {
         self.parse_directory_entries(dir_lba, dir_size)
     }
 
-    /// Find a directory by path and return its LBA and size
-    fn find_directory(&self, path: &str) -> Result<(u32, u32)> {
-        let parts: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    /// Find every entry whose full path matches a shell-style glob
+    ///
+    /// `*` and `?` match within a single path component and `**` matches
+    /// any number of directories. Matching is case-insensitive, like path
+    /// lookups. Returns `(path, entry)` pairs with each directory listed
+    /// before its contents.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use psxutils::cdrom::CdRom;
+    /// # let disc = CdRom::open("game.bin")?;
+    /// for (path, entry) in disc.glob("/**/*.TIM")? {
+    ///     println!("{}: {} bytes", path, entry.size);
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn glob(&self, pattern: &str) -> Result<Vec<(String, DirectoryEntry)>> {
+        let pattern: Vec<&str> = pattern.split('/').filter(|c| !c.is_empty()).collect();
+        let mut matches = Vec::new();
+        let mut visited = HashSet::from([self.root_dir_lba]);
+        self.glob_dir(
+            "",
+            self.root_dir_lba,
+            self.root_dir_size,
+            &pattern,
+            &mut visited,
+            &mut matches,
+        )?;
+        Ok(matches)
+    }
 
-        let mut current_lba = self.root_dir_lba;
-        let mut current_size = self.root_dir_size;
+    /// Recursively collect the entries below a directory that match `pattern`
+    ///
+    /// Like [`CdRom::walk`], a directory extent already in `visited` is not
+    /// entered again, so self-referential records cannot loop.
+    fn glob_dir(
+        &self,
+        dir_path: &str,
+        lba: u32,
+        size: u32,
+        pattern: &[&str],
+        visited: &mut HashSet<u32>,
+        matches: &mut Vec<(String, DirectoryEntry)>,
+    ) -> Result<()> {
+        for entry in self.parse_directory_entries(lba, size)? {
+            let path = format!("{}/{}", dir_path, entry.name);
+            let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+
+            let (is_dir, lba, size) = (entry.is_dir, entry.lba, entry.size);
+            if glob_match_path(pattern, &components) {
+                matches.push((path.clone(), entry));
+            }
+            if !is_dir {
+                continue;
+            }
 
-        for part in parts {
-            // Parse the current directory
-            let entries = self.parse_directory_entries(current_lba, current_size)?;
+            if !visited.insert(lba) {
+                tracing::warn!(
+                    "Skipping directory {} at LBA {}: already visited",
+                    path,
+                    lba
+                );
+                continue;
+            }
+            self.glob_dir(&path, lba, size, pattern, visited, matches)?;
+        }
+
+        Ok(())
+    }
+
+    /// Iterate over every file on the disc, depth-first, with its full path
+    ///
+    /// Directories are read as the walk reaches them rather than up front,
+    /// so the first files are available immediately even on large discs.
+    /// Only files are yielded; paths start with `/`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use psxutils::cdrom::CdRom;
+    /// # let disc = CdRom::open("game.bin")?;
+    /// for file in disc.walk() {
+    ///     let (path, entry) = file?;
+    ///     println!("{} ({} bytes)", path, entry.size);
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn walk(&self) -> Walk<'_> {
+        self.walk_dir(String::new(), self.root_dir_lba, self.root_dir_size)
+    }
 
-            // Find the subdirectory
-            let entry = entries
+    /// Every file below `start` with its full path, sorted by path
+    ///
+    /// Like [`CdRom::walk`], but rooted at any directory and collected up
+    /// front. Directories come from the directory cache, and an extent is
+    /// never entered twice, so self-referential records cannot loop.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use psxutils::cdrom::CdRom;
+    /// # let disc = CdRom::open("game.bin")?;
+    /// for (path, entry) in disc.read_dir_recursive("/MOV")? {
+    ///     println!("{} ({} bytes)", path, entry.size);
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn read_dir_recursive(&self, start: &str) -> Result<Vec<(String, DirectoryEntry)>> {
+        let dir = self.stat(start)?;
+        if !dir.is_dir {
+            return Err(PsxError::ParseError(format!(
+                "'{}' is a file, not a directory",
+                start
+            )));
+        }
+
+        let start = start.trim_matches('/');
+        let prefix = if start.is_empty() {
+            String::new()
+        } else {
+            format!("/{}", start)
+        };
+
+        let mut files = self
+            .walk_dir(prefix, dir.lba, dir.size)
+            .collect::<Result<Vec<_>>>()?;
+        files.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(files)
+    }
+
+    /// Walk the files below the directory at `lba`, prefixing paths with `prefix`
+    fn walk_dir(&self, prefix: String, lba: u32, size: u32) -> Walk<'_> {
+        let mut walk = Walk {
+            cdrom: self,
+            stack: Vec::new(),
+            visited: HashSet::from([lba]),
+            root_error: None,
+        };
+
+        match self.parse_directory_entries(lba, size) {
+            Ok(entries) => walk.stack.push((prefix, entries.into_iter())),
+            Err(e) => walk.root_error = Some(e),
+        }
+
+        walk
+    }
+
+    /// Read the type L (little-endian) path table
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use psxutils::cdrom::CdRom;
+    /// # let disc = CdRom::open("game.bin")?;
+    /// let table = disc.read_path_table()?;
+    /// for entry in &table[1..] {
+    ///     println!("{} (parent: {})", entry.name, table[entry.parent].name);
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn read_path_table(&self) -> Result<Vec<PathTableEntry>> {
+        let data = self.read_data(self.path_table_lba, self.path_table_size as usize)?;
+        let mut entries = Vec::new();
+        let mut offset = 0;
+
+        // Each record: name length (1), extended attribute length (1),
+        // LBA (4), parent directory number (2, 1-based), name, padding to even
+        while offset + 8 <= data.len() {
+            let name_len = data[offset] as usize;
+            if name_len == 0 || offset + 8 + name_len > data.len() {
+                break;
+            }
+
+            let lba = u32::from_le_bytes([
+                data[offset + 2],
+                data[offset + 3],
+                data[offset + 4],
+                data[offset + 5],
+            ]);
+            let parent = u16::from_le_bytes([data[offset + 6], data[offset + 7]]) as usize;
+
+            // The root's identifier is a single 0x00 byte
+            let name_bytes = &data[offset + 8..offset + 8 + name_len];
+            let name = if name_bytes == [0] {
+                String::new()
+            } else {
+                self.decode_name(name_bytes)
+            };
+
+            entries.push(PathTableEntry {
+                name,
+                lba,
+                parent: parent.saturating_sub(1),
+            });
+
+            offset += 8 + name_len + (name_len % 2);
+        }
+
+        if entries.is_empty() {
+            return Err(PsxError::ParseError(
+                "Empty ISO 9660 path table".to_string(),
+            ));
+        }
+
+        Ok(entries)
+    }
+
+    /// Find a directory by path and return its LBA and size
+    ///
+    /// Resolves each path component against the path table, then reads the
+    /// directory's own '.' record for its size.
+    fn find_directory(&self, path: &str) -> Result<(u32, u32)> {
+        let table = self.read_path_table()?;
+        let mut current = 0;
+
+        for part in path.split('/').filter(|s| !s.is_empty()) {
+            current = table
                 .iter()
-                .find(|e| e.is_dir && e.name.eq_ignore_ascii_case(part))
+                .enumerate()
+                .skip(1)
+                .find(|(_, e)| e.parent == current && e.name.eq_ignore_ascii_case(part))
+                .map(|(index, _)| index)
                 .ok_or_else(|| {
                     PsxError::FileNotFound(format!(
                         "Directory '{}' not found in path '{}'",
                         part, path
                     ))
                 })?;
+        }
+
+        let lba = table[current].lba;
 
-            current_lba = entry.lba;
-            current_size = entry.size;
+        // The first record of every directory is '.', describing the directory itself
+        let record = self.read_sector(lba)?;
+        if record[0] < 33 {
+            return Err(PsxError::ParseError(format!(
+                "Invalid directory record at sector {}",
+                lba
+            )));
         }
+        let size = parse_pair(&record[10..18]);
 
-        Ok((current_lba, current_size))
+        Ok((lba, size))
     }
 
-    /// Parse directory entries from a directory's LBA and size
+    /// Drop the cached directory records
+    ///
+    /// Directories are parsed once and kept for the life of the `CdRom`, so
+    /// repeated lookups under the same parent don't re-read its sectors.
+    /// Clearing the cache frees that memory; directories are parsed again
+    /// on next use.
+    pub fn clear_cache(&self) {
+        self.dir_cache
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    /// Directory entries of a directory's LBA and size, parsed on first use
     fn parse_directory_entries(&self, dir_lba: u32, dir_size: u32) -> Result<Vec<DirectoryEntry>> {
+        let cached = self
+            .dir_cache
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&dir_lba)
+            .cloned();
+        if let Some(entries) = cached {
+            return Ok(entries);
+        }
+
+        let entries = self.read_directory_records(dir_lba, dir_size)?;
+        self.dir_cache
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(dir_lba, entries.clone());
+        Ok(entries)
+    }
+
+    /// Parse directory entries from a directory's LBA and size
+    fn read_directory_records(&self, dir_lba: u32, dir_size: u32) -> Result<Vec<DirectoryEntry>> {
+        #[cfg(test)]
+        self.directory_parses.fetch_add(1, Ordering::Relaxed);
+
         let mut entries = Vec::new();
         let mut offset = 0;
 
@@ -274,11 +1017,9 @@ impl CdRom {
             return Ok(None); // Invalid record
         }
 
-        // LBA at offset 2 (LSB), 4 bytes
-        let lba = u32::from_le_bytes([record[2], record[3], record[4], record[5]]);
-
-        // File size at offset 10 (LSB), 4 bytes
-        let size = u32::from_le_bytes([record[10], record[11], record[12], record[13]]);
+        // LBA at offset 2 and file size at offset 10 (both-byte order)
+        let lba = parse_pair(&record[2..10]);
+        let size = parse_pair(&record[10..18]);
 
         // Flags at offset 25
         let flags = record[25];
@@ -295,7 +1036,8 @@ impl CdRom {
         let name_bytes = &record[33..33 + name_len];
 
         // Convert to string, removing version suffix (;1)
-        let name = String::from_utf8_lossy(name_bytes)
+        let name = self
+            .decode_name(name_bytes)
             .split(';')
             .next()
             .unwrap_or("")
@@ -306,11 +1048,17 @@ impl CdRom {
             return Ok(None);
         }
 
+        // System use area follows the identifier, padded to an even offset
+        let system_use_start = 33 + name_len + (name_len + 1) % 2;
+        let xa = record.get(system_use_start..).and_then(XaEntry::parse);
+
         Ok(Some(DirectoryEntry {
             name,
             size,
             lba,
             is_dir,
+            recorded: RecordingTime::parse(&record[18..25]),
+            xa,
         }))
     }
 
@@ -332,12 +1080,31 @@ impl CdRom {
             )));
         }
 
-        let sector_count = ((size + DATA_SIZE - 1) / DATA_SIZE) as u32;
-        let mut data = Vec::with_capacity(size);
+        self.read_lba_range(start_lba, size)
+    }
 
-        for i in 0..sector_count {
-            let sector = self.read_sector(start_lba + i)?;
-            let to_copy = std::cmp::min(DATA_SIZE, size - data.len());
+    /// Read `byte_len` bytes of sector data starting at `start_lba`
+    ///
+    /// Reads consecutive 2048-byte data areas without consulting the ISO
+    /// directory, for assets the game loads by absolute sector such as MIPS
+    /// overlays and regions inside PROT.DAT. Unlike [`read_data`](Self::read_data)
+    /// there is no size cap, but the whole range must lie within the image.
+    pub fn read_lba_range(&self, start_lba: u32, byte_len: usize) -> Result<Vec<u8>> {
+        let sectors = byte_len.div_ceil(DATA_SIZE);
+        let end = start_lba as usize + sectors;
+        if end > self.sector_count() {
+            return Err(PsxError::ParseError(format!(
+                "Range of {} sectors at LBA {} extends past the end of the disc ({} sectors)",
+                sectors,
+                start_lba,
+                self.sector_count()
+            )));
+        }
+
+        let mut data = Vec::with_capacity(byte_len);
+        for lba in start_lba..end as u32 {
+            let sector = self.read_sector(lba)?;
+            let to_copy = DATA_SIZE.min(byte_len - data.len());
             data.extend_from_slice(&sector[..to_copy]);
         }
 
@@ -358,8 +1125,102 @@ impl CdRom {
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
     pub fn read_file(&self, path: &str) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.read_file_into(path, &mut data)?;
+        Ok(data)
+    }
+
+    /// Copy a file by path into a writer, one sector at a time
+    ///
+    /// Returns the number of bytes written. Only one sector's payload is
+    /// handled at a time, so writing PROT.DAT to disk doesn't buffer all
+    /// 121MB in memory.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use psxutils::cdrom::CdRom;
+    /// # let disc = CdRom::open("game.bin")?;
+    /// let mut out = std::fs::File::create("PROT.DAT")?;
+    /// let written = disc.read_file_into("/PROT.DAT", &mut out)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn read_file_into<W: Write>(&self, path: &str, out: &mut W) -> Result<u64> {
+        let mut written = 0;
+
+        for sector in self.read_file_stream(path)? {
+            let sector = sector?;
+            out.write_all(sector)?;
+            written += sector.len() as u64;
+        }
+
+        Ok(written)
+    }
+
+    /// Stream a file by path, one sector at a time
+    ///
+    /// Unlike [`CdRom::read_file`], nothing is copied up front, so very large
+    /// files (like the 121MB PROT.DAT) can be processed in constant memory.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use psxutils::cdrom::CdRom;
+    /// # let disc = CdRom::open("game.bin")?;
+    /// let mut total = 0;
+    /// for sector in disc.read_file_stream("/PROT.DAT")? {
+    ///     total += sector?.len();
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn read_file_stream(&self, path: &str) -> Result<SectorReader<'_>> {
+        let entry = self.stat(path)?;
+
+        if entry.is_dir {
+            return Err(PsxError::ParseError(format!(
+                "'{}' is a directory, not a file",
+                path
+            )));
+        }
+
+        Ok(SectorReader {
+            cdrom: self,
+            lba: entry.lba,
+            remaining: entry.size as usize,
+            current: &[],
+        })
+    }
+
+    /// Look up the directory entry for a file or directory path
+    ///
+    /// Only the directories along the path are read, so this is cheaper than
+    /// searching [`CdRom::read_dir`] output by hand. Lookups are
+    /// case-insensitive. The root (`/` or an empty path) has no record of its
+    /// own, so a synthetic directory entry named `/` is returned for it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use psxutils::cdrom::CdRom;
+    /// # let disc = CdRom::open("game.bin")?;
+    /// let prot = disc.stat("/PROT.DAT")?;
+    /// println!("PROT.DAT: {} bytes at LBA {}", prot.size, prot.lba);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn stat(&self, path: &str) -> Result<DirectoryEntry> {
         // Normalize path
-        let normalized = path.trim_start_matches('/');
+        let normalized = path.trim_start_matches('/').trim_end_matches('/');
+
+        if normalized.is_empty() {
+            return Ok(DirectoryEntry {
+                name: "/".to_string(),
+                size: self.root_dir_size,
+                lba: self.root_dir_lba,
+                is_dir: true,
+                recorded: RecordingTime::default(),
+                xa: None,
+            });
+        }
 
         // Split into directory and filename
         let (dir_path, filename) = if let Some(idx) = normalized.rfind('/') {
@@ -376,37 +1237,961 @@ impl CdRom {
         let entries = self.read_dir(dir)?;
 
         // Find the file
-        let entry = entries
-            .iter()
+        entries
+            .into_iter()
             .find(|e| e.name.eq_ignore_ascii_case(filename))
             .ok_or_else(|| {
                 PsxError::FileNotFound(format!("File '{}' not found in '{}'", filename, dir))
-            })?;
+            })
+    }
 
-        if entry.is_dir {
+    /// Check whether a path names a file (not a directory) on the disc
+    pub fn file_exists(&self, path: &str) -> bool {
+        self.stat(path).is_ok_and(|entry| !entry.is_dir)
+    }
+
+    /// Get the total number of sectors
+    pub fn sector_count(&self) -> usize {
+        self.source.bytes().len().saturating_sub(self.data_offset) / SECTOR_SIZE
+    }
+
+    /// Tracks of the disc image
+    ///
+    /// Single-track BINs opened with [`CdRom::open`] report one MODE2 track
+    /// spanning the whole file.
+    pub fn tracks(&self) -> &[TrackInfo] {
+        &self.tracks
+    }
+
+    /// Read an audio track as raw PCM
+    ///
+    /// CD-DA is 16-bit signed little-endian, stereo interleaved, 44100 Hz.
+    /// The pregap before INDEX 01 is not included.
+    pub fn read_audio_track(&self, number: u8) -> Result<Vec<i16>> {
+        self.read_audio_range(number, 0, None)
+    }
+
+    /// Read part of an audio track as raw PCM
+    ///
+    /// # Arguments
+    /// * `number` - Track number
+    /// * `start` - First sector to read, relative to the track's INDEX 01
+    /// * `length` - Number of sectors to read (`None` for the rest of the track)
+    pub fn read_audio_range(
+        &self,
+        number: u8,
+        start: u32,
+        length: Option<u32>,
+    ) -> Result<Vec<i16>> {
+        let track = self
+            .tracks
+            .iter()
+            .find(|track| track.number == number)
+            .ok_or_else(|| PsxError::FileNotFound(format!("Track {} not found", number)))?;
+
+        if track.track_type != TrackType::Audio {
+            return Err(PsxError::InvalidFormat(format!(
+                "Track {} is not an audio track",
+                number
+            )));
+        }
+
+        let available = track.length.saturating_sub(start);
+        let length = length.unwrap_or(available);
+        if start > track.length || length > available {
             return Err(PsxError::ParseError(format!(
-                "'{}' is a directory, not a file",
-                path
+                "Sectors {}..{} out of range for track {} ({} sectors)",
+                start,
+                start as u64 + length as u64,
+                number,
+                track.length
             )));
         }
 
-        // Read the file data
-        self.read_data(entry.lba, entry.size as usize)
+        let mut file = File::open(&track.file)?;
+        file.seek(SeekFrom::Start(
+            track.file_offset + start as u64 * SECTOR_SIZE as u64,
+        ))?;
+
+        let mut bytes = vec![0u8; length as usize * SECTOR_SIZE];
+        file.read_exact(&mut bytes)?;
+
+        Ok(bytes
+            .chunks_exact(2)
+            .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+            .collect())
     }
 
-    /// Get the total number of sectors
-    pub fn sector_count(&self) -> usize {
-        self.mmap.len() / SECTOR_SIZE
+    /// Extract an audio track to a WAV file
+    ///
+    /// Writes 16-bit stereo PCM at 44100 Hz; each sector holds 588 stereo
+    /// frames. The 2-second pregap is skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use psxutils::cdrom::CdRom;
+    /// let disc = CdRom::open_cue("Legend of Legaia.cue".as_ref())?;
+    /// disc.extract_cdda(2, "track02.wav".as_ref())?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "extraction")]
+    pub fn extract_cdda(&self, number: u8, output: &Path) -> Result<()> {
+        self.extract_cdda_range(number, 0, None, output)
+    }
+
+    /// Extract part of an audio track to a WAV file
+    ///
+    /// `start` and `length` are in sectors, as for [`CdRom::read_audio_range`].
+    #[cfg(feature = "extraction")]
+    pub fn extract_cdda_range(
+        &self,
+        number: u8,
+        start: u32,
+        length: Option<u32>,
+        output: &Path,
+    ) -> Result<()> {
+        let samples = self.read_audio_range(number, start, length)?;
+
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: CDDA_SAMPLE_RATE,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+
+        let to_io = |e: hound::Error| match e {
+            hound::Error::IoError(e) => PsxError::Io(e),
+            e => PsxError::InvalidFormat(e.to_string()),
+        };
+
+        let mut writer = hound::WavWriter::create(output, spec).map_err(to_io)?;
+        for sample in samples {
+            writer.write_sample(sample).map_err(to_io)?;
+        }
+        writer.finalize().map_err(to_io)?;
+
+        Ok(())
+    }
+
+    /// Scan the whole disc for XA audio streams
+    ///
+    /// Parses the XA sub-header of every Mode 2 sector and groups audio
+    /// sectors sharing the same file number, channel, and coding info into
+    /// streams. A sector with the EOF sub-mode bit, or a break in the
+    /// stream's sector sequence, ends its stream.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use psxutils::cdrom::CdRom;
+    /// # let disc = CdRom::open("game.bin")?;
+    /// for stream in disc.scan_xa_streams() {
+    ///     println!(
+    ///         "File {} Ch {}: {:.1}s",
+    ///         stream.file_number,
+    ///         stream.channel,
+    ///         stream.duration_seconds()
+    ///     );
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn scan_xa_streams(&self) -> Vec<XaAudioStream> {
+        let headers = (0..self.sector_count() as u32).filter_map(|lba| {
+            let raw_sector = self.read_raw_sector(lba).ok()?;
+
+            // Mode byte at offset 15; XA sub-headers only exist on Mode 2 sectors
+            if raw_sector[15] != 2 {
+                return None;
+            }
+
+            XaSubHeader::parse(&raw_sector[XA_SUBHEADER_OFFSET..XA_DATA_OFFSET])
+                .map(|header| (lba, header))
+        });
+
+        xa::group_xa_streams(headers)
     }
 }
 
+/// Match path components against glob components (`**` spans directories)
+fn glob_match_path(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| glob_match_path(rest, &path[skip..])),
+        Some((component, rest)) => match path.split_first() {
+            Some((name, tail)) => {
+                let component: Vec<char> = component.chars().collect();
+                let name: Vec<char> = name.chars().collect();
+                glob_match_component(&component, &name) && glob_match_path(rest, tail)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Case-insensitively match one path component against `*` and `?` wildcards
+fn glob_match_component(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skip| glob_match_component(rest, &name[skip..])),
+        Some((c, rest)) => match name.split_first() {
+            Some((n, tail)) => {
+                (*c == '?' || c.eq_ignore_ascii_case(n)) && glob_match_component(rest, tail)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Decode an ISO 9660 both-byte-order u32 (LSB half followed by MSB half)
+///
+/// Both halves are decoded with an explicit byte order, so the result does
+/// not depend on the host. The LSB half is authoritative; a disagreeing MSB
+/// half (seen on some mastering tools) is only logged.
+fn parse_pair(field: &[u8]) -> u32 {
+    let lsb = u32::from_le_bytes([field[0], field[1], field[2], field[3]]);
+    let msb = u32::from_be_bytes([field[4], field[5], field[6], field[7]]);
+
+    if lsb != msb {
+        tracing::debug!(
+            "Both-byte-order field mismatch: LSB {:#010x}, MSB {:#010x}",
+            lsb,
+            msb
+        );
+    }
+
+    lsb
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::formats::xa::SubMode;
+
+    #[test]
+    fn test_parse_pair() {
+        let field = [0x01, 0x02, 0x03, 0x04, 0x04, 0x03, 0x02, 0x01];
+        assert_eq!(parse_pair(&field), 0x0403_0201);
+
+        // Disagreeing halves resolve to the LSB half on every host
+        let mismatched = [0x01, 0x02, 0x03, 0x04, 0x00, 0x00, 0x00, 0x00];
+        assert_eq!(parse_pair(&mismatched), 0x0403_0201);
+    }
 
     #[test]
     fn test_sector_size() {
         assert_eq!(SECTOR_SIZE, 2352);
         assert_eq!(DATA_SIZE, 2048);
     }
+
+    /// Build a directory record with the given identifier
+    fn dir_record(name: &[u8], lba: u32, size: u32, flags: u8) -> Vec<u8> {
+        let len = 33 + name.len() + (name.len() + 1) % 2;
+        let mut record = vec![0u8; len];
+        record[0] = len as u8;
+        record[2..6].copy_from_slice(&lba.to_le_bytes());
+        record[10..14].copy_from_slice(&size.to_le_bytes());
+        record[25] = flags;
+        record[32] = name.len() as u8;
+        record[33..33 + name.len()].copy_from_slice(name);
+        record
+    }
+
+    /// Build a type L path table record
+    fn path_record(name: &[u8], lba: u32, parent: u16) -> Vec<u8> {
+        let mut record = vec![name.len() as u8, 0];
+        record.extend_from_slice(&lba.to_le_bytes());
+        record.extend_from_slice(&parent.to_le_bytes());
+        record.extend_from_slice(name);
+        if name.len() % 2 == 1 {
+            record.push(0);
+        }
+        record
+    }
+
+    /// Encode a Joliet (UCS-2 big-endian) identifier
+    fn ucs2(name: &str) -> Vec<u8> {
+        name.encode_utf16().flat_map(u16::to_be_bytes).collect()
+    }
+
+    /// Craft a tiny raw (2352-byte sector) image:
+    /// `/` (LBA 20) -> `MOV` (LBA 21) -> `DEMO` (LBA 22) -> `A.STR` (LBA 23)
+    ///
+    /// With `joliet`, an SVD at sector 17 adds a second tree whose root
+    /// (LBA 24) holds `Mixed Case Name.txt` pointing at the same file data.
+    /// The file is named after `tag` so parallel tests don't share it.
+    fn crafted_image(tag: &str, joliet: bool, contents: &[u8]) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("psxutils_{}_{}.bin", tag, std::process::id()));
+        std::fs::write(&path, crafted_bytes(joliet, contents)).unwrap();
+        path
+    }
+
+    /// Raw bytes of the image written by [`crafted_image`]
+    fn crafted_bytes(joliet: bool, contents: &[u8]) -> Vec<u8> {
+        let file_sectors = contents.len().div_ceil(DATA_SIZE).max(1);
+        let mut sectors = vec![vec![0u8; DATA_SIZE]; 25.max(23 + file_sectors)];
+
+        let table = [
+            path_record(&[0], 20, 1),
+            path_record(b"MOV", 21, 1),
+            path_record(b"DEMO", 22, 2),
+        ]
+        .concat();
+
+        let pvd = &mut sectors[PVD_SECTOR as usize];
+        pvd[0] = VD_PRIMARY;
+        pvd[1..6].copy_from_slice(b"CD001");
+        pvd[8..40].copy_from_slice(format!("{:<32}", "PLAYSTATION").as_bytes());
+        pvd[40..72].copy_from_slice(format!("{:<32}", "LEGAIA").as_bytes());
+        pvd[132..136].copy_from_slice(&(table.len() as u32).to_le_bytes());
+        pvd[140..144].copy_from_slice(&18u32.to_le_bytes());
+        let root = dir_record(&[0], 20, DATA_SIZE as u32, FLAG_DIRECTORY);
+        pvd[156..156 + root.len()].copy_from_slice(&root);
+
+        sectors[18][..table.len()].copy_from_slice(&table);
+
+        let dirs: [(u32, Vec<u8>); 3] = [
+            (20, dir_record(b"MOV", 21, DATA_SIZE as u32, FLAG_DIRECTORY)),
+            (
+                21,
+                dir_record(b"DEMO", 22, DATA_SIZE as u32, FLAG_DIRECTORY),
+            ),
+            (22, dir_record(b"A.STR;1", 23, contents.len() as u32, 0)),
+        ];
+        for (lba, child) in dirs {
+            let records = [
+                dir_record(&[0], lba, DATA_SIZE as u32, FLAG_DIRECTORY),
+                dir_record(&[1], 20, DATA_SIZE as u32, FLAG_DIRECTORY),
+                child,
+            ]
+            .concat();
+            sectors[lba as usize][..records.len()].copy_from_slice(&records);
+        }
+        for (sector, chunk) in sectors[23..].iter_mut().zip(contents.chunks(DATA_SIZE)) {
+            sector[..chunk.len()].copy_from_slice(chunk);
+        }
+
+        if joliet {
+            let table = path_record(&[0], 24, 1);
+
+            let svd = &mut sectors[PVD_SECTOR as usize + 1];
+            svd[0] = VD_SUPPLEMENTARY;
+            svd[1..6].copy_from_slice(b"CD001");
+            svd[88..91].copy_from_slice(b"%/E");
+            svd[132..136].copy_from_slice(&(table.len() as u32).to_le_bytes());
+            svd[140..144].copy_from_slice(&19u32.to_le_bytes());
+            let root = dir_record(&[0], 24, DATA_SIZE as u32, FLAG_DIRECTORY);
+            svd[156..156 + root.len()].copy_from_slice(&root);
+
+            sectors[19][..table.len()].copy_from_slice(&table);
+
+            let records = [
+                dir_record(&[0], 24, DATA_SIZE as u32, FLAG_DIRECTORY),
+                dir_record(&[1], 24, DATA_SIZE as u32, FLAG_DIRECTORY),
+                dir_record(&ucs2("Mixed Case Name.txt;1"), 23, contents.len() as u32, 0),
+            ]
+            .concat();
+            sectors[24][..records.len()].copy_from_slice(&records);
+        }
+
+        sectors
+            .iter()
+            .flat_map(|data| {
+                let mut raw = vec![0u8; SECTOR_SIZE];
+                raw[24..24 + DATA_SIZE].copy_from_slice(data);
+                raw
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_path_table() {
+        let path = crafted_image("path_table", false, b"hello");
+        let disc = CdRom::open(&path).unwrap();
+        assert!(!disc.is_joliet());
+
+        let table = disc.read_path_table().unwrap();
+        assert_eq!(
+            table,
+            vec![
+                PathTableEntry {
+                    name: String::new(),
+                    lba: 20,
+                    parent: 0
+                },
+                PathTableEntry {
+                    name: "MOV".to_string(),
+                    lba: 21,
+                    parent: 0
+                },
+                PathTableEntry {
+                    name: "DEMO".to_string(),
+                    lba: 22,
+                    parent: 1
+                },
+            ]
+        );
+
+        let entries = disc.read_dir("/MOV/DEMO").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "A.STR");
+        assert!(entries[0].xa.is_none());
+        assert_eq!(disc.read_file("/mov/demo/A.STR").unwrap(), b"hello");
+        assert!(disc.read_file("/MOV").is_err());
+        assert!(disc.read_dir("/DEMO").is_err());
+
+        drop(disc);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_open_from_bytes() {
+        let disc = CdRom::open_from_bytes(crafted_bytes(true, b"hello")).unwrap();
+        assert!(disc.is_joliet());
+        assert_eq!(disc.sector_count(), 25);
+        assert_eq!(disc.tracks().len(), 1);
+        assert_eq!(disc.read_file("/Mixed Case Name.txt").unwrap(), b"hello");
+        assert_eq!(disc.volume_info().unwrap().volume_id, "LEGAIA");
+
+        assert!(CdRom::open_from_bytes(vec![0; SECTOR_SIZE * 4]).is_err());
+    }
+
+    #[test]
+    fn test_open_verified_joliet() {
+        // Re-encode every sector with a valid EDC, the way IsoBuilder writes them
+        let mut image: Vec<u8> = crafted_bytes(true, b"hello")
+            .chunks(SECTOR_SIZE)
+            .enumerate()
+            .flat_map(|(lba, raw)| {
+                ecc::encode_form1_sector(lba as u32, SubMode::DATA, &raw[24..24 + DATA_SIZE])
+            })
+            .collect();
+        let path = std::env::temp_dir().join(format!(
+            "psxutils_open_verified_joliet_{}.bin",
+            std::process::id()
+        ));
+        std::fs::write(&path, &image).unwrap();
+
+        let disc = CdRom::open_verified(&path).unwrap();
+        assert!(disc.is_joliet());
+        let names: Vec<String> = disc
+            .read_dir("/")
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect();
+        assert_eq!(names, ["Mixed Case Name.txt"]);
+        assert_eq!(disc.read_file("/Mixed Case Name.txt").unwrap(), b"hello");
+        drop(disc);
+
+        // A corrupted file sector now fails the read
+        image[23 * SECTOR_SIZE + 24] ^= 0xFF;
+        std::fs::write(&path, &image).unwrap();
+        let disc = CdRom::open_verified(&path).unwrap();
+        assert!(disc.read_file("/Mixed Case Name.txt").is_err());
+
+        drop(disc);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_stat() {
+        let path = crafted_image("stat", false, b"hello");
+        let disc = CdRom::open(&path).unwrap();
+
+        let file = disc.stat("/mov/demo/A.STR").unwrap();
+        assert_eq!((file.lba, file.size, file.is_dir), (23, 5, false));
+        assert!(disc.file_exists("/MOV/DEMO/A.STR"));
+
+        assert!(matches!(
+            disc.stat("/MOV/DEMO/B.STR"),
+            Err(PsxError::FileNotFound(_))
+        ));
+        assert!(disc.stat("/NONE/A.STR").is_err());
+        assert!(!disc.file_exists("/MOV/DEMO/B.STR"));
+
+        let dir = disc.stat("/MOV/DEMO/").unwrap();
+        assert_eq!((dir.lba, dir.is_dir), (22, true));
+        assert!(!disc.file_exists("/MOV/DEMO"));
+
+        let root = disc.stat("/").unwrap();
+        assert_eq!((root.name.as_str(), root.lba, root.is_dir), ("/", 20, true));
+
+        drop(disc);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_directory_cache() {
+        let path = crafted_image("dir_cache", false, b"hello");
+        let disc = CdRom::open(&path).unwrap();
+        let parses = || disc.directory_parses.load(Ordering::Relaxed);
+
+        assert_eq!(disc.read_file("/MOV/DEMO/A.STR").unwrap(), b"hello");
+        assert_eq!(parses(), 1);
+        assert_eq!(disc.read_file("/MOV/DEMO/A.STR").unwrap(), b"hello");
+        assert_eq!(parses(), 1);
+
+        disc.clear_cache();
+        assert!(disc.file_exists("/MOV/DEMO/A.STR"));
+        assert_eq!(parses(), 2);
+
+        drop(disc);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_glob() {
+        let path = crafted_image("glob", false, b"hello");
+        let disc = CdRom::open(&path).unwrap();
+
+        let paths = |pattern: &str| -> Vec<String> {
+            disc.glob(pattern)
+                .unwrap()
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect()
+        };
+
+        assert_eq!(paths("/**/*.str"), vec!["/MOV/DEMO/A.STR"]);
+        assert_eq!(paths("/m?v/*"), vec!["/MOV/DEMO"]);
+        assert_eq!(
+            paths("/MOV/**"),
+            vec!["/MOV", "/MOV/DEMO", "/MOV/DEMO/A.STR"]
+        );
+        assert!(paths("/*.STR").is_empty());
+
+        let (_, entry) = &disc.glob("**/A.*").unwrap()[0];
+        assert_eq!(entry.lba, 23);
+        assert_eq!(entry.size, 5);
+        drop(disc);
+
+        // A MOV/LOOP record pointing back at the root is listed, not entered
+        let mut image = std::fs::read(&path).unwrap();
+        let records = [
+            dir_record(&[0], 21, DATA_SIZE as u32, FLAG_DIRECTORY),
+            dir_record(&[1], 20, DATA_SIZE as u32, FLAG_DIRECTORY),
+            dir_record(b"DEMO", 22, DATA_SIZE as u32, FLAG_DIRECTORY),
+            dir_record(b"LOOP", 20, DATA_SIZE as u32, FLAG_DIRECTORY),
+        ]
+        .concat();
+        let start = 21 * SECTOR_SIZE + XA_DATA_OFFSET;
+        image[start..start + records.len()].copy_from_slice(&records);
+        std::fs::write(&path, image).unwrap();
+
+        let disc = CdRom::open(&path).unwrap();
+        let paths: Vec<String> = disc
+            .glob("/**")
+            .unwrap()
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(
+            paths,
+            vec!["/MOV", "/MOV/DEMO", "/MOV/DEMO/A.STR", "/MOV/LOOP"]
+        );
+
+        drop(disc);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_walk() {
+        let path = crafted_image("walk", false, b"hello");
+
+        // Add files around MOV, and a MOV/LOOP record pointing back at the root
+        let mut image = std::fs::read(&path).unwrap();
+        let mut write_dir = |lba: u32, records: &[Vec<u8>]| {
+            let records = records.concat();
+            let start = lba as usize * SECTOR_SIZE + XA_DATA_OFFSET;
+            image[start..start + DATA_SIZE].fill(0);
+            image[start..start + records.len()].copy_from_slice(&records);
+        };
+        let dot = |lba| dir_record(&[0], lba, DATA_SIZE as u32, FLAG_DIRECTORY);
+        let dotdot = dir_record(&[1], 20, DATA_SIZE as u32, FLAG_DIRECTORY);
+        write_dir(
+            20,
+            &[
+                dot(20),
+                dotdot.clone(),
+                dir_record(b"B.BIN;1", 23, 5, 0),
+                dir_record(b"MOV", 21, DATA_SIZE as u32, FLAG_DIRECTORY),
+                dir_record(b"Z.BIN;1", 23, 5, 0),
+            ],
+        );
+        write_dir(
+            21,
+            &[
+                dot(21),
+                dotdot,
+                dir_record(b"DEMO", 22, DATA_SIZE as u32, FLAG_DIRECTORY),
+                dir_record(b"LOOP", 20, DATA_SIZE as u32, FLAG_DIRECTORY),
+            ],
+        );
+        std::fs::write(&path, image).unwrap();
+
+        let disc = CdRom::open(&path).unwrap();
+        let files: Vec<(String, u32)> = disc
+            .walk()
+            .map(|file| file.map(|(path, entry)| (path, entry.lba)))
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(
+            files,
+            [
+                ("/B.BIN".to_string(), 23),
+                ("/MOV/DEMO/A.STR".to_string(), 23),
+                ("/Z.BIN".to_string(), 23),
+            ]
+        );
+
+        drop(disc);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_read_dir_recursive() {
+        let path = crafted_image("recursive", false, b"hello");
+
+        // Files at the root and in MOV, plus a MOV/SELF record pointing at MOV
+        let mut image = std::fs::read(&path).unwrap();
+        let mut write_dir = |lba: u32, records: &[Vec<u8>]| {
+            let records = records.concat();
+            let start = lba as usize * SECTOR_SIZE + XA_DATA_OFFSET;
+            image[start..start + DATA_SIZE].fill(0);
+            image[start..start + records.len()].copy_from_slice(&records);
+        };
+        let dot = |lba| dir_record(&[0], lba, DATA_SIZE as u32, FLAG_DIRECTORY);
+        let dotdot = dir_record(&[1], 20, DATA_SIZE as u32, FLAG_DIRECTORY);
+        write_dir(
+            20,
+            &[
+                dot(20),
+                dotdot.clone(),
+                dir_record(b"Z.BIN;1", 23, 5, 0),
+                dir_record(b"MOV", 21, DATA_SIZE as u32, FLAG_DIRECTORY),
+            ],
+        );
+        write_dir(
+            21,
+            &[
+                dot(21),
+                dotdot,
+                dir_record(b"SELF", 21, DATA_SIZE as u32, FLAG_DIRECTORY),
+                dir_record(b"DEMO", 22, DATA_SIZE as u32, FLAG_DIRECTORY),
+                dir_record(b"C.BIN;1", 23, 5, 0),
+            ],
+        );
+        std::fs::write(&path, image).unwrap();
+
+        let disc = CdRom::open(&path).unwrap();
+        let paths = |start: &str| -> Vec<String> {
+            disc.read_dir_recursive(start)
+                .unwrap()
+                .into_iter()
+                .map(|(path, entry)| {
+                    assert!(!entry.is_dir);
+                    path
+                })
+                .collect()
+        };
+
+        assert_eq!(paths("/"), ["/MOV/C.BIN", "/MOV/DEMO/A.STR", "/Z.BIN"]);
+        assert_eq!(paths("/MOV/"), ["/MOV/C.BIN", "/MOV/DEMO/A.STR"]);
+        assert_eq!(paths("MOV/DEMO"), ["/MOV/DEMO/A.STR"]);
+        assert!(disc.read_dir_recursive("/Z.BIN").is_err());
+        assert!(disc.read_dir_recursive("/NOPE").is_err());
+
+        drop(disc);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_malformed_directory_records() {
+        let path = crafted_image("malformed", false, b"hello");
+        let clean = std::fs::read(&path).unwrap();
+        let demo = 22 * SECTOR_SIZE + XA_DATA_OFFSET;
+
+        let open_with_demo = |sector: &[u8]| {
+            let mut image = clean.clone();
+            image[demo..demo + DATA_SIZE].copy_from_slice(sector);
+            std::fs::write(&path, image).unwrap();
+            CdRom::open(&path).unwrap()
+        };
+
+        // Oversized identifier length, a record shorter than the fixed
+        // fields, and a record running past the end of the directory
+        let mut oversized = dir_record(b"B.STR;1", 23, 5, 0);
+        oversized[32] = 200;
+        let mut sector = [
+            dir_record(&[0], 22, DATA_SIZE as u32, FLAG_DIRECTORY),
+            dir_record(&[1], 20, DATA_SIZE as u32, FLAG_DIRECTORY),
+            dir_record(b"A.STR;1", 23, 5, 0),
+            oversized,
+            vec![20; 20],
+        ]
+        .concat();
+        // Unnamed filler records (skipped like '.') up to the last 8 bytes
+        while sector.len() < DATA_SIZE - 8 {
+            let len = (DATA_SIZE - 8 - sector.len()).min(234);
+            let mut filler = vec![0; len];
+            filler[0] = len as u8;
+            sector.extend_from_slice(&filler);
+        }
+        sector.extend_from_slice(&[255, 0, 0, 0, 0, 0, 0, 0]);
+
+        let disc = open_with_demo(&sector);
+        let entries = disc.read_dir("/MOV/DEMO").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "A.STR");
+        drop(disc);
+
+        // Garbage directories must never panic, whatever they decode to
+        let mut state = 0x1234_5678u32;
+        for round in 0..64 {
+            let sector: Vec<u8> = (0..DATA_SIZE)
+                .map(|_| {
+                    state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                    (state >> 16) as u8
+                })
+                .collect();
+            let disc = open_with_demo(&sector);
+            let _ = disc.read_dir("/MOV/DEMO");
+            let _ = disc.stat(&format!("/MOV/DEMO/{}", round));
+        }
+
+        // Images cut off before the volume descriptor fail to open
+        std::fs::write(&path, &clean[..PVD_SECTOR as usize * SECTOR_SIZE + 100]).unwrap();
+        assert!(CdRom::open(&path).is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_xa_directory_metadata() {
+        let mut record = dir_record(b"A.STR;1", 23, 4096, 0);
+        record[18..25].copy_from_slice(&[98, 12, 24, 10, 30, 5, 36]);
+        // Group 0, user 0, Form 2 + interleaved + owner/group/world read, file 1
+        record.extend_from_slice(&[0, 0, 0, 0, 0x31, 0x11, b'X', b'A', 1, 0, 0, 0, 0, 0]);
+
+        assert_eq!(
+            RecordingTime::parse(&record[18..25]),
+            RecordingTime {
+                year: 1998,
+                month: 12,
+                day: 24,
+                hour: 10,
+                minute: 30,
+                second: 5,
+                gmt_offset: 36,
+            }
+        );
+
+        // Odd-length identifier, so the system use area follows directly
+        let xa = XaEntry::parse(&record[40..]).unwrap();
+        assert!(xa.is_interleaved());
+        assert!(xa.attributes.contains(XaAttributes::FORM2));
+        assert!(xa.attributes.contains(XaAttributes::WORLD_READ));
+        assert_eq!(xa.file_number, 1);
+
+        assert!(XaEntry::parse(&record[40..50]).is_none());
+    }
+
+    #[test]
+    fn test_volume_info() {
+        let path = crafted_image("volume_info", false, b"hello");
+        let disc = CdRom::open(&path).unwrap();
+
+        let info = disc.volume_info().unwrap();
+        assert_eq!(info.system_id, "PLAYSTATION");
+        assert_eq!(info.volume_id, "LEGAIA");
+        assert_eq!(info.publisher_id, "");
+
+        drop(disc);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_joliet_names() {
+        let path = crafted_image("joliet", true, b"hello");
+        let disc = CdRom::open(&path).unwrap();
+        assert!(disc.is_joliet());
+
+        let entries = disc.read_dir("/").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "Mixed Case Name.txt");
+        assert_eq!(disc.read_file("/Mixed Case Name.txt").unwrap(), b"hello");
+
+        drop(disc);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_open_cue() {
+        let data_path = crafted_image("cue", false, b"hello");
+        let audio_path = data_path.with_extension("audio.bin");
+        let cue_path = data_path.with_extension("cue");
+
+        // Two seconds of audio, split into two tracks at 00:01:00
+        let audio: Vec<u8> = (0..150 * SECTOR_SIZE).map(|i| i as u8).collect();
+        std::fs::write(&audio_path, &audio).unwrap();
+        std::fs::write(
+            &cue_path,
+            format!(
+                "FILE \"{}\" BINARY\n  TRACK 01 MODE2/2352\n    INDEX 01 00:00:00\n\
+                 FILE \"{}\" BINARY\n  TRACK 02 AUDIO\n    INDEX 01 00:00:00\n\
+                   TRACK 03 AUDIO\n    INDEX 00 00:00:74\n    INDEX 01 00:01:00\n",
+                data_path.file_name().unwrap().to_string_lossy(),
+                audio_path.file_name().unwrap().to_string_lossy(),
+            ),
+        )
+        .unwrap();
+
+        let disc = CdRom::open_cue(&cue_path).unwrap();
+        let tracks = disc.tracks();
+        assert_eq!(tracks.len(), 3);
+        assert_eq!(tracks[0].track_type, TrackType::Mode2);
+        assert_eq!((tracks[0].start_lba, tracks[0].length), (0, 25));
+        assert_eq!(tracks[1].track_type, TrackType::Audio);
+        // Track 2 ends where track 3's one-sector pregap begins
+        assert_eq!((tracks[1].start_lba, tracks[1].length), (25, 74));
+        assert_eq!((tracks[2].start_lba, tracks[2].length), (100, 75));
+        assert_eq!(tracks[2].pregap, 1);
+
+        assert_eq!(disc.read_file("/MOV/DEMO/A.STR").unwrap(), b"hello");
+
+        let pcm = disc.read_audio_track(3).unwrap();
+        assert_eq!(pcm.len(), 75 * SECTOR_SIZE / 2);
+        let offset = 75 * SECTOR_SIZE;
+        assert_eq!(
+            pcm[0],
+            i16::from_le_bytes([audio[offset], audio[offset + 1]])
+        );
+        assert!(disc.read_audio_track(1).is_err());
+
+        let partial = disc.read_audio_range(3, 10, Some(1)).unwrap();
+        assert_eq!(partial.len(), SECTOR_SIZE / 2);
+        assert_eq!(partial[..], pcm[10 * SECTOR_SIZE / 2..11 * SECTOR_SIZE / 2]);
+        assert!(disc.read_audio_range(3, 70, Some(10)).is_err());
+
+        drop(disc);
+        for path in [data_path, audio_path, cue_path] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_stream_scan_straddling_tim() {
+        // 16-bit 8x4 TIM starting 40 bytes before the first sector boundary
+        let mut tim = Vec::new();
+        tim.extend_from_slice(&0x10u32.to_le_bytes());
+        tim.extend_from_slice(&2u32.to_le_bytes());
+        tim.extend_from_slice(&76u32.to_le_bytes());
+        for value in [0u16, 0, 8, 4] {
+            tim.extend_from_slice(&value.to_le_bytes());
+        }
+        tim.resize(84, 0x11);
+
+        let mut contents = vec![0u8; 3 * DATA_SIZE];
+        contents[DATA_SIZE - 40..DATA_SIZE - 40 + tim.len()].copy_from_slice(&tim);
+
+        let path = crafted_image("stream", false, &contents);
+        let disc = CdRom::open(&path).unwrap();
+
+        let sectors: Vec<&[u8]> = disc
+            .read_file_stream("/MOV/DEMO/A.STR")
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(sectors.len(), 3);
+        assert_eq!(sectors.concat(), contents);
+
+        let reader = disc.read_file_stream("/MOV/DEMO/A.STR").unwrap();
+        let assets = crate::AssetScanner::scan_streaming(reader, 64).unwrap();
+        assert_eq!(assets.len(), 1);
+        assert_eq!(assets[0].offset, DATA_SIZE - 40);
+        assert_eq!(assets[0].size, 84);
+
+        drop(disc);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_read_file_into() {
+        // Partial last sector, so the final chunk must be trimmed
+        let contents: Vec<u8> = (0..2 * DATA_SIZE + 100).map(|i| (i % 251) as u8).collect();
+
+        let path = crafted_image("read_into", false, &contents);
+        let disc = CdRom::open(&path).unwrap();
+
+        let mut streamed = Vec::new();
+        let written = disc
+            .read_file_into("/MOV/DEMO/A.STR", &mut streamed)
+            .unwrap();
+        assert_eq!(written, contents.len() as u64);
+        assert_eq!(streamed, disc.read_file("/MOV/DEMO/A.STR").unwrap());
+        assert_eq!(streamed, contents);
+
+        drop(disc);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_read_lba_range() {
+        let contents: Vec<u8> = (0..3 * DATA_SIZE).map(|i| (i % 247) as u8).collect();
+
+        let path = crafted_image("lba_range", false, &contents);
+        let disc = CdRom::open(&path).unwrap();
+
+        // File data starts at LBA 23; read across a sector boundary
+        let range = disc.read_lba_range(24, DATA_SIZE + 10).unwrap();
+        assert_eq!(range, contents[DATA_SIZE..2 * DATA_SIZE + 10]);
+        assert!(disc.read_lba_range(23, 0).unwrap().is_empty());
+
+        let last = disc.sector_count() as u32 - 1;
+        assert_eq!(
+            disc.read_lba_range(last, DATA_SIZE).unwrap().len(),
+            DATA_SIZE
+        );
+        assert!(disc.read_lba_range(last, DATA_SIZE + 1).is_err());
+        assert!(disc.read_lba_range(last + 1, 1).is_err());
+
+        drop(disc);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(feature = "extraction")]
+    #[test]
+    fn test_extract_cdda_one_sector() {
+        let data_path = crafted_image("cdda", false, b"hello");
+        let audio_path = data_path.with_extension("audio.bin");
+        let cue_path = data_path.with_extension("cue");
+        let wav_path = data_path.with_extension("wav");
+
+        std::fs::write(&audio_path, vec![0x7Fu8; SECTOR_SIZE]).unwrap();
+        std::fs::write(
+            &cue_path,
+            format!(
+                "FILE \"{}\" BINARY\n  TRACK 01 MODE2/2352\n    INDEX 01 00:00:00\n\
+                 FILE \"{}\" BINARY\n  TRACK 02 AUDIO\n    INDEX 01 00:00:00\n",
+                data_path.file_name().unwrap().to_string_lossy(),
+                audio_path.file_name().unwrap().to_string_lossy(),
+            ),
+        )
+        .unwrap();
+
+        let disc = CdRom::open_cue(&cue_path).unwrap();
+        disc.extract_cdda(2, &wav_path).unwrap();
+
+        let reader = hound::WavReader::open(&wav_path).unwrap();
+        assert_eq!(reader.spec().channels, 2);
+        assert_eq!(reader.spec().sample_rate, CDDA_SAMPLE_RATE);
+        assert_eq!(reader.duration(), 588);
+
+        drop(disc);
+        for path in [data_path, audio_path, cue_path, wav_path] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
 }
@@ -0,0 +1,219 @@
+//! Legaia custom model container parser
+//!
+//! Character and field models in PROT.DAT are not bare TMD files but a
+//! Legaia-specific container. Only the header is understood so far; the
+//! body is searched for embedded TMD models and TIM textures.
+//!
+//! ## Format Structure
+//!
+//! ```text
+//! Header (8 bytes):
+//!   u32 size        // Total container size in bytes, including the header
+//!   u32 signature   // 0x80000002
+//!
+//! Body (size - 8 bytes):
+//!   Unknown tables, with TMD/TIM blocks embedded at 4-byte aligned offsets
+//! ```
+
+use super::tim::TIM_MAGIC;
+use super::tmd::TMD_MAGIC;
+use super::{Tim, Tmd};
+use crate::{PsxError, Result};
+
+/// Signature at offset +4 of every custom model
+pub const LEGAIA_MODEL_SIGNATURE: u32 = 0x80000002;
+
+/// Size of the container header
+pub const LEGAIA_MODEL_HEADER_SIZE: usize = 8;
+
+/// Smallest plausible container size
+const MIN_MODEL_SIZE: usize = 100;
+
+/// Largest plausible container size
+const MAX_MODEL_SIZE: usize = 1024 * 1024;
+
+/// TMD or TIM block found inside a model container
+#[derive(Debug, Clone)]
+pub struct EmbeddedBlock {
+    /// Offset of the block from the start of the container
+    pub offset: usize,
+    /// Raw block bytes
+    pub data: Vec<u8>,
+}
+
+/// Legaia custom model container
+#[derive(Debug, Clone)]
+pub struct LegaiaModel {
+    /// Total container size from the header
+    pub size: u32,
+    /// Embedded TMD models
+    sub_models: Vec<EmbeddedBlock>,
+    /// Embedded TIM textures
+    textures: Vec<EmbeddedBlock>,
+}
+
+impl LegaiaModel {
+    /// Parse a custom model container
+    ///
+    /// Validates the header and splits out embedded TMD and TIM blocks. Each
+    /// block is checked with the same validation the asset scanner uses, and
+    /// the search resumes after it, so blocks never overlap.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let size = Self::validate(data)?;
+        let body = &data[..size];
+
+        let mut sub_models = Vec::new();
+        let mut textures = Vec::new();
+        let mut offset = LEGAIA_MODEL_HEADER_SIZE;
+
+        while offset + 12 <= size {
+            let magic = u32::from_le_bytes([
+                body[offset],
+                body[offset + 1],
+                body[offset + 2],
+                body[offset + 3],
+            ]);
+
+            let block_size = match magic {
+                TMD_MAGIC => Tmd::validate(&body[offset..]).ok().map(|(_, size)| size),
+                TIM_MAGIC => Tim::validate(&body[offset..]).ok().map(|(_, _, size)| size),
+                _ => None,
+            };
+
+            match block_size {
+                Some(block_size) => {
+                    let block = EmbeddedBlock {
+                        offset,
+                        data: body[offset..offset + block_size].to_vec(),
+                    };
+                    if magic == TMD_MAGIC {
+                        sub_models.push(block);
+                    } else {
+                        textures.push(block);
+                    }
+                    // Blocks are word aligned
+                    offset += block_size.next_multiple_of(4);
+                }
+                None => offset += 4,
+            }
+        }
+
+        Ok(Self {
+            size: size as u32,
+            sub_models,
+            textures,
+        })
+    }
+
+    /// Validate the container header without searching the body
+    ///
+    /// Returns the total container size if the signature matches and the
+    /// size word is plausible and within `data`.
+    pub fn validate(data: &[u8]) -> Result<usize> {
+        if data.len() < LEGAIA_MODEL_HEADER_SIZE {
+            return Err(PsxError::InvalidFormat(
+                "Legaia model too small for header".to_string(),
+            ));
+        }
+
+        let signature = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+        if signature != LEGAIA_MODEL_SIGNATURE {
+            return Err(PsxError::InvalidFormat(format!(
+                "Invalid Legaia model signature: 0x{:08X}, expected 0x{:08X}",
+                signature, LEGAIA_MODEL_SIGNATURE
+            )));
+        }
+
+        let size = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+        if !(MIN_MODEL_SIZE..=MAX_MODEL_SIZE).contains(&size) {
+            return Err(PsxError::InvalidFormat(format!(
+                "Implausible Legaia model size: {}",
+                size
+            )));
+        }
+
+        if size > data.len() {
+            return Err(PsxError::InvalidFormat(format!(
+                "Legaia model size {} exceeds available data ({} bytes)",
+                size,
+                data.len()
+            )));
+        }
+
+        Ok(size)
+    }
+
+    /// Embedded TMD models, in container order
+    pub fn sub_models(&self) -> &[EmbeddedBlock] {
+        &self.sub_models
+    }
+
+    /// Embedded TIM textures, in container order
+    pub fn textures(&self) -> &[EmbeddedBlock] {
+        &self.textures
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(size: u32) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&size.to_le_bytes());
+        data.extend_from_slice(&LEGAIA_MODEL_SIGNATURE.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_validate_header() {
+        let mut data = header(128);
+        data.resize(128, 0);
+        assert_eq!(LegaiaModel::validate(&data).unwrap(), 128);
+
+        // Size past the end of the buffer
+        assert!(LegaiaModel::validate(&data[..64]).is_err());
+
+        data[4] = 0;
+        assert!(LegaiaModel::validate(&data).is_err());
+    }
+
+    #[test]
+    fn test_parse_embedded_blocks() {
+        let mut data = header(256);
+        data.resize(16, 0);
+
+        // Single-object TMD: 4 vertices, 2 normals, 1 primitive (8-word packet)
+        let mut tmd = Vec::new();
+        tmd.extend_from_slice(&TMD_MAGIC.to_le_bytes());
+        tmd.extend_from_slice(&0u32.to_le_bytes());
+        tmd.extend_from_slice(&1u32.to_le_bytes());
+        for value in [40u32, 4, 72, 2, 88, 1, 1] {
+            tmd.extend_from_slice(&value.to_le_bytes());
+        }
+        tmd.resize(120, 0x22);
+        tmd[88] = 8;
+        data.extend_from_slice(&tmd);
+
+        // 16-bit direct TIM, 8x4 pixels
+        let mut tim = Vec::new();
+        tim.extend_from_slice(&TIM_MAGIC.to_le_bytes());
+        tim.extend_from_slice(&2u32.to_le_bytes());
+        tim.extend_from_slice(&76u32.to_le_bytes());
+        for value in [0u16, 0, 8, 4] {
+            tim.extend_from_slice(&value.to_le_bytes());
+        }
+        tim.resize(84, 0x11);
+        data.extend_from_slice(&tim);
+        data.resize(256, 0);
+
+        let model = LegaiaModel::parse(&data).unwrap();
+        assert_eq!(model.size, 256);
+        assert_eq!(model.sub_models().len(), 1);
+        assert_eq!(model.sub_models()[0].offset, 16);
+        assert_eq!(model.sub_models()[0].data.len(), 120);
+        assert_eq!(model.textures().len(), 1);
+        assert_eq!(model.textures()[0].offset, 136);
+        assert_eq!(model.textures()[0].data.len(), 84);
+    }
+}
@@ -1,4 +1,4 @@
-//! LZSS (Lempel-Ziv-Storer-Szymanski) decompression
+//! LZSS (Lempel-Ziv-Storer-Szymanski) compression and decompression
 //!
 //! LZSS is a dictionary-based compression algorithm commonly used in PlayStation 1 games.
 //! It uses a sliding window to reference previously decompressed data.
@@ -16,10 +16,52 @@
 //! - Look-ahead buffer: 18 bytes (4-bit length + 2)
 //! - Minimum match length: 3 bytes
 //! - Control byte: 8 flags (1 bit per token, processed LSB to MSB)
+//!
+//! # Reference Layouts
+//!
+//! Games disagree on how a reference's two bytes are split between offset
+//! and length. [`LzssConfig`] describes the layout explicitly (byte order,
+//! and the position and width of each field), with presets for the common
+//! ones:
+//!
+//! - [`LzssConfig::standard`]: big-endian, offset in the high 12 bits
+//! - [`LzssConfig::high_nibble_length`]: little-endian, length in the high
+//!   4 bits
+//! - [`LzssConfig::long_match`]: big-endian 10-bit offset, 6-bit length
 
+use crate::{PsxError, Result};
 use std::io::{self, Read, Write};
 
+/// Order in which the two bytes of a reference form a 16-bit word
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LzssByteOrder {
+    /// First byte is the high byte
+    BigEndian,
+    /// First byte is the low byte
+    LittleEndian,
+}
+
 /// LZSS decompression configuration
+///
+/// A reference is two bytes read as a 16-bit word in [`byte_order`], with
+/// the offset in `offset_bits` bits starting at bit `offset_shift` and the
+/// length (minus `min_match_len`) in `length_bits` bits starting at bit
+/// `length_shift`. Custom layouts can be built from a preset:
+///
+/// ```
+/// use psxutils::formats::LzssConfig;
+///
+/// let config = LzssConfig {
+///     window_size: 2048,
+///     offset_bits: 11,
+///     offset_shift: 5,
+///     length_bits: 5,
+///     max_match_len: 34,
+///     ..LzssConfig::standard()
+/// };
+/// ```
+///
+/// [`byte_order`]: LzssConfig::byte_order
 #[derive(Debug, Clone, Copy)]
 pub struct LzssConfig {
     /// Size of the sliding window (typically 4096 for standard LZSS)
@@ -32,6 +74,12 @@ pub struct LzssConfig {
     pub offset_bits: u8,
     /// Number of bits for length encoding (typically 4)
     pub length_bits: u8,
+    /// Lowest bit of the offset field in the reference word (typically 4)
+    pub offset_shift: u8,
+    /// Lowest bit of the length field in the reference word (typically 0)
+    pub length_shift: u8,
+    /// How the reference bytes form a word (typically big-endian)
+    pub byte_order: LzssByteOrder,
 }
 
 impl Default for LzssConfig {
@@ -42,6 +90,9 @@ impl Default for LzssConfig {
 
 impl LzssConfig {
     /// Standard LZSS configuration (12-bit offset, 4-bit length)
+    ///
+    /// References are `OO OL` big-endian: the offset is the first byte and
+    /// the high nibble of the second, the length the low nibble.
     pub const fn standard() -> Self {
         Self {
             window_size: 4096, // 2^12
@@ -49,13 +100,77 @@ impl LzssConfig {
             min_match_len: 3,
             offset_bits: 12,
             length_bits: 4,
+            offset_shift: 4,
+            length_shift: 0,
+            byte_order: LzssByteOrder::BigEndian,
         }
     }
 
-    /// PlayStation 1 variant (may differ per game)
+    /// 12-bit offset and 4-bit length with the length in the high nibble
+    ///
+    /// References are a little-endian word `LOOO`: the first byte is the low
+    /// byte of the offset, the second holds the offset's top nibble in its
+    /// low half and the length in its high half.
+    pub const fn high_nibble_length() -> Self {
+        Self {
+            offset_shift: 0,
+            length_shift: 12,
+            byte_order: LzssByteOrder::LittleEndian,
+            ..Self::standard()
+        }
+    }
+
+    /// 10-bit offset and 6-bit length, trading window size for longer matches
+    ///
+    /// References are big-endian with the offset in the high 10 bits.
+    pub const fn long_match() -> Self {
+        Self {
+            window_size: 1024, // 2^10
+            max_match_len: 66, // 2^6 + 2
+            min_match_len: 3,
+            offset_bits: 10,
+            length_bits: 6,
+            offset_shift: 6,
+            length_shift: 0,
+            byte_order: LzssByteOrder::BigEndian,
+        }
+    }
+
+    /// PlayStation 1 variant used by Legend of Legaia's "sszl" blocks
+    ///
+    /// Other games may need one of the other presets or a custom layout.
     pub const fn ps1_variant() -> Self {
         Self::standard()
     }
+
+    /// Split a reference into its window offset and length code
+    fn decode_reference(&self, bytes: [u8; 2]) -> (usize, usize) {
+        let word = match self.byte_order {
+            LzssByteOrder::BigEndian => u16::from_be_bytes(bytes),
+            LzssByteOrder::LittleEndian => u16::from_le_bytes(bytes),
+        } as u32;
+
+        let offset = (word >> self.offset_shift) & field_mask(self.offset_bits);
+        let length = (word >> self.length_shift) & field_mask(self.length_bits);
+        (offset as usize, length as usize)
+    }
+
+    /// Pack a window offset and length code into reference bytes
+    fn encode_reference(&self, offset: usize, length: usize) -> [u8; 2] {
+        let offset = offset as u32 & field_mask(self.offset_bits);
+        let length = length as u32 & field_mask(self.length_bits);
+        let word = ((offset << self.offset_shift) | (length << self.length_shift)) as u16;
+
+        match self.byte_order {
+            LzssByteOrder::BigEndian => word.to_be_bytes(),
+            LzssByteOrder::LittleEndian => word.to_le_bytes(),
+        }
+    }
+}
+
+/// Mask covering the low `bits` bits
+fn field_mask(bits: u8) -> u32 {
+    (1u32 << bits) - 1
 }
 
 /// LZSS decompressor
@@ -113,9 +228,14 @@ impl LzssDecoder {
 
             // Check LSB of flags
             if flags & 1 != 0 {
-                // Literal byte - copy directly
+                // Literal byte - copy directly. The final control byte may
+                // describe more tokens than remain, so EOF here ends the stream.
                 let mut byte = [0u8; 1];
-                input.read_exact(&mut byte)?;
+                match input.read_exact(&mut byte) {
+                    Ok(_) => {}
+                    Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                    Err(e) => return Err(e),
+                }
 
                 output.write_all(&byte)?;
                 self.write_to_window(byte[0]);
@@ -123,15 +243,18 @@ impl LzssDecoder {
             } else {
                 // Reference - read offset and length
                 let mut ref_bytes = [0u8; 2];
-                input.read_exact(&mut ref_bytes)?;
+                match input.read_exact(&mut ref_bytes) {
+                    Ok(_) => {}
+                    Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                    Err(e) => return Err(e),
+                }
 
-                // Standard LZSS encoding: 12-bit offset, 4-bit length
-                let offset = ((ref_bytes[0] as usize) << 4) | ((ref_bytes[1] as usize) >> 4);
-                let length = ((ref_bytes[1] & 0x0F) as usize) + self.config.min_match_len;
+                let (offset, length_code) = self.config.decode_reference(ref_bytes);
+                let length = length_code + self.config.min_match_len;
 
-                // Copy from window
-                for _ in 0..length {
-                    let byte = self.window[offset];
+                // Copy from window (source may overlap bytes written by this copy)
+                for i in 0..length {
+                    let byte = self.window[(offset + i) % self.config.window_size];
                     output.write_all(&[byte])?;
                     self.write_to_window(byte);
                     total_written += 1;
@@ -175,6 +298,129 @@ impl LzssDecoder {
     }
 }
 
+/// LZSS compressor
+///
+/// Produces a stream that [`LzssDecoder`] with the same configuration decodes
+/// back to the original input: LSB-first control bytes (set bit = literal),
+/// and references holding an absolute window offset and a length, packed
+/// the way the configuration describes. The window starts zero-filled at
+/// position 0, mirroring the decoder, so runs of zeros at the start of the
+/// input can be encoded as references too.
+pub struct LzssEncoder {
+    config: LzssConfig,
+}
+
+impl LzssEncoder {
+    /// Create a new LZSS encoder with the given configuration
+    pub fn new(config: LzssConfig) -> Self {
+        Self { config }
+    }
+
+    /// Create an encoder with standard LZSS parameters
+    pub fn standard() -> Self {
+        Self::new(LzssConfig::standard())
+    }
+
+    /// Compress a buffer using a greedy longest-match search
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - Uncompressed data
+    ///
+    /// # Returns
+    ///
+    /// Compressed data (without the 'sszl' magic number)
+    pub fn compress(&self, input: &[u8]) -> Vec<u8> {
+        let window_size = self.config.window_size;
+        let max_len = self
+            .config
+            .max_match_len
+            .min(self.config.min_match_len + (1 << self.config.length_bits) - 1);
+
+        let mut window = vec![0u8; window_size];
+        let mut window_pos = 0;
+        let mut output = Vec::with_capacity(input.len() + input.len() / 8 + 1);
+        let mut flag_index = 0;
+        let mut flag_count = 8;
+        let mut pos = 0;
+
+        while pos < input.len() {
+            if flag_count == 8 {
+                flag_index = output.len();
+                output.push(0);
+                flag_count = 0;
+            }
+
+            let remaining = &input[pos..];
+            let (best_offset, best_len) = self.find_match(&window, window_pos, remaining, max_len);
+
+            let token_len = if best_len >= self.config.min_match_len {
+                let length_code = best_len - self.config.min_match_len;
+                output.extend(self.config.encode_reference(best_offset, length_code));
+                best_len
+            } else {
+                output[flag_index] |= 1 << flag_count;
+                output.push(remaining[0]);
+                1
+            };
+
+            for &byte in &remaining[..token_len] {
+                window[window_pos] = byte;
+                window_pos = (window_pos + 1) % window_size;
+            }
+
+            pos += token_len;
+            flag_count += 1;
+        }
+
+        output
+    }
+
+    /// Find the longest match for the start of `data` anywhere in the window
+    ///
+    /// Returns `(offset, length)`; the length is 0 if nothing matches.
+    fn find_match(
+        &self,
+        window: &[u8],
+        window_pos: usize,
+        data: &[u8],
+        max_len: usize,
+    ) -> (usize, usize) {
+        let window_size = self.config.window_size;
+        let limit = max_len.min(data.len());
+        let mut best = (0, 0);
+
+        for offset in 0..window_size {
+            let mut len = 0;
+            while len < limit {
+                // Bytes written earlier in this same copy overwrite the window,
+                // so read them from the input instead
+                let src = (offset + len) % window_size;
+                let ahead = (src + window_size - window_pos) % window_size;
+                let byte = if ahead < len {
+                    data[ahead]
+                } else {
+                    window[src]
+                };
+
+                if byte != data[len] {
+                    break;
+                }
+                len += 1;
+            }
+
+            if len > best.1 {
+                best = (offset, len);
+                if len == limit {
+                    break;
+                }
+            }
+        }
+
+        best
+    }
+}
+
 /// Decompress LZSS data with standard configuration
 ///
 /// Legend of Legaia LZSS magic number: "sszl" (little-endian)
@@ -219,6 +465,101 @@ pub fn decompress(compressed: &[u8]) -> io::Result<Vec<u8>> {
     LzssDecoder::standard().decompress_buf(data)
 }
 
+/// Size of the "sszl" container header in bytes (provisional, see [`SszlHeader`])
+pub const SSZL_HEADER_SIZE: usize = 12;
+
+/// Header of an "sszl" LZSS container
+///
+/// Layout (little-endian):
+/// - 0x00: Magic "sszl"
+/// - 0x04: Decompressed size (u32)
+/// - 0x08: Compressed block length, excluding this header (u32)
+///
+/// **Provisional:** this layout has not been checked against a disc sample
+/// or the game's loader. [`decompress`] still treats everything after the
+/// magic as compressed data, and the asset scanners do not rely on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SszlHeader {
+    /// Size of the data after decompression
+    pub decompressed_size: u32,
+    /// Length of the compressed block following the header
+    pub compressed_size: u32,
+}
+
+impl SszlHeader {
+    /// Total size of the container on disc (header + compressed block)
+    pub fn total_size(&self) -> usize {
+        SSZL_HEADER_SIZE + self.compressed_size as usize
+    }
+}
+
+/// Parse the "sszl" container header at the start of `data`
+///
+/// Uses the provisional [`SszlHeader`] layout. Fails if the magic is missing
+/// or the compressed block would extend past the end of `data`.
+pub fn parse_sszl_header(data: &[u8]) -> Result<SszlHeader> {
+    if data.len() < SSZL_HEADER_SIZE {
+        return Err(PsxError::InvalidFormat(format!(
+            "sszl header too short: {} bytes",
+            data.len()
+        )));
+    }
+
+    if &data[0..4] != LZSS_MAGIC {
+        return Err(PsxError::InvalidFormat("Missing sszl magic".to_string()));
+    }
+
+    let header = SszlHeader {
+        decompressed_size: u32::from_le_bytes([data[4], data[5], data[6], data[7]]),
+        compressed_size: u32::from_le_bytes([data[8], data[9], data[10], data[11]]),
+    };
+
+    if header.total_size() > data.len() {
+        return Err(PsxError::InvalidFormat(format!(
+            "sszl block length {} exceeds available data ({} bytes)",
+            header.compressed_size,
+            data.len() - SSZL_HEADER_SIZE
+        )));
+    }
+
+    Ok(header)
+}
+
+/// Decompress an "sszl" container using the sizes from its header
+///
+/// Relies on the provisional [`SszlHeader`] layout; prefer [`decompress`]
+/// for data whose header has not been confirmed. Only the compressed block
+/// is fed to the decoder, and the output is exactly `decompressed_size`
+/// bytes long.
+///
+/// # Example
+///
+/// ```no_run
+/// use psxutils::formats::lzss;
+///
+/// let data = std::fs::read("player.lzs")?;
+/// let decompressed = lzss::decompress_sszl(&data)?;
+/// # Ok::<(), psxutils::PsxError>(())
+/// ```
+pub fn decompress_sszl(data: &[u8]) -> Result<Vec<u8>> {
+    let header = parse_sszl_header(data)?;
+    let block = &data[SSZL_HEADER_SIZE..header.total_size()];
+
+    let mut output = LzssDecoder::standard().decompress_buf(block)?;
+    let expected = header.decompressed_size as usize;
+    if output.len() < expected {
+        return Err(PsxError::ParseError(format!(
+            "sszl block decompressed to {} bytes, expected {}",
+            output.len(),
+            expected
+        )));
+    }
+
+    // The last control byte can describe tokens past the real end of the data
+    output.truncate(expected);
+    Ok(output)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -305,5 +646,163 @@ mod tests {
         assert_eq!(config.min_match_len, 3);
         assert_eq!(config.offset_bits, 12);
         assert_eq!(config.length_bits, 4);
+        assert_eq!(config.offset_shift, 4);
+        assert_eq!(config.byte_order, LzssByteOrder::BigEndian);
+    }
+
+    #[test]
+    fn test_preset_reference_streams() {
+        // "xyABC" as literals, then a reference to offset 2
+        let stream =
+            |reference: [u8; 2]| [&[0x1F, b'x', b'y', b'A', b'B', b'C'][..], &reference].concat();
+
+        let cases = [
+            // Word 0x0023: offset 2, length code 3
+            (LzssConfig::standard(), [0x00, 0x23], &b"xyABCABCABC"[..]),
+            // Word 0x3002 stored low byte first
+            (
+                LzssConfig::high_nibble_length(),
+                [0x02, 0x30],
+                b"xyABCABCABC",
+            ),
+            // Word 0x0086: offset 2 in the high 10 bits, length code 6
+            (LzssConfig::long_match(), [0x00, 0x86], b"xyABCABCABCABC"),
+        ];
+
+        for (config, reference, expected) in cases {
+            let output = LzssDecoder::new(config)
+                .decompress_buf(&stream(reference))
+                .unwrap();
+            assert_eq!(output, expected, "{:?}", config);
+        }
+
+        // A custom layout is honored the same way
+        let custom = LzssConfig {
+            byte_order: LzssByteOrder::LittleEndian,
+            ..LzssConfig::standard()
+        };
+        let output = LzssDecoder::new(custom)
+            .decompress_buf(&stream([0x23, 0x00]))
+            .unwrap();
+        assert_eq!(output, b"xyABCABCABC");
+    }
+
+    #[test]
+    fn test_preset_round_trips() {
+        let input = b"Legend of Legaia, Legend of Legaia!".repeat(40);
+
+        for config in [
+            LzssConfig::standard(),
+            LzssConfig::high_nibble_length(),
+            LzssConfig::long_match(),
+        ] {
+            let compressed = LzssEncoder::new(config).compress(&input);
+            let decompressed = LzssDecoder::new(config)
+                .decompress_buf(&compressed)
+                .unwrap();
+            assert_eq!(decompressed, input, "{:?}", config);
+        }
+    }
+
+    /// Tiny xorshift generator so the round-trip tests are deterministic
+    struct XorShift(u32);
+
+    impl XorShift {
+        fn next(&mut self) -> u32 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 17;
+            self.0 ^= self.0 << 5;
+            self.0
+        }
+    }
+
+    fn round_trip(input: &[u8]) {
+        let compressed = LzssEncoder::standard().compress(input);
+        let decompressed = LzssDecoder::standard().decompress_buf(&compressed).unwrap();
+        assert_eq!(
+            decompressed,
+            input,
+            "round-trip failed for {} bytes",
+            input.len()
+        );
+    }
+
+    #[test]
+    fn test_encoder_round_trip_short_inputs() {
+        // Shorter than min_match_len, including empty input
+        round_trip(b"");
+        round_trip(b"A");
+        round_trip(b"AB");
+        round_trip(&[0, 0]);
+    }
+
+    #[test]
+    fn test_encoder_round_trip_random() {
+        let mut rng = XorShift(0x1234_5678);
+
+        for size in [3, 7, 8, 9, 17, 100, 1000, 5000] {
+            // Small alphabets produce plenty of matches, full bytes mostly literals
+            for alphabet in [2, 4, 256] {
+                let input: Vec<u8> = (0..size).map(|_| (rng.next() % alphabet) as u8).collect();
+                round_trip(&input);
+            }
+        }
+    }
+
+    #[test]
+    fn test_encoder_overlapping_run() {
+        // A long run must be encoded with references that overlap the
+        // bytes they are producing
+        let input = vec![b'X'; 300];
+        let compressed = LzssEncoder::standard().compress(&input);
+        assert!(compressed.len() < input.len() / 4);
+        round_trip(&input);
+    }
+
+    #[test]
+    fn test_encoder_compresses_repeated_data() {
+        let input = b"Legend of Legaia ".repeat(64);
+        let compressed = LzssEncoder::standard().compress(&input);
+        assert!(compressed.len() < input.len() / 4);
+        assert_eq!(decompress(&compressed).unwrap(), input);
+    }
+
+    fn build_sszl(payload: &[u8]) -> Vec<u8> {
+        let compressed = LzssEncoder::standard().compress(payload);
+        let mut data = LZSS_MAGIC.to_vec();
+        data.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        data.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+        data.extend_from_slice(&compressed);
+        data
+    }
+
+    #[test]
+    fn test_parse_sszl_header() {
+        let payload = b"Legend of Legaia ".repeat(8);
+        let mut data = build_sszl(&payload);
+        let block_len = data.len() - SSZL_HEADER_SIZE;
+
+        // Trailing bytes belong to the next asset and must be ignored
+        data.extend_from_slice(b"VAGp");
+
+        let header = parse_sszl_header(&data).unwrap();
+        assert_eq!(header.decompressed_size as usize, payload.len());
+        assert_eq!(header.compressed_size as usize, block_len);
+        assert_eq!(header.total_size(), data.len() - 4);
+
+        assert!(parse_sszl_header(b"sszl").is_err());
+        assert!(parse_sszl_header(b"lzss\0\0\0\0\0\0\0\0").is_err());
+        // Block length pointing past the end of the buffer
+        assert!(parse_sszl_header(b"sszl\x10\0\0\0\xFF\0\0\0").is_err());
+    }
+
+    #[test]
+    fn test_decompress_sszl_exact_size() {
+        let payload = b"ABCDEFGHIJ".repeat(20);
+        let mut data = build_sszl(&payload);
+        data.extend_from_slice(&[0xFF; 32]);
+
+        let result = decompress_sszl(&data).unwrap();
+        assert_eq!(result, payload);
     }
 }
@@ -0,0 +1,463 @@
+//! MDEC (Macroblock Decoder) bitstream decoder
+//!
+//! The MDEC decompresses JPEG-like images for STR movie frames and still
+//! images. Pixels are coded as 16x16 macroblocks stored column by column,
+//! each made of six 8x8 blocks (YCbCr 4:2:0):
+//!
+//! ```text
+//! Cr, Cb, Y1 (top-left), Y2 (top-right), Y3 (bottom-left), Y4 (bottom-right)
+//! ```
+//!
+//! ## Block Bitstream (version 2)
+//!
+//! ```text
+//! s10       DC coefficient (scaled by the DC quant entry only)
+//! vlc+sign  AC run/level pairs (MPEG-1 table), in zig-zag order
+//! 000001    Escape: u6 run + s10 level
+//! 10        End of block
+//! ```
+//!
+//! The bitstream is a sequence of little-endian 16-bit words read MSB first.
+//!
+//! # References
+//!
+//! - psx-spx: "Macroblock Decoder (MDEC)"
+
+use crate::{PsxError, Result};
+
+/// Zig-zag scan order (zig-zag index -> natural row-major index)
+const ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20,
+    13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59,
+    52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+/// PSX default quantization table (row-major, MPEG-1 intra matrix with DC = 2)
+const QUANT_TABLE: [i32; 64] = [
+    2, 16, 19, 22, 26, 27, 29, 34, //
+    16, 16, 22, 24, 27, 29, 34, 37, //
+    19, 22, 26, 27, 29, 34, 34, 38, //
+    22, 22, 26, 27, 29, 34, 37, 40, //
+    22, 26, 27, 29, 32, 35, 40, 48, //
+    26, 27, 29, 32, 35, 40, 48, 58, //
+    26, 27, 29, 34, 38, 46, 56, 69, //
+    27, 29, 35, 38, 46, 56, 69, 83, //
+];
+
+/// AC coefficient variable-length codes: (bit length, code, run, level)
+///
+/// MPEG-1 DCT coefficient table (excluding the trailing sign bit). The
+/// end-of-block code `10` and escape code `000001` are handled separately.
+#[rustfmt::skip]
+const AC_VLC_TABLE: [(u8, u16, u8, u8); 111] = [
+    (2, 0b11, 0, 1),
+    (3, 0b011, 1, 1),
+    (4, 0b0100, 0, 2),
+    (4, 0b0101, 2, 1),
+    (5, 0b00101, 0, 3),
+    (5, 0b00111, 3, 1),
+    (5, 0b00110, 4, 1),
+    (6, 0b000110, 1, 2),
+    (6, 0b000111, 5, 1),
+    (6, 0b000101, 6, 1),
+    (6, 0b000100, 7, 1),
+    (7, 0b0000110, 0, 4),
+    (7, 0b0000100, 2, 2),
+    (7, 0b0000111, 8, 1),
+    (7, 0b0000101, 9, 1),
+    (8, 0b00100110, 0, 5),
+    (8, 0b00100001, 0, 6),
+    (8, 0b00100101, 1, 3),
+    (8, 0b00100100, 3, 2),
+    (8, 0b00100111, 10, 1),
+    (8, 0b00100011, 11, 1),
+    (8, 0b00100010, 12, 1),
+    (8, 0b00100000, 13, 1),
+    (10, 0b0000001010, 0, 7),
+    (10, 0b0000001100, 1, 4),
+    (10, 0b0000001011, 2, 3),
+    (10, 0b0000001111, 4, 2),
+    (10, 0b0000001001, 5, 2),
+    (10, 0b0000001110, 14, 1),
+    (10, 0b0000001101, 15, 1),
+    (10, 0b0000001000, 16, 1),
+    (12, 0b000000011101, 0, 8),
+    (12, 0b000000011000, 0, 9),
+    (12, 0b000000010011, 0, 10),
+    (12, 0b000000010000, 0, 11),
+    (12, 0b000000011011, 1, 5),
+    (12, 0b000000010100, 2, 4),
+    (12, 0b000000011100, 3, 3),
+    (12, 0b000000010010, 4, 3),
+    (12, 0b000000011110, 6, 2),
+    (12, 0b000000010101, 7, 2),
+    (12, 0b000000010001, 8, 2),
+    (12, 0b000000011111, 17, 1),
+    (12, 0b000000011010, 18, 1),
+    (12, 0b000000011001, 19, 1),
+    (12, 0b000000010111, 20, 1),
+    (12, 0b000000010110, 21, 1),
+    (13, 0b0000000011010, 0, 12),
+    (13, 0b0000000011001, 0, 13),
+    (13, 0b0000000011000, 0, 14),
+    (13, 0b0000000010111, 0, 15),
+    (13, 0b0000000010110, 1, 6),
+    (13, 0b0000000010101, 1, 7),
+    (13, 0b0000000010100, 2, 5),
+    (13, 0b0000000010011, 3, 4),
+    (13, 0b0000000010010, 5, 3),
+    (13, 0b0000000010001, 9, 2),
+    (13, 0b0000000010000, 10, 2),
+    (13, 0b0000000011111, 22, 1),
+    (13, 0b0000000011110, 23, 1),
+    (13, 0b0000000011101, 24, 1),
+    (13, 0b0000000011100, 25, 1),
+    (13, 0b0000000011011, 26, 1),
+    (14, 0b00000000011111, 0, 16),
+    (14, 0b00000000011110, 0, 17),
+    (14, 0b00000000011101, 0, 18),
+    (14, 0b00000000011100, 0, 19),
+    (14, 0b00000000011011, 0, 20),
+    (14, 0b00000000011010, 0, 21),
+    (14, 0b00000000011001, 0, 22),
+    (14, 0b00000000011000, 0, 23),
+    (14, 0b00000000010111, 0, 24),
+    (14, 0b00000000010110, 0, 25),
+    (14, 0b00000000010101, 0, 26),
+    (14, 0b00000000010100, 0, 27),
+    (14, 0b00000000010011, 0, 28),
+    (14, 0b00000000010010, 0, 29),
+    (14, 0b00000000010001, 0, 30),
+    (14, 0b00000000010000, 0, 31),
+    (15, 0b000000000011000, 0, 32),
+    (15, 0b000000000010111, 0, 33),
+    (15, 0b000000000010110, 0, 34),
+    (15, 0b000000000010101, 0, 35),
+    (15, 0b000000000010100, 0, 36),
+    (15, 0b000000000010011, 0, 37),
+    (15, 0b000000000010010, 0, 38),
+    (15, 0b000000000010001, 0, 39),
+    (15, 0b000000000010000, 0, 40),
+    (15, 0b000000000011111, 1, 8),
+    (15, 0b000000000011110, 1, 9),
+    (15, 0b000000000011101, 1, 10),
+    (15, 0b000000000011100, 1, 11),
+    (15, 0b000000000011011, 1, 12),
+    (15, 0b000000000011010, 1, 13),
+    (15, 0b000000000011001, 1, 14),
+    (16, 0b0000000000010011, 1, 15),
+    (16, 0b0000000000010010, 1, 16),
+    (16, 0b0000000000010001, 1, 17),
+    (16, 0b0000000000010000, 1, 18),
+    (16, 0b0000000000010100, 6, 3),
+    (16, 0b0000000000011010, 11, 2),
+    (16, 0b0000000000011001, 12, 2),
+    (16, 0b0000000000011000, 13, 2),
+    (16, 0b0000000000010111, 14, 2),
+    (16, 0b0000000000010110, 15, 2),
+    (16, 0b0000000000010101, 16, 2),
+    (16, 0b0000000000011111, 27, 1),
+    (16, 0b0000000000011110, 28, 1),
+    (16, 0b0000000000011101, 29, 1),
+    (16, 0b0000000000011100, 30, 1),
+    (16, 0b0000000000011011, 31, 1),
+];
+
+/// Bit reader over a bitstream of little-endian 16-bit words, read MSB first
+struct BitReader<'a> {
+    data: &'a [u8],
+    bit_pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, bit_pos: 0 }
+    }
+
+    /// Peek at the next `count` bits (max 16) without consuming them
+    fn peek(&self, count: usize) -> Result<u32> {
+        let mut value = 0u32;
+        for i in 0..count {
+            let pos = self.bit_pos + i;
+            let word_offset = (pos / 16) * 2;
+            if word_offset + 2 > self.data.len() {
+                return Err(PsxError::ParseError("MDEC bitstream truncated".to_string()));
+            }
+            let word = u16::from_le_bytes([self.data[word_offset], self.data[word_offset + 1]]);
+            let bit = (word >> (15 - pos % 16)) & 1;
+            value = (value << 1) | bit as u32;
+        }
+        Ok(value)
+    }
+
+    fn skip(&mut self, count: usize) {
+        self.bit_pos += count;
+    }
+
+    fn read(&mut self, count: usize) -> Result<u32> {
+        let value = self.peek(count)?;
+        self.skip(count);
+        Ok(value)
+    }
+
+    /// Read a two's complement signed value of `count` bits
+    fn read_signed(&mut self, count: usize) -> Result<i32> {
+        let value = self.read(count)? as i32;
+        let shift = 32 - count as u32;
+        Ok((value << shift) >> shift)
+    }
+}
+
+/// Decode one 8x8 block of coefficients into `coeffs` (row-major, dequantized)
+fn decode_block(reader: &mut BitReader, quant_scale: i32, coeffs: &mut [i32; 64]) -> Result<()> {
+    coeffs.fill(0);
+
+    // DC coefficient: 10-bit signed, scaled by the DC quant entry only
+    coeffs[0] = reader.read_signed(10)? * QUANT_TABLE[0];
+
+    let mut index = 0;
+    loop {
+        // End of block
+        if reader.peek(2)? == 0b10 {
+            reader.skip(2);
+            return Ok(());
+        }
+
+        let (run, level) = if reader.peek(6)? == 0b000001 {
+            // Escape: 6-bit run + 10-bit signed level
+            reader.skip(6);
+            let run = reader.read(6)? as usize;
+            let level = reader.read_signed(10)?;
+            (run, level)
+        } else {
+            let (len, run, level) = lookup_ac_code(reader)?;
+            reader.skip(len);
+            let negative = reader.read(1)? == 1;
+            (run, if negative { -level } else { level })
+        };
+
+        index += run + 1;
+        if index >= 64 {
+            return Err(PsxError::ParseError(format!(
+                "MDEC coefficient index out of range: {}",
+                index
+            )));
+        }
+
+        let natural = ZIGZAG[index];
+        coeffs[natural] = (level * QUANT_TABLE[natural] * quant_scale + 4) / 8;
+    }
+}
+
+/// Match the next AC variable-length code against the table
+fn lookup_ac_code(reader: &BitReader) -> Result<(usize, usize, i32)> {
+    for &(len, code, run, level) in AC_VLC_TABLE.iter() {
+        if let Ok(bits) = reader.peek(len as usize)
+            && bits == code as u32
+        {
+            return Ok((len as usize, run as usize, level as i32));
+        }
+    }
+
+    Err(PsxError::ParseError(
+        "Invalid MDEC AC code in bitstream".to_string(),
+    ))
+}
+
+/// 2D inverse DCT of one 8x8 block (row-major in and out)
+fn idct(coeffs: &[i32; 64], out: &mut [f32; 64]) {
+    let mut basis = [[0f32; 8]; 8];
+    for (u, row) in basis.iter_mut().enumerate() {
+        let scale = if u == 0 {
+            std::f32::consts::FRAC_1_SQRT_2
+        } else {
+            1.0
+        } * 0.5;
+        for (x, value) in row.iter_mut().enumerate() {
+            *value = scale * (((2 * x + 1) as f32 * u as f32 * std::f32::consts::PI) / 16.0).cos();
+        }
+    }
+
+    for (y, out_row) in out.chunks_exact_mut(8).enumerate() {
+        for (x, value) in out_row.iter_mut().enumerate() {
+            let mut sum = 0.0;
+            for (v, basis_v) in basis.iter().enumerate() {
+                for (u, basis_u) in basis.iter().enumerate() {
+                    let coeff = coeffs[v * 8 + u];
+                    if coeff != 0 {
+                        sum += basis_v[y] * basis_u[x] * coeff as f32;
+                    }
+                }
+            }
+            *value = sum;
+        }
+    }
+}
+
+/// MDEC bitstream decoder
+///
+/// # Examples
+///
+/// ```no_run
+/// use psxutils::formats::MdecDecoder;
+///
+/// # let bitstream: &[u8] = &[];
+/// let rgb = MdecDecoder::new(1).decode(bitstream, 320, 240)?;
+/// assert_eq!(rgb.len(), 320 * 240 * 3);
+/// # Ok::<(), psxutils::PsxError>(())
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct MdecDecoder {
+    /// Quantization scale applied to AC coefficients
+    quant_scale: i32,
+}
+
+impl MdecDecoder {
+    /// Create a decoder for a bitstream with the given quantization scale
+    pub fn new(quant_scale: u16) -> Self {
+        Self {
+            quant_scale: quant_scale as i32,
+        }
+    }
+
+    /// Decode a bitstream to RGB8 (3 bytes per pixel)
+    pub fn decode(&self, bitstream: &[u8], width: u16, height: u16) -> Result<Vec<u8>> {
+        self.decode_pixels::<3>(bitstream, width, height)
+    }
+
+    /// Decode a bitstream to RGBA8 (4 bytes per pixel, fully opaque)
+    pub fn decode_rgba(&self, bitstream: &[u8], width: u16, height: u16) -> Result<Vec<u8>> {
+        self.decode_pixels::<4>(bitstream, width, height)
+    }
+
+    /// Decode macroblocks into an image with `N` bytes per pixel
+    fn decode_pixels<const N: usize>(
+        &self,
+        bitstream: &[u8],
+        width: u16,
+        height: u16,
+    ) -> Result<Vec<u8>> {
+        let width = width as usize;
+        let height = height as usize;
+        let mb_cols = width.div_ceil(16);
+        let mb_rows = height.div_ceil(16);
+
+        let mut pixels = vec![255u8; width * height * N];
+        let mut reader = BitReader::new(bitstream);
+        let mut coeffs = [0i32; 64];
+
+        // Macroblocks are stored column by column
+        for mb_x in 0..mb_cols {
+            for mb_y in 0..mb_rows {
+                // Block order: Cr, Cb, Y1 (top-left), Y2 (top-right), Y3 (bottom-left), Y4 (bottom-right)
+                let mut blocks = [[0f32; 64]; 6];
+                for block in blocks.iter_mut() {
+                    decode_block(&mut reader, self.quant_scale, &mut coeffs)?;
+                    idct(&coeffs, block);
+                }
+
+                let [cr, cb, y1, y2, y3, y4] = &blocks;
+                for py in 0..16 {
+                    for px in 0..16 {
+                        let x = mb_x * 16 + px;
+                        let y = mb_y * 16 + py;
+                        if x >= width || y >= height {
+                            continue;
+                        }
+
+                        let luma_block = match (px < 8, py < 8) {
+                            (true, true) => y1,
+                            (false, true) => y2,
+                            (true, false) => y3,
+                            (false, false) => y4,
+                        };
+                        let luma = luma_block[(py % 8) * 8 + (px % 8)];
+                        let chroma_idx = (py / 2) * 8 + (px / 2);
+
+                        let [r, g, b] = ycbcr_to_rgb(luma, cb[chroma_idx], cr[chroma_idx]);
+                        let out = (y * width + x) * N;
+                        pixels[out..out + 3].copy_from_slice(&[r, g, b]);
+                    }
+                }
+            }
+        }
+
+        Ok(pixels)
+    }
+}
+
+/// Convert signed YCbCr (centered on 0) to RGB8
+#[inline]
+fn ycbcr_to_rgb(y: f32, cb: f32, cr: f32) -> [u8; 3] {
+    let r = y + 1.402 * cr;
+    let g = y - 0.3437 * cb - 0.7143 * cr;
+    let b = y + 1.772 * cb;
+
+    let clamp = |v: f32| (v + 128.0).round().clamp(0.0, 255.0) as u8;
+    [clamp(r), clamp(g), clamp(b)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pack bits MSB first into little-endian 16-bit words
+    fn pack_bits(bits: &[(u32, usize)]) -> Vec<u8> {
+        let mut words = Vec::new();
+        let mut current = 0u16;
+        let mut used = 0;
+        for &(value, count) in bits {
+            for i in (0..count).rev() {
+                current = (current << 1) | ((value >> i) & 1) as u16;
+                used += 1;
+                if used == 16 {
+                    words.push(current);
+                    current = 0;
+                    used = 0;
+                }
+            }
+        }
+        if used > 0 {
+            words.push(current << (16 - used));
+        }
+        words.iter().flat_map(|w| w.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn test_bit_reader() {
+        // Words are little-endian, bits read MSB first
+        let data = [0x00, 0xA0]; // 0xA000 = 1010 0000 ...
+        let mut reader = BitReader::new(&data);
+        assert_eq!(reader.read(2).unwrap(), 0b10);
+        assert_eq!(reader.read(2).unwrap(), 0b10);
+        assert_eq!(reader.read_signed(4).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_ycbcr_neutral_is_gray() {
+        assert_eq!(ycbcr_to_rgb(0.0, 0.0, 0.0), [128, 128, 128]);
+    }
+
+    #[test]
+    fn test_decode_dc_macroblock() {
+        // Cr = Cb = 0; each luma block DC = 64 -> coefficient 128 -> level 16
+        let mut bits = Vec::new();
+        for dc in [0, 0, 64, 64, 64, 64] {
+            bits.push((dc, 10));
+            bits.push((0b10, 2));
+        }
+        let bitstream = pack_bits(&bits);
+
+        let rgb = MdecDecoder::new(1).decode(&bitstream, 16, 16).unwrap();
+        assert_eq!(rgb.len(), 16 * 16 * 3);
+        assert!(rgb.iter().all(|&value| value == 144));
+
+        let rgba = MdecDecoder::new(1).decode_rgba(&bitstream, 16, 16).unwrap();
+        assert_eq!(&rgba[..4], &[144, 144, 144, 255]);
+    }
+
+    #[test]
+    fn test_truncated_bitstream() {
+        assert!(MdecDecoder::new(1).decode(&[0, 0], 16, 16).is_err());
+    }
+}
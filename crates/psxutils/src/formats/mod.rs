@@ -1,17 +1,45 @@
 //! PlayStation 1 asset format parsers
+//!
+//! Only [`tim`] and [`tmd`] are available without the `std` feature.
 
+#[cfg(feature = "std")]
+pub mod legaia_model;
+#[cfg(feature = "std")]
 pub mod lzss;
+#[cfg(feature = "std")]
+pub mod mdec;
+#[cfg(feature = "std")]
+pub mod seq;
+#[cfg(feature = "std")]
+pub mod str;
 pub mod tim;
 pub mod tmd;
+#[cfg(feature = "std")]
 pub mod vab;
+#[cfg(feature = "std")]
 pub mod vag;
+#[cfg(feature = "std")]
 pub mod xa;
+#[cfg(feature = "std")]
 pub mod xa_adpcm;
 
-pub use lzss::{LzssConfig, LzssDecoder};
-pub use tim::Tim;
+#[cfg(feature = "std")]
+pub use legaia_model::LegaiaModel;
+#[cfg(feature = "std")]
+pub use lzss::{LzssByteOrder, LzssConfig, LzssDecoder, LzssEncoder, SszlHeader};
+#[cfg(feature = "std")]
+pub use mdec::MdecDecoder;
+#[cfg(feature = "std")]
+pub use seq::Sequence;
+#[cfg(feature = "std")]
+pub use str::{StrFrame, StrMovie};
+pub use tim::{Tim, TimAlphaMode};
 pub use tmd::Tmd;
+#[cfg(feature = "std")]
 pub use vab::Vab;
+#[cfg(feature = "std")]
 pub use vag::Vag;
+#[cfg(feature = "std")]
 pub use xa::{XaAudioStream, XaSubHeader};
+#[cfg(feature = "std")]
 pub use xa_adpcm::XaAdpcmDecoder;
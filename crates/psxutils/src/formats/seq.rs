@@ -0,0 +1,364 @@
+//! SEQ (Sound Sequence) format parser
+//!
+//! SEQ is the PlayStation 1 sequenced music format: a single MIDI-style
+//! track played against the programs of a VAB sound bank.
+//!
+//! ## Format Specification
+//!
+//! ```text
+//! SEQ Header (15 bytes, big-endian):
+//!   char[4] magic      = "pQES"
+//!   u32 version        = 0x00000001
+//!   u16 resolution     // Ticks per quarter note
+//!   u24 tempo          // Microseconds per quarter note
+//!   u8  numerator      // Time signature
+//!   u8  denominator    // Time signature, as a power of two
+//!
+//! Event stream (until end of track):
+//!   var delta_time     // Ticks since the previous event (MIDI variable length)
+//!   u8  status         // Optional: the previous status is reused (running status)
+//!   u8[] data          // 1 or 2 bytes depending on status
+//! ```
+//!
+//! Unlike standard MIDI, meta events carry no length byte: tempo changes are
+//! `FF 51 tt tt tt` and end of track is `FF 2F`.
+
+use crate::{PsxError, Result};
+
+/// SEQ magic number as stored on disc
+pub const SEQ_MAGIC: [u8; 4] = *b"pQES";
+
+/// SEQ version
+pub const SEQ_VERSION: u32 = 0x00000001;
+
+/// Size of the SEQ header in bytes
+pub const SEQ_HEADER_SIZE: usize = 15;
+
+/// Meta event status byte
+const META_EVENT: u8 = 0xFF;
+
+/// Meta event type: set tempo
+const META_TEMPO: u8 = 0x51;
+
+/// Meta event type: end of track
+const META_END_OF_TRACK: u8 = 0x2F;
+
+/// Parsed SEQ sequence
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sequence {
+    /// Ticks per quarter note
+    pub resolution: u16,
+    /// Initial tempo in microseconds per quarter note
+    pub tempo: u32,
+    /// Time signature numerator
+    pub numerator: u8,
+    /// Time signature denominator (as a power of two)
+    pub denominator: u8,
+    /// Events in playback order
+    pub events: Vec<SeqEvent>,
+}
+
+/// Timed event in a sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeqEvent {
+    /// Absolute time in ticks from the start of the sequence
+    pub tick: u32,
+    /// The event itself
+    pub kind: SeqEventKind,
+}
+
+/// Sequence event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SeqEventKind {
+    /// Start a note (a note on with velocity 0 is reported as [`NoteOff`](Self::NoteOff))
+    NoteOn { channel: u8, note: u8, velocity: u8 },
+    /// Release a note
+    NoteOff { channel: u8, note: u8 },
+    /// Select the VAB program for a channel
+    ProgramChange { channel: u8, program: u8 },
+    /// Controller change (volume, pan, loop markers via NRPN, ...)
+    Controller {
+        channel: u8,
+        controller: u8,
+        value: u8,
+    },
+    /// Pitch bend, centered on 0 (-8192 to 8191)
+    PitchBend { channel: u8, value: i16 },
+    /// Tempo change in microseconds per quarter note
+    Tempo(u32),
+    /// End of the sequence
+    EndOfTrack,
+}
+
+impl Sequence {
+    /// Parse a SEQ file from bytes
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < SEQ_HEADER_SIZE {
+            return Err(PsxError::InvalidFormat("SEQ file too small".to_string()));
+        }
+
+        if data[0..4] != SEQ_MAGIC {
+            return Err(PsxError::InvalidFormat(format!(
+                "Invalid SEQ magic: {:?}, expected {:?}",
+                &data[0..4],
+                SEQ_MAGIC
+            )));
+        }
+
+        let version = u32::from_be_bytes([data[4], data[5], data[6], data[7]]);
+        if version != SEQ_VERSION {
+            tracing::warn!("Unexpected SEQ version: 0x{:08X}", version);
+        }
+
+        let resolution = u16::from_be_bytes([data[8], data[9]]);
+        let tempo = u32::from_be_bytes([0, data[10], data[11], data[12]]);
+        let numerator = data[13];
+        let denominator = data[14];
+
+        let events = Self::parse_events(&data[SEQ_HEADER_SIZE..])?;
+
+        Ok(Sequence {
+            resolution,
+            tempo,
+            numerator,
+            denominator,
+            events,
+        })
+    }
+
+    /// Parse the event stream following the header
+    ///
+    /// Stops at the end of track event, or at the end of the data if the
+    /// stream was truncated.
+    fn parse_events(data: &[u8]) -> Result<Vec<SeqEvent>> {
+        let mut events = Vec::new();
+        let mut pos = 0;
+        let mut tick = 0u32;
+        let mut running_status = None;
+
+        while pos < data.len() {
+            tick = tick.saturating_add(read_var_len(data, &mut pos)?);
+
+            let mut status = *data.get(pos).ok_or_else(|| {
+                PsxError::ParseError("SEQ event missing after delta time".to_string())
+            })?;
+            if status & 0x80 != 0 {
+                pos += 1;
+                running_status = Some(status);
+            } else {
+                status = running_status.ok_or_else(|| {
+                    PsxError::ParseError(format!("SEQ data byte without status at {}", pos))
+                })?;
+            }
+
+            let mut next = || -> Result<u8> {
+                let byte = *data
+                    .get(pos)
+                    .ok_or_else(|| PsxError::ParseError("Truncated SEQ event".to_string()))?;
+                pos += 1;
+                Ok(byte)
+            };
+
+            let channel = status & 0x0F;
+            let kind = match status & 0xF0 {
+                0x80 => {
+                    let note = next()?;
+                    next()?; // Release velocity
+                    SeqEventKind::NoteOff { channel, note }
+                }
+                0x90 => {
+                    let note = next()?;
+                    let velocity = next()?;
+                    if velocity == 0 {
+                        SeqEventKind::NoteOff { channel, note }
+                    } else {
+                        SeqEventKind::NoteOn {
+                            channel,
+                            note,
+                            velocity,
+                        }
+                    }
+                }
+                0xA0 => {
+                    // Polyphonic aftertouch: not used by the SPU
+                    next()?;
+                    next()?;
+                    continue;
+                }
+                0xB0 => SeqEventKind::Controller {
+                    channel,
+                    controller: next()?,
+                    value: next()?,
+                },
+                0xC0 => SeqEventKind::ProgramChange {
+                    channel,
+                    program: next()?,
+                },
+                0xD0 => {
+                    // Channel pressure: not used by the SPU
+                    next()?;
+                    continue;
+                }
+                0xE0 => {
+                    let lsb = next()? as i16;
+                    let msb = next()? as i16;
+                    SeqEventKind::PitchBend {
+                        channel,
+                        value: ((msb << 7) | lsb) - 8192,
+                    }
+                }
+                _ if status == META_EVENT => match next()? {
+                    META_TEMPO => {
+                        let tempo = u32::from_be_bytes([0, next()?, next()?, next()?]);
+                        SeqEventKind::Tempo(tempo)
+                    }
+                    META_END_OF_TRACK => SeqEventKind::EndOfTrack,
+                    other => {
+                        return Err(PsxError::ParseError(format!(
+                            "Unknown SEQ meta event: 0x{:02X}",
+                            other
+                        )))
+                    }
+                },
+                _ => {
+                    return Err(PsxError::ParseError(format!(
+                        "Unknown SEQ status byte: 0x{:02X}",
+                        status
+                    )))
+                }
+            };
+
+            events.push(SeqEvent { tick, kind });
+            if kind == SeqEventKind::EndOfTrack {
+                break;
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Length of a tick in seconds at the given tempo
+    pub fn tick_secs(&self, tempo: u32) -> f64 {
+        tempo as f64 / 1_000_000.0 / self.resolution.max(1) as f64
+    }
+}
+
+/// Read a MIDI variable-length quantity at `pos`, advancing past it
+///
+/// Each byte carries 7 bits, most significant first, with the top bit set
+/// on every byte but the last. At most four bytes (28 bits) are allowed.
+pub fn read_var_len(data: &[u8], pos: &mut usize) -> Result<u32> {
+    let mut value = 0u32;
+
+    for _ in 0..4 {
+        let byte = *data
+            .get(*pos)
+            .ok_or_else(|| PsxError::ParseError("Truncated SEQ delta time".to_string()))?;
+        *pos += 1;
+
+        value = (value << 7) | (byte & 0x7F) as u32;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+
+    Err(PsxError::ParseError(
+        "SEQ delta time longer than 4 bytes".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_var_len() {
+        let cases: &[(&[u8], u32)] = &[
+            (&[0x00], 0),
+            (&[0x40], 0x40),
+            (&[0x7F], 0x7F),
+            (&[0x81, 0x00], 0x80),
+            (&[0xC0, 0x00], 0x2000),
+            (&[0xFF, 0x7F], 0x3FFF),
+            (&[0x81, 0x80, 0x00], 0x4000),
+            (&[0xFF, 0xFF, 0xFF, 0x7F], 0x0FFF_FFFF),
+        ];
+
+        for (bytes, expected) in cases {
+            let mut pos = 0;
+            assert_eq!(read_var_len(bytes, &mut pos).unwrap(), *expected);
+            assert_eq!(pos, bytes.len());
+        }
+
+        // Truncated and overlong quantities
+        assert!(read_var_len(&[0x81], &mut 0).is_err());
+        assert!(read_var_len(&[0x80, 0x80, 0x80, 0x80, 0x00], &mut 0).is_err());
+    }
+
+    #[test]
+    fn test_parse_sequence() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&SEQ_MAGIC);
+        data.extend_from_slice(&SEQ_VERSION.to_be_bytes());
+        data.extend_from_slice(&480u16.to_be_bytes());
+        data.extend_from_slice(&[0x07, 0xA1, 0x20]); // 500000 us
+        data.extend_from_slice(&[4, 2]);
+        data.extend_from_slice(&[
+            0x00, 0xC0, 0x05, // Program 5
+            0x00, 0x90, 0x3C, 0x64, // Note on C4
+            0x83, 0x60, 0x3C, 0x00, // 480 ticks later, running status note off
+            0x00, 0xFF, 0x51, 0x0F, 0x42, 0x40, // Tempo 1000000 us
+            0x10, 0xFF, 0x2F, // End of track
+        ]);
+
+        let seq = Sequence::parse(&data).unwrap();
+        assert_eq!(seq.resolution, 480);
+        assert_eq!(seq.tempo, 500_000);
+        assert_eq!((seq.numerator, seq.denominator), (4, 2));
+        assert_eq!(
+            seq.events,
+            [
+                SeqEvent {
+                    tick: 0,
+                    kind: SeqEventKind::ProgramChange {
+                        channel: 0,
+                        program: 5
+                    }
+                },
+                SeqEvent {
+                    tick: 0,
+                    kind: SeqEventKind::NoteOn {
+                        channel: 0,
+                        note: 60,
+                        velocity: 100
+                    }
+                },
+                SeqEvent {
+                    tick: 480,
+                    kind: SeqEventKind::NoteOff {
+                        channel: 0,
+                        note: 60
+                    }
+                },
+                SeqEvent {
+                    tick: 480,
+                    kind: SeqEventKind::Tempo(1_000_000)
+                },
+                SeqEvent {
+                    tick: 496,
+                    kind: SeqEventKind::EndOfTrack
+                },
+            ]
+        );
+        assert!((seq.tick_secs(seq.tempo) * 480.0 - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_invalid_seq() {
+        assert!(Sequence::parse(&[0u8; 10]).is_err());
+        assert!(Sequence::parse(&[0u8; 32]).is_err());
+    }
+}
@@ -0,0 +1,274 @@
+//! STR (streaming movie) format parser
+//!
+//! STR files interleave MDEC-compressed video sectors with XA audio sectors.
+//! Each video frame is split across several consecutive video sectors, each
+//! carrying a 32-byte header followed by 2016 bytes of bitstream.
+//!
+//! ## Video Sector Header
+//!
+//! ```text
+//! u16 magic          = 0x0160
+//! u16 type           = 0x8001   // Video sector
+//! u16 sector_index             // Sector number within this frame
+//! u16 sector_count             // Number of sectors in this frame
+//! u32 frame_number             // Frame number (starts at 1)
+//! u32 frame_size               // Demuxed bitstream size in bytes
+//! u16 width, height            // Frame dimensions in pixels
+//! u8[12] reserved
+//! ```
+//!
+//! ## Frame Bitstream Header
+//!
+//! ```text
+//! u16 code_count               // Number of MDEC codes (in 32-bit units)
+//! u16 magic          = 0x3800
+//! u16 quant_scale              // Quantization scale
+//! u16 version                  // Bitstream version (2 supported)
+//! ```
+//!
+//! # References
+//!
+//! - psx-spx: "Macroblock Decoder (MDEC)"
+//! - jPSXdec: `jpsxdec/src/jpsxdec/modules/video/sectorbased/`
+
+use super::mdec::MdecDecoder;
+use crate::cdrom::CdRom;
+use crate::{PsxError, Result};
+use std::collections::BTreeMap;
+
+/// Video sector header magic
+pub const STR_SECTOR_MAGIC: u16 = 0x0160;
+
+/// Video sector type
+pub const STR_VIDEO_TYPE: u16 = 0x8001;
+
+/// Frame bitstream header magic
+pub const STR_FRAME_MAGIC: u16 = 0x3800;
+
+/// Size of the video sector header
+const SECTOR_HEADER_SIZE: usize = 32;
+
+/// Bitstream bytes carried by one video sector (2048 - 32 header bytes)
+const SECTOR_PAYLOAD_SIZE: usize = 2016;
+
+/// Offset of the user data in a raw Mode 2 sector
+const RAW_DATA_OFFSET: usize = 24;
+
+/// Size of the frame bitstream header
+const FRAME_HEADER_SIZE: usize = 8;
+
+/// One demuxed video frame
+#[derive(Debug, Clone)]
+pub struct StrFrame {
+    /// Frame number from the sector headers
+    pub frame_number: u32,
+    /// Width in pixels
+    pub width: u16,
+    /// Height in pixels
+    pub height: u16,
+    /// Reassembled MDEC bitstream (including the 8-byte frame header)
+    pub data: Vec<u8>,
+}
+
+/// Demuxed STR movie
+#[derive(Debug, Clone)]
+pub struct StrMovie {
+    /// Video frames in frame-number order
+    pub frames: Vec<StrFrame>,
+}
+
+/// Partially reassembled frame while demuxing
+struct PendingFrame {
+    width: u16,
+    height: u16,
+    frame_size: usize,
+    chunks: Vec<Option<Vec<u8>>>,
+}
+
+impl StrMovie {
+    /// Open and demux an STR movie from a disc
+    ///
+    /// Walks the raw sectors of the file, skips audio sectors, and reassembles
+    /// each frame's bitstream from its video sectors.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use psxutils::cdrom::CdRom;
+    /// use psxutils::formats::StrMovie;
+    ///
+    /// let disc = CdRom::open("game.bin")?;
+    /// let movie = StrMovie::open(&disc, "/MOV/INTRO.STR")?;
+    /// let rgba = movie.decode_frame(0)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn open(disc: &CdRom, path: &str) -> Result<Self> {
+        let entry = disc.stat(path)?;
+        let sector_count = (entry.size as usize).div_ceil(crate::cdrom::DATA_SIZE) as u32;
+
+        Self::demux((0..sector_count).filter_map(|i| {
+            let raw_sector = disc.read_raw_sector(entry.lba + i).ok()?;
+            Some(&raw_sector[RAW_DATA_OFFSET..RAW_DATA_OFFSET + crate::cdrom::DATA_SIZE])
+        }))
+    }
+
+    /// Demux video frames from a sequence of 2048-byte sector payloads
+    ///
+    /// Sectors that do not carry a video sector header are ignored.
+    pub fn demux<'a>(sectors: impl IntoIterator<Item = &'a [u8]>) -> Result<Self> {
+        let mut pending: BTreeMap<u32, PendingFrame> = BTreeMap::new();
+
+        for sector in sectors {
+            if sector.len() < SECTOR_HEADER_SIZE {
+                continue;
+            }
+
+            let magic = u16::from_le_bytes([sector[0], sector[1]]);
+            let sector_type = u16::from_le_bytes([sector[2], sector[3]]);
+            if magic != STR_SECTOR_MAGIC || sector_type != STR_VIDEO_TYPE {
+                continue; // Audio or unrelated sector
+            }
+
+            let sector_index = u16::from_le_bytes([sector[4], sector[5]]) as usize;
+            let sectors_in_frame = u16::from_le_bytes([sector[6], sector[7]]) as usize;
+            let frame_number = u32::from_le_bytes([sector[8], sector[9], sector[10], sector[11]]);
+            let frame_size =
+                u32::from_le_bytes([sector[12], sector[13], sector[14], sector[15]]) as usize;
+            let width = u16::from_le_bytes([sector[16], sector[17]]);
+            let height = u16::from_le_bytes([sector[18], sector[19]]);
+
+            if sectors_in_frame == 0 || sector_index >= sectors_in_frame {
+                tracing::warn!(
+                    "Invalid STR sector {}/{} in frame {}",
+                    sector_index,
+                    sectors_in_frame,
+                    frame_number
+                );
+                continue;
+            }
+
+            let frame = pending.entry(frame_number).or_insert_with(|| PendingFrame {
+                width,
+                height,
+                frame_size,
+                chunks: vec![None; sectors_in_frame],
+            });
+
+            if let Some(slot) = frame.chunks.get_mut(sector_index) {
+                let end = sector.len().min(SECTOR_HEADER_SIZE + SECTOR_PAYLOAD_SIZE);
+                *slot = Some(sector[SECTOR_HEADER_SIZE..end].to_vec());
+            }
+        }
+
+        let mut frames = Vec::with_capacity(pending.len());
+        for (frame_number, frame) in pending {
+            if frame.chunks.iter().any(Option::is_none) {
+                tracing::warn!("STR frame {} is missing sectors, skipping", frame_number);
+                continue;
+            }
+
+            let mut data: Vec<u8> = frame.chunks.into_iter().flatten().flatten().collect();
+            if frame.frame_size > 0 {
+                data.truncate(frame.frame_size);
+            }
+
+            frames.push(StrFrame {
+                frame_number,
+                width: frame.width,
+                height: frame.height,
+                data,
+            });
+        }
+
+        Ok(Self { frames })
+    }
+
+    /// Get the number of video frames
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Decode a frame to RGBA8
+    ///
+    /// Returns a Vec<u8> with RGBA data (4 bytes per pixel), sized
+    /// `width * height * 4` of the frame.
+    pub fn decode_frame(&self, index: usize) -> Result<Vec<u8>> {
+        let frame = self.frames.get(index).ok_or_else(|| {
+            PsxError::ParseError(format!(
+                "STR frame index {} out of range ({} frames)",
+                index,
+                self.frames.len()
+            ))
+        })?;
+
+        frame.decode()
+    }
+}
+
+impl StrFrame {
+    /// Decode this frame to RGBA8
+    pub fn decode(&self) -> Result<Vec<u8>> {
+        if self.data.len() < FRAME_HEADER_SIZE {
+            return Err(PsxError::InvalidFormat(
+                "STR frame too small for header".to_string(),
+            ));
+        }
+
+        let magic = u16::from_le_bytes([self.data[2], self.data[3]]);
+        if magic != STR_FRAME_MAGIC {
+            return Err(PsxError::InvalidFormat(format!(
+                "Invalid STR frame magic: 0x{:04X}, expected 0x{:04X}",
+                magic, STR_FRAME_MAGIC
+            )));
+        }
+
+        let quant_scale = u16::from_le_bytes([self.data[4], self.data[5]]);
+        let version = u16::from_le_bytes([self.data[6], self.data[7]]);
+        if version != 2 {
+            return Err(PsxError::UnsupportedVersion(version as u32));
+        }
+
+        MdecDecoder::new(quant_scale).decode_rgba(
+            &self.data[FRAME_HEADER_SIZE..],
+            self.width,
+            self.height,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a video sector payload with the given header fields
+    fn video_sector(index: u16, count: u16, frame: u32, size: u32, payload: &[u8]) -> Vec<u8> {
+        let mut sector = vec![0u8; 2048];
+        sector[0..2].copy_from_slice(&STR_SECTOR_MAGIC.to_le_bytes());
+        sector[2..4].copy_from_slice(&STR_VIDEO_TYPE.to_le_bytes());
+        sector[4..6].copy_from_slice(&index.to_le_bytes());
+        sector[6..8].copy_from_slice(&count.to_le_bytes());
+        sector[8..12].copy_from_slice(&frame.to_le_bytes());
+        sector[12..16].copy_from_slice(&size.to_le_bytes());
+        sector[16..18].copy_from_slice(&16u16.to_le_bytes());
+        sector[18..20].copy_from_slice(&16u16.to_le_bytes());
+        sector[32..32 + payload.len()].copy_from_slice(payload);
+        sector
+    }
+
+    #[test]
+    fn test_demux_reassembles_frames() {
+        let audio = vec![0xAAu8; 2048];
+        let s0 = video_sector(0, 2, 1, 2020, &[1; 2016]);
+        let s1 = video_sector(1, 2, 1, 2020, &[2; 4]);
+
+        // Out-of-order sectors with an interleaved audio sector
+        let movie = StrMovie::demux([&s1[..], &audio[..], &s0[..]]).unwrap();
+
+        assert_eq!(movie.frame_count(), 1);
+        let frame = &movie.frames[0];
+        assert_eq!(frame.frame_number, 1);
+        assert_eq!(frame.data.len(), 2020);
+        assert_eq!(frame.data[0], 1);
+        assert_eq!(frame.data[2019], 2);
+    }
+}
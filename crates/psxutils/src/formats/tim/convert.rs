@@ -1,46 +1,47 @@
 //! TIM color conversion functions
 
 use super::types::*;
+use crate::prelude::*;
 use crate::{PsxError, Result};
+#[cfg(feature = "extraction")]
+use std::path::Path;
 
 /// Convert RGB555 color to RGBA8 format
 ///
-/// PSX RGB555 format: 0BBBBBGGGGGRRRRR (15-bit color + 1 STP bit)
+/// PSX RGB555 format: SBBBBBGGGGGRRRRR (15-bit color + 1 STP bit)
 ///
-/// STP (semi-transparent) bit behavior:
-/// - Black (RGB=0,0,0) with STP=0: Fully transparent (alpha=0) - transparency key
-/// - Black (RGB=0,0,0) with STP=1: Fully opaque (alpha=255) - solid black
-/// - Color with STP=0: Fully opaque (alpha=255) - normal rendering
-/// - Color with STP=1: Semi-transparent (alpha=254) - blending enabled
+/// Alpha is derived from the STP (semi-transparent) bit according to `mode`;
+/// see [`TimAlphaMode`].
 #[inline]
-fn rgb555_to_rgba(color: u16) -> [u8; 4] {
+pub(crate) fn rgb555_to_rgba(color: u16, mode: TimAlphaMode) -> [u8; 4] {
     let r = ((color & 0x1F) << 3) as u8;
     let g = (((color >> 5) & 0x1F) << 3) as u8;
     let b = (((color >> 10) & 0x1F) << 3) as u8;
-    
-    let a = if r == 0 && g == 0 && b == 0 {
-        // Black pixels: bit 15 determines transparency
-        // STP=0 (bit clear) → transparent (used as transparency key)
-        // STP=1 (bit set) → opaque black
-        if color & 0x8000 == 0 { 0 } else { 255 }
-    } else {
-        // Non-black pixels: bit 15 determines blending mode
-        // STP=0 (bit clear) → fully opaque (normal rendering)
-        // STP=1 (bit set) → semi-transparent (PSX blending)
-        if color & 0x8000 == 0 { 255 } else { 254 }
-    };
-    
-    [r, g, b, a]
-}
-    } else {
-        // Non-black pixels: bit 15 determines blending mode
-        // STP=0 (bit clear) → fully opaque (normal rendering)
-        // STP=1 (bit set) → semi-transparent (PSX blending)
-        if color & 0x8000 == 0 {
-            255
-        } else {
-            254
+
+    let is_black = color & 0x7FFF == 0;
+    let stp = color & 0x8000 != 0;
+
+    let a = match mode {
+        // Black: STP=0 → transparency key, STP=1 → opaque black
+        // Color: STP=0 → opaque, STP=1 → opaque with blending marker
+        TimAlphaMode::StpAsBinary => match (is_black, stp) {
+            (true, false) => 0,
+            (false, true) => 254,
+            _ => 255,
+        },
+        TimAlphaMode::BlackIsTransparent => {
+            if is_black {
+                0
+            } else {
+                255
+            }
         }
+        TimAlphaMode::Opaque => 255,
+        TimAlphaMode::SemiTransparent => match (is_black, stp) {
+            (true, false) => 0,
+            (false, true) => 128,
+            _ => 255,
+        },
     };
 
     [r, g, b, a]
@@ -49,50 +50,128 @@ fn rgb555_to_rgba(color: u16) -> [u8; 4] {
 impl Tim {
     /// Convert to RGBA8 format
     ///
+    /// Indexed modes use the first CLUT row and alpha follows
+    /// [`TimAlphaMode::StpAsBinary`].
     /// Returns a Vec<u8> with RGBA data (4 bytes per pixel)
     pub fn to_rgba8(&self) -> Result<Vec<u8>> {
+        self.convert_to_rgba8(0, TimAlphaMode::default())
+    }
+
+    /// Convert to RGBA8 format using a specific CLUT row
+    ///
+    /// Multi-row CLUTs hold several palettes for the same pixels (palette-swapped
+    /// sprites); `row` selects which one to apply. Ignored for direct-color modes.
+    pub fn to_rgba8_with_palette(&self, row: usize) -> Result<Vec<u8>> {
+        self.convert_to_rgba8(row, TimAlphaMode::default())
+    }
+
+    /// Convert to RGBA8 format with a specific STP/transparency policy
+    ///
+    /// 24-bit TIMs have no STP bit and are always opaque.
+    pub fn to_rgba8_with_alpha(&self, mode: TimAlphaMode) -> Result<Vec<u8>> {
+        self.convert_to_rgba8(0, mode)
+    }
+
+    /// Crop a rectangle out of the RGBA8 image
+    ///
+    /// Coordinates are in decoded pixels, so 4-bit and 8-bit TIMs are
+    /// addressed by [`Tim::width`] rather than the VRAM word width. Returns
+    /// `w * h` tightly packed RGBA8 pixels, or an error if the rectangle is
+    /// empty or extends past the image.
+    pub fn sub_rgba8(&self, x: u16, y: u16, w: u16, h: u16) -> Result<Vec<u8>> {
+        let rgba = self.to_rgba8()?;
+        let (width, height) = (self.width() as usize, self.height() as usize);
+
+        let (x, y, w, h) = (x as usize, y as usize, w as usize, h as usize);
+        if w == 0 || h == 0 || x + w > width || y + h > height {
+            return Err(PsxError::InvalidFormat(format!(
+                "Crop {}x{} at ({}, {}) outside {}x{} TIM",
+                w, h, x, y, width, height
+            )));
+        }
+
+        let mut cropped = Vec::with_capacity(w * h * 4);
+        for row in rgba.chunks_exact(width * 4).skip(y).take(h) {
+            cropped.extend_from_slice(&row[x * 4..(x + w) * 4]);
+        }
+
+        Ok(cropped)
+    }
+
+    /// Crop a rectangle out of the image, see [`Tim::sub_rgba8`]
+    #[cfg(feature = "extraction")]
+    pub fn sub_image(&self, x: u16, y: u16, w: u16, h: u16) -> Result<image::RgbaImage> {
+        let rgba = self.sub_rgba8(x, y, w, h)?;
+        image::RgbaImage::from_raw(w as u32, h as u32, rgba)
+            .ok_or_else(|| PsxError::InvalidFormat(format!("Pixel data too small for {}x{}", w, h)))
+    }
+
+    fn convert_to_rgba8(&self, row: usize, mode: TimAlphaMode) -> Result<Vec<u8>> {
         match self.pixel_mode {
-            PixelMode::Direct16Bit => self.convert_16bit_to_rgba8(),
+            PixelMode::Direct16Bit => self.convert_16bit_to_rgba8(mode),
             PixelMode::Direct24Bit => self.convert_24bit_to_rgba8(),
-            PixelMode::Clut4Bit => self.convert_4bit_to_rgba8(),
-            PixelMode::Clut8Bit => self.convert_8bit_to_rgba8(),
-            PixelMode::Mixed => Err(PsxError::InvalidFormat(
-                "Mixed mode TIM conversion not yet supported".to_string(),
-            )),
+            PixelMode::Clut4Bit => self.convert_4bit_to_rgba8(self.palette_row(row)?, mode),
+            PixelMode::Clut8Bit => self.convert_8bit_to_rgba8(self.palette_row(row)?, mode),
+            PixelMode::Mixed => match self.mixed_layout() {
+                Some(MixedLayout::Indexed4) => {
+                    self.convert_4bit_to_rgba8(self.palette_row(row)?, mode)
+                }
+                Some(MixedLayout::Indexed8) => {
+                    self.convert_8bit_to_rgba8(self.palette_row(row)?, mode)
+                }
+                _ => self.convert_16bit_to_rgba8(mode),
+            },
+        }
+    }
+
+    /// Look up a CLUT row, failing if the TIM has no CLUT or the row is missing
+    fn palette_row(&self, row: usize) -> Result<&[u16]> {
+        let clut = self.clut.as_ref().ok_or_else(|| {
+            PsxError::InvalidFormat(format!("{:?} TIM requires CLUT", self.pixel_mode))
+        })?;
+
+        if row >= clut.palette_count() {
+            return Err(PsxError::InvalidFormat(format!(
+                "CLUT row {} out of range (CLUT has {} rows)",
+                row,
+                clut.palette_count()
+            )));
         }
+
+        Ok(clut.palette(row))
     }
 
-    fn convert_16bit_to_rgba8(&self) -> Result<Vec<u8>> {
+    fn convert_16bit_to_rgba8(&self, mode: TimAlphaMode) -> Result<Vec<u8>> {
         let mut rgba = Vec::with_capacity(
             self.pixels.dimensions.0 as usize * self.pixels.dimensions.1 as usize * 4,
         );
 
         for chunk in self.pixels.data.chunks_exact(2) {
             let color = u16::from_le_bytes([chunk[0], chunk[1]]);
-            rgba.extend_from_slice(&rgb555_to_rgba(color));
+            rgba.extend_from_slice(&rgb555_to_rgba(color, mode));
         }
 
         Ok(rgba)
     }
 
     fn convert_24bit_to_rgba8(&self) -> Result<Vec<u8>> {
-        let mut rgba = Vec::with_capacity(
-            self.pixels.dimensions.0 as usize * self.pixels.dimensions.1 as usize * 4,
-        );
+        let width = self.width() as usize;
+        let height = self.height() as usize;
+        let mut rgba = Vec::with_capacity(width * height * 4);
 
-        for chunk in self.pixels.data.chunks_exact(3) {
-            rgba.extend_from_slice(&[chunk[0], chunk[1], chunk[2], 255]);
+        // Rows are a whole number of 16-bit words, so a row whose pixels
+        // don't fill its last word ends in padding bytes that must be skipped
+        let row_bytes = self.pixels.dimensions.0 as usize * 2;
+        for row in self.pixels.data.chunks_exact(row_bytes.max(1)).take(height) {
+            for pixel in row[..width * 3].chunks_exact(3) {
+                rgba.extend_from_slice(&[pixel[0], pixel[1], pixel[2], 255]);
+            }
         }
 
         Ok(rgba)
     }
 
-    fn convert_4bit_to_rgba8(&self) -> Result<Vec<u8>> {
-        let clut = self
-            .clut
-            .as_ref()
-            .ok_or_else(|| PsxError::InvalidFormat("4-bit TIM requires CLUT".to_string()))?;
-
+    fn convert_4bit_to_rgba8(&self, palette: &[u16], mode: TimAlphaMode) -> Result<Vec<u8>> {
         let mut rgba = Vec::with_capacity(
             self.pixels.dimensions.0 as usize * 2 * self.pixels.dimensions.1 as usize * 4,
         );
@@ -103,9 +182,9 @@ impl Tim {
             let idx2 = ((byte >> 4) & 0x0F) as usize;
 
             for idx in [idx1, idx2] {
-                if idx < clut.data.len() {
-                    let color = clut.data[idx];
-                    rgba.extend_from_slice(&rgb555_to_rgba(color));
+                if idx < palette.len() {
+                    let color = palette[idx];
+                    rgba.extend_from_slice(&rgb555_to_rgba(color, mode));
                 }
             }
         }
@@ -113,24 +192,125 @@ impl Tim {
         Ok(rgba)
     }
 
-    fn convert_8bit_to_rgba8(&self) -> Result<Vec<u8>> {
-        let clut = self
-            .clut
-            .as_ref()
-            .ok_or_else(|| PsxError::InvalidFormat("8-bit TIM requires CLUT".to_string()))?;
-
+    fn convert_8bit_to_rgba8(&self, palette: &[u16], mode: TimAlphaMode) -> Result<Vec<u8>> {
         let mut rgba = Vec::with_capacity(
             self.pixels.dimensions.0 as usize * self.pixels.dimensions.1 as usize * 4,
         );
 
         for &idx in &self.pixels.data {
             let idx = idx as usize;
-            if idx < clut.data.len() {
-                let color = clut.data[idx];
-                rgba.extend_from_slice(&rgb555_to_rgba(color));
+            if idx < palette.len() {
+                let color = palette[idx];
+                rgba.extend_from_slice(&rgb555_to_rgba(color, mode));
             }
         }
 
         Ok(rgba)
     }
+
+    /// Extract a palette and raw index pixels from a 4-bit or 8-bit TIM
+    ///
+    /// Mixed-mode TIMs with an indexed [`MixedLayout`] work too.
+    ///
+    /// `palette_index` selects the CLUT row, since multi-row CLUTs hold several
+    /// palettes for the same pixel data (palette animation, team colors).
+    ///
+    /// Returns `(palette, indices)` where `palette` holds RGBA8 colors (alpha
+    /// follows the STP rules of [`Tim::to_rgba8`]) and `indices` holds one byte
+    /// per pixel, row-major.
+    pub fn to_indexed(&self, palette_index: usize) -> Result<(Vec<[u8; 4]>, Vec<u8>)> {
+        let four_bit = match (self.pixel_mode, self.mixed_layout()) {
+            (PixelMode::Clut4Bit, _) | (_, Some(MixedLayout::Indexed4)) => true,
+            (PixelMode::Clut8Bit, _) | (_, Some(MixedLayout::Indexed8)) => false,
+            _ => {
+                return Err(PsxError::InvalidFormat(format!(
+                    "{:?} TIM has no palette, use to_rgba8 instead",
+                    self.pixel_mode
+                )))
+            }
+        };
+
+        let colors = if four_bit { 16 } else { 256 };
+        let row = self.palette_row(palette_index)?;
+        let palette = row[..colors.min(row.len())]
+            .iter()
+            .map(|&color| rgb555_to_rgba(color, TimAlphaMode::default()))
+            .collect();
+
+        let indices = if four_bit {
+            // Low nibble is the leftmost pixel
+            self.pixels
+                .data
+                .iter()
+                .flat_map(|&byte| [byte & 0x0F, byte >> 4])
+                .collect()
+        } else {
+            self.pixels.data.clone()
+        };
+
+        Ok((palette, indices))
+    }
+
+    /// Render a preview no larger than `max_dim` pixels on either side
+    ///
+    /// The aspect ratio is preserved and downscaling uses a Lanczos3 filter.
+    /// Textures already within `max_dim` are returned at their original size.
+    #[cfg(feature = "extraction")]
+    pub fn thumbnail(&self, max_dim: u32) -> Result<image::RgbaImage> {
+        let (width, height) = (self.width() as u32, self.height() as u32);
+        let img = image::RgbaImage::from_raw(width, height, self.to_rgba8()?).ok_or_else(|| {
+            PsxError::InvalidFormat(format!("Pixel data too small for {}x{}", width, height))
+        })?;
+
+        if width <= max_dim && height <= max_dim {
+            return Ok(img);
+        }
+
+        let scale = max_dim as f32 / width.max(height) as f32;
+        let thumb_width = ((width as f32 * scale) as u32).max(1);
+        let thumb_height = ((height as f32 * scale) as u32).max(1);
+
+        Ok(image::imageops::resize(
+            &img,
+            thumb_width,
+            thumb_height,
+            image::imageops::FilterType::Lanczos3,
+        ))
+    }
+
+    /// Write a 4-bit or 8-bit TIM as an indexed PNG
+    ///
+    /// The CLUT row selected by `palette_index` becomes the PNG `PLTE` chunk
+    /// (with alpha in `tRNS`) and pixels keep their original indices, so the
+    /// image can be edited and re-imported without losing the palette.
+    /// Direct-color TIMs return an error; use [`Tim::to_rgba8`] for those.
+    #[cfg(feature = "extraction")]
+    pub fn to_indexed_png(&self, output: &Path, palette_index: usize) -> Result<()> {
+        let (palette, indices) = self.to_indexed(palette_index)?;
+
+        let file = std::fs::File::create(output)?;
+        let mut encoder = png::Encoder::new(
+            std::io::BufWriter::new(file),
+            self.width() as u32,
+            self.height() as u32,
+        );
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_palette(
+            palette
+                .iter()
+                .flat_map(|c| [c[0], c[1], c[2]])
+                .collect::<Vec<u8>>(),
+        );
+        encoder.set_trns(palette.iter().map(|c| c[3]).collect::<Vec<u8>>());
+
+        let mut writer = encoder
+            .write_header()
+            .map_err(|e| PsxError::InvalidFormat(format!("PNG encoding failed: {}", e)))?;
+        writer
+            .write_image_data(&indices)
+            .map_err(|e| PsxError::InvalidFormat(format!("PNG encoding failed: {}", e)))?;
+
+        Ok(())
+    }
 }
@@ -5,6 +5,7 @@
 //! - 8-bit indexed color (256 colors with CLUT)
 //! - 16-bit direct color (RGB555)
 //! - 24-bit direct color (RGB888)
+//! - Mixed mode, in the layouts described by [`MixedLayout`]
 //!
 //! ## Format Specification
 //!
@@ -29,9 +30,13 @@
 mod convert;
 mod parse;
 mod types;
+mod write;
+
+#[cfg(feature = "std")]
+pub(crate) use convert::rgb555_to_rgba;
 
 // Re-export public API
-pub use types::{ClutData, PixelData, PixelMode, Tim, TIM_MAGIC};
+pub use types::{ClutData, MixedLayout, PixelData, PixelMode, Tim, TimAlphaMode, TIM_MAGIC};
 
 #[cfg(test)]
 mod tests {
@@ -43,7 +48,10 @@ mod tests {
         assert_eq!(PixelMode::from_u32(1).unwrap(), PixelMode::Clut8Bit);
         assert_eq!(PixelMode::from_u32(2).unwrap(), PixelMode::Direct16Bit);
         assert_eq!(PixelMode::from_u32(3).unwrap(), PixelMode::Direct24Bit);
-        assert!(PixelMode::from_u32(7).is_err());
+        assert!(matches!(
+            PixelMode::from_u32(7),
+            Err(crate::PsxError::UnsupportedMode(7))
+        ));
     }
 
     #[test]
@@ -53,4 +61,344 @@ mod tests {
         assert_eq!(PixelMode::Direct16Bit.bits_per_pixel(), 16);
         assert_eq!(PixelMode::Direct24Bit.bits_per_pixel(), 24);
     }
+
+    #[cfg(feature = "extraction")]
+    #[test]
+    fn test_serde_skips_pixel_data() {
+        let tim = indexed_tim(PixelMode::Clut4Bit, 1, vec![0x21; 64]);
+
+        let json = serde_json::to_value(&tim).unwrap();
+        assert_eq!(json["pixel_mode"], "Clut4Bit");
+        assert_eq!(json["clut"]["data"].as_array().unwrap().len(), 16);
+        assert!(json["pixels"].get("data").is_none());
+
+        let restored: Tim = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.pixels.dimensions, tim.pixels.dimensions);
+        assert!(restored.pixels.data.is_empty());
+    }
+
+    #[cfg(feature = "extraction")]
+    #[test]
+    fn test_thumbnail() {
+        let direct_tim = |width: u16, height: u16| Tim {
+            pixel_mode: PixelMode::Direct16Bit,
+            has_clut: false,
+            clut: None,
+            pixels: PixelData {
+                vram_pos: (0, 0),
+                dimensions: (width, height),
+                data: vec![0xFF; width as usize * height as usize * 2],
+            },
+        };
+
+        let thumb = direct_tim(512, 128).thumbnail(256).unwrap();
+        assert_eq!(thumb.dimensions(), (256, 64));
+
+        let thumb = direct_tim(32, 32).thumbnail(256).unwrap();
+        assert_eq!(thumb.dimensions(), (32, 32));
+    }
+
+    fn indexed_tim(pixel_mode: PixelMode, clut_rows: u16, pixels: Vec<u8>) -> Tim {
+        let clut_width = if pixel_mode == PixelMode::Clut4Bit {
+            16
+        } else {
+            256
+        };
+        Tim {
+            pixel_mode,
+            has_clut: true,
+            clut: Some(ClutData {
+                vram_pos: (0, 0),
+                dimensions: (clut_width, clut_rows),
+                data: (0..clut_width * clut_rows).collect(),
+            }),
+            pixels: PixelData {
+                vram_pos: (0, 0),
+                dimensions: (1, 1),
+                data: pixels,
+            },
+        }
+    }
+
+    #[test]
+    fn test_to_indexed_4bit() {
+        let tim = indexed_tim(PixelMode::Clut4Bit, 2, vec![0x21, 0x43]);
+
+        let (palette, indices) = tim.to_indexed(1).unwrap();
+        assert_eq!(palette.len(), 16);
+        // Row 1 starts at color 16 (RGB555 0x0010 = red 128)
+        assert_eq!(palette[0], [128, 0, 0, 255]);
+        assert_eq!(indices, vec![1, 2, 3, 4]);
+
+        assert!(tim.to_indexed(2).is_err());
+    }
+
+    #[test]
+    fn test_to_indexed_8bit() {
+        let tim = indexed_tim(PixelMode::Clut8Bit, 1, vec![0, 255]);
+
+        let (palette, indices) = tim.to_indexed(0).unwrap();
+        assert_eq!(palette.len(), 256);
+        // Color 0 is black with STP clear: the transparency key
+        assert_eq!(palette[0][3], 0);
+        assert_eq!(indices, vec![0, 255]);
+    }
+
+    #[test]
+    fn test_to_indexed_direct_color_fails() {
+        let mut tim = indexed_tim(PixelMode::Clut8Bit, 1, vec![0, 0]);
+        tim.pixel_mode = PixelMode::Direct16Bit;
+        assert!(tim.to_indexed(0).is_err());
+    }
+
+    #[test]
+    fn test_two_row_clut() {
+        let tim = indexed_tim(PixelMode::Clut4Bit, 2, vec![0x11]);
+        let clut = tim.clut.as_ref().unwrap();
+
+        assert_eq!(clut.palette_count(), 2);
+        assert_eq!(clut.palette(0), &(0..16).collect::<Vec<u16>>()[..]);
+        assert_eq!(clut.palette(1), &(16..32).collect::<Vec<u16>>()[..]);
+        assert!(clut.palette(2).is_empty());
+
+        // Index 1: RGB555 0x0001 in row 0, 0x0011 in row 1
+        assert_eq!(tim.to_rgba8().unwrap()[0..4], [8, 0, 0, 255]);
+        assert_eq!(
+            tim.to_rgba8_with_palette(1).unwrap()[0..4],
+            [136, 0, 0, 255]
+        );
+        assert!(tim.to_rgba8_with_palette(2).is_err());
+    }
+
+    #[test]
+    fn test_from_indexed_packs_4bit() {
+        let clut: Vec<u16> = (0..16).collect();
+        let tim =
+            Tim::from_indexed(4, 2, PixelMode::Clut4Bit, &clut, &[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+
+        assert_eq!(tim.width(), 4);
+        assert_eq!(tim.height(), 2);
+        assert_eq!(tim.pixels.data, vec![0x21, 0x43, 0x65, 0x87]);
+        assert_eq!(tim.clut.as_ref().unwrap().dimensions, (16, 1));
+
+        // Width must fill whole 16-bit words
+        assert!(Tim::from_indexed(3, 1, PixelMode::Clut4Bit, &clut, &[0; 3]).is_err());
+        assert!(Tim::from_indexed(2, 1, PixelMode::Direct16Bit, &clut, &[0; 2]).is_err());
+    }
+
+    #[test]
+    fn test_sub_rgba8() {
+        // 8x4 4-bit texture, each 4x2 quadrant a different palette color
+        let mut clut = vec![0x001F, 0x03E0, 0x7C00, 0x7FFF];
+        clut.resize(16, 0);
+        let indices: Vec<u8> = (0..4)
+            .flat_map(|y| (0..8).map(move |x| (y / 2) * 2 + x / 4))
+            .collect();
+        let tim = Tim::from_indexed(8, 4, PixelMode::Clut4Bit, &clut, &indices).unwrap();
+
+        let colors = [0x001F, 0x03E0, 0x7C00, 0x7FFF]
+            .map(|color| rgb555_to_rgba(color, TimAlphaMode::default()));
+
+        // Top-right quadrant
+        let cropped = tim.sub_rgba8(4, 0, 4, 2).unwrap();
+        assert_eq!(cropped.len(), 4 * 2 * 4);
+        assert!(cropped.chunks_exact(4).all(|pixel| pixel == colors[1]));
+
+        // Straddling all four quadrants keeps row order
+        let cropped = tim.sub_rgba8(3, 1, 2, 2).unwrap();
+        assert_eq!(cropped, colors.concat());
+
+        assert!(tim.sub_rgba8(5, 0, 4, 2).is_err());
+        assert!(tim.sub_rgba8(0, 3, 1, 2).is_err());
+        assert!(tim.sub_rgba8(0, 0, 0, 1).is_err());
+    }
+
+    #[test]
+    fn test_to_bytes_round_trip() {
+        // 8-bit TIM with a 2-row, 4-color CLUT and 4x2 pixels
+        let mut data = Vec::new();
+        data.extend_from_slice(&TIM_MAGIC.to_le_bytes());
+        data.extend_from_slice(&0x09u32.to_le_bytes());
+        data.extend_from_slice(&(12u32 + 16).to_le_bytes());
+        for value in [0u16, 480, 4, 2] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        for color in [
+            0x0000u16, 0x7FFF, 0x001F, 0x83E0, 0x7C00, 0x0001, 0x0002, 0x0003,
+        ] {
+            data.extend_from_slice(&color.to_le_bytes());
+        }
+        data.extend_from_slice(&(12u32 + 8).to_le_bytes());
+        for value in [320u16, 0, 2, 2] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&[0, 1, 2, 3, 3, 2, 1, 0]);
+
+        let tim = Tim::parse(&data).unwrap();
+        let bytes = tim.to_bytes();
+        assert_eq!(bytes, data);
+        assert_eq!(Tim::parse(&bytes).unwrap(), tim);
+
+        let rebuilt = Tim::from_indexed(
+            4,
+            2,
+            PixelMode::Clut8Bit,
+            &[0, 1, 2, 3],
+            &[0, 1, 2, 3, 3, 2, 1, 0],
+        )
+        .unwrap();
+        assert_eq!(Tim::parse(&rebuilt.to_bytes()).unwrap(), rebuilt);
+    }
+
+    #[test]
+    fn test_parse_multi_row_8bit_clut() {
+        // Three 256-color palette rows, well past a single row's 512 bytes
+        let clut: Vec<u16> = (0..768).collect();
+        let indices: Vec<u8> = (0..8).collect();
+        let tim = Tim::from_indexed(4, 2, PixelMode::Clut8Bit, &clut, &indices).unwrap();
+
+        let parsed = Tim::parse(&tim.to_bytes()).unwrap();
+        assert_eq!(parsed, tim);
+
+        let parsed_clut = parsed.clut.as_ref().unwrap();
+        assert_eq!(parsed_clut.dimensions, (256, 3));
+        assert_eq!(parsed_clut.palette_count(), 3);
+        assert_eq!(parsed_clut.palette(2)[0], 512);
+
+        // A CLUT header claiming more rows than the block holds is rejected
+        let mut bytes = tim.to_bytes();
+        bytes[18..20].copy_from_slice(&4u16.to_le_bytes());
+        assert!(Tim::parse(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_to_rgba8_with_alpha() {
+        let mut data = Vec::new();
+        // Black, black + STP, red + STP, red
+        for color in [0x0000u16, 0x8000, 0x801F, 0x001F] {
+            data.extend_from_slice(&color.to_le_bytes());
+        }
+        let tim = Tim {
+            pixel_mode: PixelMode::Direct16Bit,
+            has_clut: false,
+            clut: None,
+            pixels: PixelData {
+                vram_pos: (0, 0),
+                dimensions: (4, 1),
+                data,
+            },
+        };
+
+        let alpha = |mode| -> Vec<u8> {
+            tim.to_rgba8_with_alpha(mode)
+                .unwrap()
+                .chunks_exact(4)
+                .map(|px| px[3])
+                .collect()
+        };
+
+        assert_eq!(alpha(TimAlphaMode::StpAsBinary), vec![0, 255, 254, 255]);
+        assert_eq!(
+            alpha(TimAlphaMode::BlackIsTransparent),
+            vec![0, 0, 255, 255]
+        );
+        assert_eq!(alpha(TimAlphaMode::Opaque), vec![255, 255, 255, 255]);
+        assert_eq!(alpha(TimAlphaMode::SemiTransparent), vec![0, 255, 128, 255]);
+        assert_eq!(
+            tim.to_rgba8().unwrap(),
+            tim.to_rgba8_with_alpha(TimAlphaMode::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_24bit_width_skips_row_padding() {
+        // 4 words per row hold two RGB888 pixels plus 2 padding bytes
+        let mut data = Vec::new();
+        for row in [[1u8, 2, 3, 4, 5, 6], [7, 8, 9, 10, 11, 12]] {
+            data.extend_from_slice(&row);
+            data.extend_from_slice(&[0xEE, 0xEE]);
+        }
+        let tim = Tim {
+            pixel_mode: PixelMode::Direct24Bit,
+            has_clut: false,
+            clut: None,
+            pixels: PixelData {
+                vram_pos: (0, 0),
+                dimensions: (4, 2),
+                data,
+            },
+        };
+
+        assert_eq!(tim.width(), 2);
+        let rgba = tim.to_rgba8().unwrap();
+        assert_eq!(rgba.len(), tim.width() as usize * tim.height() as usize * 4);
+        assert_eq!(
+            rgba,
+            [1, 2, 3, 255, 4, 5, 6, 255, 7, 8, 9, 255, 10, 11, 12, 255]
+        );
+    }
+
+    /// Mixed-mode (4) TIM bytes with an optional CLUT and one row of pixels
+    fn mixed_tim(clut: Option<&[u16]>, words: &[u16]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&TIM_MAGIC.to_le_bytes());
+        let flags: u32 = if clut.is_some() { 0x0C } else { 0x04 };
+        data.extend_from_slice(&flags.to_le_bytes());
+
+        if let Some(clut) = clut {
+            data.extend_from_slice(&(12 + clut.len() as u32 * 2).to_le_bytes());
+            for value in [0u16, 480, clut.len() as u16, 1] {
+                data.extend_from_slice(&value.to_le_bytes());
+            }
+            for color in clut {
+                data.extend_from_slice(&color.to_le_bytes());
+            }
+        }
+
+        data.extend_from_slice(&(12 + words.len() as u32 * 2).to_le_bytes());
+        for value in [0u16, 0, words.len() as u16, 1] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        for word in words {
+            data.extend_from_slice(&word.to_le_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn test_mixed_mode_layouts() {
+        // 16-color CLUT: one word holds four 4-bit indices (0, 1, 2, 3)
+        let clut: Vec<u16> = (0..16).map(|i| 0x8000 | i).collect();
+        let data = mixed_tim(Some(&clut), &[0x3210]);
+        let tim = Tim::parse(&data).unwrap();
+
+        assert_eq!(tim.pixel_mode, PixelMode::Mixed);
+        assert_eq!(tim.mixed_layout(), Some(MixedLayout::Indexed4));
+        assert_eq!(tim.width(), 4);
+        assert_eq!(Tim::validate(&data).unwrap().0, 4);
+
+        let rgba = tim.to_rgba8().unwrap();
+        assert_eq!(rgba.len(), 4 * 4);
+        for (pixel, &color) in rgba.chunks_exact(4).zip(&clut) {
+            assert_eq!(
+                pixel,
+                convert::rgb555_to_rgba(color, TimAlphaMode::default())
+            );
+        }
+        assert_eq!(tim.to_indexed(0).unwrap().1, vec![0, 1, 2, 3]);
+
+        // Without a CLUT the words are direct colors
+        let tim = Tim::parse(&mixed_tim(None, &[0x001F, 0x7C00])).unwrap();
+        assert_eq!(tim.mixed_layout(), Some(MixedLayout::Direct16));
+        assert_eq!(tim.width(), 2);
+        assert_eq!(
+            tim.to_rgba8().unwrap(),
+            [248, 0, 0, 255, 0, 0, 248, 255].to_vec()
+        );
+        assert!(tim.to_indexed(0).is_err());
+
+        let mut direct = tim.clone();
+        direct.pixel_mode = PixelMode::Direct16Bit;
+        assert_eq!(direct.mixed_layout(), None);
+    }
 }
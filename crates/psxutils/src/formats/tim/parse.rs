@@ -1,24 +1,29 @@
 //! TIM format parsing logic
 
 use super::types::*;
+use crate::prelude::*;
 use crate::{PsxError, Result};
 
 impl Tim {
     /// Parse a TIM file from bytes
     pub fn parse(data: &[u8]) -> Result<Self> {
         if data.len() < 8 {
-            return Err(PsxError::InvalidFormat("TIM file too small".to_string()));
+            return Err(PsxError::Truncated {
+                what: "TIM header",
+                needed: 8,
+                got: data.len(),
+            });
         }
 
         // Parse header
-        let header: &TimHeader = bytemuck::try_from_bytes(&data[0..8])
-            .map_err(|e| PsxError::ParseError(format!("Failed to parse TIM header: {}", e)))?;
+        let header: TimHeader = bytemuck::pod_read_unaligned(&data[0..8]);
 
         if header.magic != TIM_MAGIC {
-            return Err(PsxError::InvalidFormat(format!(
-                "Invalid TIM magic: 0x{:08X}, expected 0x{:08X}",
-                header.magic, TIM_MAGIC
-            )));
+            return Err(PsxError::BadMagic {
+                format: "TIM",
+                expected: TIM_MAGIC,
+                found: header.magic,
+            });
         }
 
         let pixel_mode = header.pixel_mode()?;
@@ -29,20 +34,22 @@ impl Tim {
         // Parse CLUT if present
         let clut = if has_clut {
             if data.len() < offset + 12 {
-                return Err(PsxError::InvalidFormat(
-                    "TIM file truncated (CLUT header)".to_string(),
-                ));
+                return Err(PsxError::Truncated {
+                    what: "TIM CLUT header",
+                    needed: offset + 12,
+                    got: data.len(),
+                });
             }
 
-            let clut_header: &ClutHeader = bytemuck::try_from_bytes(&data[offset..offset + 12])
-                .map_err(|e| PsxError::ParseError(format!("Failed to parse CLUT header: {}", e)))?;
+            let clut_header: ClutHeader = bytemuck::pod_read_unaligned(&data[offset..offset + 12]);
 
             offset += 12;
 
             let clut_data_size = (clut_header.size as usize).saturating_sub(12);
 
-            // Sanity check for CLUT size
-            const MAX_CLUT_SIZE: usize = 256 * 2; // 256 colors * 2 bytes max
+            // Sanity check for CLUT size; multi-row CLUTs hold one palette per
+            // row, so the bound comes from the header dimensions
+            const MAX_CLUT_SIZE: usize = MAX_TIM_WORD_WIDTH as usize * 2 * MAX_TIM_HEIGHT as usize;
             if clut_data_size > MAX_CLUT_SIZE {
                 return Err(PsxError::InvalidFormat(format!(
                     "TIM CLUT data size too large: {} bytes (max {} bytes)",
@@ -50,10 +57,20 @@ impl Tim {
                 )));
             }
 
+            let clut_table_size = clut_header.width as usize * clut_header.height as usize * 2;
+            if clut_table_size > clut_data_size {
+                return Err(PsxError::InvalidFormat(format!(
+                    "TIM CLUT block too small: {}x{} colors need {} bytes, block has {}",
+                    clut_header.width, clut_header.height, clut_table_size, clut_data_size
+                )));
+            }
+
             if data.len() < offset + clut_data_size {
-                return Err(PsxError::InvalidFormat(
-                    "TIM file truncated (CLUT data)".to_string(),
-                ));
+                return Err(PsxError::Truncated {
+                    what: "TIM CLUT data",
+                    needed: offset + clut_data_size,
+                    got: data.len(),
+                });
             }
 
             let clut_data = data[offset..offset + clut_data_size]
@@ -74,13 +91,14 @@ impl Tim {
 
         // Parse pixel data
         if data.len() < offset + 12 {
-            return Err(PsxError::InvalidFormat(
-                "TIM file truncated (pixel header)".to_string(),
-            ));
+            return Err(PsxError::Truncated {
+                what: "TIM pixel header",
+                needed: offset + 12,
+                got: data.len(),
+            });
         }
 
-        let pixel_header: &PixelHeader = bytemuck::try_from_bytes(&data[offset..offset + 12])
-            .map_err(|e| PsxError::ParseError(format!("Failed to parse pixel header: {}", e)))?;
+        let pixel_header: PixelHeader = bytemuck::pod_read_unaligned(&data[offset..offset + 12]);
 
         offset += 12;
 
@@ -97,9 +115,11 @@ impl Tim {
         }
 
         if data.len() < offset + pixel_data_size {
-            return Err(PsxError::InvalidFormat(
-                "TIM file truncated (pixel data)".to_string(),
-            ));
+            return Err(PsxError::Truncated {
+                what: "TIM pixel data",
+                needed: offset + pixel_data_size,
+                got: data.len(),
+            });
         }
 
         let pixel_data = data[offset..offset + pixel_data_size].to_vec();
@@ -125,18 +145,22 @@ impl Tim {
     /// the size of the complete TIM file in bytes.
     pub fn validate(data: &[u8]) -> Result<(u16, u16, usize)> {
         if data.len() < 8 {
-            return Err(PsxError::InvalidFormat("TIM file too small".to_string()));
+            return Err(PsxError::Truncated {
+                what: "TIM header",
+                needed: 8,
+                got: data.len(),
+            });
         }
 
         // Parse header
-        let header: &TimHeader = bytemuck::try_from_bytes(&data[0..8])
-            .map_err(|e| PsxError::ParseError(format!("Failed to parse TIM header: {}", e)))?;
+        let header: TimHeader = bytemuck::pod_read_unaligned(&data[0..8]);
 
         if header.magic != TIM_MAGIC {
-            return Err(PsxError::InvalidFormat(format!(
-                "Invalid TIM magic: 0x{:08X}, expected 0x{:08X}",
-                header.magic, TIM_MAGIC
-            )));
+            return Err(PsxError::BadMagic {
+                format: "TIM",
+                expected: TIM_MAGIC,
+                found: header.magic,
+            });
         }
 
         // Validate flags field more strictly (from jPSXdec TimValidator line 84)
@@ -153,17 +177,19 @@ impl Tim {
 
         let mut offset = 8;
         let mut total_size = 8; // Header size
+        let mut clut_width = None;
 
         // Validate CLUT if present (without reading data)
         if has_clut {
             if data.len() < offset + 12 {
-                return Err(PsxError::InvalidFormat(
-                    "TIM file truncated (CLUT header)".to_string(),
-                ));
+                return Err(PsxError::Truncated {
+                    what: "TIM CLUT header",
+                    needed: offset + 12,
+                    got: data.len(),
+                });
             }
 
-            let clut_header: &ClutHeader = bytemuck::try_from_bytes(&data[offset..offset + 12])
-                .map_err(|e| PsxError::ParseError(format!("Failed to parse CLUT header: {}", e)))?;
+            let clut_header: ClutHeader = bytemuck::pod_read_unaligned(&data[offset..offset + 12]);
 
             offset += 12;
 
@@ -194,24 +220,28 @@ impl Tim {
             }
 
             if data.len() < offset + clut_data_size {
-                return Err(PsxError::InvalidFormat(
-                    "TIM file truncated (CLUT data)".to_string(),
-                ));
+                return Err(PsxError::Truncated {
+                    what: "TIM CLUT data",
+                    needed: offset + clut_data_size,
+                    got: data.len(),
+                });
             }
 
             offset += clut_data_size;
             total_size += 12 + clut_data_size;
+            clut_width = Some(clut_header.width);
         }
 
         // Validate pixel data (without reading data)
         if data.len() < offset + 12 {
-            return Err(PsxError::InvalidFormat(
-                "TIM file truncated (pixel header)".to_string(),
-            ));
+            return Err(PsxError::Truncated {
+                what: "TIM pixel header",
+                needed: offset + 12,
+                got: data.len(),
+            });
         }
 
-        let pixel_header: &PixelHeader = bytemuck::try_from_bytes(&data[offset..offset + 12])
-            .map_err(|e| PsxError::ParseError(format!("Failed to parse pixel header: {}", e)))?;
+        let pixel_header: PixelHeader = bytemuck::pod_read_unaligned(&data[offset..offset + 12]);
 
         offset += 12;
 
@@ -242,9 +272,11 @@ impl Tim {
         }
 
         if data.len() < offset + pixel_data_size {
-            return Err(PsxError::InvalidFormat(
-                "TIM file truncated (pixel data)".to_string(),
-            ));
+            return Err(PsxError::Truncated {
+                what: "TIM pixel data",
+                needed: offset + pixel_data_size,
+                got: data.len(),
+            });
         }
 
         // Check consistency (jPSXdec TimValidator lines 242-252)
@@ -268,7 +300,9 @@ impl Tim {
             PixelMode::Clut8Bit => pixel_header.width * 2, // iWordWidth * 2
             PixelMode::Direct16Bit => pixel_header.width,  // iWordWidth
             PixelMode::Direct24Bit => pixel_header.width * 2 / 3, // iWordWidth * 2 / 3
-            PixelMode::Mixed => pixel_header.width,
+            PixelMode::Mixed => {
+                pixel_header.width * MixedLayout::from_clut_width(clut_width).pixels_per_word()
+            }
         };
 
         Ok((width, pixel_header.height, total_size))
@@ -279,10 +313,23 @@ impl Tim {
         match self.pixel_mode {
             PixelMode::Clut4Bit => self.pixels.dimensions.0 * 4, // 4 pixels per 16-bit word
             PixelMode::Clut8Bit => self.pixels.dimensions.0 * 2, // 2 pixels per 16-bit word
+            PixelMode::Direct24Bit => self.pixels.dimensions.0 * 2 / 3, // 3 bytes per pixel
+            PixelMode::Mixed => {
+                let layout = self.mixed_layout().unwrap_or(MixedLayout::Direct16);
+                self.pixels.dimensions.0 * layout.pixels_per_word()
+            }
             _ => self.pixels.dimensions.0,
         }
     }
 
+    /// Pixel layout of a mixed-mode TIM, see [`MixedLayout`]
+    ///
+    /// Returns `None` for the other pixel modes.
+    pub fn mixed_layout(&self) -> Option<MixedLayout> {
+        (self.pixel_mode == PixelMode::Mixed)
+            .then(|| MixedLayout::from_clut_width(self.clut.as_ref().map(|clut| clut.dimensions.0)))
+    }
+
     /// Get the height in pixels
     pub fn height(&self) -> u16 {
         self.pixels.dimensions.1
@@ -1,5 +1,6 @@
 //! TIM format type definitions
 
+use crate::prelude::*;
 use crate::{PsxError, Result};
 use bitflags::bitflags;
 use bytemuck::{Pod, Zeroable};
@@ -13,6 +14,7 @@ pub(super) const MAX_TIM_HEIGHT: u16 = 8192;
 
 /// TIM pixel modes
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PixelMode {
     /// 4-bit indexed color (16 colors, requires CLUT)
     Clut4Bit = 0,
@@ -22,7 +24,7 @@ pub enum PixelMode {
     Direct16Bit = 2,
     /// 24-bit direct color (RGB888)
     Direct24Bit = 3,
-    /// Mixed mode (rare, used for special effects)
+    /// Mixed mode (rare, used for special effects); see [`MixedLayout`]
     Mixed = 4,
 }
 
@@ -34,10 +36,7 @@ impl PixelMode {
             2 => Ok(Self::Direct16Bit),
             3 => Ok(Self::Direct24Bit),
             4 => Ok(Self::Mixed),
-            _ => Err(PsxError::InvalidFormat(format!(
-                "Invalid TIM pixel mode: {}",
-                value
-            ))),
+            mode => Err(PsxError::UnsupportedMode(mode as u8)),
         }
     }
 
@@ -53,6 +52,66 @@ impl PixelMode {
     }
 }
 
+/// Pixel layout of a [`PixelMode::Mixed`] TIM
+///
+/// Mode 4 only says the block isn't one of the standard depths. The mixed
+/// TIMs handled here are VRAM blocks that hold either plain 16-bit color or
+/// indices into the CLUT stored with them, and the CLUT row width tells
+/// which: 16 entries means 4-bit indices, 256 means 8-bit, and any other
+/// width (or no CLUT at all) means 16-bit direct color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MixedLayout {
+    /// 16-bit direct color (RGB555), any CLUT is ignored
+    Direct16,
+    /// 4-bit indices into a 16-color CLUT row
+    Indexed4,
+    /// 8-bit indices into a 256-color CLUT row
+    Indexed8,
+}
+
+impl MixedLayout {
+    /// Layout implied by the CLUT row width, if the TIM has a CLUT
+    pub(super) const fn from_clut_width(clut_width: Option<u16>) -> Self {
+        match clut_width {
+            Some(16) => Self::Indexed4,
+            Some(256) => Self::Indexed8,
+            _ => Self::Direct16,
+        }
+    }
+
+    /// Pixels stored in each 16-bit VRAM word
+    pub const fn pixels_per_word(&self) -> u16 {
+        match self {
+            Self::Direct16 => 1,
+            Self::Indexed4 => 4,
+            Self::Indexed8 => 2,
+        }
+    }
+}
+
+/// How the STP (semi-transparency) bit and black pixels map to alpha
+///
+/// On PSX hardware, a 15-bit color of 0x0000 is never drawn (transparency key)
+/// and the STP bit enables blending for non-black pixels when the primitive is
+/// semi-transparent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimAlphaMode {
+    /// Black with STP clear is transparent, everything else is opaque.
+    /// STP colors get alpha 254 so blending candidates stay distinguishable.
+    #[default]
+    StpAsBinary,
+    /// Any black pixel (including palette index 0 when black) is transparent,
+    /// regardless of STP
+    BlackIsTransparent,
+    /// Every pixel is fully opaque
+    Opaque,
+    /// Black with STP clear is transparent and STP colors are 50% alpha,
+    /// approximating the default B/2+F/2 blend
+    SemiTransparent,
+}
+
 /// TIM file header
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Pod, Zeroable)]
@@ -119,7 +178,8 @@ pub(super) struct PixelHeader {
 }
 
 /// Parsed TIM texture
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tim {
     /// Pixel mode (color depth)
     pub pixel_mode: PixelMode,
@@ -132,7 +192,8 @@ pub struct Tim {
 }
 
 /// Color Lookup Table data
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClutData {
     /// Position in VRAM
     pub vram_pos: (u16, u16),
@@ -142,13 +203,32 @@ pub struct ClutData {
     pub data: Vec<u16>,
 }
 
+impl ClutData {
+    /// Number of palettes stacked in this CLUT (one per row)
+    pub fn palette_count(&self) -> usize {
+        self.dimensions.1 as usize
+    }
+
+    /// Get the colors of one palette row
+    ///
+    /// Returns an empty slice if `row` is out of range.
+    pub fn palette(&self, row: usize) -> &[u16] {
+        let width = self.dimensions.0 as usize;
+        let start = (row * width).min(self.data.len());
+        let end = (start + width).min(self.data.len());
+        &self.data[start..end]
+    }
+}
+
 /// Pixel data
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PixelData {
     /// Position in VRAM
     pub vram_pos: (u16, u16),
     /// Dimensions (width x height in pixels)
     pub dimensions: (u16, u16),
     /// Raw pixel data
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub data: Vec<u8>,
 }
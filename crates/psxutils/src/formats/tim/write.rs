@@ -0,0 +1,141 @@
+//! TIM serialization and construction
+
+use super::types::*;
+use crate::prelude::*;
+use crate::{PsxError, Result};
+
+impl Tim {
+    /// Serialize to TIM file bytes
+    ///
+    /// Block `size` fields are computed from the CLUT and pixel data, so a
+    /// modified `Tim` always produces a consistent file.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let clut_size = self
+            .clut
+            .as_ref()
+            .map_or(0, |clut| 12 + clut.data.len() * 2);
+        let mut bytes = Vec::with_capacity(8 + clut_size + 12 + self.pixels.data.len());
+
+        let mut flags = self.pixel_mode as u32;
+        if self.clut.is_some() {
+            flags |= TimHeaderFlags::HAS_CLUT.bits();
+        }
+
+        bytes.extend_from_slice(&TIM_MAGIC.to_le_bytes());
+        bytes.extend_from_slice(&flags.to_le_bytes());
+
+        if let Some(clut) = &self.clut {
+            let header = ClutHeader {
+                size: clut_size as u32,
+                vram_x: clut.vram_pos.0,
+                vram_y: clut.vram_pos.1,
+                width: clut.dimensions.0,
+                height: clut.dimensions.1,
+            };
+            bytes.extend_from_slice(bytemuck::bytes_of(&header));
+
+            for color in &clut.data {
+                bytes.extend_from_slice(&color.to_le_bytes());
+            }
+        }
+
+        let header = PixelHeader {
+            size: (12 + self.pixels.data.len()) as u32,
+            vram_x: self.pixels.vram_pos.0,
+            vram_y: self.pixels.vram_pos.1,
+            width: self.pixels.dimensions.0,
+            height: self.pixels.dimensions.1,
+        };
+        bytes.extend_from_slice(bytemuck::bytes_of(&header));
+        bytes.extend_from_slice(&self.pixels.data);
+
+        bytes
+    }
+
+    /// Build an indexed TIM from palette indices
+    ///
+    /// # Arguments
+    /// * `width` - Width in pixels (multiple of 4 for 4-bit, 2 for 8-bit)
+    /// * `height` - Height in pixels
+    /// * `mode` - `PixelMode::Clut4Bit` or `PixelMode::Clut8Bit`
+    /// * `clut` - RGB555 colors; more than 16/256 entries are split into
+    ///   multiple palette rows
+    /// * `indices` - One palette index per pixel, row-major
+    ///
+    /// 4-bit indices are packed two per byte, low nibble first. VRAM positions
+    /// are left at (0, 0).
+    pub fn from_indexed(
+        width: u16,
+        height: u16,
+        mode: PixelMode,
+        clut: &[u16],
+        indices: &[u8],
+    ) -> Result<Self> {
+        let (colors, pixels_per_word) = match mode {
+            PixelMode::Clut4Bit => (16, 4),
+            PixelMode::Clut8Bit => (256, 2),
+            _ => {
+                return Err(PsxError::InvalidFormat(format!(
+                    "{:?} is not an indexed pixel mode",
+                    mode
+                )))
+            }
+        };
+
+        if width == 0 || height == 0 || !width.is_multiple_of(pixels_per_word) {
+            return Err(PsxError::InvalidFormat(format!(
+                "Invalid {:?} TIM dimensions: {}x{} (width must be a multiple of {})",
+                mode, width, height, pixels_per_word
+            )));
+        }
+
+        if indices.len() != width as usize * height as usize {
+            return Err(PsxError::InvalidFormat(format!(
+                "Expected {} indices for {}x{} TIM, got {}",
+                width as usize * height as usize,
+                width,
+                height,
+                indices.len()
+            )));
+        }
+
+        let clut_width = clut.len().min(colors);
+        if clut_width == 0 || !clut.len().is_multiple_of(clut_width) {
+            return Err(PsxError::InvalidFormat(format!(
+                "CLUT of {} colors cannot be split into {}-color rows",
+                clut.len(),
+                colors
+            )));
+        }
+
+        if let Some(&index) = indices.iter().find(|&&i| i as usize >= clut_width) {
+            return Err(PsxError::InvalidFormat(format!(
+                "Palette index {} out of range ({} colors)",
+                index, clut_width
+            )));
+        }
+
+        let data = match mode {
+            PixelMode::Clut4Bit => indices
+                .chunks_exact(2)
+                .map(|pair| pair[0] | (pair[1] << 4))
+                .collect(),
+            _ => indices.to_vec(),
+        };
+
+        Ok(Tim {
+            pixel_mode: mode,
+            has_clut: true,
+            clut: Some(ClutData {
+                vram_pos: (0, 0),
+                dimensions: (clut_width as u16, (clut.len() / clut_width) as u16),
+                data: clut.to_vec(),
+            }),
+            pixels: PixelData {
+                vram_pos: (0, 0),
+                dimensions: (width / pixels_per_word, height),
+                data,
+            },
+        })
+    }
+}
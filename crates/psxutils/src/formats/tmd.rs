@@ -24,13 +24,21 @@
 //! Primitive Data: Variable format based on primitive type
 //! ```
 
+use crate::prelude::*;
 use crate::{PsxError, Result};
 
 /// TMD format magic number
 pub const TMD_MAGIC: u32 = 0x00000041;
 
+/// Primitive mode family for lines (top 3 bits of the mode byte)
+const PRIM_LINE: u8 = 0x40;
+
+/// Primitive mode family for 3D sprites (top 3 bits of the mode byte)
+const PRIM_SPRITE: u8 = 0x60;
+
 /// TMD model file
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tmd {
     /// Flags from header
     pub flags: u32,
@@ -40,6 +48,7 @@ pub struct Tmd {
 
 /// Single object (mesh) in a TMD file
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TmdObject {
     /// Vertices
     pub vertices: Vec<TmdVertex>,
@@ -51,8 +60,37 @@ pub struct TmdObject {
     pub scale: i32,
 }
 
+impl TmdObject {
+    /// Divisor applied to raw coordinates (a scale of 0 means 1)
+    pub fn scale_factor(&self) -> f32 {
+        if self.scale == 0 {
+            1.0
+        } else {
+            self.scale as f32
+        }
+    }
+
+    /// Axis-aligned bounding box `(min, max)` of the scaled vertices
+    ///
+    /// Returns `None` for an object without vertices.
+    pub fn bounds(&self) -> Option<([f32; 3], [f32; 3])> {
+        let scale = self.scale_factor();
+        self.vertices.iter().fold(None, |bounds, v| {
+            let p = [v.x as f32 / scale, v.y as f32 / scale, v.z as f32 / scale];
+            Some(match bounds {
+                None => (p, p),
+                Some((min, max)) => (
+                    core::array::from_fn(|i| min[i].min(p[i])),
+                    core::array::from_fn(|i| max[i].max(p[i])),
+                ),
+            })
+        })
+    }
+}
+
 /// 3D vertex position
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TmdVertex {
     pub x: i16,
     pub y: i16,
@@ -61,6 +99,7 @@ pub struct TmdVertex {
 
 /// 3D normal vector
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TmdNormal {
     pub nx: i16,
     pub ny: i16,
@@ -69,6 +108,7 @@ pub struct TmdNormal {
 
 /// Primitive (polygon) in TMD
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TmdPrimitive {
     /// Triangle with 3 vertices
     Triangle {
@@ -96,10 +136,45 @@ pub enum TmdPrimitive {
         /// Texture page/CLUT info
         texture_info: Option<TextureInfo>,
     },
+    /// Line segment between 2 vertices
+    Line {
+        /// Vertex indices
+        vertices: [u16; 2],
+        /// Line colors (one per end)
+        colors: Option<[(u8, u8, u8); 2]>,
+    },
+}
+
+/// How colors are stored in a primitive packet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColorMode {
+    /// No color words (textured primitives)
+    None,
+    /// One color shared by all vertices
+    Flat,
+    /// One color per vertex
+    PerVertex,
+}
+
+/// How normal indices are stored in a primitive packet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NormalMode {
+    /// No normals (unlit primitives)
+    None,
+    /// One normal shared by all vertices
+    Flat,
+    /// One normal per vertex
+    PerVertex,
+}
+
+/// True if a per-vertex attribute isn't the same for every vertex
+fn varies<T: PartialEq>(values: &[T]) -> bool {
+    values.windows(2).any(|pair| pair[0] != pair[1])
 }
 
 /// Texture page and CLUT (Color Lookup Table) information
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextureInfo {
     /// CLUT X coordinate (in VRAM)
     pub clut_x: u16,
@@ -109,29 +184,189 @@ pub struct TextureInfo {
     pub tpage: u16,
 }
 
+/// Primitive statistics for one TMD object
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TmdObjectStats {
+    /// Triangle primitives
+    pub triangles: usize,
+    /// Quad primitives
+    pub quads: usize,
+    /// Line primitives
+    pub lines: usize,
+    /// Triangles and quads with texture coordinates
+    pub textured: usize,
+    /// Triangles, quads and lines whose normals or colors vary per vertex
+    pub gouraud: usize,
+    /// Vertices in the object
+    pub vertices: usize,
+    /// Normals in the object
+    pub normals: usize,
+}
+
+/// A primitive vertex index past the end of its object's vertex list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DanglingIndex {
+    /// Object containing the primitive
+    pub object: usize,
+    /// Primitive within the object
+    pub primitive: usize,
+    /// The out-of-range vertex index
+    pub index: u16,
+}
+
+/// Primitive statistics and index validation for a whole TMD
+///
+/// Built by [`Tmd::stats`]. A model with dangling vertex indices can't be
+/// converted as-is; check [`TmdStats::is_valid`] before exporting it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TmdStats {
+    /// Per-object counts, in object order
+    pub objects: Vec<TmdObjectStats>,
+    /// Vertices across all objects
+    pub total_vertices: usize,
+    /// Normals across all objects
+    pub total_normals: usize,
+    /// Every out-of-range vertex index, in primitive order
+    pub dangling: Vec<DanglingIndex>,
+}
+
+impl TmdStats {
+    /// True if every primitive only references vertices of its own object
+    pub fn is_valid(&self) -> bool {
+        self.dangling.is_empty()
+    }
+}
+
 impl Tmd {
     /// Parse a TMD file from bytes
     ///
     /// Parses standard PSX TMD format with magic number 0x00000041.
     pub fn parse(data: &[u8]) -> Result<Self> {
         if data.len() < 12 {
-            return Err(PsxError::ParseError(
-                "TMD file too small for header".to_string(),
-            ));
+            return Err(PsxError::Truncated {
+                what: "TMD header",
+                needed: 12,
+                got: data.len(),
+            });
         }
 
         // Check magic at offset 0
         let magic = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
         if magic != TMD_MAGIC {
-            return Err(PsxError::ParseError(format!(
-                "Invalid TMD magic number: expected {:#010x}, found {:#010x}",
-                TMD_MAGIC, magic
-            )));
+            return Err(PsxError::BadMagic {
+                format: "TMD",
+                expected: TMD_MAGIC,
+                found: magic,
+            });
         }
 
         Self::parse_standard_tmd(data)
     }
 
+    /// Validate TMD format without parsing vertex or primitive data
+    ///
+    /// TMD files have no explicit size field, so the extent is calculated from
+    /// the object table: the furthest end of any object's vertex, normal and
+    /// primitive data. Primitive packets are variable-sized, so their headers
+    /// are walked the same way [`Tmd::parse`] does (clamped to the available
+    /// data).
+    ///
+    /// Returns `Ok((object_count, total_size))` if valid.
+    pub fn validate(data: &[u8]) -> Result<(u32, usize)> {
+        if data.len() < 12 {
+            return Err(PsxError::Truncated {
+                what: "TMD header",
+                needed: 12,
+                got: data.len(),
+            });
+        }
+
+        let magic = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+        if magic != TMD_MAGIC {
+            return Err(PsxError::BadMagic {
+                format: "TMD",
+                expected: TMD_MAGIC,
+                found: magic,
+            });
+        }
+
+        let object_count = u32::from_le_bytes([data[8], data[9], data[10], data[11]]);
+
+        // Stricter than parse(): scanning hits the magic in arbitrary data
+        const MAX_OBJECTS: u32 = 100;
+        const MAX_ELEMENTS: usize = 10000;
+        if object_count == 0 || object_count > MAX_OBJECTS {
+            return Err(PsxError::InvalidFormat(format!(
+                "Invalid TMD object count: {}",
+                object_count
+            )));
+        }
+
+        let obj_table_size = object_count as usize * 28;
+        let data_start = 12 + obj_table_size;
+        if data_start > data.len() {
+            return Err(PsxError::Truncated {
+                what: "TMD object table",
+                needed: data_start,
+                got: data.len(),
+            });
+        }
+
+        let read_u32 = |pos: usize| {
+            u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as usize
+        };
+
+        let mut extent = data_start;
+        for i in 0..object_count as usize {
+            let entry = 12 + i * 28;
+            let vert_offset = read_u32(entry);
+            let vert_count = read_u32(entry + 4);
+            let normal_offset = read_u32(entry + 8);
+            let normal_count = read_u32(entry + 12);
+            let prim_offset = read_u32(entry + 16);
+            let prim_count = read_u32(entry + 20);
+
+            if vert_count > MAX_ELEMENTS || normal_count > MAX_ELEMENTS || prim_count > MAX_ELEMENTS
+            {
+                return Err(PsxError::InvalidFormat(format!(
+                    "TMD object {} has implausible counts",
+                    i
+                )));
+            }
+
+            let vert_end = vert_offset + vert_count * 8;
+            let normal_end = normal_offset + normal_count * 8;
+            if vert_end > data.len() || normal_end > data.len() || prim_offset > data.len() {
+                return Err(PsxError::InvalidFormat(format!(
+                    "TMD object {} data out of bounds",
+                    i
+                )));
+            }
+
+            let mut prim_end = prim_offset;
+            for _ in 0..prim_count {
+                if prim_end + 4 > data.len() {
+                    break;
+                }
+                let packet_size = Self::primitive_packet_size(data, prim_end)?;
+                if packet_size == 0 {
+                    return Err(PsxError::InvalidFormat(format!(
+                        "TMD object {} has an empty primitive packet",
+                        i
+                    )));
+                }
+                prim_end += packet_size;
+            }
+            let prim_end = prim_end.min(data.len());
+            extent = extent.max(vert_end).max(normal_end).max(prim_end);
+        }
+
+        Ok((object_count, extent))
+    }
+
     /// Parse standard PSX TMD format
     fn parse_standard_tmd(data: &[u8]) -> Result<Self> {
         let flags = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
@@ -157,10 +392,11 @@ impl Tmd {
         for i in 0..num_objects {
             let obj_offset = obj_table_offset + (i * 28);
             if obj_offset + 28 > data.len() {
-                return Err(PsxError::ParseError(format!(
-                    "Object table entry {} out of bounds",
-                    i
-                )));
+                return Err(PsxError::Truncated {
+                    what: "TMD object table",
+                    needed: obj_offset + 28,
+                    got: data.len(),
+                });
             }
 
             let obj_data = &data[obj_offset..obj_offset + 28];
@@ -215,7 +451,11 @@ impl Tmd {
         for i in 0..vert_count {
             let voffset = vert_offset + (i * 8);
             if voffset + 8 > file_data.len() {
-                return Err(PsxError::ParseError(format!("Vertex {} out of bounds", i)));
+                return Err(PsxError::Truncated {
+                    what: "TMD vertex data",
+                    needed: voffset + 8,
+                    got: file_data.len(),
+                });
             }
 
             let vdata = &file_data[voffset..voffset + 8];
@@ -231,7 +471,11 @@ impl Tmd {
         for i in 0..normal_count {
             let noffset = normal_offset + (i * 8);
             if noffset + 8 > file_data.len() {
-                return Err(PsxError::ParseError(format!("Normal {} out of bounds", i)));
+                return Err(PsxError::Truncated {
+                    what: "TMD normal data",
+                    needed: noffset + 8,
+                    got: file_data.len(),
+                });
             }
 
             let ndata = &file_data[noffset..noffset + 8];
@@ -259,7 +503,10 @@ impl Tmd {
             let prim = Self::parse_primitive(file_data, prim_pos)?;
             let packet_size = Self::primitive_packet_size(file_data, prim_pos)?;
 
-            primitives.push(prim);
+            // Sprites are skipped, but their packet size still advances the stream
+            if let Some(prim) = prim {
+                primitives.push(prim);
+            }
             prim_pos += packet_size;
         }
 
@@ -272,11 +519,15 @@ impl Tmd {
     }
 
     /// Parse a single primitive from data
-    fn parse_primitive(data: &[u8], offset: usize) -> Result<TmdPrimitive> {
+    ///
+    /// Returns `None` for primitive types that are not decoded (3D sprites).
+    fn parse_primitive(data: &[u8], offset: usize) -> Result<Option<TmdPrimitive>> {
         if offset + 4 > data.len() {
-            return Err(PsxError::ParseError(
-                "Primitive header out of bounds".to_string(),
-            ));
+            return Err(PsxError::Truncated {
+                what: "TMD primitive header",
+                needed: offset + 4,
+                got: data.len(),
+            });
         }
 
         // Primitive packet structure:
@@ -293,16 +544,130 @@ impl Tmd {
         // Determine primitive type from mode/flag
         let is_quad = (mode & 0x08) != 0;
         let is_textured = (mode & 0x04) != 0;
-        let is_gouraud = (mode & 0x10) != 0; // Smooth shading (per-vertex normals)
-        let _is_light_calc = (flag & 0x01) == 0; // Light source calculation enabled
+        let is_gouraud = (mode & 0x10) != 0; // Smooth shading
+        let is_lit = (flag & 0x01) == 0; // Light source calculation enabled
+        let is_gradation = (flag & 0x04) != 0; // Per-vertex colors on lit polygons
+
+        // Untextured primitives lead with their color words: one per vertex for
+        // gradation polygons and unlit gouraud polygons, otherwise a single
+        // flat color (lit gouraud polygons shade through their normals)
+        let color_mode = match (is_textured, is_gradation || (is_gouraud && !is_lit)) {
+            (true, _) => ColorMode::None,
+            (false, true) => ColorMode::PerVertex,
+            (false, false) => ColorMode::Flat,
+        };
+
+        // Unlit primitives carry no normals at all
+        let normal_mode = match (is_lit, is_gouraud) {
+            (false, _) => NormalMode::None,
+            (true, true) => NormalMode::PerVertex,
+            (true, false) => NormalMode::Flat,
+        };
 
         let pos = offset + 4;
 
-        if is_quad {
-            Self::parse_quad(data, pos, is_textured, is_gouraud)
+        // Top 3 bits of mode select the GPU primitive family
+        match mode & 0xE0 {
+            PRIM_LINE => Self::parse_line(data, pos, is_gouraud).map(Some),
+            PRIM_SPRITE => Ok(None),
+            _ if is_quad => {
+                Self::parse_quad(data, pos, is_textured, color_mode, normal_mode).map(Some)
+            }
+            _ => Self::parse_triangle(data, pos, is_textured, color_mode, normal_mode).map(Some),
+        }
+    }
+
+    /// Parse a line primitive
+    ///
+    /// Lines carry one color (or one per end for gouraud lines) followed by
+    /// two vertex indices; they have no normals or texture data.
+    fn parse_line(data: &[u8], mut pos: usize, is_gouraud: bool) -> Result<TmdPrimitive> {
+        let color_mode = if is_gouraud {
+            ColorMode::PerVertex
         } else {
-            Self::parse_triangle(data, pos, is_textured, is_gouraud)
+            ColorMode::Flat
+        };
+        let colors = Self::parse_colors::<2>(data, &mut pos, color_mode)?;
+
+        if pos + 4 > data.len() {
+            return Err(PsxError::Truncated {
+                what: "TMD line vertices",
+                needed: pos + 4,
+                got: data.len(),
+            });
         }
+        let v0 = u16::from_le_bytes([data[pos], data[pos + 1]]);
+        let v1 = u16::from_le_bytes([data[pos + 2], data[pos + 3]]);
+
+        Ok(TmdPrimitive::Line {
+            vertices: [v0, v1],
+            colors,
+        })
+    }
+
+    /// Parse the color words at the start of an untextured primitive
+    ///
+    /// Each color is a 32-bit word with R, G, B in the low three bytes (the
+    /// high byte repeats the GPU command code and is ignored).
+    fn parse_colors<const N: usize>(
+        data: &[u8],
+        pos: &mut usize,
+        color_mode: ColorMode,
+    ) -> Result<Option<[(u8, u8, u8); N]>> {
+        let count = match color_mode {
+            ColorMode::None => return Ok(None),
+            ColorMode::Flat => 1,
+            ColorMode::PerVertex => N,
+        };
+
+        if *pos + count * 4 > data.len() {
+            return Err(PsxError::Truncated {
+                what: "TMD primitive colors",
+                needed: *pos + count * 4,
+                got: data.len(),
+            });
+        }
+
+        let mut colors = [(0, 0, 0); N];
+        for (i, color) in colors.iter_mut().enumerate() {
+            // A flat color applies to every vertex
+            let word = *pos + (i % count) * 4;
+            *color = (data[word], data[word + 1], data[word + 2]);
+        }
+        *pos += count * 4;
+
+        Ok(Some(colors))
+    }
+
+    /// Parse the 16-bit normal indices of a polygon primitive
+    fn parse_normals<const N: usize>(
+        data: &[u8],
+        pos: &mut usize,
+        normal_mode: NormalMode,
+    ) -> Result<Option<[u16; N]>> {
+        let count = match normal_mode {
+            NormalMode::None => return Ok(None),
+            NormalMode::Flat => 1,
+            NormalMode::PerVertex => N,
+        };
+
+        if *pos + count * 2 > data.len() {
+            return Err(PsxError::Truncated {
+                what: "TMD primitive normals",
+                needed: *pos + count * 2,
+                got: data.len(),
+            });
+        }
+
+        let mut normals = [0; N];
+        for (i, normal) in normals.iter_mut().enumerate() {
+            // A flat normal applies to every vertex
+            let index = *pos + (i % count) * 2;
+            *normal = u16::from_le_bytes([data[index], data[index + 1]]);
+        }
+        *pos += count * 2;
+
+        Ok(Some(normals))
     }
 
     /// Parse a triangle primitive
@@ -310,36 +675,19 @@ impl Tmd {
         data: &[u8],
         mut pos: usize,
         is_textured: bool,
-        is_gouraud: bool,
+        color_mode: ColorMode,
+        normal_mode: NormalMode,
     ) -> Result<TmdPrimitive> {
-        // Normal indices (0 or 3 depending on gouraud)
-        let normals = if is_gouraud {
-            if pos + 6 > data.len() {
-                return Err(PsxError::ParseError(
-                    "Triangle normals out of bounds".to_string(),
-                ));
-            }
-            let n0 = u16::from_le_bytes([data[pos], data[pos + 1]]);
-            let n1 = u16::from_le_bytes([data[pos + 2], data[pos + 3]]);
-            let n2 = u16::from_le_bytes([data[pos + 4], data[pos + 5]]);
-            pos += 6;
-            Some([n0, n1, n2])
-        } else {
-            if pos + 2 > data.len() {
-                return Err(PsxError::ParseError(
-                    "Triangle normal out of bounds".to_string(),
-                ));
-            }
-            let n = u16::from_le_bytes([data[pos], data[pos + 1]]);
-            pos += 2;
-            Some([n, n, n]) // Use same normal for all vertices
-        };
+        let colors = Self::parse_colors::<3>(data, &mut pos, color_mode)?;
+        let normals = Self::parse_normals::<3>(data, &mut pos, normal_mode)?;
 
         // Vertex indices
         if pos + 6 > data.len() {
-            return Err(PsxError::ParseError(
-                "Triangle vertices out of bounds".to_string(),
-            ));
+            return Err(PsxError::Truncated {
+                what: "TMD triangle vertices",
+                needed: pos + 6,
+                got: data.len(),
+            });
         }
         let v0 = u16::from_le_bytes([data[pos], data[pos + 1]]);
         let v1 = u16::from_le_bytes([data[pos + 2], data[pos + 3]]);
@@ -349,9 +697,11 @@ impl Tmd {
         // UVs and texture info (if textured)
         let (uvs, texture_info) = if is_textured {
             if pos + 12 > data.len() {
-                return Err(PsxError::ParseError(
-                    "Triangle texture data out of bounds".to_string(),
-                ));
+                return Err(PsxError::Truncated {
+                    what: "TMD triangle texture data",
+                    needed: pos + 12,
+                    got: data.len(),
+                });
             }
 
             let u0 = data[pos];
@@ -382,7 +732,7 @@ impl Tmd {
             vertices: [v0, v1, v2],
             normals,
             uvs,
-            colors: None, // Colors typically not stored in TMD
+            colors,
             texture_info,
         })
     }
@@ -392,37 +742,19 @@ impl Tmd {
         data: &[u8],
         mut pos: usize,
         is_textured: bool,
-        is_gouraud: bool,
+        color_mode: ColorMode,
+        normal_mode: NormalMode,
     ) -> Result<TmdPrimitive> {
-        // Normal indices (0 or 4 depending on gouraud)
-        let normals = if is_gouraud {
-            if pos + 8 > data.len() {
-                return Err(PsxError::ParseError(
-                    "Quad normals out of bounds".to_string(),
-                ));
-            }
-            let n0 = u16::from_le_bytes([data[pos], data[pos + 1]]);
-            let n1 = u16::from_le_bytes([data[pos + 2], data[pos + 3]]);
-            let n2 = u16::from_le_bytes([data[pos + 4], data[pos + 5]]);
-            let n3 = u16::from_le_bytes([data[pos + 6], data[pos + 7]]);
-            pos += 8;
-            Some([n0, n1, n2, n3])
-        } else {
-            if pos + 2 > data.len() {
-                return Err(PsxError::ParseError(
-                    "Quad normal out of bounds".to_string(),
-                ));
-            }
-            let n = u16::from_le_bytes([data[pos], data[pos + 1]]);
-            pos += 2;
-            Some([n, n, n, n]) // Use same normal for all vertices
-        };
+        let colors = Self::parse_colors::<4>(data, &mut pos, color_mode)?;
+        let normals = Self::parse_normals::<4>(data, &mut pos, normal_mode)?;
 
         // Vertex indices
         if pos + 8 > data.len() {
-            return Err(PsxError::ParseError(
-                "Quad vertices out of bounds".to_string(),
-            ));
+            return Err(PsxError::Truncated {
+                what: "TMD quad vertices",
+                needed: pos + 8,
+                got: data.len(),
+            });
         }
         let v0 = u16::from_le_bytes([data[pos], data[pos + 1]]);
         let v1 = u16::from_le_bytes([data[pos + 2], data[pos + 3]]);
@@ -433,9 +765,11 @@ impl Tmd {
         // UVs and texture info (if textured)
         let (uvs, texture_info) = if is_textured {
             if pos + 16 > data.len() {
-                return Err(PsxError::ParseError(
-                    "Quad texture data out of bounds".to_string(),
-                ));
+                return Err(PsxError::Truncated {
+                    what: "TMD quad texture data",
+                    needed: pos + 16,
+                    got: data.len(),
+                });
             }
 
             let u0 = data[pos];
@@ -469,7 +803,7 @@ impl Tmd {
             vertices: [v0, v1, v2, v3],
             normals,
             uvs,
-            colors: None,
+            colors,
             texture_info,
         })
     }
@@ -477,9 +811,11 @@ impl Tmd {
     /// Calculate the size of a primitive packet in bytes
     fn primitive_packet_size(data: &[u8], offset: usize) -> Result<usize> {
         if offset >= data.len() {
-            return Err(PsxError::ParseError(
-                "Primitive offset out of bounds".to_string(),
-            ));
+            return Err(PsxError::Truncated {
+                what: "TMD primitive packet",
+                needed: offset + 1,
+                got: data.len(),
+            });
         }
 
         // olen is the packet length in 32-bit words (including the header)
@@ -496,11 +832,7 @@ impl Tmd {
         self.objects
             .iter()
             .map(|obj| {
-                let scale = if obj.scale == 0 {
-                    1.0
-                } else {
-                    obj.scale as f32
-                };
+                let scale = obj.scale_factor();
 
                 obj.vertices
                     .iter()
@@ -511,6 +843,9 @@ impl Tmd {
     }
 
     /// Convert to normalized floating point normals
+    ///
+    /// Needs the `std` feature for `f32::sqrt`.
+    #[cfg(feature = "std")]
     pub fn to_f32_normals(&self) -> Vec<Vec<[f32; 3]>> {
         self.objects
             .iter()
@@ -534,6 +869,127 @@ impl Tmd {
             .collect()
     }
 
+    /// Axis-aligned bounding box `(min, max)` of all objects
+    ///
+    /// Per-object scale is applied as in [`Tmd::to_f32_vertices`]. Returns
+    /// `None` if no object has vertices.
+    pub fn bounds(&self) -> Option<([f32; 3], [f32; 3])> {
+        self.objects.iter().filter_map(TmdObject::bounds).reduce(
+            |(min_a, max_a), (min_b, max_b)| {
+                (
+                    core::array::from_fn(|i| min_a[i].min(min_b[i])),
+                    core::array::from_fn(|i| max_a[i].max(max_b[i])),
+                )
+            },
+        )
+    }
+
+    /// Move the model so the center of its bounding box is at the origin
+    ///
+    /// The offset is converted back to each object's raw units, so results
+    /// are rounded to whole coordinates and clamped to the i16 range. Needs
+    /// the `std` feature for `f32::round`.
+    #[cfg(feature = "std")]
+    pub fn recenter(&mut self) {
+        let Some((min, max)) = self.bounds() else {
+            return;
+        };
+        let center: [f32; 3] = core::array::from_fn(|i| (min[i] + max[i]) / 2.0);
+
+        for object in &mut self.objects {
+            let scale = object.scale_factor();
+            let offset = center.map(|c| c * scale);
+            let shift = |value: i16, offset: f32| {
+                (value as f32 - offset)
+                    .round()
+                    .clamp(i16::MIN as f32, i16::MAX as f32) as i16
+            };
+
+            for vertex in &mut object.vertices {
+                vertex.x = shift(vertex.x, offset[0]);
+                vertex.y = shift(vertex.y, offset[1]);
+                vertex.z = shift(vertex.z, offset[2]);
+            }
+        }
+    }
+
+    /// Count primitives by kind and check every vertex index
+    pub fn stats(&self) -> TmdStats {
+        let mut stats = TmdStats::default();
+
+        for (object_index, object) in self.objects.iter().enumerate() {
+            let mut counts = TmdObjectStats {
+                vertices: object.vertices.len(),
+                normals: object.normals.len(),
+                ..Default::default()
+            };
+
+            for (primitive_index, primitive) in object.primitives.iter().enumerate() {
+                let (indices, normals, colors, textured): (&[u16], Option<&[u16]>, _, bool) =
+                    match primitive {
+                        TmdPrimitive::Triangle {
+                            vertices,
+                            normals,
+                            colors,
+                            uvs,
+                            ..
+                        } => {
+                            counts.triangles += 1;
+                            (
+                                vertices,
+                                normals.as_ref().map(|n| &n[..]),
+                                colors.as_ref().map(|c| &c[..]),
+                                uvs.is_some(),
+                            )
+                        }
+                        TmdPrimitive::Quad {
+                            vertices,
+                            normals,
+                            colors,
+                            uvs,
+                            ..
+                        } => {
+                            counts.quads += 1;
+                            (
+                                vertices,
+                                normals.as_ref().map(|n| &n[..]),
+                                colors.as_ref().map(|c| &c[..]),
+                                uvs.is_some(),
+                            )
+                        }
+                        TmdPrimitive::Line { vertices, colors } => {
+                            counts.lines += 1;
+                            (vertices, None, colors.as_ref().map(|c| &c[..]), false)
+                        }
+                    };
+
+                if textured {
+                    counts.textured += 1;
+                }
+                // Flat primitives repeat one normal/color for every vertex
+                if normals.is_some_and(varies) || colors.is_some_and(varies) {
+                    counts.gouraud += 1;
+                }
+
+                for &index in indices {
+                    if index as usize >= object.vertices.len() {
+                        stats.dangling.push(DanglingIndex {
+                            object: object_index,
+                            primitive: primitive_index,
+                            index,
+                        });
+                    }
+                }
+            }
+
+            stats.total_vertices += counts.vertices;
+            stats.total_normals += counts.normals;
+            stats.objects.push(counts);
+        }
+
+        stats
+    }
+
     /// Get the number of objects
     pub fn object_count(&self) -> usize {
         self.objects.len()
@@ -557,7 +1013,14 @@ mod tests {
     #[test]
     fn test_tmd_parse_empty() {
         let data = vec![0; 11];
-        assert!(Tmd::parse(&data).is_err());
+        assert!(matches!(
+            Tmd::parse(&data),
+            Err(PsxError::Truncated {
+                needed: 12,
+                got: 11,
+                ..
+            })
+        ));
     }
 
     #[test]
@@ -565,6 +1028,367 @@ mod tests {
         let mut data = vec![0; 12];
         // Set wrong magic
         data[0..4].copy_from_slice(&0xDEADBEEFu32.to_le_bytes());
-        assert!(Tmd::parse(&data).is_err());
+        assert!(matches!(
+            Tmd::parse(&data),
+            Err(PsxError::BadMagic {
+                found: 0xDEADBEEF,
+                ..
+            })
+        ));
+    }
+
+    /// Build a single-object TMD: 4 vertices, 2 normals, 1 primitive
+    fn synthetic_tmd() -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&TMD_MAGIC.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&1u32.to_le_bytes());
+        // Object table: vertices at 40, normals at 72, primitive at 88
+        for value in [40u32, 4, 72, 2, 88, 1, 1] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.resize(88 + 32, 0);
+        // One 8-word primitive packet
+        data[88] = 8;
+        data
+    }
+
+    #[test]
+    fn test_tmd_validate() {
+        let mut data = synthetic_tmd();
+        // Trailing bytes are not part of the model
+        data.extend_from_slice(&[0xAA; 64]);
+
+        let (object_count, size) = Tmd::validate(&data).unwrap();
+        assert_eq!(object_count, 1);
+        assert_eq!(size, 120);
+    }
+
+    #[test]
+    fn test_tmd_validate_rejects_out_of_bounds() {
+        let mut data = synthetic_tmd();
+        // Vertex count pointing past the end of the buffer
+        data[16..20].copy_from_slice(&1000u32.to_le_bytes());
+        assert!(Tmd::validate(&data).is_err());
+
+        let mut data = synthetic_tmd();
+        data[8..12].copy_from_slice(&0u32.to_le_bytes());
+        assert!(Tmd::validate(&data).is_err());
+
+        // Bogus object count from random data hitting the magic
+        let mut data = synthetic_tmd();
+        data[8..12].copy_from_slice(&0x0001_0000u32.to_le_bytes());
+        assert!(Tmd::validate(&data).is_err());
+
+        // Zero-length primitive packet
+        let mut data = synthetic_tmd();
+        data[88] = 0;
+        assert!(Tmd::validate(&data).is_err());
+    }
+
+    #[test]
+    fn test_tmd_validate_walks_primitive_packets() {
+        let mut data = synthetic_tmd();
+        // Three primitives of 4, 6 and 3 words
+        data[32..36].copy_from_slice(&3u32.to_le_bytes());
+        data.truncate(88);
+        for olen in [4u8, 6, 3] {
+            let mut packet = vec![0u8; olen as usize * 4];
+            packet[0] = olen;
+            data.extend_from_slice(&packet);
+        }
+        // Another asset right after the model
+        data.extend_from_slice(&[0xAA; 64]);
+
+        let (_, size) = Tmd::validate(&data).unwrap();
+        assert_eq!(size, 88 + 13 * 4);
+    }
+
+    #[test]
+    fn test_parse_flat_triangle_color() {
+        // Header (olen, ilen, flag, mode), one color word, normal, 3 vertices
+        let mut data = vec![4, 3, 0x00, 0x20];
+        data.extend_from_slice(&[10, 20, 30, 0x20]);
+        for index in [0u16, 1, 2, 3] {
+            data.extend_from_slice(&index.to_le_bytes());
+        }
+
+        match Tmd::parse_primitive(&data, 0).unwrap().unwrap() {
+            TmdPrimitive::Triangle {
+                vertices, colors, ..
+            } => {
+                assert_eq!(vertices, [1, 2, 3]);
+                assert_eq!(colors, Some([(10, 20, 30); 3]));
+            }
+            other => panic!("expected triangle, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_gouraud_quad_colors() {
+        // Lit gouraud quad: one color word, 4 normals, 4 vertices
+        let mut data = vec![6, 5, 0x00, 0x38];
+        data.extend_from_slice(&[10, 20, 30, 0x38]);
+        for index in [0u16, 1, 2, 3, 4, 5, 6, 7] {
+            data.extend_from_slice(&index.to_le_bytes());
+        }
+
+        match Tmd::parse_primitive(&data, 0).unwrap().unwrap() {
+            TmdPrimitive::Quad {
+                vertices,
+                normals,
+                colors,
+                ..
+            } => {
+                assert_eq!(normals, Some([0, 1, 2, 3]));
+                assert_eq!(vertices, [4, 5, 6, 7]);
+                assert_eq!(colors, Some([(10, 20, 30); 4]));
+            }
+            other => panic!("expected quad, got {:?}", other),
+        }
+
+        // Gradation (GRD) flag: one color word per vertex before the normals
+        let mut data = vec![9, 8, 0x04, 0x38];
+        for i in 0..4u8 {
+            data.extend_from_slice(&[i, i + 1, i + 2, 0x38]);
+        }
+        for index in [0u16, 1, 2, 3, 4, 5, 6, 7] {
+            data.extend_from_slice(&index.to_le_bytes());
+        }
+
+        match Tmd::parse_primitive(&data, 0).unwrap().unwrap() {
+            TmdPrimitive::Quad {
+                vertices,
+                normals,
+                colors,
+                ..
+            } => {
+                assert_eq!(normals, Some([0, 1, 2, 3]));
+                assert_eq!(vertices, [4, 5, 6, 7]);
+                assert_eq!(colors, Some([(0, 1, 2), (1, 2, 3), (2, 3, 4), (3, 4, 5)]));
+            }
+            other => panic!("expected quad, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_unlit_primitives_skip_normals() {
+        // Unlit (LGT) flat triangle: one color word, then straight to 3 vertices
+        let mut data = vec![3, 2, 0x01, 0x20];
+        data.extend_from_slice(&[10, 20, 30, 0x20]);
+        for index in [1u16, 2, 3, 0] {
+            data.extend_from_slice(&index.to_le_bytes());
+        }
+
+        match Tmd::parse_primitive(&data, 0).unwrap().unwrap() {
+            TmdPrimitive::Triangle {
+                vertices,
+                normals,
+                colors,
+                ..
+            } => {
+                assert_eq!(normals, None);
+                assert_eq!(vertices, [1, 2, 3]);
+                assert_eq!(colors, Some([(10, 20, 30); 3]));
+            }
+            other => panic!("expected triangle, got {:?}", other),
+        }
+
+        // Unlit gouraud quad: 4 color words, then 4 vertices
+        let mut data = vec![7, 6, 0x01, 0x38];
+        for i in 0..4u8 {
+            data.extend_from_slice(&[i, i + 1, i + 2, 0x38]);
+        }
+        for index in [4u16, 5, 6, 7] {
+            data.extend_from_slice(&index.to_le_bytes());
+        }
+
+        match Tmd::parse_primitive(&data, 0).unwrap().unwrap() {
+            TmdPrimitive::Quad {
+                vertices,
+                normals,
+                colors,
+                ..
+            } => {
+                assert_eq!(normals, None);
+                assert_eq!(vertices, [4, 5, 6, 7]);
+                assert_eq!(colors, Some([(0, 1, 2), (1, 2, 3), (2, 3, 4), (3, 4, 5)]));
+            }
+            other => panic!("expected quad, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_mixed_triangle_and_line() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&TMD_MAGIC.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&1u32.to_le_bytes());
+        // Object table: 3 vertices at 40, no normals, 4 primitives at 64
+        for value in [40u32, 3, 64, 0, 64, 4, 1] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        for vertex in [[0i16, 0, 0], [100, 0, 0], [0, 100, 0]] {
+            for component in vertex {
+                data.extend_from_slice(&component.to_le_bytes());
+            }
+            data.extend_from_slice(&0i16.to_le_bytes());
+        }
+
+        // Flat triangle: color, normal, 3 vertices (padded to 4 words)
+        data.extend_from_slice(&[4, 3, 0x00, 0x20]);
+        data.extend_from_slice(&[255, 0, 0, 0x20]);
+        for index in [0u16, 0, 1, 2] {
+            data.extend_from_slice(&index.to_le_bytes());
+        }
+        // Gouraud line: 2 colors, 2 vertices
+        data.extend_from_slice(&[4, 3, 0x01, 0x50]);
+        data.extend_from_slice(&[0, 255, 0, 0x50]);
+        data.extend_from_slice(&[0, 0, 255, 0x50]);
+        for index in [1u16, 2] {
+            data.extend_from_slice(&index.to_le_bytes());
+        }
+        // Sprite: skipped using its packet length
+        data.extend_from_slice(&[3, 2, 0x00, 0x64]);
+        data.extend_from_slice(&[0xEE; 8]);
+        // Flat line after the sprite
+        data.extend_from_slice(&[3, 2, 0x01, 0x40]);
+        data.extend_from_slice(&[1, 2, 3, 0x40]);
+        for index in [2u16, 0] {
+            data.extend_from_slice(&index.to_le_bytes());
+        }
+
+        let tmd = Tmd::parse(&data).unwrap();
+        let primitives = &tmd.objects[0].primitives;
+        assert_eq!(primitives.len(), 3);
+
+        assert!(matches!(
+            primitives[0],
+            TmdPrimitive::Triangle {
+                vertices: [0, 1, 2],
+                ..
+            }
+        ));
+        match &primitives[1] {
+            TmdPrimitive::Line { vertices, colors } => {
+                assert_eq!(*vertices, [1, 2]);
+                assert_eq!(*colors, Some([(0, 255, 0), (0, 0, 255)]));
+            }
+            other => panic!("expected line, got {:?}", other),
+        }
+        match &primitives[2] {
+            TmdPrimitive::Line { vertices, colors } => {
+                assert_eq!(*vertices, [2, 0]);
+                assert_eq!(*colors, Some([(1, 2, 3); 2]));
+            }
+            other => panic!("expected line, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_bounds_and_recenter() {
+        let object = |vertices: &[(i16, i16, i16)], scale: i32| TmdObject {
+            vertices: vertices
+                .iter()
+                .map(|&(x, y, z)| TmdVertex { x, y, z })
+                .collect(),
+            normals: Vec::new(),
+            primitives: Vec::new(),
+            scale,
+        };
+
+        let mut tmd = Tmd {
+            flags: 0,
+            objects: vec![
+                object(&[(100, 0, 0), (200, 50, 10)], 0),
+                // Scale 2 halves raw coordinates: (300..400, -50..0, 10..30)
+                object(&[(600, -100, 20), (800, 0, 60)], 2),
+                object(&[], 0),
+            ],
+        };
+
+        assert_eq!(
+            tmd.objects[1].bounds(),
+            Some(([300.0, -50.0, 10.0], [400.0, 0.0, 30.0]))
+        );
+        assert_eq!(tmd.objects[2].bounds(), None);
+        assert_eq!(
+            tmd.bounds(),
+            Some(([100.0, -50.0, 0.0], [400.0, 50.0, 30.0]))
+        );
+
+        tmd.recenter();
+        assert_eq!(
+            tmd.bounds(),
+            Some(([-150.0, -50.0, -15.0], [150.0, 50.0, 15.0]))
+        );
+        assert_eq!(tmd.objects[1].vertices[0].x, 100);
+    }
+
+    #[test]
+    fn test_stats_reports_dangling_index() {
+        let vertex = TmdVertex { x: 0, y: 0, z: 0 };
+        let tmd = Tmd {
+            flags: 0,
+            objects: vec![TmdObject {
+                vertices: vec![vertex; 4],
+                normals: vec![
+                    TmdNormal {
+                        nx: 0,
+                        ny: 4096,
+                        nz: 0
+                    };
+                    2
+                ],
+                primitives: vec![
+                    // Flat textured triangle
+                    TmdPrimitive::Triangle {
+                        vertices: [0, 1, 2],
+                        normals: Some([0; 3]),
+                        uvs: Some([(0, 0); 3]),
+                        colors: None,
+                        texture_info: None,
+                    },
+                    // Gouraud quad with vertex 7 past the end
+                    TmdPrimitive::Quad {
+                        vertices: [0, 1, 7, 3],
+                        normals: Some([0, 1, 0, 1]),
+                        uvs: None,
+                        colors: None,
+                        texture_info: None,
+                    },
+                    TmdPrimitive::Line {
+                        vertices: [2, 3],
+                        colors: Some([(1, 2, 3); 2]),
+                    },
+                ],
+                scale: 1,
+            }],
+        };
+
+        let stats = tmd.stats();
+        assert_eq!(
+            stats.objects[0],
+            TmdObjectStats {
+                triangles: 1,
+                quads: 1,
+                lines: 1,
+                textured: 1,
+                gouraud: 1,
+                vertices: 4,
+                normals: 2,
+            }
+        );
+        assert_eq!(stats.total_vertices, 4);
+        assert_eq!(stats.total_normals, 2);
+        assert_eq!(
+            stats.dangling,
+            vec![DanglingIndex {
+                object: 0,
+                primitive: 1,
+                index: 7,
+            }]
+        );
+        assert!(!stats.is_valid());
     }
 }
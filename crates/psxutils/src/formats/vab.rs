@@ -65,6 +65,7 @@
 //!   Raw VAG sample data concatenated
 //! ```
 
+use crate::formats::vag::Vag;
 use crate::{PsxError, Result};
 use bytemuck::{Pod, Zeroable};
 
@@ -83,6 +84,9 @@ pub const MAX_TONES: usize = 256;
 /// Maximum number of VAG samples in a VAB
 pub const MAX_VAGS: usize = 256;
 
+/// Rate the SPU plays a sample at to sound its tone's center note
+pub const DEFAULT_SAMPLE_RATE: u32 = 44100;
+
 /// VAB file header (32 bytes, rest of 2048 is padding/reserved)
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Pod, Zeroable)]
@@ -147,6 +151,7 @@ struct ToneEntry {
 
 /// Parsed VAB sound bank
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vab {
     /// VAB ID
     pub vab_id: u32,
@@ -164,6 +169,7 @@ pub struct Vab {
 
 /// Program (instrument) in a VAB
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Program {
     pub num_tones: u8,
     pub volume: u8,
@@ -175,6 +181,7 @@ pub struct Program {
 
 /// Tone (individual sound) in a VAB
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tone {
     pub priority: u8,
     pub mode: u8,
@@ -198,8 +205,10 @@ pub struct Tone {
 
 /// VAG sample within a VAB
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VagSample {
     /// Raw VAG data
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub data: Vec<u8>,
 }
 
@@ -207,7 +216,11 @@ impl Vab {
     /// Parse a VAB file from bytes
     pub fn parse(data: &[u8]) -> Result<Self> {
         if data.len() < 2048 {
-            return Err(PsxError::InvalidFormat("VAB file too small".to_string()));
+            return Err(PsxError::Truncated {
+                what: "VAB header",
+                needed: 2048,
+                got: data.len(),
+            });
         }
 
         // Parse header
@@ -215,10 +228,11 @@ impl Vab {
             .map_err(|e| PsxError::ParseError(format!("Failed to parse VAB header: {}", e)))?;
 
         if header.magic != VAB_MAGIC {
-            return Err(PsxError::InvalidFormat(format!(
-                "Invalid VAB magic: {:?}, expected {:?}",
-                header.magic, VAB_MAGIC
-            )));
+            return Err(PsxError::BadMagic {
+                format: "VAB",
+                expected: u32::from_be_bytes(VAB_MAGIC),
+                found: u32::from_be_bytes(header.magic),
+            });
         }
 
         let version = u32::from_le(header.version);
@@ -339,6 +353,44 @@ impl Vab {
     pub fn get_tone(&self, index: usize) -> Option<&Tone> {
         self.tones.get(index)
     }
+
+    /// Rebuild every sample as a standalone VAG named `vag_NNN`
+    ///
+    /// Samples play at [`DEFAULT_SAMPLE_RATE`], adjusted by the fine tuning
+    /// of the first tone that uses them so the center note sounds in tune.
+    pub fn split_vags(&self) -> Vec<Vag> {
+        self.vag_samples
+            .iter()
+            .enumerate()
+            .map(|(index, sample)| {
+                Vag::from_adpcm(
+                    &format!("vag_{:03}", index),
+                    self.sample_rate(index),
+                    sample.data.clone(),
+                )
+            })
+            .collect()
+    }
+
+    /// Playback rate of a sample, from the tuning of the first tone using it
+    fn sample_rate(&self, vag_index: usize) -> u32 {
+        let tune = self
+            .tones
+            .iter()
+            .find(|tone| tone.vag_index as usize == vag_index)
+            .map_or(0, |tone| tone.center_tune);
+
+        // center_tune is in 1/128 semitones
+        let ratio = 2f64.powf(tune as f64 / 128.0 / 12.0);
+        (DEFAULT_SAMPLE_RATE as f64 * ratio).round() as u32
+    }
+}
+
+impl VagSample {
+    /// Serialize as a standalone VAG file with the given sample rate
+    pub fn to_vag_bytes(&self, sample_rate: u32) -> Vec<u8> {
+        Vag::from_adpcm("", sample_rate, self.data.clone()).to_bytes()
+    }
 }
 
 #[cfg(test)]
@@ -363,6 +415,48 @@ mod tests {
     #[test]
     fn test_invalid_vab() {
         let data = vec![0u8; 10];
-        assert!(Vab::parse(&data).is_err());
+        assert!(matches!(
+            Vab::parse(&data),
+            Err(PsxError::Truncated {
+                needed: 2048,
+                got: 10,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_split_vags() {
+        let bank = Vab {
+            vab_id: 0,
+            master_volume: 127,
+            master_pan: 64,
+            programs: Vec::new(),
+            tones: Vec::new(),
+            vag_samples: vec![
+                VagSample { data: vec![0; 64] },
+                VagSample {
+                    data: vec![0x11; 4096],
+                },
+            ],
+        };
+
+        let vags = bank.split_vags();
+        assert_eq!(vags.len(), 2);
+        assert_eq!(vags[1].name, "vag_001");
+
+        let bytes = vags[1].to_bytes();
+        assert_eq!(
+            Vag::validate(&bytes).unwrap(),
+            (DEFAULT_SAMPLE_RATE, 48 + 4096)
+        );
+        let reparsed = Vag::parse(&bytes).unwrap();
+        assert_eq!(reparsed.name, "vag_001");
+        assert_eq!(reparsed.data.len(), bank.vag_samples[1].data.len());
+
+        let bytes = bank.vag_samples[0].to_vag_bytes(22050);
+        let reparsed = Vag::parse(&bytes).unwrap();
+        assert_eq!(reparsed.sample_rate, 22050);
+        assert_eq!(reparsed.data, bank.vag_samples[0].data);
     }
 }
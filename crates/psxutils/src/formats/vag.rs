@@ -65,12 +65,14 @@ struct VagHeader {
 
 /// Parsed VAG audio sample
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vag {
     /// Sample name
     pub name: String,
     /// Sample rate in Hz
     pub sample_rate: u32,
     /// Raw ADPCM data (16-byte blocks)
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub data: Vec<u8>,
     /// Loop start position (in samples)
     pub loop_start: Option<usize>,
@@ -82,18 +84,22 @@ impl Vag {
     /// Parse a VAG file from bytes
     pub fn parse(data: &[u8]) -> Result<Self> {
         if data.len() < 48 {
-            return Err(PsxError::InvalidFormat("VAG file too small".to_string()));
+            return Err(PsxError::Truncated {
+                what: "VAG header",
+                needed: 48,
+                got: data.len(),
+            });
         }
 
         // Parse header
-        let header: &VagHeader = bytemuck::try_from_bytes(&data[0..48])
-            .map_err(|e| PsxError::ParseError(format!("Failed to parse VAG header: {}", e)))?;
+        let header: VagHeader = bytemuck::pod_read_unaligned(&data[0..48]);
 
         if header.magic != VAG_MAGIC {
-            return Err(PsxError::InvalidFormat(format!(
-                "Invalid VAG magic: {:?}, expected {:?}",
-                header.magic, VAG_MAGIC
-            )));
+            return Err(PsxError::BadMagic {
+                format: "VAG",
+                expected: u32::from_be_bytes(VAG_MAGIC),
+                found: u32::from_be_bytes(header.magic),
+            });
         }
 
         let version = u32::from_be(header.version);
@@ -129,6 +135,81 @@ impl Vag {
         })
     }
 
+    /// Wrap headerless ADPCM data, such as a sample from a VAB body
+    pub fn from_adpcm(name: &str, sample_rate: u32, data: Vec<u8>) -> Self {
+        let (loop_start, loop_end) = Self::find_loop_points(&data);
+
+        Vag {
+            name: name.to_string(),
+            sample_rate,
+            data,
+            loop_start,
+            loop_end,
+        }
+    }
+
+    /// Serialize to a standalone VAG file (48-byte "VAGp" header plus data)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(48 + self.data.len());
+        bytes.extend_from_slice(&VAG_MAGIC);
+        bytes.extend_from_slice(&VAG_VERSION.to_be_bytes());
+        bytes.extend_from_slice(&0u32.to_be_bytes());
+        bytes.extend_from_slice(&(self.data.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&self.sample_rate.to_be_bytes());
+        bytes.extend_from_slice(&[0; 12]);
+
+        let mut name = [0u8; 16];
+        let len = self.name.len().min(15);
+        name[..len].copy_from_slice(&self.name.as_bytes()[..len]);
+        bytes.extend_from_slice(&name);
+
+        bytes.extend_from_slice(&self.data);
+        bytes
+    }
+
+    /// Validate VAG format without copying the audio data
+    ///
+    /// Returns `Ok((sample_rate, total_size))` if valid, where total_size is
+    /// the 48-byte header plus the data size from the header.
+    pub fn validate(data: &[u8]) -> Result<(u32, usize)> {
+        if data.len() < 48 {
+            return Err(PsxError::Truncated {
+                what: "VAG header",
+                needed: 48,
+                got: data.len(),
+            });
+        }
+
+        let header: VagHeader = bytemuck::pod_read_unaligned(&data[0..48]);
+
+        if header.magic != VAG_MAGIC {
+            return Err(PsxError::BadMagic {
+                format: "VAG",
+                expected: u32::from_be_bytes(VAG_MAGIC),
+                found: u32::from_be_bytes(header.magic),
+            });
+        }
+
+        let size = u32::from_be(header.size) as usize;
+        if size == 0 || size > data.len() - 48 {
+            return Err(PsxError::InvalidFormat(format!(
+                "Invalid VAG data size: {} ({} bytes available)",
+                size,
+                data.len() - 48
+            )));
+        }
+
+        let sample_rate = u32::from_be(header.rate);
+        if sample_rate == 0 || sample_rate > 48000 {
+            return Err(PsxError::InvalidFormat(format!(
+                "Invalid VAG sample rate: {}",
+                sample_rate
+            )));
+        }
+
+        Ok((sample_rate, 48 + size))
+    }
+
     /// Find loop start and end points by scanning block flags
     fn find_loop_points(data: &[u8]) -> (Option<usize>, Option<usize>) {
         let mut loop_start = None;
@@ -212,6 +293,35 @@ impl Vag {
         output
     }
 
+    /// Decode and resample to play at `to_note` a sample recorded at `from_note`
+    ///
+    /// `center_tune` is the VAB tone's fine tuning in 1/128 semitone steps
+    /// above `from_note`. The pitch ratio is applied by linear interpolation
+    /// over the decoded PCM, so one octave up yields half as many samples at
+    /// the same sample rate.
+    pub fn resample(&self, from_note: u8, to_note: u8, center_tune: u8) -> Vec<i16> {
+        let pcm = self.decode_to_pcm();
+        if pcm.is_empty() {
+            return pcm;
+        }
+
+        let semitones = to_note as f64 - from_note as f64 + center_tune as f64 / 128.0;
+        let ratio = 2f64.powf(semitones / 12.0);
+        let output_len = (pcm.len() as f64 / ratio).round() as usize;
+        let last = pcm.len() - 1;
+
+        (0..output_len)
+            .map(|i| {
+                let position = i as f64 * ratio;
+                let index = (position as usize).min(last);
+                let frac = position - index as f64;
+                let a = pcm[index] as f64;
+                let b = pcm[(index + 1).min(last)] as f64;
+                (a + (b - a) * frac).round() as i16
+            })
+            .collect()
+    }
+
     /// Get the duration in seconds
     pub fn duration_secs(&self) -> f64 {
         let num_samples = (self.data.len() / 16) * 28;
@@ -231,6 +341,72 @@ mod tests {
     #[test]
     fn test_invalid_vag() {
         let data = vec![0u8; 10];
-        assert!(Vag::parse(&data).is_err());
+        assert!(matches!(
+            Vag::parse(&data),
+            Err(PsxError::Truncated { got: 10, .. })
+        ));
+
+        let data = vec![0u8; 64];
+        assert!(matches!(
+            Vag::parse(&data),
+            Err(PsxError::BadMagic {
+                expected: 0x56414770,
+                found: 0,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_vag_validate() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&VAG_MAGIC);
+        data.extend_from_slice(&VAG_VERSION.to_be_bytes());
+        data.extend_from_slice(&0u32.to_be_bytes());
+        data.extend_from_slice(&32u32.to_be_bytes());
+        data.extend_from_slice(&22050u32.to_be_bytes());
+        data.resize(48 + 32 + 16, 0);
+
+        assert_eq!(Vag::validate(&data).unwrap(), (22050, 80));
+
+        // Scanners probe arbitrary offsets, so the header may be unaligned
+        let mut shifted = vec![0u8; 1];
+        shifted.extend_from_slice(&data);
+        assert_eq!(Vag::validate(&shifted[1..]).unwrap(), (22050, 80));
+        assert_eq!(Vag::parse(&shifted[1..]).unwrap().sample_rate, 22050);
+
+        // Data size larger than the buffer
+        data[12..16].copy_from_slice(&1000u32.to_be_bytes());
+        assert!(Vag::validate(&data).is_err());
+    }
+
+    #[test]
+    fn test_resample() {
+        // Ten blocks of a slow ramp (filter 1 keeps accumulating)
+        let mut data = Vec::new();
+        for _ in 0..10 {
+            data.extend_from_slice(&[0x41, 0x00]);
+            data.extend_from_slice(&[0x11; 14]);
+        }
+        let vag = Vag {
+            name: "ramp".to_string(),
+            sample_rate: 22050,
+            data,
+            loop_start: None,
+            loop_end: None,
+        };
+        let pcm = vag.decode_to_pcm();
+
+        assert_eq!(vag.resample(60, 60, 0), pcm);
+
+        let up = vag.resample(60, 72, 0);
+        assert_eq!(up.len(), pcm.len() / 2);
+        assert_eq!(up[10], pcm[20]);
+
+        let down = vag.resample(60, 48, 0);
+        assert_eq!(down.len(), pcm.len() * 2);
+
+        // Fine tune raises the pitch slightly
+        assert!(vag.resample(60, 60, 64).len() < pcm.len());
     }
 }
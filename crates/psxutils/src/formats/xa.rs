@@ -17,7 +17,11 @@
 //! - `jpsxdec/src/jpsxdec/cdreaders/CdSectorXaSubHeader.java`
 //! - `jpsxdec/src/jpsxdec/modules/xa/SectorXaAudio.java`
 
+use crate::cdrom::CdRom;
+use crate::formats::xa_adpcm::XaAdpcmDecoder;
+use crate::{PsxError, Result};
 use bitflags::bitflags;
+use std::collections::HashMap;
 use std::fmt;
 
 /// Size of XA sub-header in bytes
@@ -32,6 +36,21 @@ pub const SOUND_GROUP_SIZE: usize = 128;
 /// Total XA audio data size (excluding unused 20 bytes at end)
 pub const XA_AUDIO_DATA_SIZE: usize = SOUND_GROUPS_PER_SECTOR * SOUND_GROUP_SIZE;
 
+/// Offset of the XA sub-header in a raw sector (after 12-byte sync + 4-byte header)
+pub const XA_SUBHEADER_OFFSET: usize = 16;
+
+/// Offset of the XA audio payload in a raw sector (sync + header + sub-header)
+pub const XA_DATA_OFFSET: usize = 24;
+
+/// MODE2FORM2 payload size in bytes
+pub const XA_FORM2_DATA_SIZE: usize = 2324;
+
+/// Largest sector stride between consecutive sectors of one XA stream
+///
+/// A disc interleaves at most 32 channels, so one stream's sectors are never
+/// further apart than this.
+const MAX_XA_INTERLEAVE: u32 = 32;
+
 /// CD-ROM XA sub-header
 ///
 /// The sub-header is duplicated twice (bytes 0-3 and 4-7) for error detection.
@@ -66,8 +85,8 @@ impl XaSubHeader {
         let file_number2 = data[4];
         let channel1 = data[1];
         let channel2 = data[5];
-        let sub_mode1 = SubMode::from_byte(data[2]);
-        let sub_mode2 = SubMode::from_byte(data[6]);
+        let sub_mode1 = SubMode::try_from(data[2]).ok()?;
+        let sub_mode2 = SubMode::try_from(data[6]).ok()?;
         let coding_info1 = CodingInfo::from_byte(data[3]);
         let coding_info2 = CodingInfo::from_byte(data[7]);
 
@@ -82,8 +101,8 @@ impl XaSubHeader {
             return None;
         }
 
-        // Validate sub-mode and coding info
-        if !sub_mode1.is_valid() || !coding_info1.is_valid() {
+        // Validate coding info (sub-mode was checked by `try_from`)
+        if !coding_info1.is_valid() {
             return None;
         }
 
@@ -180,6 +199,24 @@ impl SubMode {
     }
 }
 
+impl TryFrom<u8> for SubMode {
+    type Error = PsxError;
+
+    /// Parse sub-mode flags, rejecting bytes with more than one of the
+    /// Data/Audio/Video bits set
+    fn try_from(byte: u8) -> Result<Self> {
+        let sub_mode = Self::from_byte(byte);
+        if sub_mode.is_valid() {
+            Ok(sub_mode)
+        } else {
+            Err(PsxError::InvalidFormat(format!(
+                "Invalid XA sub-mode: 0x{:02X} ({})",
+                byte, sub_mode
+            )))
+        }
+    }
+}
+
 impl fmt::Display for SubMode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -204,7 +241,7 @@ impl fmt::Display for SubMode {
 /// - Bits 5,4: Bits per sample (00=4-bit, 01=8-bit)
 /// - Bits 3,2: Sample rate (00=37800Hz, 01=18900Hz)
 /// - Bits 1,0: Stereo (00=mono, 01=stereo)
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct CodingInfo {
     bits: u8,
 }
@@ -305,12 +342,149 @@ impl XaAudioStream {
     pub fn total_size(&self) -> usize {
         self.sector_count as usize * XA_AUDIO_DATA_SIZE
     }
+
+    /// Check whether a sector sub-header belongs to this stream
+    ///
+    /// A sector matches if it is an audio sector with the same file number
+    /// and channel as the stream.
+    pub fn matches(&self, header: &XaSubHeader) -> bool {
+        header.is_audio()
+            && header.file_number == self.file_number
+            && header.channel == self.channel
+    }
+
+    /// Decode the stream to 16-bit PCM
+    ///
+    /// Walks the raw sectors from `start_sector` to `end_sector` (inclusive),
+    /// skipping any sector whose sub-header does not match this stream's file
+    /// number and channel. PSX discs interleave several channels in the same
+    /// file, so decoding every sector in the range would mix tracks together.
+    ///
+    /// Streams flagged with emphasis in their coding info are de-emphasized.
+    ///
+    /// # Returns
+    /// Interleaved PCM samples. For stereo: [L, R, L, R, ...]
+    pub fn decode_pcm(&self, disc: &CdRom) -> Result<Vec<i16>> {
+        let mut decoder = XaAdpcmDecoder::new(
+            self.coding_info.bits_per_sample(),
+            self.coding_info.is_stereo(),
+            1.0,
+        )
+        .with_emphasis(
+            self.coding_info.has_emphasis(),
+            self.coding_info.sample_rate(),
+        );
+
+        let channels = if self.coding_info.is_stereo() { 2 } else { 1 };
+        let mut pcm_data = Vec::with_capacity(
+            self.sector_count as usize * decoder.samples_per_sector() * channels,
+        );
+
+        for lba in self.start_sector..=self.end_sector {
+            let raw_sector = disc.read_raw_sector(lba)?;
+
+            let Some(header) = XaSubHeader::parse(&raw_sector[XA_SUBHEADER_OFFSET..XA_DATA_OFFSET])
+            else {
+                continue;
+            };
+
+            if !self.matches(&header) {
+                continue; // Interleaved sector from another channel
+            }
+
+            let audio_data = &raw_sector[XA_DATA_OFFSET..XA_DATA_OFFSET + XA_FORM2_DATA_SIZE];
+            pcm_data.extend_from_slice(&decoder.decode_sector(audio_data));
+        }
+
+        Ok(pcm_data)
+    }
+}
+
+/// Group audio sector sub-headers into contiguous streams
+///
+/// Sectors sharing `(file_number, channel, coding_info)` are merged into one
+/// stream until a sector with the EOF sub-mode bit closes it. Non-audio
+/// sectors are ignored, so interleaved channels each form their own stream.
+///
+/// A stream's interleave stride is set by the gap between its first two
+/// sectors. A later sector at a different stride, or more than
+/// [`MAX_XA_INTERLEAVE`] sectors after the previous one, starts a new stream.
+/// Otherwise separate files that reuse the same file number and channel would
+/// merge into one stream spanning the disc.
+///
+/// Returned streams are sorted by start sector.
+pub(crate) fn group_xa_streams(
+    headers: impl IntoIterator<Item = (u32, XaSubHeader)>,
+) -> Vec<XaAudioStream> {
+    // Open streams with their interleave stride, once known
+    let mut open: HashMap<(u8, u8, CodingInfo), (XaAudioStream, Option<u32>)> = HashMap::new();
+    let mut streams = Vec::new();
+
+    for (lba, header) in headers {
+        if !header.is_audio() {
+            continue;
+        }
+
+        let key = (header.file_number, header.channel, header.coding_info);
+
+        // A gap that breaks the stream's sector sequence closes it
+        if let Some((stream, stride)) = open.get_mut(&key) {
+            let gap = lba.saturating_sub(stream.end_sector);
+            if gap > MAX_XA_INTERLEAVE || stride.is_some_and(|stride| stride != gap) {
+                if let Some((stream, _)) = open.remove(&key) {
+                    streams.push(stream);
+                }
+            } else {
+                stride.get_or_insert(gap);
+            }
+        }
+
+        let (stream, _) = open.entry(key).or_insert_with(|| {
+            let stream = XaAudioStream {
+                start_sector: lba,
+                end_sector: lba,
+                file_number: header.file_number,
+                channel: header.channel,
+                coding_info: header.coding_info,
+                sector_count: 0,
+            };
+            (stream, None)
+        });
+
+        stream.end_sector = lba;
+        stream.sector_count += 1;
+
+        // EOF marks the last sector of a stream; the next matching sector starts a new one
+        if header.sub_mode.is_end_of_file()
+            && let Some((stream, _)) = open.remove(&key)
+        {
+            streams.push(stream);
+        }
+    }
+
+    streams.extend(open.into_values().map(|(stream, _)| stream));
+    streams.sort_by_key(|s| (s.start_sector, s.file_number, s.channel));
+    streams
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_submode_try_from() {
+        let submode = SubMode::try_from(0x64).unwrap();
+        assert_eq!(submode, SubMode::REAL_TIME | SubMode::FORM | SubMode::AUDIO);
+        assert_eq!(SubMode::try_from(0x08).unwrap(), SubMode::DATA);
+        assert_eq!(SubMode::try_from(0x00).unwrap(), SubMode::empty());
+
+        // Audio + Video
+        assert!(matches!(
+            SubMode::try_from(0x06),
+            Err(PsxError::InvalidFormat(_))
+        ));
+    }
+
     #[test]
     fn test_submode_validation() {
         // Valid: Only Audio flag set
@@ -388,4 +562,85 @@ mod tests {
         let duration = stream.duration_seconds();
         assert!((duration - 0.444).abs() < 0.001);
     }
+
+    #[test]
+    fn test_xa_audio_stream_matches() {
+        let stream = XaAudioStream {
+            start_sector: 0,
+            end_sector: 15,
+            file_number: 1,
+            channel: 2,
+            coding_info: CodingInfo::from_byte(0x00),
+            sector_count: 16,
+        };
+
+        let header = XaSubHeader::parse(&[1, 2, 0x64, 0x00, 1, 2, 0x64, 0x00]).unwrap();
+        assert!(stream.matches(&header));
+
+        // Same file, different channel (interleaved)
+        let header = XaSubHeader::parse(&[1, 3, 0x64, 0x00, 1, 3, 0x64, 0x00]).unwrap();
+        assert!(!stream.matches(&header));
+
+        // Matching channel but data sector
+        let header = XaSubHeader::parse(&[1, 2, 0x08, 0x00, 1, 2, 0x08, 0x00]).unwrap();
+        assert!(!stream.matches(&header));
+    }
+
+    #[test]
+    fn test_group_xa_streams() {
+        let header = |channel: u8, sub_mode: u8| {
+            XaSubHeader::parse(&[1, channel, sub_mode, 0x00, 1, channel, sub_mode, 0x00]).unwrap()
+        };
+
+        // Two interleaved channels; channel 0 hits EOF at sector 12 and restarts at 14
+        let sectors = vec![
+            (10, header(0, 0x64)),
+            (11, header(1, 0x64)),
+            (12, header(0, 0xE4)), // EOF
+            (13, header(1, 0x64)),
+            (14, header(0, 0x64)),
+            (15, header(1, 0x08)), // Data sector, ignored
+        ];
+
+        let streams = group_xa_streams(sectors);
+        assert_eq!(streams.len(), 3);
+
+        assert_eq!(streams[0].channel, 0);
+        assert_eq!(streams[0].start_sector, 10);
+        assert_eq!(streams[0].end_sector, 12);
+        assert_eq!(streams[0].sector_count, 2);
+
+        assert_eq!(streams[1].channel, 1);
+        assert_eq!(streams[1].start_sector, 11);
+        assert_eq!(streams[1].end_sector, 13);
+        assert_eq!(streams[1].sector_count, 2);
+
+        assert_eq!(streams[2].channel, 0);
+        assert_eq!(streams[2].start_sector, 14);
+        assert_eq!(streams[2].sector_count, 1);
+    }
+
+    #[test]
+    fn test_group_xa_streams_splits_on_sequence_break() {
+        let header = |channel: u8| {
+            XaSubHeader::parse(&[1, channel, 0x64, 0x00, 1, channel, 0x64, 0x00]).unwrap()
+        };
+
+        // Channel 0 every 4 sectors, then a stride change, then another file far away
+        let sectors = vec![
+            (100, header(0)),
+            (104, header(0)),
+            (108, header(0)),
+            (110, header(0)), // Stride breaks
+            (112, header(0)),
+            (5000, header(0)), // Same file and channel, elsewhere on the disc
+        ];
+
+        let streams = group_xa_streams(sectors);
+        let ranges: Vec<_> = streams
+            .iter()
+            .map(|s| (s.start_sector, s.end_sector, s.sector_count))
+            .collect();
+        assert_eq!(ranges, [(100, 108, 3), (110, 112, 2), (5000, 5000, 1)]);
+    }
 }
@@ -37,6 +37,52 @@ const K1_FILTERS: [f64; 4] = [
     -0.859375, // Filter 3: -55.0 / 64.0
 ];
 
+/// CD emphasis time constant of the pole, in seconds (50 µs)
+const EMPHASIS_POLE: f64 = 50e-6;
+
+/// CD emphasis time constant of the zero, in seconds (15 µs)
+const EMPHASIS_ZERO: f64 = 15e-6;
+
+/// De-emphasis filter for one channel of pre-emphasized audio
+///
+/// First-order shelf undoing the standard CD 50/15 µs pre-emphasis: unity
+/// gain at DC, falling to 15/50 (about -10.5 dB) at high frequencies.
+/// Derived from the analog response `(1 + s*15µs) / (1 + s*50µs)` by
+/// bilinear transform.
+#[derive(Debug, Clone)]
+pub struct DeEmphasis {
+    b0: f64,
+    b1: f64,
+    a1: f64,
+    prev_in: f64,
+    prev_out: f64,
+}
+
+impl DeEmphasis {
+    /// Create a filter for the given sample rate
+    pub fn new(sample_rate: u32) -> Self {
+        let k = 2.0 * sample_rate as f64;
+        let norm = 1.0 + EMPHASIS_POLE * k;
+
+        Self {
+            b0: (1.0 + EMPHASIS_ZERO * k) / norm,
+            b1: (1.0 - EMPHASIS_ZERO * k) / norm,
+            a1: (1.0 - EMPHASIS_POLE * k) / norm,
+            prev_in: 0.0,
+            prev_out: 0.0,
+        }
+    }
+
+    /// Filter one sample
+    pub fn process(&mut self, sample: i16) -> i16 {
+        let input = sample as f64;
+        let output = self.b0 * input + self.b1 * self.prev_in - self.a1 * self.prev_out;
+        self.prev_in = input;
+        self.prev_out = output;
+        output.round().clamp(-32768.0, 32767.0) as i16
+    }
+}
+
 /// ADPCM decoding context (maintains state between samples)
 #[derive(Debug, Clone)]
 struct AdpcmContext {
@@ -86,6 +132,10 @@ pub struct XaAdpcmDecoder {
     left_context: AdpcmContext,
     /// Right channel context (if stereo)
     right_context: Option<AdpcmContext>,
+    /// De-emphasis filters (left/mono, right) for pre-emphasized streams
+    de_emphasis: Option<[DeEmphasis; 2]>,
+    /// Whether to undo pre-emphasis on pre-emphasized streams
+    apply_emphasis: bool,
 }
 
 impl XaAdpcmDecoder {
@@ -110,9 +160,32 @@ impl XaAdpcmDecoder {
             } else {
                 None
             },
+            de_emphasis: None,
+            apply_emphasis: true,
         }
     }
 
+    /// Mark the stream as pre-emphasized, per its coding info emphasis bit
+    ///
+    /// Decoded PCM from an emphasized stream goes through a [`DeEmphasis`]
+    /// filter so it doesn't sound overly bright, unless turned off with
+    /// [`set_apply_emphasis`](Self::set_apply_emphasis).
+    pub fn with_emphasis(mut self, emphasized: bool, sample_rate: u32) -> Self {
+        self.de_emphasis =
+            emphasized.then(|| [DeEmphasis::new(sample_rate), DeEmphasis::new(sample_rate)]);
+        self
+    }
+
+    /// Choose whether to undo pre-emphasis (on by default)
+    pub fn set_apply_emphasis(&mut self, apply: bool) {
+        self.apply_emphasis = apply;
+    }
+
+    /// Whether pre-emphasis is undone on emphasized streams
+    pub fn apply_emphasis(&self) -> bool {
+        self.apply_emphasis
+    }
+
     /// Calculate number of PCM sample frames generated by one sector
     ///
     /// A "frame" is one sample for mono, or one sample per channel for stereo.
@@ -153,6 +226,14 @@ impl XaAdpcmDecoder {
             self.decode_sound_group(sound_group, &mut output);
         }
 
+        // Filter after decoding: ADPCM prediction works on the raw samples
+        if let Some(filters) = self.de_emphasis.as_mut().filter(|_| self.apply_emphasis) {
+            let channels = if self.stereo { 2 } else { 1 };
+            for (i, sample) in output.iter_mut().enumerate() {
+                *sample = filters[i % channels].process(*sample);
+            }
+        }
+
         output
     }
 
@@ -239,25 +320,17 @@ impl XaAdpcmDecoder {
 
     /// Extract sound parameters (filter index and range) for a sound unit
     fn get_sound_parameters(&self, params: &[u8], unit_idx: usize) -> (usize, u8) {
-        // Sound parameters are stored in first 16 bytes
-        // For 4-bit: parameters are duplicated and interleaved
-        // For 8-bit: parameters are repeated 4 times
-
-        let param_byte = if self.bits_per_sample == 4 {
-            // 4-bit: 8 units, params at [0..4] and [4..8] (duplicated)
-            params[unit_idx % 4]
-        } else {
-            // 8-bit: 4 units, params at [0..4] repeated
-            params[unit_idx]
-        };
+        // Bytes 4..12 hold one parameter byte per unit (8 units at 4-bit,
+        // 4 at 8-bit); bytes 0..4 and 12..16 are copies
+        let param_byte = params[4 + unit_idx];
 
-        let filter_idx = ((param_byte & 0x0C) >> 2) as usize & 0x03;
+        let filter_idx = ((param_byte >> 4) & 0x03) as usize;
         let range = (param_byte & 0x0F).min(12); // Clamp to max 12
 
         (filter_idx, range)
     }
 
-    /// Extract one ADPCM sample from sound group
+    /// Extract one ADPCM sample from sound group, scaled to the top of an i16
     fn get_adpcm_sample(&self, sound_group: &[u8], unit_idx: usize, sample_idx: usize) -> i16 {
         // ADPCM data starts after the 16-byte parameter header
         let data_offset = 16;
@@ -266,24 +339,21 @@ impl XaAdpcmDecoder {
             // 4-bit samples: 2 samples per byte
             // Sound units are interleaved
             let byte_idx = data_offset + sample_idx * 4 + unit_idx / 2;
-            let byte = sound_group[byte_idx] as i16;
+            let byte = sound_group[byte_idx] as u16;
 
-            // Even units use high nibble, odd units use low nibble
+            // Even units use low nibble, odd units use high nibble
             let nibble = if unit_idx % 2 == 0 {
-                (byte >> 4) & 0x0F
-            } else {
                 byte & 0x0F
+            } else {
+                (byte >> 4) & 0x0F
             };
 
-            // Sign-extend 4-bit to 16-bit (shift to top, then arithmetic shift back)
-            (nibble << 12) >> 4
+            // Moving the nibble to the top sign-extends it once shifted by range
+            (nibble << 12) as i16
         } else {
             // 8-bit samples: 1 sample per byte
             let byte_idx = data_offset + sample_idx * 4 + unit_idx;
-            let byte = sound_group[byte_idx] as i8 as i16;
-
-            // Sign-extend 8-bit to 16-bit (shift to top, then arithmetic shift back)
-            (byte << 8) >> 4
+            ((sound_group[byte_idx] as u16) << 8) as i16
         }
     }
 }
@@ -301,6 +371,85 @@ mod tests {
         assert_eq!(decoder.samples_per_sector(), 18 * 28 * 4);
     }
 
+    /// Build a sound group with the same parameter byte for every unit
+    fn sound_group(param: u8, data: u8) -> Vec<u8> {
+        let mut group = vec![param; 16];
+        group.resize(SOUND_GROUP_SIZE, data);
+        group
+    }
+
+    #[test]
+    fn test_decode_4bit() {
+        // Filter 0, range 0: units alternate between the low and high nibble
+        let mut decoder = XaAdpcmDecoder::new(4, false, 1.0);
+        let pcm = decoder.decode_sector(&sound_group(0x00, 0xF1));
+
+        assert_eq!(pcm.len(), SAMPLES_PER_SOUND_UNIT * SOUND_UNITS_4BIT);
+        assert!(pcm[..28].iter().all(|&s| s == 0x1000));
+        assert!(pcm[28..56].iter().all(|&s| s == -0x1000));
+
+        // Range 4 shifts the samples down
+        let mut decoder = XaAdpcmDecoder::new(4, false, 1.0);
+        let pcm = decoder.decode_sector(&sound_group(0x04, 0x11));
+        assert!(pcm.iter().all(|&s| s == 0x100));
+    }
+
+    #[test]
+    fn test_decode_8bit() {
+        let mut decoder = XaAdpcmDecoder::new(8, false, 1.0);
+        let pcm = decoder.decode_sector(&sound_group(0x02, 0x80));
+
+        assert_eq!(pcm.len(), SAMPLES_PER_SOUND_UNIT * SOUND_UNITS_8BIT);
+        assert!(pcm.iter().all(|&s| s == -0x8000 >> 2));
+
+        // Filter 1 accumulates the previous sample (60/64 of it)
+        let mut decoder = XaAdpcmDecoder::new(8, false, 1.0);
+        let pcm = decoder.decode_sector(&sound_group(0x18, 0x01));
+        assert_eq!(pcm[0], 1);
+        assert_eq!(pcm[1], 2); // 1 + round(0.9375)
+    }
+
+    #[test]
+    fn test_de_emphasis_passes_dc() {
+        let mut filter = DeEmphasis::new(37800);
+        let output: Vec<i16> = (0..200).map(|_| filter.process(10000)).collect();
+        assert!(output[100..].iter().all(|&s| (s - 10000).abs() <= 1));
+    }
+
+    #[test]
+    fn test_de_emphasis_attenuates_high_frequencies() {
+        let mut filter = DeEmphasis::new(37800);
+        let output: Vec<i16> = (0..200)
+            .map(|i| filter.process(if i % 2 == 0 { 10000 } else { -10000 }))
+            .collect();
+
+        // Nyquist gain is 15/50
+        let peak = output[100..]
+            .iter()
+            .map(|s| s.unsigned_abs())
+            .max()
+            .unwrap();
+        assert!((2900..=3100).contains(&peak), "peak {}", peak);
+    }
+
+    #[test]
+    fn test_emphasis_flag() {
+        let group = sound_group(0x00, 0x77);
+
+        let mut plain = XaAdpcmDecoder::new(4, false, 1.0);
+        let mut emphasized = XaAdpcmDecoder::new(4, false, 1.0).with_emphasis(true, 37800);
+        assert!(emphasized.apply_emphasis());
+        assert_ne!(
+            plain.decode_sector(&group),
+            emphasized.decode_sector(&group)
+        );
+
+        let mut disabled = XaAdpcmDecoder::new(4, false, 1.0).with_emphasis(true, 37800);
+        disabled.set_apply_emphasis(false);
+        let mut plain = XaAdpcmDecoder::new(4, false, 1.0);
+        assert_eq!(plain.decode_sector(&group), disabled.decode_sector(&group));
+    }
+
     #[test]
     fn test_filter_coefficients() {
         // Verify XA filter coefficients
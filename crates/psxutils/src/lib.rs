@@ -4,18 +4,37 @@
 //!
 //! ## Supported Formats
 //!
-//! - **CD-ROM**: ISO 9660 with CD-XA extensions
+//! - **CD-ROM**: ISO 9660 with CD-XA extensions, Joliet names, and CUE sheets
 //! - **TIM**: Texture Image format (4/8/16/24-bit)
 //! - **VAB**: Sound bank format
 //! - **VAG**: Sound sample format (ADPCM)
 //! - **TMD**: 3D model format
-//! - **STR**: Movie/video format (planned)
+//! - **STR**: Movie/video format (MDEC version 2 frames)
+//!
+//! With the `serde` feature (enabled by `extraction`), parsed TIM, TMD, VAB
+//! and VAG metadata can be serialized, e.g. to build an asset index. Raw
+//! pixel and ADPCM buffers are skipped and deserialize as empty.
 //!
 //! ## Asset Scanning
 //!
 //! The `scanner` module provides tools for discovering embedded assets in
 //! binary containers by scanning for format signatures.
 //!
+//! ## VRAM Reconstruction
+//!
+//! The `vram` module rebuilds the 1024x512 VRAM layout from TIMs so TMD
+//! texture page and CLUT references can be resolved to colors.
+//!
+//! ## `no_std`
+//!
+//! The TIM and TMD parsers only need `alloc`. Building without the default
+//! `std` feature leaves just those (plus [`PsxError`]), so they can be used
+//! from WASM or embedded targets:
+//!
+//! ```toml
+//! psxutils = { version = "0.1", default-features = false }
+//! ```
+//!
 //! ## Example
 //!
 //! ```no_run
@@ -34,18 +53,49 @@
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
 pub mod cdrom;
 pub mod formats;
+#[cfg(feature = "std")]
+pub mod mips;
+#[cfg(feature = "std")]
 pub mod scanner;
+#[cfg(feature = "std")]
+pub mod vram;
 
 // Re-export commonly used types
+#[cfg(feature = "std")]
 pub use cdrom::CdRom;
-pub use formats::{tim::Tim, tmd::Tmd, vab::Vab, vag::Vag};
-pub use scanner::{AssetScanner, AssetType, DiscoveredAsset};
+pub use formats::{tim::Tim, tmd::Tmd};
+#[cfg(feature = "std")]
+pub use formats::{vab::Vab, vag::Vag};
+#[cfg(feature = "std")]
+pub use scanner::{
+    detect_format, AssetScanner, AssetType, DiscoveredAsset, KnownFormat, SequentialScanner,
+};
+#[cfg(feature = "std")]
+pub use vram::VramAtlas;
+
+use alloc::string::String;
+
+/// `alloc` items the `no_std` parsers would otherwise get from the std prelude
+mod prelude {
+    pub use alloc::format;
+    pub use alloc::string::ToString;
+    pub use alloc::vec::Vec;
+}
 
 /// Common error type for psxutils
+///
+/// Parse failures need only `alloc`; the I/O variant exists with the `std`
+/// feature.
 #[derive(Debug, thiserror::Error)]
 pub enum PsxError {
+    #[cfg(feature = "std")]
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -60,7 +110,28 @@ pub enum PsxError {
 
     #[error("Unsupported format version: {0}")]
     UnsupportedVersion(u32),
+
+    /// The data ends before a structure the parser needs
+    #[error("{what} truncated: needed {needed} bytes, got {got}")]
+    Truncated {
+        what: &'static str,
+        needed: usize,
+        got: usize,
+    },
+
+    /// The magic number doesn't match the format (four-character magics are
+    /// read big-endian, so `"VAGp"` is `0x56414770`)
+    #[error("Invalid {format} magic: 0x{found:08X}, expected 0x{expected:08X}")]
+    BadMagic {
+        format: &'static str,
+        expected: u32,
+        found: u32,
+    },
+
+    /// TIM pixel mode outside the defined 0-4 range
+    #[error("Unsupported TIM pixel mode: {0}")]
+    UnsupportedMode(u8),
 }
 
 /// Common result type for psxutils
-pub type Result<T> = std::result::Result<T, PsxError>;
+pub type Result<T> = core::result::Result<T, PsxError>;
@@ -0,0 +1,281 @@
+//! MIPS R3000 code detection
+//!
+//! Heuristics for finding PlayStation machine code, such as the overlays the
+//! game loads into RAM at fixed addresses, inside otherwise opaque archives.
+//!
+//! Opcode validity alone is a weak signal: almost half of all random words
+//! decode to some valid instruction, and zero padding is a valid `nop`. So
+//! [`detect_overlay`] also requires a realistic share of jumps and branches
+//! whose targets land somewhere plausible, which data rarely manages.
+
+/// Words sampled from the start of a candidate region
+pub const SAMPLE_WORDS: usize = 256;
+
+/// Default minimum fraction of valid instructions for [`detect_overlay`]
+pub const DEFAULT_THRESHOLD: f32 = 0.9;
+
+/// Minimum fraction of sampled words that are jumps or branches
+const MIN_CONTROL_FLOW_RATIO: f32 = 0.02;
+
+/// Minimum fraction of jumps and branches with a plausible target
+const MIN_PLAUSIBLE_TARGET_RATIO: f32 = 0.8;
+
+/// Maximum fraction of sampled words that are `nop`
+const MAX_NOP_RATIO: f32 = 0.5;
+
+/// Size of PSX main RAM, which bounds absolute jump targets
+const RAM_SIZE: u32 = 2 * 1024 * 1024;
+
+/// Largest overlay [`estimate_overlay_size`] will report
+const MAX_OVERLAY_SIZE: usize = 256 * 1024;
+
+/// Smallest region worth testing
+const MIN_OVERLAY_SIZE: usize = 256;
+
+/// MIPS code found by [`detect_overlay`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MipsOverlay {
+    /// Estimated size of the code in bytes
+    pub size: usize,
+    /// Fraction of sampled words that are valid instructions
+    pub confidence: f32,
+}
+
+/// Check whether a word decodes to a valid R3000 instruction
+///
+/// Covers the base ISA plus the COP0 and GTE (COP2) instructions the PSX
+/// uses; reserved SPECIAL functions and REGIMM encodings are rejected.
+pub fn is_likely_instruction(word: u32) -> bool {
+    let opcode = word >> 26;
+
+    match opcode {
+        // SPECIAL: shifts, jr/jalr, syscall/break, mfhi..mtlo, mult/div, ALU
+        0x00 => matches!(
+            word & 0x3F,
+            0x00 | 0x02..=0x04 | 0x06..=0x09 | 0x0C | 0x0D | 0x10..=0x13 | 0x18..=0x1B
+                | 0x20..=0x27 | 0x2A | 0x2B
+        ),
+        // REGIMM: bltz, bgez, bltzal, bgezal
+        0x01 => matches!((word >> 16) & 0x1F, 0x00 | 0x01 | 0x10 | 0x11),
+        // j, jal, branches, immediate ALU, lui
+        0x02..=0x0F => true,
+        // COP0 and GTE
+        0x10 | 0x12 => true,
+        // Loads and stores, including lwl/lwr/swl/swr
+        0x20..=0x26 | 0x28..=0x2B | 0x2E => true,
+        // GTE loads and stores (lwc2/swc2)
+        0x32 | 0x3A => true,
+        _ => false,
+    }
+}
+
+/// Fraction of valid instructions among the first [`SAMPLE_WORDS`] words
+///
+/// Returns 0.0 for data shorter than one word.
+pub fn mips_confidence(data: &[u8]) -> f32 {
+    let words: Vec<u32> = sample_words(data).collect();
+    if words.is_empty() {
+        return 0.0;
+    }
+
+    let valid = words
+        .iter()
+        .filter(|&&word| is_likely_instruction(word))
+        .count();
+    valid as f32 / words.len() as f32
+}
+
+/// Estimate where a block of code starting at `data[0]` ends
+///
+/// Scans for a run of zero padding or the magic of the next known asset
+/// (TIM, VAG or LZSS), up to 256 KB. Falls back to 64 KB, or the whole
+/// buffer if shorter.
+pub fn estimate_overlay_size(data: &[u8]) -> usize {
+    let max_scan = MAX_OVERLAY_SIZE.min(data.len());
+
+    for i in (MIN_OVERLAY_SIZE..max_scan).step_by(4) {
+        if i + 4 > data.len() {
+            break;
+        }
+
+        let word = u32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+
+        // TIM, "VAGp" and "sszl" magic
+        if word == 0x00000010 || word == 0x70474156 || word == 0x6C7A7373 {
+            return i;
+        }
+
+        // Section padding
+        if word == 0 && i + 16 <= data.len() && data[i..i + 16].iter().all(|&b| b == 0) {
+            return i;
+        }
+    }
+
+    (64 * 1024).min(data.len())
+}
+
+/// Detect MIPS code at the start of `data`
+///
+/// Requires at least `threshold` of the sampled words to be valid
+/// instructions, a realistic density of jumps and branches, mostly
+/// plausible targets for them (branches inside the buffer, jumps inside
+/// RAM), and not too much `nop` padding. See [`DEFAULT_THRESHOLD`].
+pub fn detect_overlay(data: &[u8], threshold: f32) -> Option<MipsOverlay> {
+    if data.len() < MIN_OVERLAY_SIZE {
+        return None;
+    }
+
+    let confidence = mips_confidence(data);
+    if confidence < threshold {
+        return None;
+    }
+
+    let words: Vec<u32> = sample_words(data).collect();
+    let total_words = (data.len() / 4) as i64;
+    let nops = words.iter().filter(|&&word| word == 0).count();
+    if nops as f32 > words.len() as f32 * MAX_NOP_RATIO {
+        return None;
+    }
+
+    let mut control_flow = 0;
+    let mut plausible = 0;
+    for (index, &word) in words.iter().enumerate() {
+        let target_ok = match word >> 26 {
+            // jr, jalr: target in a register
+            0x00 if matches!(word & 0x3F, 0x08 | 0x09) => true,
+            // j, jal: absolute target within RAM
+            0x02 | 0x03 => (word & 0x03FF_FFFF) << 2 < RAM_SIZE,
+            // Branches: relative target within the buffer
+            0x01 if is_likely_instruction(word) => branch_in_bounds(index, word, total_words),
+            0x04..=0x07 => branch_in_bounds(index, word, total_words),
+            _ => continue,
+        };
+
+        control_flow += 1;
+        if target_ok {
+            plausible += 1;
+        }
+    }
+
+    if (control_flow as f32) < words.len() as f32 * MIN_CONTROL_FLOW_RATIO
+        || (plausible as f32) < control_flow as f32 * MIN_PLAUSIBLE_TARGET_RATIO
+    {
+        return None;
+    }
+
+    Some(MipsOverlay {
+        size: estimate_overlay_size(data),
+        confidence,
+    })
+}
+
+/// Whether a branch at word `index` targets a word inside the buffer
+fn branch_in_bounds(index: usize, word: u32, total_words: i64) -> bool {
+    let offset = (word & 0xFFFF) as i16 as i64;
+    let target = index as i64 + 1 + offset;
+    (0..total_words).contains(&target)
+}
+
+/// Little-endian words from the start of `data`, up to [`SAMPLE_WORDS`]
+fn sample_words(data: &[u8]) -> impl Iterator<Item = u32> + '_ {
+    data.chunks_exact(4)
+        .take(SAMPLE_WORDS)
+        .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A small function body, repeated to fill a realistic code slab
+    fn code_slab(repeats: usize) -> Vec<u8> {
+        const FUNCTION: [u32; 16] = [
+            0x27BDFFE0, // addiu sp, sp, -32
+            0xAFBF001C, // sw ra, 28(sp)
+            0xAFB00018, // sw s0, 24(sp)
+            0x3C028007, // lui v0, 0x8007
+            0x8C421234, // lw v0, 0x1234(v0)
+            0x10400004, // beq v0, zero, +4
+            0x00000000, // nop
+            0x0C0048D0, // jal 0x80012340
+            0x02002021, // addu a0, s0, zero
+            0x1440FFFA, // bne v0, zero, -6
+            0x304300FF, // andi v1, v0, 0xFF
+            0x00031880, // sll v1, v1, 2
+            0x8FBF001C, // lw ra, 28(sp)
+            0x8FB00018, // lw s0, 24(sp)
+            0x03E00008, // jr ra
+            0x27BD0020, // addiu sp, sp, 32
+        ];
+
+        FUNCTION
+            .iter()
+            .cycle()
+            .take(FUNCTION.len() * repeats)
+            .flat_map(|word| word.to_le_bytes())
+            .collect()
+    }
+
+    /// Deterministic pseudo-random bytes
+    fn noise(len: usize, seed: u32) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (state >> 24) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_instruction_validity() {
+        assert!(is_likely_instruction(0x00000000)); // nop
+        assert!(is_likely_instruction(0x03E00008)); // jr ra
+        assert!(is_likely_instruction(0x4A180001)); // GTE command
+        assert!(!is_likely_instruction(0x00000001)); // Reserved SPECIAL function
+        assert!(!is_likely_instruction(0x04020000)); // Reserved REGIMM encoding
+        assert!(!is_likely_instruction(0xFC000000)); // Reserved opcode
+    }
+
+    #[test]
+    fn test_code_is_detected() {
+        let mut data = code_slab(40);
+        data.extend_from_slice(&[0; 64]);
+
+        assert_eq!(mips_confidence(&data), 1.0);
+        let overlay = detect_overlay(&data, DEFAULT_THRESHOLD).unwrap();
+        assert_eq!(overlay.size, 40 * 64);
+        assert_eq!(overlay.confidence, 1.0);
+    }
+
+    #[test]
+    fn test_random_bytes_are_rejected() {
+        for seed in 0..32 {
+            let data = noise(4096, seed);
+            assert!(mips_confidence(&data) < DEFAULT_THRESHOLD);
+            assert_eq!(detect_overlay(&data, DEFAULT_THRESHOLD), None);
+            // Even with no opcode threshold, the control flow checks hold
+            assert_eq!(detect_overlay(&data, 0.0), None, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_padding_is_rejected() {
+        // All nops: perfectly "valid" but not code
+        let data = vec![0u8; 4096];
+        assert_eq!(mips_confidence(&data), 1.0);
+        assert_eq!(detect_overlay(&data, DEFAULT_THRESHOLD), None);
+    }
+
+    #[test]
+    fn test_estimate_overlay_size() {
+        let mut data = code_slab(32);
+        data.extend_from_slice(b"VAGp");
+        data.extend_from_slice(&[0xAA; 128]);
+        assert_eq!(estimate_overlay_size(&data), 32 * 64);
+
+        // No boundary: the whole short buffer
+        let data = code_slab(8);
+        assert_eq!(estimate_overlay_size(&data), data.len());
+    }
+}
@@ -3,8 +3,14 @@
 //! Scans through binary data containers looking for embedded assets by their
 //! magic numbers and signatures. Similar to forensic tools like binwalk or foremost.
 
+use crate::formats::legaia_model::LEGAIA_MODEL_SIGNATURE;
+use crate::formats::lzss::LZSS_MAGIC;
+use crate::formats::str::{STR_SECTOR_MAGIC, STR_VIDEO_TYPE};
 use crate::formats::tmd::TMD_MAGIC;
-use crate::formats::{Tim, Tmd};
+use crate::formats::vab::VAB_MAGIC;
+use crate::formats::{LegaiaModel, Tim, Tmd, Vag};
+use crate::Result;
+use std::io::Read;
 
 /// Magic number for TIM texture format (0x00000010)
 const TIM_MAGIC: u32 = 0x00000010;
@@ -12,6 +18,13 @@ const TIM_MAGIC: u32 = 0x00000010;
 /// Magic number for VAG audio format ("VAGp")
 const VAG_MAGIC: u32 = 0x70474156; // "VAGp" in little-endian
 
+/// Lookahead kept by [`AssetScanner::scan_streaming`]; bounds the largest
+/// detectable asset (a full 1024x512 16-bit TIM is just over 1MB)
+pub const STREAM_WINDOW: usize = 4 * 1024 * 1024;
+
+/// Read size used by [`AssetScanner::scan_streaming`]
+const STREAM_CHUNK: usize = 64 * 1024;
+
 /// Discovered asset in a container file
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -34,6 +47,140 @@ pub enum AssetType {
     Tmd { object_count: u32 },
     /// VAG audio sample
     Vag,
+    /// Legaia custom 3D model (see [`LegaiaModel`])
+    CustomModel,
+}
+
+/// File format identified by its magic number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KnownFormat {
+    /// TIM texture (0x00000010)
+    Tim,
+    /// VAG audio sample ("VAGp")
+    Vag,
+    /// VAB sound bank ("VABp")
+    Vab,
+    /// TMD model (0x00000041)
+    Tmd,
+    /// LZSS-compressed block ("sszl")
+    Lzss,
+    /// STR video sector (0x0160 followed by 0x8001)
+    Str,
+}
+
+impl KnownFormat {
+    /// Conventional file extension for the raw format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            KnownFormat::Tim => "tim",
+            KnownFormat::Vag => "vag",
+            KnownFormat::Vab => "vab",
+            KnownFormat::Tmd => "tmd",
+            KnownFormat::Lzss => "lzs",
+            KnownFormat::Str => "str",
+        }
+    }
+}
+
+/// Identify a file from the magic number at its start
+///
+/// Only the magic is checked, so this is cheap but can be fooled by data
+/// that happens to start with the same bytes; use [`AssetScanner::detect`]
+/// to also validate the header.
+pub fn detect_format(data: &[u8]) -> Option<KnownFormat> {
+    let magic: [u8; 4] = data.get(..4)?.try_into().ok()?;
+
+    match u32::from_le_bytes(magic) {
+        TIM_MAGIC => return Some(KnownFormat::Tim),
+        TMD_MAGIC => return Some(KnownFormat::Tmd),
+        _ => {}
+    }
+
+    if magic == VAG_MAGIC.to_le_bytes() {
+        Some(KnownFormat::Vag)
+    } else if magic == VAB_MAGIC {
+        Some(KnownFormat::Vab)
+    } else if &magic == LZSS_MAGIC {
+        Some(KnownFormat::Lzss)
+    } else if u16::from_le_bytes([magic[0], magic[1]]) == STR_SECTOR_MAGIC
+        && u16::from_le_bytes([magic[2], magic[3]]) == STR_VIDEO_TYPE
+    {
+        Some(KnownFormat::Str)
+    } else {
+        None
+    }
+}
+
+/// Asset kinds the scanner looks for
+#[derive(Debug, Clone, Copy)]
+enum AssetKind {
+    Tim,
+    Tmd,
+    Vag,
+    CustomModel,
+}
+
+impl AssetKind {
+    /// Bytes needed before a candidate offset is worth checking
+    fn min_header(self) -> usize {
+        match self {
+            AssetKind::Tim | AssetKind::Tmd | AssetKind::CustomModel => 12,
+            AssetKind::Vag => 48,
+        }
+    }
+
+    /// First byte of this kind's magic number, if it has one
+    ///
+    /// Lets the scanner jump between candidate offsets instead of probing
+    /// every byte.
+    fn magic_byte(self) -> Option<u8> {
+        let magic = match self {
+            AssetKind::Tim => TIM_MAGIC,
+            AssetKind::Tmd => TMD_MAGIC,
+            AssetKind::Vag => VAG_MAGIC,
+            AssetKind::CustomModel => return None,
+        };
+        Some(magic.to_le_bytes()[0])
+    }
+
+    /// Check for an asset of this kind at the start of `data`
+    ///
+    /// Validation never allocates, so probing every offset stays cheap.
+    fn probe(self, data: &[u8], offset: usize, min_size: usize) -> Option<DiscoveredAsset> {
+        let magic = u32::from_le_bytes(data.get(..4)?.try_into().ok()?);
+
+        let (size, asset_type) = match self {
+            AssetKind::Tim if magic == TIM_MAGIC => {
+                let (width, height, size) = Tim::validate(data).ok()?;
+                (size, AssetType::Tim { width, height })
+            }
+            AssetKind::Tmd if magic == TMD_MAGIC => {
+                // Header and object table only, geometry is not parsed
+                let (object_count, size) = Tmd::validate(data).ok()?;
+                (size, AssetType::Tmd { object_count })
+            }
+            AssetKind::Vag if magic == VAG_MAGIC => {
+                // Header data size is bounds-checked against the buffer
+                let (_sample_rate, size) = Vag::validate(data).ok()?;
+                (size, AssetType::Vag)
+            }
+            AssetKind::CustomModel => {
+                // No magic of its own: size at offset 0, signature at offset 4
+                let signature = u32::from_le_bytes(data.get(4..8)?.try_into().ok()?);
+                if signature != LEGAIA_MODEL_SIGNATURE {
+                    return None;
+                }
+                (LegaiaModel::validate(data).ok()?, AssetType::CustomModel)
+            }
+            _ => return None,
+        };
+
+        (size >= min_size).then_some(DiscoveredAsset {
+            offset,
+            size,
+            asset_type,
+        })
+    }
 }
 
 /// Asset scanner for binary data
@@ -67,11 +214,11 @@ impl<'a> AssetScanner<'a> {
         // Scan for TIM textures
         assets.extend(self.scan_tim());
 
-        // Scan for TMD models - DISABLED: causes OOM
-        // assets.extend(self.scan_tmd());
+        // Scan for TMD models
+        assets.extend(self.scan_tmd());
 
-        // Scan for VAG audio - DISABLED: causes OOM
-        // assets.extend(self.scan_vag());
+        // Scan for VAG audio
+        assets.extend(self.scan_vag());
 
         // Sort by offset
         assets.sort_by_key(|a| a.offset);
@@ -81,82 +228,41 @@ impl<'a> AssetScanner<'a> {
 
     /// Scan for TIM textures
     fn scan_tim(&self) -> Vec<DiscoveredAsset> {
-        let mut assets = Vec::new();
-        let mut offset = 0;
-
-        while offset + 12 <= self.data.len() {
-            // Check for TIM magic at this offset
-            if let Some(magic_bytes) = self.data.get(offset..offset + 4) {
-                let magic = u32::from_le_bytes([
-                    magic_bytes[0],
-                    magic_bytes[1],
-                    magic_bytes[2],
-                    magic_bytes[3],
-                ]);
-
-                if magic == TIM_MAGIC {
-                    // Try to validate TIM without allocating memory
-                    match Tim::validate(&self.data[offset..]) {
-                        Ok((width, height, size)) => {
-                            if size >= self.min_size {
-                                assets.push(DiscoveredAsset {
-                                    offset,
-                                    size,
-                                    asset_type: AssetType::Tim { width, height },
-                                });
-                                // Skip past this TIM
-                                offset += size;
-                                continue;
-                            }
-                        }
-                        Err(_e) => {
-                            // TIM magic but invalid format - just skip
-                        }
-                    }
-                }
-            }
-
-            offset += 1;
-        }
-
-        assets
+        self.scan_kind(AssetKind::Tim)
     }
 
     /// Scan for TMD models
     fn scan_tmd(&self) -> Vec<DiscoveredAsset> {
+        self.scan_kind(AssetKind::Tmd)
+    }
+
+    /// Scan for VAG audio samples
+    fn scan_vag(&self) -> Vec<DiscoveredAsset> {
+        self.scan_kind(AssetKind::Vag)
+    }
+
+    /// Scan for one kind of asset, skipping past each one found
+    fn scan_kind(&self, kind: AssetKind) -> Vec<DiscoveredAsset> {
         let mut assets = Vec::new();
         let mut offset = 0;
 
-        while offset + 12 <= self.data.len() {
-            // Check for TMD magic at this offset
-            if let Some(magic_bytes) = self.data.get(offset..offset + 4) {
-                let magic = u32::from_le_bytes([
-                    magic_bytes[0],
-                    magic_bytes[1],
-                    magic_bytes[2],
-                    magic_bytes[3],
-                ]);
-
-                if magic == TMD_MAGIC {
-                    // Try to parse and validate TMD
-                    if let Ok(tmd) = Tmd::parse(&self.data[offset..]) {
-                        // Estimate TMD size based on object count
-                        // This is approximate - TMD files don't have explicit size field
-                        let object_count = tmd.object_count() as u32;
-                        let estimated_size = 12 + (object_count as usize * 1024); // Rough estimate
-
-                        if estimated_size >= self.min_size {
-                            assets.push(DiscoveredAsset {
-                                offset,
-                                size: estimated_size,
-                                asset_type: AssetType::Tmd { object_count },
-                            });
-                            // Skip past this TMD
-                            offset += estimated_size;
-                            continue;
-                        }
-                    }
+        while offset + kind.min_header() <= self.data.len() {
+            // Only offsets starting with the magic's first byte can match
+            if let Some(byte) = kind.magic_byte() {
+                match self.data[offset..].iter().position(|&b| b == byte) {
+                    Some(skip) => offset += skip,
+                    None => break,
                 }
+                if offset + kind.min_header() > self.data.len() {
+                    break;
+                }
+            }
+
+            if let Some(asset) = kind.probe(&self.data[offset..], offset, self.min_size) {
+                // Skip past this asset
+                offset += asset.size;
+                assets.push(asset);
+                continue;
             }
 
             offset += 1;
@@ -165,51 +271,85 @@ impl<'a> AssetScanner<'a> {
         assets
     }
 
-    /// Scan for VAG audio samples
-    fn scan_vag(&self) -> Vec<DiscoveredAsset> {
+    /// Scan a stream for embedded assets without loading it into memory
+    ///
+    /// Data is read through a sliding window of [`STREAM_WINDOW`] bytes of
+    /// lookahead, so memory use stays bounded regardless of the stream
+    /// length. Assets larger than the window are not detected. Results match
+    /// [`AssetScanner::scan`] on the same bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use psxutils::{AssetScanner, CdRom};
+    /// let disc = CdRom::open("game.bin")?;
+    /// let reader = disc.read_file_stream("/PROT.DAT")?;
+    /// for asset in AssetScanner::scan_streaming(reader, 64)? {
+    ///     println!("{:?} at 0x{:08X}", asset.asset_type, asset.offset);
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn scan_streaming<R: Read>(mut reader: R, min_size: usize) -> Result<Vec<DiscoveredAsset>> {
+        const KINDS: [AssetKind; 3] = [AssetKind::Tim, AssetKind::Tmd, AssetKind::Vag];
+
         let mut assets = Vec::new();
-        let mut offset = 0;
+        let mut window = Vec::new();
+        let mut chunk = vec![0u8; STREAM_CHUNK];
+        // Stream offset of window[0], and of the first position not yet scanned
+        let mut base = 0;
+        let mut scan_pos = 0;
+        // Per kind, the first offset not covered by an already found asset
+        let mut next = [0usize; KINDS.len()];
+        let mut eof = false;
+
+        loop {
+            while !eof && window.len() < 2 * STREAM_WINDOW {
+                let n = reader.read(&mut chunk)?;
+                eof = n == 0;
+                window.extend_from_slice(&chunk[..n]);
+            }
 
-        while offset + 48 <= self.data.len() {
-            // Check for VAG magic at this offset
-            if let Some(magic_bytes) = self.data.get(offset..offset + 4) {
-                let magic = u32::from_le_bytes([
-                    magic_bytes[0],
-                    magic_bytes[1],
-                    magic_bytes[2],
-                    magic_bytes[3],
-                ]);
-
-                if magic == VAG_MAGIC {
-                    // VAG header is 48 bytes, followed by data
-                    // Size is stored at offset 12 (4 bytes, big-endian)
-                    if let Some(size_bytes) = self.data.get(offset + 12..offset + 16) {
-                        let size = u32::from_be_bytes([
-                            size_bytes[0],
-                            size_bytes[1],
-                            size_bytes[2],
-                            size_bytes[3],
-                        ]) as usize
-                            + 48; // Add header size
-
-                        if size >= self.min_size && offset + size <= self.data.len() {
-                            assets.push(DiscoveredAsset {
-                                offset,
-                                size,
-                                asset_type: AssetType::Vag,
-                            });
-                            // Skip past this VAG
-                            offset += size;
-                            continue;
-                        }
+            // Only scan positions with a full window of lookahead until EOF
+            let end = if eof {
+                base + window.len()
+            } else {
+                base + window.len() - STREAM_WINDOW
+            };
+
+            for offset in scan_pos..end {
+                for (kind, next) in KINDS.iter().zip(next.iter_mut()) {
+                    if offset < *next || offset - base + kind.min_header() > window.len() {
+                        continue;
+                    }
+                    if let Some(asset) = kind.probe(&window[offset - base..], offset, min_size) {
+                        *next = offset + asset.size;
+                        assets.push(asset);
                     }
                 }
             }
 
-            offset += 1;
+            if eof {
+                break;
+            }
+
+            window.drain(..end - base);
+            base = end;
+            scan_pos = end;
         }
 
-        assets
+        assets.sort_by_key(|a| a.offset);
+        Ok(assets)
+    }
+
+    /// Identify an asset starting at the beginning of `data`
+    ///
+    /// Uses the same magic and header checks as [`AssetScanner::scan`], so a
+    /// standalone file or container entry can be given a meaningful type
+    /// (and extension) without scanning every offset.
+    pub fn detect(data: &[u8]) -> Option<DiscoveredAsset> {
+        [AssetKind::Tim, AssetKind::Tmd, AssetKind::Vag]
+            .into_iter()
+            .find_map(|kind| kind.probe(data, 0, 0))
     }
 
     /// Extract a discovered asset as bytes
@@ -222,10 +362,101 @@ impl<'a> AssetScanner<'a> {
     }
 }
 
+/// Single-pass scanner that carves a container front to back
+///
+/// Where [`AssetScanner`] scans for each kind independently, this checks
+/// every kind at each offset (TIM, VAG, then custom models) and resumes
+/// right after the first match. Assets therefore never overlap and come out
+/// in discovery order, which is how PROT.DAT entries are numbered.
+/// TMD is not probed: Legaia stores its models in the custom format. `sszl`
+/// blocks are not carved either, since their header layout is provisional.
+#[derive(Debug, Clone, Default)]
+pub struct SequentialScanner {
+    min_size: usize,
+}
+
+impl SequentialScanner {
+    /// Kinds probed at each offset, most reliable signature first
+    const KINDS: [AssetKind; 3] = [AssetKind::Tim, AssetKind::Vag, AssetKind::CustomModel];
+
+    /// Create a sequential scanner with no minimum asset size
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set minimum asset size filter
+    pub fn with_min_size(mut self, min_size: usize) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Scan `data`, calling `callback` for each asset as it is found
+    ///
+    /// The callback sees assets in offset order, so the end of the last
+    /// asset doubles as a progress position. Returns all discovered assets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use psxutils::SequentialScanner;
+    /// let data = vec![0u8; 4096];
+    /// let assets = SequentialScanner::new().scan(&data, |asset| {
+    ///     println!("{:?} at 0x{:08X}", asset.asset_type, asset.offset);
+    /// });
+    /// assert!(assets.is_empty());
+    /// ```
+    pub fn scan(
+        &self,
+        data: &[u8],
+        mut callback: impl FnMut(&DiscoveredAsset),
+    ) -> Vec<DiscoveredAsset> {
+        let mut assets = Vec::new();
+        let mut offset = 0;
+
+        while offset < data.len() {
+            let found = Self::KINDS
+                .iter()
+                .filter(|kind| offset + kind.min_header() <= data.len())
+                .find_map(|kind| kind.probe(&data[offset..], offset, self.min_size));
+
+            match found {
+                Some(asset) => {
+                    callback(&asset);
+                    // Zero-sized assets are rejected by every probe, but never stall
+                    offset += asset.size.max(1);
+                    assets.push(asset);
+                }
+                None => offset += 1,
+            }
+        }
+
+        assets
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_detect_format() {
+        let cases: [(&[u8], KnownFormat); 6] = [
+            (&[0x10, 0, 0, 0, 0x08, 0, 0, 0], KnownFormat::Tim),
+            (b"VAGp\0\0\0\x20", KnownFormat::Vag),
+            (b"VABp\x07\0\0\0", KnownFormat::Vab),
+            (&[0x41, 0, 0, 0, 0, 0, 0, 0], KnownFormat::Tmd),
+            (b"sszl\0\x10\0\0", KnownFormat::Lzss),
+            (&[0x60, 0x01, 0x01, 0x80, 0, 0, 0, 0], KnownFormat::Str),
+        ];
+        for (data, format) in cases {
+            assert_eq!(detect_format(data), Some(format));
+        }
+
+        assert_eq!(detect_format(b"RIFF\0\0\0\0"), None);
+        assert_eq!(detect_format(&[0x10, 0]), None);
+        assert_eq!(detect_format(&[0x10, 0, 0, 0]).unwrap().extension(), "tim");
+    }
+
     #[test]
     fn test_scanner_empty() {
         let data = vec![0; 1024];
@@ -245,4 +476,197 @@ mod tests {
         // Should not detect invalid TIM
         assert_eq!(assets.len(), 0);
     }
+
+    #[test]
+    fn test_scanner_finds_tim_tmd_vag() {
+        let mut data = vec![0u8; 1024];
+
+        // 16-bit direct TIM, 8x4 pixels: 8-byte header + 12-byte block header + 64 bytes
+        let mut tim = Vec::new();
+        tim.extend_from_slice(&TIM_MAGIC.to_le_bytes());
+        tim.extend_from_slice(&2u32.to_le_bytes());
+        tim.extend_from_slice(&76u32.to_le_bytes());
+        for value in [0u16, 0, 8, 4] {
+            tim.extend_from_slice(&value.to_le_bytes());
+        }
+        tim.resize(84, 0x11);
+        data[64..64 + tim.len()].copy_from_slice(&tim);
+
+        // Single-object TMD: 4 vertices, 2 normals, 1 primitive (8-word packet)
+        let mut tmd = Vec::new();
+        tmd.extend_from_slice(&TMD_MAGIC.to_le_bytes());
+        tmd.extend_from_slice(&0u32.to_le_bytes());
+        tmd.extend_from_slice(&1u32.to_le_bytes());
+        for value in [40u32, 4, 72, 2, 88, 1, 1] {
+            tmd.extend_from_slice(&value.to_le_bytes());
+        }
+        tmd.resize(120, 0x22);
+        tmd[88] = 8;
+        data[256..256 + tmd.len()].copy_from_slice(&tmd);
+
+        // VAG with 64 bytes of ADPCM data
+        let mut vag = Vec::new();
+        vag.extend_from_slice(&VAG_MAGIC.to_le_bytes());
+        vag.extend_from_slice(&0x20u32.to_be_bytes());
+        vag.extend_from_slice(&0u32.to_be_bytes());
+        vag.extend_from_slice(&64u32.to_be_bytes());
+        vag.extend_from_slice(&22050u32.to_be_bytes());
+        vag.resize(48 + 64, 0x33);
+        data[512..512 + vag.len()].copy_from_slice(&vag);
+
+        let assets = AssetScanner::new(&data).scan();
+        assert_eq!(assets.len(), 3);
+
+        assert_eq!(assets[0].offset, 64);
+        assert_eq!(assets[0].size, 84);
+        assert_eq!(
+            assets[0].asset_type,
+            AssetType::Tim {
+                width: 8,
+                height: 4
+            }
+        );
+
+        assert_eq!(assets[1].offset, 256);
+        assert_eq!(assets[1].size, 120);
+        assert_eq!(assets[1].asset_type, AssetType::Tmd { object_count: 1 });
+
+        assert_eq!(assets[2].offset, 512);
+        assert_eq!(assets[2].size, 112);
+        assert_eq!(assets[2].asset_type, AssetType::Vag);
+    }
+
+    /// Reference scan that checks the full magic and calls the format's
+    /// validator at every offset, without `AssetKind::probe` or the
+    /// magic-byte skip
+    fn scan_kind_reference(data: &[u8], kind: AssetKind) -> Vec<(usize, usize, AssetType)> {
+        let mut assets = Vec::new();
+        let mut offset = 0;
+        while offset + 4 <= data.len() {
+            let rest = &data[offset..];
+            let magic = u32::from_le_bytes(rest[..4].try_into().unwrap());
+            let found = match kind {
+                AssetKind::Tim if magic == TIM_MAGIC => Tim::validate(rest)
+                    .ok()
+                    .map(|(width, height, size)| (size, AssetType::Tim { width, height })),
+                AssetKind::Tmd if magic == TMD_MAGIC => Tmd::validate(rest)
+                    .ok()
+                    .map(|(object_count, size)| (size, AssetType::Tmd { object_count })),
+                AssetKind::Vag if magic == VAG_MAGIC => Vag::validate(rest)
+                    .ok()
+                    .map(|(_, size)| (size, AssetType::Vag)),
+                _ => None,
+            };
+
+            match found.filter(|&(size, _)| size >= 64) {
+                Some((size, asset_type)) => {
+                    assets.push((offset, size, asset_type));
+                    offset += size;
+                }
+                None => offset += 1,
+            }
+        }
+        assets
+    }
+
+    #[test]
+    fn test_magic_byte_scan_matches_reference() {
+        // 16-bit direct TIM, 8x4 pixels, whose pixels are full of magic bytes
+        let mut tim = Vec::new();
+        tim.extend_from_slice(&TIM_MAGIC.to_le_bytes());
+        tim.extend_from_slice(&2u32.to_le_bytes());
+        tim.extend_from_slice(&76u32.to_le_bytes());
+        for value in [0u16, 0, 8, 4] {
+            tim.extend_from_slice(&value.to_le_bytes());
+        }
+        tim.resize(84, 0x10);
+
+        let offsets = [0, 97, 181, 1000, 2049, 4096 - 84];
+        let mut data = vec![0u8; 4096];
+        for offset in offsets {
+            data[offset..offset + tim.len()].copy_from_slice(&tim);
+        }
+
+        // Stray magic bytes, and a TIM header with an invalid flags word
+        for offset in [300, 1500, 3000, 4095] {
+            data[offset] = 0x10;
+        }
+        data[1500..1504].copy_from_slice(&TIM_MAGIC.to_le_bytes());
+        data[1504] = 0xFF;
+
+        let scanner = AssetScanner::new(&data);
+        for kind in [AssetKind::Tim, AssetKind::Tmd, AssetKind::Vag] {
+            let fast: Vec<_> = scanner
+                .scan_kind(kind)
+                .into_iter()
+                .map(|asset| (asset.offset, asset.size, asset.asset_type))
+                .collect();
+            assert_eq!(fast, scan_kind_reference(&data, kind));
+        }
+
+        let found: Vec<_> = scanner.scan_tim().iter().map(|a| a.offset).collect();
+        assert_eq!(found, offsets);
+    }
+
+    #[test]
+    fn test_detect_at_start() {
+        let mut vag = Vec::new();
+        vag.extend_from_slice(&VAG_MAGIC.to_le_bytes());
+        vag.extend_from_slice(&0x20u32.to_be_bytes());
+        vag.extend_from_slice(&0u32.to_be_bytes());
+        vag.extend_from_slice(&16u32.to_be_bytes());
+        vag.extend_from_slice(&22050u32.to_be_bytes());
+        vag.resize(48 + 16, 0);
+
+        let asset = AssetScanner::detect(&vag).unwrap();
+        assert_eq!(asset.asset_type, AssetType::Vag);
+        assert_eq!(asset.size, 64);
+
+        // Magic must be at offset 0
+        let mut shifted = vec![0u8; 4];
+        shifted.extend_from_slice(&vag);
+        assert!(AssetScanner::detect(&shifted).is_none());
+    }
+
+    #[test]
+    fn test_sequential_scan_model_skips_sszl() {
+        let mut data = vec![0u8; 1024];
+
+        // sszl block with a provisional 12-byte header; not carved
+        data[16..20].copy_from_slice(LZSS_MAGIC);
+        data[20..24].copy_from_slice(&64u32.to_le_bytes());
+        data[24..28].copy_from_slice(&20u32.to_le_bytes());
+
+        // Custom model of 200 bytes
+        data[128..132].copy_from_slice(&200u32.to_le_bytes());
+        data[132..136].copy_from_slice(&LEGAIA_MODEL_SIGNATURE.to_le_bytes());
+
+        let mut seen = Vec::new();
+        let assets = SequentialScanner::new().scan(&data, |asset| seen.push(asset.offset));
+
+        assert_eq!(seen, vec![128]);
+        assert_eq!(assets.len(), 1);
+        assert_eq!(assets[0].size, 200);
+        assert_eq!(assets[0].asset_type, AssetType::CustomModel);
+    }
+
+    #[test]
+    fn test_sequential_scan_skips_inside_assets() {
+        let mut data = vec![0u8; 512];
+
+        // Model whose body contains another model signature
+        data[0..4].copy_from_slice(&256u32.to_le_bytes());
+        data[4..8].copy_from_slice(&LEGAIA_MODEL_SIGNATURE.to_le_bytes());
+        data[64..68].copy_from_slice(&128u32.to_le_bytes());
+        data[68..72].copy_from_slice(&LEGAIA_MODEL_SIGNATURE.to_le_bytes());
+
+        let assets = SequentialScanner::new().scan(&data, |_| {});
+        assert_eq!(assets.len(), 1);
+        assert_eq!(assets[0].offset, 0);
+
+        let filtered = SequentialScanner::new()
+            .with_min_size(512)
+            .scan(&data, |_| {});
+        assert!(filtered.is_empty());
+    }
 }
@@ -0,0 +1,199 @@
+//! PSX VRAM reconstruction
+//!
+//! The PlayStation GPU has 1MB of VRAM laid out as a 1024x512 grid of 16-bit
+//! words. TIM files carry the VRAM position they are uploaded to, and TMD
+//! primitives reference textures indirectly through a texture page (TSB) and
+//! CLUT address (CBA) into that grid.
+//!
+//! `VramAtlas` blits TIMs to their positions so those references can be
+//! resolved back to colors.
+//!
+//! ## Texture Page (TSB) Layout
+//!
+//! ```text
+//! bits 0-3: X base in 64-word units
+//! bit  4:   Y base in 256-line units
+//! bits 5-6: Semi-transparency mode
+//! bits 7-8: Color mode (0 = 4-bit CLUT, 1 = 8-bit CLUT, 2 = 15-bit direct)
+//! ```
+//!
+//! ## CLUT Address (CBA) Layout
+//!
+//! ```text
+//! bits 0-5:  X in 16-word units
+//! bits 6-14: Y in lines
+//! ```
+
+use crate::formats::tim::{rgb555_to_rgba, Tim, TimAlphaMode};
+
+/// VRAM width in 16-bit words
+pub const VRAM_WIDTH: usize = 1024;
+
+/// VRAM height in lines
+pub const VRAM_HEIGHT: usize = 512;
+
+/// Reconstructed PSX VRAM
+#[derive(Debug, Clone)]
+pub struct VramAtlas {
+    words: Vec<u16>,
+}
+
+impl Default for VramAtlas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VramAtlas {
+    /// Create an empty (all zero) VRAM
+    pub fn new() -> Self {
+        Self {
+            words: vec![0; VRAM_WIDTH * VRAM_HEIGHT],
+        }
+    }
+
+    /// Blit a TIM's CLUT and pixel data to their VRAM positions
+    ///
+    /// Pixel data is copied as raw 16-bit words, so 4-bit and 8-bit TIMs
+    /// occupy a quarter/half of their pixel width, exactly as on hardware.
+    /// Anything outside the 1024x512 area is clipped.
+    pub fn insert(&mut self, tim: &Tim) {
+        if let Some(clut) = &tim.clut {
+            self.blit(
+                clut.vram_pos,
+                clut.dimensions.0 as usize,
+                clut.dimensions.1 as usize,
+                clut.data.iter().copied(),
+            );
+        }
+
+        let pixels = &tim.pixels;
+        self.blit(
+            pixels.vram_pos,
+            pixels.dimensions.0 as usize,
+            pixels.dimensions.1 as usize,
+            pixels
+                .data
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]])),
+        );
+    }
+
+    /// Copy a row-major block of words into VRAM
+    fn blit(
+        &mut self,
+        (x, y): (u16, u16),
+        width: usize,
+        height: usize,
+        words: impl Iterator<Item = u16>,
+    ) {
+        let (x, y) = (x as usize, y as usize);
+
+        for (i, word) in words.take(width * height).enumerate() {
+            let (dx, dy) = (x + i % width, y + i / width);
+            if dx < VRAM_WIDTH && dy < VRAM_HEIGHT {
+                self.words[dy * VRAM_WIDTH + dx] = word;
+            }
+        }
+    }
+
+    /// Read a VRAM word, wrapping coordinates like the GPU does
+    pub fn word(&self, x: usize, y: usize) -> u16 {
+        self.words[(y % VRAM_HEIGHT) * VRAM_WIDTH + (x % VRAM_WIDTH)]
+    }
+
+    /// Look up the color of a texel as the GPU would sample it
+    ///
+    /// # Arguments
+    /// * `tpage` - Texture page attribute (TSB)
+    /// * `clut` - CLUT attribute (CBA); ignored for 15-bit pages
+    /// * `u`, `v` - Texture coordinates within the page
+    pub fn resolve_texture(&self, tpage: u16, clut: u16, u: u8, v: u8) -> [u8; 4] {
+        let page_x = (tpage as usize & 0x0F) * 64;
+        let page_y = ((tpage as usize >> 4) & 0x01) * 256;
+        let clut_x = (clut as usize & 0x3F) * 16;
+        let clut_y = (clut as usize >> 6) & 0x1FF;
+        let (u, v) = (u as usize, v as usize);
+
+        let color = match (tpage >> 7) & 0x03 {
+            0 => {
+                let word = self.word(page_x + u / 4, page_y + v);
+                let index = (word >> ((u % 4) * 4)) & 0x0F;
+                self.word(clut_x + index as usize, clut_y)
+            }
+            1 => {
+                let word = self.word(page_x + u / 2, page_y + v);
+                let index = (word >> ((u % 2) * 8)) & 0xFF;
+                self.word(clut_x + index as usize, clut_y)
+            }
+            // 15-bit direct (mode 3 is reserved and behaves the same)
+            _ => self.word(page_x + u, page_y + v),
+        };
+
+        rgb555_to_rgba(color, TimAlphaMode::default())
+    }
+
+    /// Dump the whole VRAM as a 1024x512 RGBA8 image
+    ///
+    /// Every word is shown as a 15-bit color, so indexed textures appear as
+    /// noise; this is meant for debugging placement.
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        self.words
+            .iter()
+            .flat_map(|&word| rgb555_to_rgba(word, TimAlphaMode::Opaque))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::tim::PixelMode;
+
+    #[test]
+    fn test_resolve_4bit_texture() {
+        let clut: Vec<u16> = (0..16).map(|i| i << 10).collect();
+        let mut tim = Tim::from_indexed(
+            8,
+            2,
+            PixelMode::Clut4Bit,
+            &clut,
+            &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+        )
+        .unwrap();
+        tim.pixels.vram_pos = (64, 0);
+        tim.clut.as_mut().unwrap().vram_pos = (32, 480);
+
+        let mut vram = VramAtlas::new();
+        vram.insert(&tim);
+
+        // Texture page 1 (x = 64), 4-bit mode; CLUT at (32, 480)
+        let tpage = 1;
+        let cba = (480 << 6) | (32 / 16);
+        assert_eq!(
+            vram.resolve_texture(tpage, cba, 5, 0),
+            rgb555_to_rgba(5 << 10, TimAlphaMode::default())
+        );
+        assert_eq!(
+            vram.resolve_texture(tpage, cba, 3, 1),
+            rgb555_to_rgba(11 << 10, TimAlphaMode::default())
+        );
+    }
+
+    #[test]
+    fn test_resolve_15bit_texture() {
+        let mut vram = VramAtlas::new();
+        vram.blit((128, 256), 2, 1, [0x001F, 0x03E0].into_iter());
+
+        // Texture page 2 on the lower half (x = 128, y = 256), 15-bit mode
+        let tpage = 2 | (1 << 4) | (2 << 7);
+        assert_eq!(vram.resolve_texture(tpage, 0, 0, 0), [248, 0, 0, 255]);
+        assert_eq!(vram.resolve_texture(tpage, 0, 1, 0), [0, 248, 0, 255]);
+    }
+
+    #[test]
+    fn test_vram_dump_size() {
+        let vram = VramAtlas::new();
+        assert_eq!(vram.to_rgba8().len(), VRAM_WIDTH * VRAM_HEIGHT * 4);
+    }
+}